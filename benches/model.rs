@@ -0,0 +1,80 @@
+//! Benchmarks of the cube model: turning cubes of each size by a scramble
+//! (apply_movements, and apply_gmove_per_sticker to compare against turning
+//! layers in bulk) and reading their stickers back out (to_facelet_model
+//! and facelets), timed by criterion, which reports each against the last
+//! run, e.g. to compare before and after a change to the model, the renderer
+//! or the solver: `cargo bench --bench model`, or
+//! `cargo bench --bench model -- facelets` for only the benchmarks with
+//! facelets in their name.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use cubedesu::{random_scramble, scramble_length, CubieCube, GCube, Rng};
+
+const SIZES: [usize; 7] = [2, 3, 4, 5, 7, 10, 20];
+
+fn turning(c: &mut Criterion) {
+    let mut rng = Rng::new(1);
+    let mut group = c.benchmark_group("apply_movements");
+    for size in SIZES {
+        let scramble = random_scramble(size, scramble_length(size), &mut rng);
+        let mut gcube = GCube::new(size);
+        group.throughput(Throughput::Elements(scramble.len() as u64));
+        group.bench_function(BenchmarkId::from_parameter(size), |b| {
+            b.iter(|| gcube.apply_movements(black_box(scramble.movements())))
+        });
+    }
+    group.finish();
+    // the same, a sticker at a time rather than a layer in bulk
+    let mut group = c.benchmark_group("apply_gmove_per_sticker");
+    for size in SIZES {
+        let scramble = random_scramble(size, scramble_length(size), &mut rng);
+        let gmoves = GCube::create_gmoves(scramble.movements());
+        let mut gcube = GCube::new(size);
+        group.throughput(Throughput::Elements(gmoves.len() as u64));
+        group.bench_function(BenchmarkId::from_parameter(size), |b| {
+            b.iter(|| {
+                for &gmove in black_box(&gmoves) {
+                    gcube.apply_gmove_per_sticker(gmove);
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn reading(c: &mut Criterion) {
+    let mut rng = Rng::new(2);
+    let mut group = c.benchmark_group("facelets");
+    for size in SIZES {
+        let mut gcube = GCube::new(size);
+        gcube.apply_movements(random_scramble(size, scramble_length(size), &mut rng).movements());
+        group.throughput(Throughput::Elements(gcube.stickers().len() as u64));
+        group.bench_function(BenchmarkId::from_parameter(size), |b| {
+            b.iter(|| black_box(&gcube).facelets())
+        });
+    }
+    group.finish();
+    let mut gcube = GCube::new(3);
+    gcube.apply_movements(random_scramble(3, scramble_length(3), &mut rng).movements());
+    c.bench_function("to_facelet_model/3", |b| {
+        b.iter(|| black_box(&gcube).to_facelet_model())
+    });
+}
+
+// the cubie model the solver turns, for comparison
+fn cubie_turns(c: &mut Criterion) {
+    let scramble = random_scramble(3, scramble_length(3), &mut Rng::new(3));
+    let mut cube = CubieCube::default();
+    c.bench_function("cubie_turns/3", |b| {
+        b.iter(|| {
+            for movement in black_box(scramble.movements()) {
+                cube.turn(movement.face().unwrap(), movement.turn());
+            }
+        })
+    });
+}
+
+criterion_group!(benches, turning, reading, cubie_turns);
+criterion_main!(benches);
@@ -0,0 +1,45 @@
+/* C API for cubedesu, built with `cargo build --release --features ffi`,
+ * which produces a shared library (e.g. target/release/libcubedesu.so). */
+
+#ifndef CUBEDESU_H
+#define CUBEDESU_H
+
+#include <stdbool.h>
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* An NxN cube, only used through pointers. */
+typedef struct Cube Cube;
+
+/* Returns a new solved NxN cube, to be freed with cubedesu_cube_free. */
+Cube *cubedesu_cube_new(size_t size);
+
+void cubedesu_cube_free(Cube *cube);
+
+/* Applies an alg, e.g. "R U R' U'". Returns 0 on success, or -1 if the alg
+ * is invalid, in which case nothing is applied. */
+int cubedesu_cube_apply_alg(Cube *cube, const char *alg);
+
+/* Writes the colour of every sticker as a face letter (U, R, F, D, L or B),
+ * ordered by U, R, F, D, L, then B face, followed by a nul. Returns the
+ * number of letters, 6 * size * size, writing nothing if len (including the
+ * nul) is too short, so out can be NULL to ask for the length. */
+size_t cubedesu_cube_facelets(const Cube *cube, char *out, size_t len);
+
+bool cubedesu_cube_is_solved(const Cube *cube);
+
+/* Returns an alg that solves a 3x3 from its state, found by the solver, to
+ * be freed with cubedesu_string_free. Returns NULL for cubes of other
+ * sizes. */
+char *cubedesu_cube_solution(const Cube *cube);
+
+void cubedesu_string_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
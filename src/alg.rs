@@ -0,0 +1,294 @@
+use std::{fmt, ops::Mul, str::FromStr};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use strum::IntoEnumIterator;
+
+use crate::{scramble_to_movements, Movement, ParseMovementError, Turn};
+
+/// A sequence of movements, which can be composed with `*` and repeated
+/// with `pow`, e.g. `(sexy * sledge).pow(3)`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Alg(pub Vec<Movement>);
+
+impl Alg {
+    pub fn new(movements: Vec<Movement>) -> Self {
+        Self(movements)
+    }
+
+    pub fn movements(&self) -> &[Movement] {
+        &self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns this alg followed by other.
+    pub fn then(mut self, other: impl Into<Alg>) -> Self {
+        self.0.extend(other.into().0);
+        self
+    }
+
+    /// Returns this alg repeated n times.
+    pub fn pow(&self, n: usize) -> Self {
+        Self(self.0.repeat(n))
+    }
+
+    /// Returns the alg with adjacent movements of the same Move merged,
+    /// e.g. R U U2 U R' simplifies to nothing.
+    pub fn simplified(&self) -> Self {
+        let mut simplified: Vec<Movement> = vec![];
+        for &movement in self.0.iter() {
+            let Movement(m, turn, depth) = movement;
+            match simplified.last() {
+                Some(&Movement(last_m, last_turn, last_depth))
+                    if last_m == m && last_depth == depth =>
+                {
+                    simplified.pop();
+                    let quarter_turns = (last_turn as usize + turn as usize) % 4;
+                    if quarter_turns != 0 {
+                        let turn = Turn::iter().nth(quarter_turns - 1).unwrap();
+                        simplified.push(Movement(m, turn, depth));
+                    }
+                }
+                _ => simplified.push(movement),
+            }
+        }
+        Self(simplified)
+    }
+
+    /// Returns the alg that undoes this alg.
+    pub fn inverse(&self) -> Self {
+        Self(self.0.iter().rev().map(|m| m.inverse()).collect())
+    }
+}
+
+impl From<Movement> for Alg {
+    fn from(movement: Movement) -> Self {
+        Self(vec![movement])
+    }
+}
+
+impl From<Vec<Movement>> for Alg {
+    fn from(movements: Vec<Movement>) -> Self {
+        Self(movements)
+    }
+}
+
+impl<T: Into<Alg>> Mul<T> for Alg {
+    type Output = Alg;
+    fn mul(self, rhs: T) -> Self::Output {
+        self.then(rhs)
+    }
+}
+
+impl<T: Into<Alg>> Mul<T> for Movement {
+    type Output = Alg;
+    fn mul(self, rhs: T) -> Self::Output {
+        Alg::from(self).then(rhs)
+    }
+}
+
+impl FromStr for Alg {
+    type Err = ParseMovementError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(scramble_to_movements(s)?))
+    }
+}
+
+impl fmt::Display for Alg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        AlgFormat::default().display(&self.0).fmt(f)
+    }
+}
+
+/// How movements are written out, e.g. a space apart on one line, or
+/// wrapped onto lines for a printed sheet. Movements written with a
+/// whitespace separator parse back to the same movements, so text written
+/// by one tool can be read by another.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AlgFormat {
+    /// written between movements on the same line
+    pub separator: String,
+    /// the most characters on a line before movements wrap onto the next,
+    /// or None not to wrap. A movement longer than a line gets a line of its
+    /// own.
+    pub line_width: Option<usize>,
+}
+
+impl Default for AlgFormat {
+    fn default() -> Self {
+        Self {
+            separator: " ".to_string(),
+            line_width: None,
+        }
+    }
+}
+
+impl AlgFormat {
+    /// Returns movements written out in this format when displayed.
+    pub fn display<'a>(&'a self, movements: &'a [Movement]) -> FormattedMovements<'a> {
+        FormattedMovements {
+            format: self,
+            movements,
+        }
+    }
+}
+
+/// Movements displayed in an AlgFormat, see AlgFormat::display.
+pub struct FormattedMovements<'a> {
+    format: &'a AlgFormat,
+    movements: &'a [Movement],
+}
+
+impl fmt::Display for FormattedMovements<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let separator = &self.format.separator;
+        let separator_width = separator.chars().count();
+        // the characters on the line so far
+        let mut width = 0;
+        for (i, movement) in self.movements.iter().enumerate() {
+            let text = movement.to_string();
+            if i > 0 {
+                let line_width = self.format.line_width.unwrap_or(usize::MAX);
+                if width + separator_width + text.len() > line_width {
+                    writeln!(f)?;
+                    width = 0;
+                } else {
+                    f.write_str(separator)?;
+                    width += separator_width;
+                }
+            }
+            f.write_str(&text)?;
+            width += text.len();
+        }
+        Ok(())
+    }
+}
+
+// algs are serialized as a single string, e.g. "R U R' U'"
+impl Serialize for Alg {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Alg {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Alg::from_str(&s).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{any_movement, GCube};
+    use proptest::{
+        collection::vec, option, prelude::proptest, prop_assert, prop_assert_eq, sample::select,
+    };
+
+    fn alg(s: &str) -> Alg {
+        Alg::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn composition() {
+        let sexy = alg("R U R' U'");
+        let sledge = alg("R' F R F'");
+        assert_eq!(sexy.clone() * sledge.clone(), alg("R U R' U' R' F R F'"));
+        assert_eq!(sexy.clone().then(sledge), alg("R U R' U' R' F R F'"));
+        assert_eq!(
+            Movement::from_str("R").unwrap() * Movement::from_str("U2").unwrap() * sexy,
+            alg("R U2 R U R' U'")
+        );
+    }
+
+    #[test]
+    fn repetition() {
+        assert_eq!(alg("R U").pow(3), alg("R U R U R U"));
+        assert_eq!(alg("R U").pow(0), Alg::default());
+        // sexy move has order 6
+        let mut gcube = GCube::new(3);
+        gcube.apply_movements(alg("R U R' U'").pow(6).movements());
+        assert_eq!(gcube, GCube::new(3));
+    }
+
+    #[test]
+    fn inverse_undoes_alg() {
+        let a = alg("F R2 u' M x S'");
+        assert_eq!(a.inverse(), alg("S x' M' u R2 F'"));
+        let mut gcube = GCube::new(4);
+        gcube.apply_movements((a.clone() * a.inverse()).movements());
+        assert_eq!(gcube, GCube::new(4));
+    }
+
+    #[test]
+    fn simplification() {
+        assert_eq!(alg("R U U2 U R'").simplified(), Alg::default());
+        assert_eq!(alg("R R").simplified(), alg("R2"));
+        assert_eq!(alg("R2 R F F F").simplified(), alg("R' F'"));
+        assert_eq!(alg("R L R").simplified(), alg("R L R"));
+        assert_eq!(alg("r R").simplified(), alg("r R"));
+        assert_eq!(alg("2R R 2R").simplified(), alg("2R R 2R"));
+        assert_eq!(alg("3Rw 3r").simplified(), alg("3Rw2"));
+    }
+
+    #[test]
+    fn serde_round_trips() {
+        let a = alg("F R2 u' M x S'");
+        let json = serde_json::to_string(&a).unwrap();
+        assert_eq!(json, "\"F R2 Uw' M X S'\"");
+        assert_eq!(serde_json::from_str::<Alg>(&json).unwrap(), a);
+        assert!(serde_json::from_str::<Alg>("\"R4\"").is_err());
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let a = alg("F R2 u' M x S'");
+        assert_eq!(alg(&a.to_string()), a);
+    }
+
+    #[test]
+    fn formats_wrap_lines() {
+        let a = alg("R U R' U' 3Rw2 F");
+        let format = AlgFormat {
+            separator: ", ".to_string(),
+            line_width: Some(8),
+        };
+        assert_eq!(
+            format.display(a.movements()).to_string(),
+            "R, U, R'\nU', 3Rw2\nF"
+        );
+        let narrow = AlgFormat {
+            line_width: Some(2),
+            ..AlgFormat::default()
+        };
+        assert_eq!(
+            narrow.display(a.movements()).to_string(),
+            "R\nU\nR'\nU'\n3Rw2\nF"
+        );
+        assert_eq!(AlgFormat::default().display(&[]).to_string(), "");
+    }
+
+    proptest! {
+        #[test]
+        fn formatted_movements_parse_back(
+            movements in vec(any_movement(9), 0..40),
+            separator in select(vec![" ", "   ", "\t", " \n "]),
+            line_width in option::of(1..30usize),
+        ) {
+            let format = AlgFormat { separator: separator.to_string(), line_width };
+            let text = format.display(&movements).to_string();
+            prop_assert_eq!(scramble_to_movements(&text).unwrap(), movements);
+            for line in text.lines().filter(|line| line.split_whitespace().count() > 1) {
+                prop_assert!(line.chars().count() <= line_width.unwrap_or(usize::MAX));
+            }
+        }
+    }
+}
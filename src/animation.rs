@@ -0,0 +1,292 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter};
+
+use crate::{Alg, GCube, Movement, ObservedCube};
+
+/// How the speed of a turn changes over its duration.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, EnumIter, Display, Serialize, Deserialize)]
+pub enum Easing {
+    Linear,
+    /// starts fast and slows down into place
+    EaseOut,
+    /// speeds up, then slows down into place
+    EaseInOut,
+}
+
+impl Easing {
+    /// Maps the fraction of time passed (0..1) to the fraction turned (0..1).
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0., 1.);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseOut => 1. - (1. - t) * (1. - t),
+            Easing::EaseInOut => t * t * (3. - 2. * t),
+        }
+    }
+}
+
+/// Whether turns are animated, or happen instantly.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, EnumIter, Display, Serialize, Deserialize)]
+pub enum TurnStyle {
+    /// turns are animated over the turn duration, for following along
+    Smooth,
+    /// turns happen instantly, for fast keyboard solving
+    Snappy,
+}
+
+/// Queues movements so they can be shown turning over time, rather than
+/// instantly, applying each movement to the cube once its turn finishes, so
+/// the cube's observers hear of it as it lands.
+/// Movements pushed together that share no layers, like R and L', turn at
+/// the same time.
+#[derive(Clone, Debug)]
+pub struct Animator {
+    queue: VecDeque<Vec<Movement>>, // movements turning at the same time
+    progress: f32,                  // fraction of the front movements' turn duration passed
+    last: Option<(Vec<Movement>, f32)>, // the movements that last finished turning, and seconds since
+    pub turn_duration: f32,             // seconds per turn
+    pub easing: Easing,
+    pub turn_style: TurnStyle,
+}
+
+impl Animator {
+    pub fn new(turn_duration: f32) -> Self {
+        Self {
+            queue: VecDeque::new(),
+            progress: 0.,
+            last: None,
+            turn_duration,
+            easing: Easing::Linear,
+            turn_style: TurnStyle::Smooth,
+        }
+    }
+
+    pub fn push(&mut self, movement: Movement) {
+        self.queue.push_back(vec![movement]);
+    }
+
+    /// Queues movements made at the same time, e.g. keys pressed in the same
+    /// frame, turning adjacent ones at once if they share no layers.
+    pub fn push_together(&mut self, movements: &[Movement], gcube: &GCube) {
+        let mut chord: Vec<Movement> = vec![];
+        for &movement in movements {
+            if !chord.iter().all(|&m| gcube.can_turn_together(m, movement)) {
+                self.queue.push_back(std::mem::take(&mut chord));
+            }
+            chord.push(movement);
+        }
+        if !chord.is_empty() {
+            self.queue.push_back(chord);
+        }
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Returns the movements currently being turned, along with the fraction
+    /// (0..1) of their turn that has been completed.
+    pub fn current(&self) -> Option<(&[Movement], f32)> {
+        let progress = self.easing.apply(self.progress);
+        self.queue.front().map(|chord| (chord.as_slice(), progress))
+    }
+
+    /// Returns the movements that last finished turning, along with the
+    /// seconds since they did, e.g. to highlight the layers they turned.
+    pub fn last_turned(&self) -> Option<(&[Movement], f32)> {
+        self.last
+            .as_ref()
+            .map(|(chord, since)| (chord.as_slice(), *since))
+    }
+
+    /// Advances the animation by dt seconds, applying finished movements to
+    /// gcube. Turns speed up while movements are queued, so fast input
+    /// doesn't lag behind.
+    pub fn update(&mut self, gcube: &mut ObservedCube, dt: f32) {
+        if let Some((_, since)) = self.last.as_mut() {
+            *since += dt;
+        }
+        if self.turn_style == TurnStyle::Snappy {
+            return self.finish(gcube);
+        }
+        let mut remaining = dt;
+        while let Some(chord) = self.queue.front() {
+            let duration = self.turn_duration / self.queue.len() as f32;
+            if duration > 0. {
+                let needed = (1. - self.progress) * duration;
+                if remaining < needed {
+                    self.progress += remaining / duration;
+                    return;
+                }
+                remaining -= needed;
+            }
+            gcube.apply_movements(chord);
+            self.last = self.queue.pop_front().map(|chord| (chord, remaining));
+            self.progress = 0.;
+        }
+    }
+
+    /// Instantly applies all queued movements.
+    pub fn finish(&mut self, gcube: &mut ObservedCube) {
+        for chord in self.queue.drain(..) {
+            gcube.apply_movements(&chord);
+            self.last = Some((chord, 0.));
+        }
+        self.progress = 0.;
+    }
+}
+
+/// Plays an alg back one movement at a time, with pause and step controls.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Playback {
+    alg: Alg,
+    position: usize,
+    pub paused: bool,
+}
+
+impl Playback {
+    pub fn new(alg: Alg) -> Self {
+        Self {
+            alg,
+            position: 0,
+            paused: false,
+        }
+    }
+
+    /// Returns the next movement to play, unless paused.
+    pub fn next_movement(&mut self) -> Option<Movement> {
+        if self.paused {
+            return None;
+        }
+        self.step()
+    }
+
+    /// Returns the next movement to play, even if paused.
+    pub fn step(&mut self) -> Option<Movement> {
+        let movement = *self.alg.movements().get(self.position)?;
+        self.position += 1;
+        Some(movement)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.alg.len()
+    }
+
+    pub fn alg(&self) -> &Alg {
+        &self.alg
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn animator_applies_finished_turns() {
+        let mut gcube = ObservedCube::new(GCube::new(3));
+        let mut animator = Animator::new(1.);
+        animator.push(Movement::from_str("R").unwrap());
+        animator.update(&mut gcube, 0.5);
+        assert_eq!(
+            animator.current(),
+            Some((&[Movement::from_str("R").unwrap()][..], 0.5))
+        );
+        assert_eq!(*gcube, GCube::new(3));
+        animator.update(&mut gcube, 0.5);
+        assert!(animator.is_idle());
+        let mut expected = GCube::new(3);
+        expected.apply_movement(&Movement::from_str("R").unwrap());
+        assert_eq!(*gcube, expected);
+    }
+
+    #[test]
+    fn animator_remembers_the_last_turn() {
+        let mut gcube = ObservedCube::new(GCube::new(3));
+        let mut animator = Animator::new(1.);
+        assert_eq!(animator.last_turned(), None);
+        let alg = Alg::from_str("R U").unwrap();
+        animator.push(alg.movements()[0]);
+        animator.push(alg.movements()[1]);
+        // R turns in half a second, as U is queued behind it
+        animator.update(&mut gcube, 0.75);
+        assert_eq!(animator.last_turned(), Some((&alg.movements()[..1], 0.25)));
+        animator.update(&mut gcube, 1.);
+        assert_eq!(animator.last_turned().unwrap().0, &alg.movements()[1..]);
+        animator.update(&mut gcube, 0.5);
+        assert_eq!(animator.last_turned().unwrap().1, 0.75);
+    }
+
+    #[test]
+    fn opposite_layers_turn_together() {
+        let mut gcube = ObservedCube::new(GCube::new(3));
+        let mut animator = Animator::new(1.);
+        let alg = Alg::from_str("R L' U D E").unwrap();
+        animator.push_together(alg.movements(), &gcube);
+        assert_eq!(animator.current().unwrap().0, &alg.movements()[..2]);
+        animator.update(&mut gcube, 0.5);
+        assert_eq!(animator.current().unwrap().0, &alg.movements()[2..]);
+        animator.finish(&mut gcube);
+        let mut expected = GCube::new(3);
+        expected.apply_movements(alg.movements());
+        assert_eq!(*gcube, expected);
+    }
+
+    #[test]
+    fn easing_starts_and_ends_in_place() {
+        for easing in [Easing::Linear, Easing::EaseOut, Easing::EaseInOut] {
+            assert_eq!(easing.apply(0.), 0.);
+            assert_eq!(easing.apply(1.), 1.);
+            assert_eq!(easing.apply(2.), 1.);
+        }
+        assert!(Easing::EaseOut.apply(0.5) > 0.5);
+        assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+    }
+
+    #[test]
+    fn snappy_turns_are_instant() {
+        let mut gcube = ObservedCube::new(GCube::new(3));
+        let mut animator = Animator::new(1.);
+        animator.turn_style = TurnStyle::Snappy;
+        animator.push(Movement::from_str("R").unwrap());
+        animator.update(&mut gcube, 0.);
+        assert!(animator.is_idle());
+        assert_ne!(*gcube, GCube::new(3));
+    }
+
+    #[test]
+    fn animator_finish_applies_everything() {
+        let mut gcube = ObservedCube::new(GCube::new(3));
+        let mut animator = Animator::new(1.);
+        let alg = Alg::from_str("R U R' U'").unwrap();
+        for &m in alg.movements() {
+            animator.push(m);
+        }
+        animator.update(&mut gcube, 0.1);
+        animator.finish(&mut gcube);
+        assert!(animator.is_idle());
+        let mut expected = GCube::new(3);
+        expected.apply_movements(alg.movements());
+        assert_eq!(*gcube, expected);
+    }
+
+    #[test]
+    fn playback_pauses_and_steps() {
+        let mut playback = Playback::new(Alg::from_str("R U").unwrap());
+        assert_eq!(
+            playback.next_movement(),
+            Some(Movement::from_str("R").unwrap())
+        );
+        playback.paused = true;
+        assert_eq!(playback.next_movement(), None);
+        assert_eq!(playback.step(), Some(Movement::from_str("U").unwrap()));
+        assert!(playback.is_finished());
+        assert_eq!(playback.step(), None);
+    }
+}
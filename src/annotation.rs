@@ -0,0 +1,228 @@
+use std::{fmt, str::FromStr};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+use crate::{Face, Point3};
+
+/// A position on the surface of a cube, as a face and the index of the
+/// facelet on it, from left to right, then top to bottom (like
+/// [facelets](crate::GCube::facelets)), e.g. U0 for the back left corner of U.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Facelet {
+    pub face: Face,
+    pub index: usize,
+}
+
+impl Facelet {
+    pub fn new(face: Face, index: usize) -> Self {
+        Self { face, index }
+    }
+
+    /// Returns where the facelet is on an NxN cube, or None if it's not on
+    /// the cube.
+    pub fn position(self, size: usize) -> Option<Point3> {
+        let n = size as i16;
+        if self.index >= size * size || self.face == Face::X {
+            return None;
+        }
+        // coordinates of the facelet's row and column, counting across the
+        // face from the left and from the top
+        let col = -n + 1 + 2 * (self.index % size) as i16;
+        let row = -n + 1 + 2 * (self.index / size) as i16;
+        Some(match self.face {
+            Face::U => Point3::new(col, n, row),
+            Face::R => Point3::new(n, -row, -col),
+            Face::F => Point3::new(col, -row, n),
+            Face::D => Point3::new(col, -n, -row),
+            Face::L => Point3::new(-n, -row, col),
+            Face::B => Point3::new(-col, -row, -n),
+            Face::X => return None,
+        })
+    }
+
+    /// Returns the facelet at position on the surface of an NxN cube, or
+    /// None if it's not on the surface, the inverse of
+    /// [position](Facelet::position).
+    pub fn at(position: Point3, size: usize) -> Option<Self> {
+        let n = size as i16;
+        let Point3 { x, y, z } = position;
+        let (face, col, row) = if y == n {
+            (Face::U, x, z)
+        } else if x == n {
+            (Face::R, -z, -y)
+        } else if z == n {
+            (Face::F, x, -y)
+        } else if y == -n {
+            (Face::D, x, -z)
+        } else if x == -n {
+            (Face::L, z, -y)
+        } else if z == -n {
+            (Face::B, -x, -y)
+        } else {
+            return None;
+        };
+        // back from coordinates to counts of facelets from the left and top
+        let (col, row) = ((col + n - 1) / 2, (row + n - 1) / 2);
+        if col < 0 || row < 0 || col >= n || row >= n {
+            return None;
+        }
+        Some(Facelet::new(face, (row * n + col) as usize))
+    }
+}
+
+impl fmt::Display for Facelet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.face, self.index)
+    }
+}
+
+/// An arrow drawn over a cube from one facelet to another, written like
+/// visualcube's arrows, e.g. "U0U2". A cycle of arrows shows where pieces
+/// go, e.g. for an alg's visualization.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Arrow {
+    pub from: Facelet,
+    pub to: Facelet,
+}
+
+impl Arrow {
+    pub fn new(from: Facelet, to: Facelet) -> Self {
+        Self { from, to }
+    }
+
+    /// Returns arrows from each facelet to the next, and from the last back
+    /// to the first, e.g. for a 3-cycle of corners.
+    pub fn cycle(facelets: &[Facelet]) -> Vec<Arrow> {
+        if facelets.len() < 2 {
+            return vec![];
+        }
+        (0..facelets.len())
+            .map(|i| Arrow::new(facelets[i], facelets[(i + 1) % facelets.len()]))
+            .collect()
+    }
+
+    /// Returns where the arrow starts and ends on an NxN cube, or None if
+    /// either end isn't on the cube.
+    pub fn positions(self, size: usize) -> Option<(Point3, Point3)> {
+        Some((self.from.position(size)?, self.to.position(size)?))
+    }
+}
+
+impl fmt::Display for Arrow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.from, self.to)
+    }
+}
+
+/// Error for text that isn't a valid arrow.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+#[error("Failed to parse arrow {0}, expected facelets like U0U2")]
+pub struct ParseArrowError(pub String);
+
+impl FromStr for Arrow {
+    type Err = ParseArrowError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseArrowError(s.to_string());
+        // a face letter then an index, for each end
+        let mut facelets = vec![];
+        let mut rest = s.trim();
+        while let Some(letter) = rest.chars().next() {
+            let face = Face::from_letter(letter).ok_or_else(error)?;
+            let digits = rest[1..].chars().take_while(char::is_ascii_digit).count();
+            let index = rest[1..=digits].parse().map_err(|_| error())?;
+            facelets.push(Facelet::new(face, index));
+            rest = &rest[1 + digits..];
+        }
+        match facelets[..] {
+            [from, to] => Ok(Arrow::new(from, to)),
+            _ => Err(error()),
+        }
+    }
+}
+
+// arrows are serialized like visualcube's, e.g. "U0U2"
+impl Serialize for Arrow {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Arrow {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Arrow::from_str(&s).map_err(de::Error::custom)
+    }
+}
+
+/// Parses comma separated arrows, e.g. "U0U2,U2U8,U8U0".
+pub fn parse_arrows(s: &str) -> Result<Vec<Arrow>, ParseArrowError> {
+    s.split(',')
+        .filter(|a| !a.trim().is_empty())
+        .map(Arrow::from_str)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GCube, ORDERED_FACES};
+
+    #[test]
+    fn facelet_positions_match_facelet_order() {
+        for size in 1..=5 {
+            let mut gcube = GCube::new(size);
+            gcube.apply_movements(&crate::scramble_to_movements("R U' F2 D L B'").unwrap());
+            // the face a facelet is on is the face of the sticker there
+            let facelets = gcube.facelets();
+            let positions: Vec<Point3> = ORDERED_FACES
+                .iter()
+                .flat_map(|&face| (0..size * size).map(move |i| Facelet::new(face, i)))
+                .map(|f| f.position(size).unwrap())
+                .collect();
+            for (i, position) in positions.iter().enumerate() {
+                assert_eq!(
+                    Facelet::at(*position, size).map(|f| f.position(size)),
+                    Some(Some(*position))
+                );
+                assert_eq!(
+                    Facelet::at(*position, size).unwrap().index,
+                    i % (size * size)
+                );
+            }
+            for (position, face) in positions.iter().zip(facelets) {
+                let sticker = gcube.stickers().find(|s| s.current == *position);
+                assert_eq!(gcube.get_initial_face(sticker.unwrap()), face);
+            }
+        }
+        assert_eq!(Facelet::new(Face::U, 9).position(3), None);
+        assert_eq!(Facelet::at(Point3::new(0, 0, 0), 3), None);
+    }
+
+    #[test]
+    fn arrows_are_written_like_visualcube() {
+        let arrows = parse_arrows("U0U2, U2U8,R10F3").unwrap();
+        assert_eq!(
+            arrows,
+            [
+                Arrow::new(Facelet::new(Face::U, 0), Facelet::new(Face::U, 2)),
+                Arrow::new(Facelet::new(Face::U, 2), Facelet::new(Face::U, 8)),
+                Arrow::new(Facelet::new(Face::R, 10), Facelet::new(Face::F, 3)),
+            ]
+        );
+        assert_eq!(arrows[2].to_string(), "R10F3");
+        assert!(parse_arrows("U0").is_err());
+        assert!(parse_arrows("U0Q2").is_err());
+        assert!(parse_arrows("U0U2U4").is_err());
+        assert!(parse_arrows("UU2").is_err());
+        assert_eq!(parse_arrows("").unwrap(), []);
+
+        let corners = [0, 2, 8].map(|i| Facelet::new(Face::U, i));
+        assert_eq!(
+            Arrow::cycle(&corners),
+            parse_arrows("U0U2,U2U8,U8U0").unwrap()
+        );
+        assert_eq!(arrows[2].positions(3), None);
+    }
+}
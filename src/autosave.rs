@@ -0,0 +1,161 @@
+use std::{fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Face, GCube, History, Penalty, TimerSnapshot};
+
+/// How often the app is autosaved, in seconds.
+pub const AUTOSAVE_INTERVAL: f64 = 10.;
+
+/// The state of the app mid-practice, written every few seconds and removed
+/// on a clean exit, so if one's left at startup the app crashed, and picks up
+/// where it was.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Autosave {
+    pub dims: [usize; 3],
+    /// an NxN cube's colours as a facelet string, e.g. painted in by hand,
+    /// or None for a cuboid, which is turned back from solved by history
+    #[serde(default)]
+    pub facelets: Option<String>,
+    pub history: History,
+    #[serde(default)]
+    pub timer: TimerSnapshot,
+    /// whether the timer starts on the next move
+    #[serde(default)]
+    pub is_timer_armed: bool,
+    /// the penalty for how long the current solve was inspected
+    #[serde(default)]
+    pub penalty: Penalty,
+}
+
+impl Autosave {
+    pub fn new(
+        gcube: &GCube,
+        history: &History,
+        timer: TimerSnapshot,
+        is_timer_armed: bool,
+        penalty: Penalty,
+    ) -> Self {
+        let facelets = gcube
+            .is_cube()
+            .then(|| gcube.facelets().iter().map(Face::to_string).collect());
+        Self {
+            dims: gcube.dims,
+            facelets,
+            history: history.clone(),
+            timer,
+            is_timer_armed,
+            penalty,
+        }
+    }
+
+    /// Returns the cube as it was saved.
+    pub fn gcube(&self) -> GCube {
+        let facelets: Option<Vec<Face>> = self
+            .facelets
+            .as_ref()
+            .and_then(|f| f.chars().map(Face::from_letter).collect());
+        facelets
+            .and_then(|f| GCube::from_facelets(self.dims[0], &f))
+            .unwrap_or_else(|| {
+                let mut gcube = GCube::cuboid(self.dims);
+                gcube.apply_movements(self.history.applied());
+                gcube
+            })
+    }
+
+    /// Loads the autosave at path, if the app didn't exit cleanly.
+    pub fn load(path: impl AsRef<Path>) -> Option<Self> {
+        let json = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&json).ok()
+    }
+
+    /// Saves to a temporary file next to path, then renames it over path, so
+    /// crashing partway through a save leaves the last autosave whole.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let temp = path.with_extension("tmp");
+        fs::write(&temp, serde_json::to_string(self)?)?;
+        fs::rename(&temp, path)
+    }
+
+    /// Removes the autosave at path, on a clean exit.
+    pub fn clear(path: impl AsRef<Path>) -> io::Result<()> {
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{scramble_to_movements, Alg, Timer};
+    use std::str::FromStr;
+
+    fn practiced(dims: [usize; 3]) -> (GCube, History) {
+        let mut gcube = GCube::cuboid(dims);
+        let mut history = History::new();
+        let scramble = Alg::from_str("R2 U2 F2").unwrap();
+        gcube.apply_movements(scramble.movements());
+        history.push_scramble(&scramble);
+        for movement in scramble_to_movements("U2 R2").unwrap() {
+            gcube.apply_movement(&movement);
+            history.push(movement);
+        }
+        (gcube, history)
+    }
+
+    #[test]
+    fn restores_cubes_and_cuboids() {
+        for dims in [[3; 3], [3, 2, 3]] {
+            let (gcube, history) = practiced(dims);
+            let autosave = Autosave::new(
+                &gcube,
+                &history,
+                TimerSnapshot::default(),
+                true,
+                Penalty::None,
+            );
+            assert_eq!(autosave.facelets.is_some(), dims == [3; 3]);
+            assert_eq!(autosave.gcube().facelets(), gcube.facelets());
+            assert_eq!(autosave.gcube().is_solved(), gcube.is_solved());
+            assert_eq!(autosave.history, history);
+        }
+
+        // painted stickers aren't in the history
+        let (mut gcube, history) = practiced([3; 3]);
+        gcube.paint(0, Face::B);
+        let autosave = Autosave::new(
+            &gcube,
+            &history,
+            TimerSnapshot::default(),
+            true,
+            Penalty::None,
+        );
+        assert_eq!(autosave.gcube().facelets(), gcube.facelets());
+    }
+
+    #[test]
+    fn save_load_and_clear() {
+        let path = std::env::temp_dir().join("cubedesu_autosave_test.json");
+        let (gcube, history) = practiced([4; 3]);
+        let mut timer = Timer::new();
+        timer.start(1.);
+        let autosave = Autosave::new(
+            &gcube,
+            &history,
+            timer.snapshot(3.),
+            false,
+            Penalty::PlusTwo,
+        );
+        autosave.save(&path).unwrap();
+        assert!(!path.with_extension("tmp").exists());
+        assert_eq!(Autosave::load(&path), Some(autosave));
+        Autosave::clear(&path).unwrap();
+        assert_eq!(Autosave::load(&path), None);
+        // nothing to clear after a clean exit
+        Autosave::clear(&path).unwrap();
+    }
+}
@@ -0,0 +1,83 @@
+//! The cube without a window, for editors, bots and scripts to drive, and
+//! replays printed move by move, built without the viewer's graphics:
+//! `cargo run --bin cubedesu-cli -- --serve-stdio` or
+//! `cargo run --bin cubedesu-cli -- replay cubedesu_last_solve.cdr`
+
+use std::{
+    env, io, process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use cubedesu::{Metric, Movement, Penalty, Replay, Rng, Settings, StdioServer};
+
+// the same file as the app's, in the directory it's run from
+const SETTINGS_PATH: &str = "cubedesu_settings.json";
+
+const USAGE: &str = "usage: cubedesu-cli --serve-stdio | replay <file>";
+
+fn main() {
+    if let Err(message) = run(env::args().skip(1).collect()) {
+        eprintln!("{}\n{}", message, USAGE);
+        process::exit(1);
+    }
+}
+
+fn run(args: Vec<String>) -> Result<(), String> {
+    match args.first().map(String::as_str) {
+        Some("--serve-stdio") => serve_stdio(),
+        Some("replay") => {
+            let path = args.get(1).ok_or("which replay should be printed?")?;
+            let replay =
+                Replay::load(path).map_err(|e| format!("Failed to load {}: {}", path, e))?;
+            print!("{}", replay_report(&replay));
+            Ok(())
+        }
+        Some(arg) => Err(format!("{} isn't a command", arg)),
+        None => Err("what should be run?".to_string()),
+    }
+}
+
+// serves a cube of the settings' size over stdin and stdout
+fn serve_stdio() -> Result<(), String> {
+    let settings = Settings::load(SETTINGS_PATH).unwrap_or_default();
+    let seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    let mut server = StdioServer::new(settings.cube_size, Rng::new(seed));
+    server
+        .serve(io::stdin().lock(), io::stdout().lock())
+        .map_err(|e| format!("Failed to serve: {}", e))
+}
+
+// the moves of a replay with when each was made, and how the solve ended
+fn replay_report(replay: &Replay) -> String {
+    let [x, y, z] = replay.dims;
+    let mut report = format!("{}x{}x{} scrambled with {}\n", x, y, z, replay.scramble);
+    let timeline = replay.timeline();
+    for (at, movement) in timeline.iter() {
+        report.push_str(&format!("{:>8.2} {}\n", *at as f64 / 1000., movement));
+    }
+    let seconds = replay.duration() as f64 / 1000.;
+    let movements: Vec<Movement> = timeline.iter().map(|&(_, m)| m).collect();
+    let moves = Metric::Htm.count_all(&movements);
+    let tps = if seconds > 0. {
+        moves as f64 / seconds
+    } else {
+        0.
+    };
+    let ending = if replay.state_at(replay.duration()).is_solved() {
+        "Solved"
+    } else {
+        "Stopped"
+    };
+    let penalty = match replay.metadata.penalty {
+        Penalty::None => String::new(),
+        p => format!(" ({})", p),
+    };
+    report.push_str(&format!(
+        "{} in {:.2}{}, {} moves (HTM), {:.2} TPS\n",
+        ending, seconds, penalty, moves, tps
+    ));
+    report
+}
@@ -0,0 +1,65 @@
+//! Samples random 3x3 states, solves each, and prints how many needed each
+//! number of moves, e.g. to check that scrambles of a length are as far from
+//! solved as random states:
+//! `cargo run --release --bin distances -- 200 --scramble 20`
+
+use std::{
+    env, fs, process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use cubedesu::{DistanceDistribution, Rng, Solver, StateSource};
+
+const USAGE: &str =
+    "usage: distances [samples, 100] [--scramble LENGTH] [--tries N, 50] [--seed N] [--csv FILE]";
+
+fn main() {
+    if let Err(message) = run(env::args().skip(1)) {
+        eprintln!("{}\n{}", message, USAGE);
+        process::exit(1);
+    }
+}
+
+fn run(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let mut samples = 100;
+    let mut source = StateSource::RandomState;
+    let mut max_tries = 50;
+    let mut seed = None;
+    let mut csv_path = None;
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+        let number = |n: String| n.parse().map_err(|_| format!("{} isn't a number", n));
+        match arg.as_str() {
+            "--scramble" => source = StateSource::Scramble(number(value()?)?),
+            "--tries" => max_tries = number(value()?)?,
+            "--seed" => seed = Some(number(value()?)? as u64),
+            "--csv" => csv_path = Some(value()?),
+            _ => samples = number(arg)?,
+        }
+    }
+    let seed = seed.unwrap_or_else(|| {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        now.as_millis() as u64
+    });
+    let distribution = DistanceDistribution::sample(
+        Solver::shared(),
+        source,
+        samples,
+        max_tries,
+        &mut Rng::new(seed),
+    );
+    print!("{}", distribution.histogram(50));
+    if let Some(mean) = distribution.mean() {
+        println!(
+            "mean {:.2} moves (HTM) over {} states (seed {})",
+            mean, samples, seed
+        );
+    }
+    if let Some(path) = csv_path {
+        fs::write(&path, distribution.to_csv())
+            .map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    }
+    Ok(())
+}
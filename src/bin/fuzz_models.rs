@@ -0,0 +1,63 @@
+//! A fuzz target checking the cubie model against the geometric model: turns
+//! both by the face turns its input stands for (a byte each), and panics
+//! where they disagree, which fuzzers report as a crash. Reads the input
+//! from stdin, as AFL++ and honggfuzz give it:
+//! `cargo afl fuzz -i in -o out target/debug/fuzz-models`
+//! or checks random algs on its own:
+//! `cargo run --release --bin fuzz-models -- --random 10000`
+
+use std::{
+    env,
+    io::{self, Read},
+    process,
+};
+
+use cubedesu::{check_models, movements_from_bytes, Rng};
+
+const USAGE: &str = "usage: fuzz-models [--random ALGS [--seed N]] < input";
+
+fn main() {
+    if let Err(message) = run(env::args().skip(1)) {
+        eprintln!("{}\n{}", message, USAGE);
+        process::exit(1);
+    }
+}
+
+fn run(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let mut random = None;
+    let mut seed = 0;
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+        let number = |n: String| n.parse().map_err(|_| format!("{} isn't a number", n));
+        match arg.as_str() {
+            "--random" => random = Some(number(value()?)?),
+            "--seed" => seed = number(value()?)? as u64,
+            _ => return Err(format!("Unknown argument {}", arg)),
+        }
+    }
+    let inputs: Vec<Vec<u8>> = match random {
+        Some(count) => {
+            let mut rng = Rng::new(seed);
+            (0..count)
+                .map(|_| {
+                    let len = rng.below(100);
+                    (0..len).map(|_| rng.below(256) as u8).collect()
+                })
+                .collect()
+        }
+        None => {
+            let mut input = vec![];
+            io::stdin()
+                .read_to_end(&mut input)
+                .map_err(|e| e.to_string())?;
+            vec![input]
+        }
+    };
+    for input in inputs.iter() {
+        if let Err(e) = check_models(&movements_from_bytes(input)) {
+            panic!("{}", e);
+        }
+    }
+    println!("The models agree on every alg ({} checked)", inputs.len());
+    Ok(())
+}
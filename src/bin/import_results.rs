@@ -0,0 +1,47 @@
+//! Adds the solves from a qqTimer or Twisty Timer export to the session
+//! cubedesu keeps its results in, e.g. to keep one history across timers:
+//! `cargo run --bin import-results -- twisty_timer_backup.txt`
+
+use std::{env, fs, process};
+
+use cubedesu::{import_results, results_csv, Session};
+
+// the same files as the app's, in the directory it's run from
+const SESSION_PATH: &str = "cubedesu_session.json";
+const RESULTS_PATH: &str = "cubedesu_results.csv";
+
+const USAGE: &str = "usage: import-results <file> [size of solves not marked with a puzzle, 3]";
+
+fn main() {
+    if let Err(message) = run(env::args().skip(1).collect()) {
+        eprintln!("{}\n{}", message, USAGE);
+        process::exit(1);
+    }
+}
+
+fn run(args: Vec<String>) -> Result<(), String> {
+    let path = args.first().ok_or("which file should be imported?")?;
+    let size = match args.get(1) {
+        Some(n) => n.parse().map_err(|_| format!("{} isn't a number", n))?,
+        None => 3,
+    };
+    let text = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let imported = import_results(&text, size).map_err(|e| format!("{}: {}", path, e))?;
+    let found = imported.len();
+    let mut session =
+        Session::load(SESSION_PATH).map_err(|e| format!("Failed to load the session: {}", e))?;
+    let added = session.import_results(imported);
+    session
+        .save(SESSION_PATH)
+        .map_err(|e| format!("Failed to save the session: {}", e))?;
+    fs::write(RESULTS_PATH, results_csv(&session.results))
+        .map_err(|e| format!("Failed to write {}: {}", RESULTS_PATH, e))?;
+    println!(
+        "Imported {} of {} solves from {} ({} were already in the session)",
+        added,
+        found,
+        path,
+        found - added
+    );
+    Ok(())
+}
@@ -0,0 +1,65 @@
+//! Writes a printable sheet of random scrambles, each with the net of the
+//! cube it scrambles to, as an SVG image, e.g. for a club meetup:
+//! `cargo run --bin scramble-sheet -- 4 12 sheet.svg --scheme japanese`
+
+use std::{
+    env, fs, process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use cubedesu::{ColorScheme, Rng, ScrambleSheet};
+use strum::IntoEnumIterator;
+
+const USAGE: &str =
+    "usage: scramble-sheet [size, 3] [count, 12] [file, scramble_sheet.svg] [--seed N] [--scheme NAME]";
+
+fn main() {
+    if let Err(message) = run(env::args().skip(1)) {
+        eprintln!("{}\n{}", message, USAGE);
+        process::exit(1);
+    }
+}
+
+fn run(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let mut positional = vec![];
+    let mut seed = None;
+    let mut scheme = ColorScheme::Standard;
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--seed" => seed = Some(value()?.parse().map_err(|_| "the seed must be a number")?),
+            "--scheme" => {
+                let name = value()?;
+                scheme = ColorScheme::iter()
+                    .find(|s| s.to_string().eq_ignore_ascii_case(&name))
+                    .ok_or(format!("unknown colour scheme {}", name))?;
+            }
+            _ => positional.push(arg),
+        }
+    }
+    let number = |i: usize, default: usize| match positional.get(i) {
+        Some(n) => n.parse().map_err(|_| format!("{} isn't a number", n)),
+        None => Ok(default),
+    };
+    let (size, count) = (number(0, 3)?, number(1, 12)?);
+    if size == 0 {
+        return Err("the cube needs at least 1 layer".to_string());
+    }
+    let path = positional
+        .get(2)
+        .map_or("scramble_sheet.svg", String::as_str);
+    // a new sheet every time, unless it's seeded to print the same one again
+    let seed = seed.unwrap_or_else(|| {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        now.as_millis() as u64
+    });
+    let sheet = ScrambleSheet::random(size, count, scheme, &mut Rng::new(seed));
+    fs::write(path, sheet.to_svg()).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    println!(
+        "Wrote {} {}x{} scrambles to {} (seed {})",
+        count, size, size, path, seed
+    );
+    Ok(())
+}
@@ -0,0 +1,256 @@
+//! The cube in a terminal, for SSH sessions and servers without a display:
+//! its net in colour, turned with the viewer's keymap (from
+//! cubedesu_settings.json), with scrambles, a timer and the viewer's console
+//! commands: `cargo run --features tui --bin cubedesu-tui`
+
+use std::{
+    io::{self, Write},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::{self, Color},
+    terminal::{self, ClearType},
+};
+use cubedesu::{
+    random_scramble, run_console_line, scramble_length, Alg, ColorScheme, ConsoleEffect, Face,
+    GCube, Keymap, Modifiers, Movement, Rng, Session, Settings, Timer,
+};
+
+const SETTINGS_PATH: &str = "cubedesu_settings.json";
+const SESSION_PATH: &str = "cubedesu_session.json";
+const HELP: &str = "Space scramble, - / = size, 1-9 layer, Alt wide, Tab console, Esc quit";
+
+fn main() -> io::Result<()> {
+    let settings = Settings::load(SETTINGS_PATH).unwrap_or_default();
+    let mut stdout = io::stdout();
+    terminal::enable_raw_mode()?;
+    execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
+    let result = run(&mut stdout, &settings);
+    // the terminal's restored even if drawing failed
+    execute!(stdout, cursor::Show, terminal::LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+    result
+}
+
+fn run(out: &mut impl Write, settings: &Settings) -> io::Result<()> {
+    let start = Instant::now();
+    let now = || start.elapsed().as_secs_f64();
+    let seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    let mut rng = Rng::new(seed);
+    let keymap = Keymap::from(settings.keymap);
+    let mut modifiers = Modifiers::default();
+    let mut gcube = GCube::new(settings.cube_size);
+    let mut timer = Timer::new();
+    // whether the timer starts on the next move, i.e. just after scrambling
+    let mut is_timer_armed = false;
+    let mut scramble: Option<Alg> = None;
+    // the movements turned since the cube was last solved (as if by magic), for bookmarks
+    let mut applied: Vec<Movement> = vec![];
+    let mut session = Session::load(SESSION_PATH).unwrap_or_default();
+    // a console command being typed, and the last command's output
+    let mut console: Option<String> = None;
+    let mut output = String::new();
+    queue!(out, terminal::Clear(ClearType::All))?;
+    loop {
+        let status = match console.as_ref() {
+            Some(text) => format!("> {}_", text),
+            None => output.clone(),
+        };
+        draw(
+            out,
+            &gcube,
+            settings,
+            &timer,
+            scramble.as_ref(),
+            &status,
+            now(),
+        )?;
+        if !event::poll(Duration::from_millis(50))? {
+            continue;
+        }
+        let key = match event::read()? {
+            Event::Key(key) if key.kind != KeyEventKind::Release => key,
+            Event::Resize(_, _) => {
+                queue!(out, terminal::Clear(ClearType::All))?;
+                continue;
+            }
+            _ => continue,
+        };
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            return Ok(());
+        }
+        if let Some(text) = console.as_mut() {
+            // keys type the command, Enter runs it and Esc closes the console
+            match key.code {
+                KeyCode::Esc => console = None,
+                KeyCode::Backspace => {
+                    text.pop();
+                }
+                KeyCode::Char(c) => text.push(c),
+                KeyCode::Enter => {
+                    let line = console.take().unwrap();
+                    let alg = Alg::new(applied.clone());
+                    output.clear();
+                    let effects = run_console_line(&line, &gcube, &alg, &mut session, &mut rng);
+                    match effects {
+                        Ok(effects) => {
+                            for effect in effects {
+                                match effect {
+                                    ConsoleEffect::Reset(size) => {
+                                        gcube = GCube::new(size);
+                                        modifiers = Modifiers::default();
+                                        applied.clear();
+                                        scramble = None;
+                                        timer.reset();
+                                        is_timer_armed = false;
+                                        queue!(out, terminal::Clear(ClearType::All))?;
+                                    }
+                                    ConsoleEffect::Scramble(alg) => {
+                                        gcube.apply_movements(alg.movements());
+                                        applied.extend_from_slice(alg.movements());
+                                        scramble = Some(alg);
+                                        is_timer_armed = true;
+                                    }
+                                    ConsoleEffect::Turn(alg) => {
+                                        gcube.apply_movements(alg.movements());
+                                        applied.extend_from_slice(alg.movements());
+                                    }
+                                    ConsoleEffect::SaveSession => {
+                                        session.save(SESSION_PATH).ok();
+                                    }
+                                    ConsoleEffect::Print(line) => output = line,
+                                }
+                            }
+                        }
+                        Err(e) => output = e.to_string(),
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+        match key.code {
+            KeyCode::Esc => return Ok(()),
+            KeyCode::Tab => console = Some(String::new()),
+            KeyCode::Char(' ') => {
+                gcube = GCube::new(gcube.size);
+                let alg = random_scramble(gcube.size, scramble_length(gcube.size), &mut rng);
+                gcube.apply_movements(alg.movements());
+                applied = alg.movements().to_vec();
+                scramble = Some(alg);
+                timer.reset();
+                is_timer_armed = true;
+            }
+            KeyCode::Char(c @ '-') | KeyCode::Char(c @ '=') => {
+                let size = if c == '-' {
+                    gcube.size - 1
+                } else {
+                    gcube.size + 1
+                };
+                match gcube.resized(size).filter(|_| settings.resize_keeps_state) {
+                    Some(resized) => gcube = resized,
+                    None => gcube.change_size(size),
+                }
+                modifiers = Modifiers::default();
+                applied.clear();
+                scramble = None;
+                timer.reset();
+                is_timer_armed = false;
+                queue!(out, terminal::Clear(ClearType::All))?;
+            }
+            KeyCode::Char(c) => {
+                modifiers.is_alt_down = key.modifiers.contains(KeyModifiers::ALT);
+                let movements = keymap.movements([c], &mut modifiers, gcube.size);
+                if movements.is_empty() {
+                    continue;
+                }
+                if is_timer_armed {
+                    timer.start(now());
+                    is_timer_armed = false;
+                    scramble = None;
+                }
+                for movement in movements {
+                    timer.record(movement, now());
+                    gcube.apply_movement(&movement);
+                    applied.push(movement);
+                }
+                if timer.is_running() && gcube.is_solved() {
+                    timer.stop(now());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// draws the cube's net, two columns a sticker, with the scramble, timer,
+// console and help below it
+fn draw(
+    out: &mut impl Write,
+    gcube: &GCube,
+    settings: &Settings,
+    timer: &Timer,
+    scramble: Option<&Alg>,
+    status: &str,
+    now: f64,
+) -> io::Result<()> {
+    let n = gcube.size;
+    let facelets = gcube.facelets();
+    // where each face is in the net, in faces, ordered by U, R, F, D, L, then B face
+    let offsets = [(1, 0), (2, 1), (1, 1), (1, 2), (0, 1), (3, 1)];
+    for row in 0..3 * n {
+        queue!(out, cursor::MoveTo(0, row as u16))?;
+        for col in 0..4 * n {
+            let face = offsets.iter().position(|&o| o == (col / n, row / n));
+            match face {
+                Some(f) => {
+                    let color = sticker_color(
+                        facelets[f * n * n + row % n * n + col % n],
+                        settings.color_scheme,
+                    );
+                    queue!(
+                        out,
+                        style::SetBackgroundColor(color),
+                        style::Print("  "),
+                        style::ResetColor
+                    )?;
+                }
+                None => queue!(out, style::Print("  "))?,
+            }
+        }
+    }
+    let metric = settings.metric;
+    let time = format!(
+        "{:.2}  {} moves ({})  {:.2} TPS",
+        timer.elapsed(now),
+        timer.move_count(metric),
+        metric,
+        timer.tps(now, metric)
+    );
+    let scramble = scramble.map_or(String::new(), |alg| alg.to_string());
+    let y = 3 * n as u16 + 1;
+    for (i, line) in [scramble.as_str(), &time, status, HELP].iter().enumerate() {
+        queue!(
+            out,
+            cursor::MoveTo(0, y + i as u16),
+            terminal::Clear(ClearType::UntilNewLine),
+            style::Print(line)
+        )?;
+    }
+    out.flush()
+}
+
+fn sticker_color(face: Face, scheme: ColorScheme) -> Color {
+    let [r, g, b] = match face {
+        Face::X => [128, 128, 128],
+        face => scheme.color(face),
+    };
+    Color::Rgb { r, g, b }
+}
@@ -0,0 +1,187 @@
+use std::{fmt, str::FromStr};
+
+use thiserror::Error;
+
+use crate::{Alg, CubieCube, FaceletModel, Solver, StateError, CORNER_FACELETS, EDGE_FACELETS};
+
+/// The corner buffer of Old Pochmann, UBL, as a Speffz letter.
+pub const CORNER_BUFFER: char = 'A';
+/// The edge buffer of Old Pochmann, UR, as a Speffz letter.
+pub const EDGE_BUFFER: char = 'B';
+
+// where each face's Speffz letters start (in the order U, L, F, R, B, then D)
+// in the facelet model, and the facelets of the corner and edge letters on a
+// face, clockwise from the top left
+const LETTER_FACES: [usize; 6] = [0, 36, 18, 9, 45, 27];
+const CORNER_LETTERS: [usize; 4] = [0, 2, 8, 6];
+const EDGE_LETTERS: [usize; 4] = [1, 5, 7, 3];
+
+// the facelet a Speffz letter names, with letters going A to D around U,
+// E to H around L, and so on
+fn letter_facelet(letter: char, facelets: &[usize; 4]) -> Option<usize> {
+    let i = (letter as u8).checked_sub(b'A').filter(|&i| i < 24)? as usize;
+    Some(LETTER_FACES[i / 4] + facelets[i % 4])
+}
+
+// the piece a facelet is on, and which of the piece's facelets it is
+fn piece_of<const N: usize>(pieces: &[[usize; N]], facelet: usize) -> (usize, usize) {
+    pieces
+        .iter()
+        .enumerate()
+        .find_map(|(p, facelets)| Some((p, facelets.iter().position(|&f| f == facelet)?)))
+        .unwrap()
+}
+
+/// A blindfolded solver's memo, as the Speffz letters of the stickers each
+/// piece at the buffer is swapped to, in order, with Old Pochmann's buffers.
+/// It's written with the corners, then a slash, then the edges, e.g.
+/// "CK BD / CE", ignoring spaces and case.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Memo {
+    pub corners: Vec<char>,
+    pub edges: Vec<char>,
+}
+
+impl Memo {
+    /// Returns the cube the memo is of, or why it's not a real cube, e.g.
+    /// when the corners have an odd number of letters but the edges don't.
+    pub fn to_cubie_cube(&self) -> Result<CubieCube, StateError> {
+        let mut facelets = FaceletModel::new();
+        // the solve swaps each target with the buffer in turn, so the cube
+        // is undone by the same swaps, last first
+        let buffer = letter_facelet(CORNER_BUFFER, &CORNER_LETTERS).unwrap();
+        for &letter in self.corners.iter().rev() {
+            let target = letter_facelet(letter, &CORNER_LETTERS).unwrap();
+            swap(&mut facelets, &CORNER_FACELETS, buffer, target);
+        }
+        let buffer = letter_facelet(EDGE_BUFFER, &EDGE_LETTERS).unwrap();
+        for &letter in self.edges.iter().rev() {
+            let target = letter_facelet(letter, &EDGE_LETTERS).unwrap();
+            swap(&mut facelets, &EDGE_FACELETS, buffer, target);
+        }
+        CubieCube::from_facelets(&facelets)
+    }
+
+    /// Returns an alg that sets the memo's cube up from solved, e.g. to
+    /// practice a particular memo.
+    pub fn setup(&self) -> Result<Alg, StateError> {
+        let cube = self.to_cubie_cube()?;
+        Ok(Solver::shared().solve(&cube, 50).inverse())
+    }
+}
+
+// swaps the pieces with facelets a and b, so a's sticker goes to b, b's to a,
+// and the rest of their stickers follow
+fn swap<const N: usize>(facelets: &mut FaceletModel, pieces: &[[usize; N]], a: usize, b: usize) {
+    let ((piece_a, i), (piece_b, j)) = (piece_of(pieces, a), piece_of(pieces, b));
+    for k in 0..N {
+        facelets
+            .0
+            .swap(pieces[piece_a][(i + k) % N], pieces[piece_b][(j + k) % N]);
+    }
+}
+
+impl fmt::Display for Memo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let pairs = |letters: &[char]| {
+            let pairs: Vec<String> = letters.chunks(2).map(|p| p.iter().collect()).collect();
+            pairs.join(" ")
+        };
+        write!(f, "{} / {}", pairs(&self.corners), pairs(&self.edges))
+    }
+}
+
+/// Error for text that isn't a valid memo.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+#[error("Failed to parse memo {0}, expected Speffz letters of corners / edges, not on the buffers")]
+pub struct ParseMemoError(pub String);
+
+impl FromStr for Memo {
+    type Err = ParseMemoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseMemoError(s.to_string());
+        let mut parts = s.split('/');
+        let (corners, edges) = (
+            parts.next().unwrap_or_default(),
+            parts.next().unwrap_or_default(),
+        );
+        if parts.next().is_some() {
+            return Err(error());
+        }
+        Ok(Memo {
+            corners: parse_letters(corners, CORNER_BUFFER, &CORNER_LETTERS, &CORNER_FACELETS)
+                .ok_or_else(error)?,
+            edges: parse_letters(edges, EDGE_BUFFER, &EDGE_LETTERS, &EDGE_FACELETS)
+                .ok_or_else(error)?,
+        })
+    }
+}
+
+// parses the letters of pieces, none of which can be the buffer's own
+fn parse_letters<const N: usize>(
+    s: &str,
+    buffer: char,
+    letter_facelets: &[usize; 4],
+    pieces: &[[usize; N]],
+) -> Option<Vec<char>> {
+    let buffer = piece_of(pieces, letter_facelet(buffer, letter_facelets)?).0;
+    s.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| {
+            let letter = c.to_ascii_uppercase();
+            let piece = piece_of(pieces, letter_facelet(letter, letter_facelets)?).0;
+            Some(letter).filter(|_| piece != buffer)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Face, GCube};
+
+    #[test]
+    fn letters_name_speffz_stickers() {
+        // UFR's U, L's top right (UFL) and DBR's B stickers
+        assert_eq!(letter_facelet('C', &CORNER_LETTERS), Some(8));
+        assert_eq!(letter_facelet('F', &CORNER_LETTERS), Some(38));
+        assert_eq!(letter_facelet('T', &CORNER_LETTERS), Some(51));
+        // UR's U, FR's R and DL's D stickers
+        assert_eq!(letter_facelet('B', &EDGE_LETTERS), Some(5));
+        assert_eq!(letter_facelet('P', &EDGE_LETTERS), Some(12));
+        assert_eq!(letter_facelet('X', &EDGE_LETTERS), Some(30));
+        assert_eq!(letter_facelet('Y', &EDGE_LETTERS), None);
+    }
+
+    #[test]
+    fn memos_set_up_their_cube() {
+        let memo: Memo = "jk / ux".parse().unwrap();
+        assert_eq!(memo.to_string(), "JK / UX");
+        let cube = memo.to_cubie_cube().unwrap();
+        let setup = memo.setup().unwrap();
+        let mut gcube = GCube::new(3);
+        gcube.apply_movements(setup.movements());
+        assert_eq!(gcube.to_facelet_model(), cube.to_facelets());
+        // the buffers' stickers belong where the first letters are, F of UFR
+        // and D of DF
+        assert_eq!(cube.to_facelets()[0], Face::F);
+        assert_eq!(cube.to_facelets()[5], Face::D);
+
+        // twisting UBR, by swapping the buffer to two of its stickers
+        let cube = Memo::from_str("BQ /").unwrap().to_cubie_cube().unwrap();
+        assert_eq!(cube.cp, CubieCube::default().cp);
+        assert_eq!(cube.co.iter().filter(|&&o| o != 0).count(), 2);
+        assert_eq!(
+            Memo::from_str("C /").unwrap().to_cubie_cube(),
+            Err(StateError::SwappedPieces)
+        );
+        assert_eq!(Memo::from_str("").unwrap().setup().unwrap().len(), 0);
+        // E is on the corner buffer, M on the edge buffer
+        assert!(Memo::from_str("E / C").is_err());
+        assert!(Memo::from_str("C / M").is_err());
+        assert!(Memo::from_str("C / D / F").is_err());
+        assert!(Memo::from_str("C1").is_err());
+        assert!(Memo::from_str("CK BD / CE").unwrap().to_cubie_cube().is_ok());
+    }
+}
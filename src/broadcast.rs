@@ -0,0 +1,189 @@
+use std::{io, net::ToSocketAddrs};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Alg, ClientId, CubeObserver, GCube, Movement, ServerEvent, WebSocketServer};
+
+/// What happened to a cube, sent to overlays as a JSON object tagged by its
+/// type, e.g. `{"type":"move","movement":"R'"}`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CubeEvent {
+    Move {
+        movement: Movement,
+    },
+    Scramble {
+        scramble: Alg,
+    },
+    Solved,
+    /// the colour of every sticker as faces, ordered by U, R, F, D, L, then
+    /// B face, e.g. "UUUUUUUUURRR..." for a solved 3x3
+    State {
+        size: usize,
+        facelets: String,
+        solved: bool,
+    },
+    /// why a client's command couldn't be done, sent to just that client
+    Error {
+        message: String,
+    },
+}
+
+impl CubeEvent {
+    pub fn state(gcube: &GCube) -> Self {
+        CubeEvent::State {
+            size: gcube.size,
+            facelets: gcube.facelets().iter().map(|f| f.to_string()).collect(),
+            solved: gcube.is_solved(),
+        }
+    }
+}
+
+/// Broadcasts what happens to a cube to every connected WebSocket client,
+/// e.g. browser overlays for streams. Clients are sent the cube's state when
+/// they connect, and again whenever it changes.
+pub struct BroadcastServer {
+    server: WebSocketServer,
+    state: Option<CubeEvent>, // the last state sent
+}
+
+impl BroadcastServer {
+    /// Listens for clients at addr, e.g. "127.0.0.1:9001".
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Self {
+            server: WebSocketServer::bind(addr)?,
+            state: None,
+        })
+    }
+
+    pub fn server(&self) -> &WebSocketServer {
+        &self.server
+    }
+
+    /// Sends an event to every client.
+    pub fn send(&mut self, event: &CubeEvent) {
+        // events are plain data, so they always serialize
+        self.server
+            .broadcast(&serde_json::to_string(event).unwrap());
+    }
+
+    pub fn send_to(&mut self, client: ClientId, event: &CubeEvent) {
+        self.server
+            .send(client, &serde_json::to_string(event).unwrap());
+    }
+
+    /// Accepts new clients, and sends the state of gcube if it changed,
+    /// along with a Solved event if it's newly solved. Call once a frame.
+    /// Returns the server's events, e.g. messages from clients.
+    pub fn update(&mut self, gcube: &GCube) -> Vec<ServerEvent> {
+        let events = self.server.poll();
+        for event in events.iter() {
+            if let (ServerEvent::Connected(id), Some(last)) = (event, self.state.clone()) {
+                self.send_to(*id, &last);
+            }
+        }
+        self.send_state(gcube);
+        events
+    }
+
+    // sends the state of gcube if it changed, along with a Solved event if
+    // it's newly solved
+    fn send_state(&mut self, gcube: &GCube) {
+        let state = CubeEvent::state(gcube);
+        if self.state.as_ref() != Some(&state) {
+            let was_solved = matches!(self.state, Some(CubeEvent::State { solved: true, .. }));
+            self.send(&state);
+            if gcube.is_solved() && !was_solved && self.state.is_some() {
+                self.send(&CubeEvent::Solved);
+            }
+            self.state = Some(state);
+        }
+    }
+}
+
+// events are sent as they happen, while clients' messages are left for update
+impl CubeObserver for BroadcastServer {
+    fn on_move(&mut self, movement: Movement, gcube: &GCube) {
+        self.send(&CubeEvent::Move { movement });
+        self.send_state(gcube);
+    }
+
+    fn on_scramble(&mut self, scramble: &Alg, gcube: &GCube) {
+        self.send(&CubeEvent::Scramble {
+            scramble: scramble.clone(),
+        });
+        self.send_state(gcube);
+    }
+
+    // followers mirror moves, so a seek is sent as the moves it makes
+    fn on_seek(&mut self, movements: &[Movement], gcube: &GCube) {
+        for &movement in movements {
+            self.send(&CubeEvent::Move { movement });
+        }
+        self.send_state(gcube);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ObservedCube, WebSocketClient};
+    use std::{str::FromStr, thread, time::Duration};
+
+    #[test]
+    fn events_are_tagged_json() {
+        let event = CubeEvent::Move {
+            movement: Movement::from_str("R'").unwrap(),
+        };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"type":"move","movement":"R'"}"#
+        );
+        let json = serde_json::to_string(&CubeEvent::state(&GCube::new(1))).unwrap();
+        assert_eq!(
+            json,
+            r#"{"type":"state","size":1,"facelets":"URFDLB","solved":true}"#
+        );
+    }
+
+    #[test]
+    fn clients_follow_the_cube() {
+        let mut server = BroadcastServer::bind("127.0.0.1:0").unwrap();
+        let addr = server.server().local_addr().unwrap();
+        let client = thread::spawn(move || WebSocketClient::connect(addr).unwrap());
+        let is_connected = |e: &ServerEvent| matches!(e, ServerEvent::Connected(_));
+        while !server.update(&GCube::new(2)).iter().any(is_connected) {
+            thread::sleep(Duration::from_millis(10));
+        }
+        let mut client = client.join().unwrap();
+        let mut cube = ObservedCube::new(GCube::new(2));
+        cube.subscribe(Box::new(server));
+        let r = Movement::from_str("R").unwrap();
+        cube.apply_movements(&[r, r.inverse()]);
+
+        let mut events: Vec<CubeEvent> = vec![];
+        for _ in 0..500 {
+            let messages = client.poll();
+            events.extend(messages.iter().map(|m| serde_json::from_str(m).unwrap()));
+            if events.len() >= 6 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        let mut turned = GCube::new(2);
+        turned.apply_movement(&r);
+        assert_eq!(
+            events,
+            [
+                CubeEvent::state(&GCube::new(2)),
+                CubeEvent::Move { movement: r },
+                CubeEvent::state(&turned),
+                CubeEvent::Move {
+                    movement: r.inverse()
+                },
+                CubeEvent::state(&GCube::new(2)),
+                CubeEvent::Solved,
+            ]
+        );
+    }
+}
@@ -0,0 +1,314 @@
+use std::{fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Alg, Move, Movement, Rng, Turn};
+
+/// How many moves more than a case's alg an attempt can take and still
+/// succeed, e.g. for AUFs or a different alg.
+pub const EXTRA_MOVES: usize = 4;
+
+/// A last layer (or last slot) case, set up by undoing the alg solving it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Case {
+    pub name: String,
+    pub alg: Alg,
+}
+
+/// A set of cases to train, e.g. the OLLs, PLLs or CMLLs.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CaseSet {
+    pub name: String,
+    pub cases: Vec<Case>,
+}
+
+impl CaseSet {
+    /// Returns the 21 PLLs.
+    pub fn pll() -> Self {
+        let cases = [
+            ("Aa", "x R' U R' D2 R U' R' D2 R2 x'"),
+            ("Ab", "x R2 D2 R U R' D2 R U' R x'"),
+            ("E", "x' R U' R' D R U R' D' R U R' D R U' R' D' x"),
+            ("F", "R' U' F' R U R' U' R' F R2 U' R' U' R U R' U R"),
+            ("Ga", "R2 U R' U R' U' R U' R2 U' D R' U R D'"),
+            ("Gb", "R' U' R U D' R2 U R' U R U' R U' R2 D"),
+            ("Gc", "R2 U' R U' R U R' U R2 U D' R U' R' D"),
+            ("Gd", "R U R' U' D R2 U' R U' R' U R' U R2 D'"),
+            ("H", "M2 U M2 U2 M2 U M2"),
+            ("Ja", "R' U L' U2 R U' R' U2 R L"),
+            ("Jb", "R U R' F' R U R' U' R' F R2 U' R'"),
+            (
+                "Na",
+                "R U R' U R U R' F' R U R' U' R' F R2 U' R' U2 R U' R'",
+            ),
+            ("Nb", "R' U R U' R' F' U' F R U R' F R' F' R U' R"),
+            ("Ra", "R U' R' U' R U R D R' U' R D' R' U2 R'"),
+            ("Rb", "R2 F R U R U' R' F' R U2 R' U2 R"),
+            ("T", "R U R' U' R' F R2 U' R' U' R U R' F'"),
+            ("Ua", "M2 U M U2 M' U M2"),
+            ("Ub", "M2 U' M U2 M' U' M2"),
+            ("V", "R U' R U R' D R D' R U' D R2 U R2 D' R2"),
+            ("Y", "F R U' R' U' R U R' F' R U R' U' R' F R F'"),
+            ("Z", "M' U M2 U M2 U M' U2 M2"),
+        ];
+        Self {
+            name: "PLL".to_string(),
+            cases: cases
+                .iter()
+                .map(|&(name, alg)| Case {
+                    name: name.to_string(),
+                    alg: alg.parse().unwrap(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the PLLs, followed by the sets in a JSON file of them (e.g.
+    /// OLL or CMLL), if there is one.
+    pub fn load_all(path: impl AsRef<Path>) -> io::Result<Vec<Self>> {
+        let mut sets = vec![Self::pll()];
+        match fs::read_to_string(path) {
+            Ok(json) => sets.extend(serde_json::from_str::<Vec<Self>>(&json)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        Ok(sets)
+    }
+}
+
+/// An attempt at a case: how long it took to recognise (from being set up
+/// to the first move) and to execute (from the first move to solved).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CaseAttempt {
+    pub set: String,
+    pub case: String,
+    /// in seconds
+    pub recognition: f64,
+    /// in seconds
+    pub execution: f64,
+    /// whether it was solved within EXTRA_MOVES of its alg, rather than
+    /// taking longer or being skipped
+    pub success: bool,
+}
+
+/// The attempts at a case.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct CaseStats {
+    pub attempts: usize,
+    pub successes: usize,
+    /// the mean times of successful attempts
+    pub recognition: Option<f64>,
+    pub execution: Option<f64>,
+}
+
+impl CaseStats {
+    /// Returns the stats of the attempts at a set's case.
+    pub fn new(attempts: &[CaseAttempt], set: &str, case: &str) -> Self {
+        let attempts: Vec<&CaseAttempt> = attempts
+            .iter()
+            .filter(|a| a.set == set && a.case == case)
+            .collect();
+        let successes: Vec<&&CaseAttempt> = attempts.iter().filter(|a| a.success).collect();
+        let mean = |time: fn(&CaseAttempt) -> f64| {
+            let total: f64 = successes.iter().map(|a| time(a)).sum();
+            (!successes.is_empty()).then(|| total / successes.len() as f64)
+        };
+        Self {
+            attempts: attempts.len(),
+            successes: successes.len(),
+            recognition: mean(|a| a.recognition),
+            execution: mean(|a| a.execution),
+        }
+    }
+
+    /// Returns the share of attempts that succeeded, or None if there
+    /// weren't any.
+    pub fn success_rate(&self) -> Option<f64> {
+        (self.attempts > 0).then(|| self.successes as f64 / self.attempts as f64)
+    }
+}
+
+/// Trains a set's cases with spaced repetition (Leitner boxes): a case comes
+/// up again after an attempt at another case, twice as many each time it's
+/// succeeded at and back to one when it's failed, so weak cases come up
+/// most. Cases falling due come up before untried ones.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CaseTrainer {
+    pub set: CaseSet,
+    /// the index of the case being trained
+    pub case: usize,
+    /// when the case was set up, in seconds from any fixed point
+    pub shown_at: Option<f64>,
+    // each case's box, the log2 of its interval
+    boxes: Vec<u32>,
+    // the attempt each case is next due at
+    due: Vec<usize>,
+    attempts: usize,
+}
+
+impl CaseTrainer {
+    /// Returns a trainer of set, scheduled as if attempts (of any set) had
+    /// been made with it.
+    pub fn new(set: CaseSet, attempts: &[CaseAttempt], rng: &mut Rng) -> Self {
+        let n = set.cases.len();
+        let name = set.name.clone();
+        let mut trainer = Self {
+            set,
+            case: 0,
+            shown_at: None,
+            boxes: vec![0; n],
+            due: vec![0; n],
+            attempts: 0,
+        };
+        for attempt in attempts.iter().filter(|a| a.set == name) {
+            if let Some(case) = trainer
+                .set
+                .cases
+                .iter()
+                .position(|c| c.name == attempt.case)
+            {
+                trainer.schedule(case, attempt.success);
+            }
+        }
+        trainer.next_case(rng);
+        trainer
+    }
+
+    /// Returns the case being trained.
+    pub fn current(&self) -> &Case {
+        &self.set.cases[self.case]
+    }
+
+    /// Moves on to the case that fell due last, or else the one due soonest,
+    /// picking at random between cases due at once.
+    pub fn next_case(&mut self, rng: &mut Rng) {
+        let is_due = |&d: &usize| d <= self.attempts;
+        let next = match self.due.iter().copied().filter(is_due).max() {
+            Some(latest) => Some(latest),
+            None => self.due.iter().copied().min(),
+        };
+        let cases: Vec<usize> = (0..self.due.len())
+            .filter(|&i| Some(self.due[i]) == next)
+            .collect();
+        if !cases.is_empty() {
+            self.case = cases[rng.below(cases.len())];
+        }
+        self.shown_at = None;
+    }
+
+    /// Returns the setup of the case, from a solved cube: its alg undone,
+    /// with a random U turn before and after so it's seen from any side.
+    pub fn setup(&mut self, now: f64, rng: &mut Rng) -> Alg {
+        self.shown_at = Some(now);
+        let auf = |rng: &mut Rng| {
+            let turns = [Turn::Single, Turn::Double, Turn::Inverse];
+            Alg::new(vec![Movement::new(Move::U, turns[rng.below(3)])])
+        };
+        auf(rng).then(self.current().alg.inverse()).then(auf(rng))
+    }
+
+    /// Returns an attempt at the case, started (by the first move) at
+    /// started_at and solved (or skipped) after execution seconds and moves
+    /// moves, and reschedules the case by whether it succeeded.
+    pub fn attempt(
+        &mut self,
+        started_at: f64,
+        execution: f64,
+        moves: Option<usize>,
+    ) -> CaseAttempt {
+        let max_moves = self.current().alg.len() + EXTRA_MOVES;
+        let success = moves.is_some_and(|n| n <= max_moves);
+        self.schedule(self.case, success);
+        CaseAttempt {
+            set: self.set.name.clone(),
+            case: self.current().name.clone(),
+            recognition: self
+                .shown_at
+                .map_or(0., |shown| (started_at - shown).max(0.)),
+            execution,
+            success,
+        }
+    }
+
+    fn schedule(&mut self, case: usize, success: bool) {
+        self.attempts += 1;
+        self.boxes[case] = if success { self.boxes[case] + 1 } else { 0 };
+        self.due[case] = self.attempts + 2usize.pow(self.boxes[case].min(10));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Face, GCube};
+
+    #[test]
+    fn plls_only_permute_the_last_layer() {
+        for case in CaseSet::pll().cases {
+            let mut gcube = GCube::new(3);
+            gcube.apply_movements(case.alg.movements());
+            let facelets = gcube.to_facelet_model();
+            let solved = GCube::new(3).to_facelet_model();
+            let is_oriented = (0..9).all(|i| facelets[i] == Face::U);
+            // the bottom two rows of each side, and D
+            let is_f2l_solved = (9..54).all(|i| i % 9 < 3 || facelets[i] == solved[i]);
+            assert!(is_oriented && is_f2l_solved, "{}", case.name);
+            assert!(!gcube.is_solved(), "{}", case.name);
+        }
+    }
+
+    #[test]
+    fn failed_cases_come_up_sooner() {
+        let mut rng = Rng::new(3);
+        let mut trainer = CaseTrainer::new(CaseSet::pll(), &[], &mut rng);
+        let failed = trainer.case;
+        trainer.setup(1., &mut rng);
+        let attempt = trainer.attempt(3., 2., None);
+        assert!(!attempt.success);
+        assert_eq!(attempt.recognition, 2.);
+        trainer.next_case(&mut rng);
+        assert_ne!(trainer.case, failed);
+        let moves = trainer.current().alg.len();
+        assert!(trainer.attempt(0., 1., Some(moves + EXTRA_MOVES)).success);
+        // due again after one other attempt, before any untried case
+        trainer.next_case(&mut rng);
+        assert_eq!(trainer.case, failed);
+
+        // cases succeeded at wait longer than ones failed
+        let attempt = |case: &str, success| CaseAttempt {
+            set: "PLL".to_string(),
+            case: case.to_string(),
+            recognition: 1.,
+            execution: 2.,
+            success,
+        };
+        let attempts = [attempt("Y", false), attempt("T", true)];
+        let trainer = CaseTrainer::new(CaseSet::pll(), &attempts, &mut rng);
+        let index = |name| {
+            trainer
+                .set
+                .cases
+                .iter()
+                .position(|c| c.name == name)
+                .unwrap()
+        };
+        assert!(trainer.due[index("T")] > trainer.due[index("Y")]);
+    }
+
+    #[test]
+    fn stats_of_successful_attempts() {
+        let attempt = |execution, success| CaseAttempt {
+            set: "PLL".to_string(),
+            case: "T".to_string(),
+            recognition: 1.,
+            execution,
+            success,
+        };
+        let attempts = [attempt(2., true), attempt(9., false), attempt(3., true)];
+        let stats = CaseStats::new(&attempts, "PLL", "T");
+        assert_eq!(stats.attempts, 3);
+        assert_eq!(stats.execution, Some(2.5));
+        assert_eq!(stats.success_rate(), Some(2. / 3.));
+        assert_eq!(CaseStats::new(&attempts, "PLL", "Y").success_rate(), None);
+    }
+}
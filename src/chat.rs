@@ -0,0 +1,301 @@
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, BufReader, Write},
+    net::TcpStream,
+};
+
+use crate::{ChatMode, ChatSettings, Command, Movement};
+
+const TWITCH_ADDRESS: &str = "irc.chat.twitch.tv:6667";
+
+/// A message sent to a chat channel.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChatMessage {
+    /// the sender's login name, in lowercase
+    pub user: String,
+    pub text: String,
+}
+
+impl ChatMessage {
+    /// Parses a chat message from a line of Twitch's IRC, e.g.
+    /// `:viewer!viewer@viewer.tmi.twitch.tv PRIVMSG #channel :R'`, or returns
+    /// None for other lines.
+    pub fn parse_irc(line: &str) -> Option<Self> {
+        // tags come first, when they're asked for
+        let line = match line.strip_prefix('@') {
+            Some(tagged) => tagged.split_once(' ')?.1,
+            None => line,
+        };
+        let (prefix, rest) = line.strip_prefix(':')?.split_once(' ')?;
+        let user = prefix.split('!').next()?.to_lowercase();
+        let (_channel, text) = rest.strip_prefix("PRIVMSG ")?.split_once(" :")?;
+        Some(Self {
+            user,
+            text: text.trim().to_string(),
+        })
+    }
+}
+
+/// Reads a Twitch channel's chat anonymously, over IRC.
+pub struct TwitchChat {
+    reader: BufReader<TcpStream>,
+    stream: TcpStream,
+    line: String,
+    is_closed: bool,
+}
+
+impl TwitchChat {
+    /// Joins the chat of channel, e.g. "stanleydesu", blocking until
+    /// connected.
+    pub fn connect(channel: &str) -> io::Result<Self> {
+        let mut stream = TcpStream::connect(TWITCH_ADDRESS)?;
+        // justinfan and a number is Twitch's anonymous, read only login
+        write!(
+            stream,
+            "NICK justinfan{}\r\nJOIN #{}\r\n",
+            std::process::id(),
+            channel.trim_start_matches('#').to_lowercase()
+        )?;
+        stream.set_nonblocking(true)?;
+        Ok(Self {
+            reader: BufReader::new(stream.try_clone()?),
+            stream,
+            line: String::new(),
+            is_closed: false,
+        })
+    }
+
+    /// Returns the messages received since the last poll, without blocking,
+    /// answering Twitch's pings to stay connected.
+    pub fn poll(&mut self) -> Vec<ChatMessage> {
+        let mut messages = vec![];
+        while !self.is_closed {
+            match self.reader.read_line(&mut self.line) {
+                Ok(0) => self.is_closed = true,
+                Ok(_) if self.line.ends_with('\n') => {
+                    let line = std::mem::take(&mut self.line);
+                    let line = line.trim_end();
+                    if let Some(server) = line.strip_prefix("PING ") {
+                        if write!(self.stream, "PONG {}\r\n", server).is_err() {
+                            self.is_closed = true;
+                        }
+                    }
+                    messages.extend(ChatMessage::parse_irc(line));
+                }
+                // the rest of the line hasn't arrived yet
+                Ok(_) => break,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(_) => self.is_closed = true,
+            }
+        }
+        messages
+    }
+
+    /// Whether Twitch closed the connection, or it was lost.
+    pub fn is_closed(&self) -> bool {
+        self.is_closed
+    }
+}
+
+/// Turns chat messages into commands, rate limiting viewers or tallying
+/// their votes as the settings say. Messages that aren't a single movement
+/// (e.g. R', 2U or x) are ordinary chatter and ignored.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChatControl {
+    settings: ChatSettings,
+    // when each viewer last had a move turned, for the cooldown
+    last_moves: HashMap<String, f64>,
+    // each viewer's vote in the open vote, and when it opened
+    votes: Vec<(String, Movement)>,
+    vote_opened_at: Option<f64>,
+}
+
+impl ChatControl {
+    pub fn new(settings: ChatSettings) -> Self {
+        Self {
+            settings,
+            last_moves: HashMap::new(),
+            votes: vec![],
+            vote_opened_at: None,
+        }
+    }
+
+    fn is_moderator(&self, user: &str) -> bool {
+        self.settings
+            .moderators
+            .iter()
+            .any(|m| m.eq_ignore_ascii_case(user))
+    }
+
+    /// Handles a message sent at now (in seconds from any fixed point),
+    /// returning the command to run at once, if any.
+    pub fn submit(&mut self, message: &ChatMessage, now: f64) -> Option<Command> {
+        if message.text == "!scramble" && self.is_moderator(&message.user) {
+            self.votes.clear();
+            self.vote_opened_at = None;
+            return Some(Command::Scramble { scramble: None });
+        }
+        let movement: Movement = message.text.parse().ok()?;
+        match self.settings.mode {
+            ChatMode::Direct => {
+                let cooldown = self.settings.cooldown as f64;
+                let last = self.last_moves.get(&message.user);
+                if last.is_some_and(|&t| now - t < cooldown) && !self.is_moderator(&message.user) {
+                    return None;
+                }
+                self.last_moves.insert(message.user.clone(), now);
+                Some(Command::Move { movement })
+            }
+            ChatMode::Vote => {
+                // a viewer's latest vote counts
+                self.votes.retain(|(user, _)| *user != message.user);
+                self.votes.push((message.user.clone(), movement));
+                self.vote_opened_at.get_or_insert(now);
+                None
+            }
+        }
+    }
+
+    /// Returns the open vote's movements, most voted first, with their
+    /// votes and the seconds left to vote, if a vote is open.
+    pub fn tally(&self, now: f64) -> Option<(Vec<(Movement, usize)>, f64)> {
+        let opened_at = self.vote_opened_at?;
+        let mut tally: Vec<(Movement, usize)> = vec![];
+        for &(_, movement) in &self.votes {
+            match tally.iter_mut().find(|(m, _)| *m == movement) {
+                Some((_, n)) => *n += 1,
+                None => tally.push((movement, 1)),
+            }
+        }
+        // stable, so ties go to the movement voted for first
+        tally.sort_by_key(|&(_, n)| std::cmp::Reverse(n));
+        let left = (opened_at + self.settings.vote_duration as f64 - now).max(0.);
+        Some((tally, left))
+    }
+
+    /// Closes the open vote once its time's up, returning the winning
+    /// movement's command. Call once a frame.
+    pub fn update(&mut self, now: f64) -> Option<Command> {
+        let (tally, left) = self.tally(now)?;
+        if left > 0. {
+            return None;
+        }
+        self.votes.clear();
+        self.vote_opened_at = None;
+        let &(movement, _) = tally.first()?;
+        Some(Command::Move { movement })
+    }
+}
+
+/// A Twitch chat solving the cube, "chat solves the cube".
+pub struct ChatSolve {
+    chat: TwitchChat,
+    pub control: ChatControl,
+}
+
+impl ChatSolve {
+    /// Joins the chat of the settings' channel.
+    pub fn connect(settings: &ChatSettings) -> io::Result<Self> {
+        Ok(Self {
+            chat: TwitchChat::connect(&settings.channel)?,
+            control: ChatControl::new(settings.clone()),
+        })
+    }
+
+    /// Handles the chat's messages since the last update, returning the
+    /// moves and scrambles to run as commands. Call once a frame.
+    pub fn update(&mut self, now: f64) -> Vec<Command> {
+        let mut commands: Vec<Command> = self
+            .chat
+            .poll()
+            .iter()
+            .filter_map(|message| self.control.submit(message, now))
+            .collect();
+        commands.extend(self.control.update(now));
+        commands
+    }
+
+    /// Whether the chat's connection was lost.
+    pub fn is_closed(&self) -> bool {
+        self.chat.is_closed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(user: &str, text: &str) -> ChatMessage {
+        ChatMessage {
+            user: user.to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    fn turn(s: &str) -> Option<Command> {
+        Some(Command::Move {
+            movement: s.parse().unwrap(),
+        })
+    }
+
+    #[test]
+    fn parses_irc_messages() {
+        let line = ":Viewer!viewer@viewer.tmi.twitch.tv PRIVMSG #stanleydesu :R' ";
+        assert_eq!(ChatMessage::parse_irc(line), Some(message("viewer", "R'")));
+        let tagged = "@badges=;color= :viewer!viewer@viewer.tmi.twitch.tv PRIVMSG #c :hi :)";
+        assert_eq!(
+            ChatMessage::parse_irc(tagged),
+            Some(message("viewer", "hi :)"))
+        );
+        assert_eq!(ChatMessage::parse_irc("PING :tmi.twitch.tv"), None);
+        let join = ":viewer!viewer@viewer.tmi.twitch.tv JOIN #stanleydesu";
+        assert_eq!(ChatMessage::parse_irc(join), None);
+    }
+
+    #[test]
+    fn direct_moves_are_rate_limited() {
+        let settings = ChatSettings {
+            mode: ChatMode::Direct,
+            cooldown: 5.,
+            moderators: vec!["Mod".to_string()],
+            ..ChatSettings::default()
+        };
+        let mut control = ChatControl::new(settings);
+        assert_eq!(control.submit(&message("a", "R"), 0.), turn("R"));
+        assert_eq!(control.submit(&message("a", "U"), 1.), None);
+        assert_eq!(control.submit(&message("b", "U"), 1.), turn("U"));
+        assert_eq!(control.submit(&message("a", "U"), 5.), turn("U"));
+        assert_eq!(control.submit(&message("c", "gg"), 5.), None);
+        // moderators aren't limited, and can scramble
+        assert_eq!(control.submit(&message("mod", "F"), 5.), turn("F"));
+        assert_eq!(control.submit(&message("mod", "F"), 5.), turn("F"));
+        assert_eq!(
+            control.submit(&message("mod", "!scramble"), 5.),
+            Some(Command::Scramble { scramble: None })
+        );
+        assert_eq!(control.submit(&message("a", "!scramble"), 9.), None);
+    }
+
+    #[test]
+    fn votes_turn_the_most_voted_move() {
+        let settings = ChatSettings {
+            mode: ChatMode::Vote,
+            vote_duration: 10.,
+            ..ChatSettings::default()
+        };
+        let mut control = ChatControl::new(settings);
+        assert_eq!(control.update(0.), None);
+        for (user, text) in [("a", "R"), ("b", "U"), ("c", "U"), ("a", "U'"), ("d", "R")] {
+            assert_eq!(control.submit(&message(user, text), 2.), None);
+        }
+        let (tally, left) = control.tally(4.).unwrap();
+        assert_eq!(tally[0], ("U".parse().unwrap(), 2));
+        assert_eq!(tally.len(), 3);
+        assert_eq!(left, 8.);
+        assert_eq!(control.update(11.), None);
+        // a changed their vote, leaving U the most voted
+        assert_eq!(control.update(12.), turn("U"));
+        assert_eq!(control.tally(12.), None);
+    }
+}
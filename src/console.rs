@@ -0,0 +1,247 @@
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::{
+    random_scramble, scramble_length, solve, Alg, GCube, ParseMovementError, Rng, Session,
+    StateError,
+};
+
+/// The commands a console accepts, one per line, for its help.
+pub const CONSOLE_HELP: &str =
+    "scramble [size], apply <alg>, solve, save <name>, load <name>, reset [size], help";
+
+/// A command typed into a console, e.g. `apply R U R'`. Every frontend runs
+/// commands the same way, carrying out the effects they return.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConsoleCommand {
+    /// scrambles the cube from solved, resizing it first if there's a size
+    Scramble(Option<usize>),
+    /// turns the cube by an alg
+    Apply(Alg),
+    /// turns a 3x3 by the solver's solution
+    Solve,
+    /// bookmarks the cube's state under a name
+    Save(String),
+    /// sets the cube up in a bookmarked state
+    Load(String),
+    /// solves the cube (as if by magic), resizing it first if there's a size
+    Reset(Option<usize>),
+    Help,
+}
+
+/// Something a frontend does to carry out a command.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConsoleEffect {
+    /// starts over from a solved NxN cube
+    Reset(usize),
+    /// scrambles the cube, which is solved
+    Scramble(Alg),
+    /// turns the cube
+    Turn(Alg),
+    /// the session changed, e.g. with a new bookmark, so it should be saved
+    SaveSession,
+    /// a line of output, e.g. a solution
+    Print(String),
+}
+
+/// Why a command couldn't be run.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum ConsoleError {
+    #[error("Unknown command {0}, try help")]
+    UnknownCommand(String),
+    #[error("{command} needs {argument}")]
+    MissingArgument {
+        command: &'static str,
+        argument: &'static str,
+    },
+    #[error("{0} isn't a cube size")]
+    InvalidSize(String),
+    #[error("{0}")]
+    InvalidAlg(#[from] ParseMovementError),
+    #[error("No bookmark named {0}")]
+    NoBookmark(String),
+    #[error("Only a 3x3 can be solved")]
+    NotThreeByThree,
+    #[error("Can't solve it: {0}")]
+    Unsolvable(#[from] StateError),
+}
+
+impl FromStr for ConsoleCommand {
+    type Err = ConsoleError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let line = line.trim();
+        let (name, argument) = match line.find(' ') {
+            Some(i) => (&line[..i], line[i + 1..].trim()),
+            None => (line, ""),
+        };
+        let size = || match argument {
+            "" => Ok(None),
+            s => match s.parse() {
+                Ok(size) if size > 0 => Ok(Some(size)),
+                _ => Err(ConsoleError::InvalidSize(s.to_string())),
+            },
+        };
+        let name_of = |command| match argument {
+            "" => Err(ConsoleError::MissingArgument {
+                command,
+                argument: "a name",
+            }),
+            name => Ok(name.to_string()),
+        };
+        match name {
+            "scramble" => Ok(ConsoleCommand::Scramble(size()?)),
+            "apply" if argument.is_empty() => Err(ConsoleError::MissingArgument {
+                command: "apply",
+                argument: "an alg",
+            }),
+            "apply" => Ok(ConsoleCommand::Apply(argument.parse()?)),
+            "solve" => Ok(ConsoleCommand::Solve),
+            "save" => Ok(ConsoleCommand::Save(name_of("save")?)),
+            "load" => Ok(ConsoleCommand::Load(name_of("load")?)),
+            "reset" => Ok(ConsoleCommand::Reset(size()?)),
+            "help" => Ok(ConsoleCommand::Help),
+            _ => Err(ConsoleError::UnknownCommand(name.to_string())),
+        }
+    }
+}
+
+impl ConsoleCommand {
+    /// Returns what a frontend does to run the command on gcube, which
+    /// applied turned from solved (for bookmarks), saving bookmarks to
+    /// session.
+    pub fn run(
+        &self,
+        gcube: &GCube,
+        applied: &Alg,
+        session: &mut Session,
+        rng: &mut Rng,
+    ) -> Result<Vec<ConsoleEffect>, ConsoleError> {
+        let effects = match self {
+            ConsoleCommand::Scramble(size) => {
+                let size = size.unwrap_or(gcube.size);
+                let alg = random_scramble(size, scramble_length(size), rng);
+                vec![ConsoleEffect::Reset(size), ConsoleEffect::Scramble(alg)]
+            }
+            ConsoleCommand::Apply(alg) => vec![ConsoleEffect::Turn(alg.clone())],
+            ConsoleCommand::Solve => {
+                if gcube.dims != [3; 3] {
+                    return Err(ConsoleError::NotThreeByThree);
+                }
+                let solution = solve(&gcube.to_facelet_model())?;
+                vec![
+                    ConsoleEffect::Print(format!("{} ({} moves)", solution, solution.len())),
+                    ConsoleEffect::Turn(solution),
+                ]
+            }
+            ConsoleCommand::Save(name) => {
+                session.set_bookmark(name, gcube.size, applied.clone());
+                vec![ConsoleEffect::SaveSession]
+            }
+            ConsoleCommand::Load(name) => {
+                let bookmark = session
+                    .bookmark(name)
+                    .ok_or_else(|| ConsoleError::NoBookmark(name.clone()))?;
+                vec![
+                    ConsoleEffect::Reset(bookmark.size),
+                    ConsoleEffect::Scramble(bookmark.alg.clone()),
+                ]
+            }
+            ConsoleCommand::Reset(size) => vec![ConsoleEffect::Reset(size.unwrap_or(gcube.size))],
+            ConsoleCommand::Help => vec![ConsoleEffect::Print(CONSOLE_HELP.to_string())],
+        };
+        Ok(effects)
+    }
+}
+
+/// Parses and runs a line typed into a console, see ConsoleCommand::run.
+pub fn run_console_line(
+    line: &str,
+    gcube: &GCube,
+    applied: &Alg,
+    session: &mut Session,
+    rng: &mut Rng,
+) -> Result<Vec<ConsoleEffect>, ConsoleError> {
+    line.parse::<ConsoleCommand>()?
+        .run(gcube, applied, session, rng)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commands() {
+        let parse = |line: &str| line.parse::<ConsoleCommand>();
+        assert_eq!(parse("scramble 4"), Ok(ConsoleCommand::Scramble(Some(4))));
+        assert_eq!(parse(" scramble "), Ok(ConsoleCommand::Scramble(None)));
+        assert_eq!(
+            parse("apply R U R'"),
+            Ok(ConsoleCommand::Apply("R U R'".parse().unwrap()))
+        );
+        assert_eq!(
+            parse("save foo"),
+            Ok(ConsoleCommand::Save("foo".to_string()))
+        );
+        assert!(matches!(
+            parse("apply"),
+            Err(ConsoleError::MissingArgument { .. })
+        ));
+        assert!(matches!(
+            parse("apply R Q"),
+            Err(ConsoleError::InvalidAlg(_))
+        ));
+        assert_eq!(
+            parse("scramble 0"),
+            Err(ConsoleError::InvalidSize("0".to_string()))
+        );
+        assert_eq!(
+            parse("jump"),
+            Err(ConsoleError::UnknownCommand("jump".to_string()))
+        );
+    }
+
+    #[test]
+    fn runs_commands() {
+        let mut session = Session::default();
+        let mut rng = Rng::new(2);
+        let mut gcube = GCube::new(3);
+        let alg: Alg = "R U".parse().unwrap();
+        gcube.apply_movements(alg.movements());
+        let mut run =
+            |line, gcube: &GCube| run_console_line(line, gcube, &alg, &mut session, &mut rng);
+
+        let effects = run("solve", &gcube).unwrap();
+        match &effects[1] {
+            ConsoleEffect::Turn(solution) => {
+                gcube.apply_movements(solution.movements());
+                assert!(gcube.is_solved());
+            }
+            effect => panic!("expected a turn, got {:?}", effect),
+        }
+        assert_eq!(
+            run("save foo", &gcube),
+            Ok(vec![ConsoleEffect::SaveSession])
+        );
+        assert_eq!(
+            run("load foo", &gcube),
+            Ok(vec![
+                ConsoleEffect::Reset(3),
+                ConsoleEffect::Scramble(alg.clone())
+            ])
+        );
+        assert_eq!(
+            run("load bar", &gcube),
+            Err(ConsoleError::NoBookmark("bar".to_string()))
+        );
+        assert_eq!(
+            run("scramble 5", &gcube).unwrap()[0],
+            ConsoleEffect::Reset(5)
+        );
+        assert_eq!(
+            run("solve", &GCube::new(4)),
+            Err(ConsoleError::NotThreeByThree)
+        );
+    }
+}
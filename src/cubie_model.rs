@@ -0,0 +1,630 @@
+use std::sync::OnceLock;
+
+use thiserror::Error;
+
+use crate::{Face, FaceletModel, Rng, Turn, ORDERED_FACES};
+
+// faces are numbered in the order of ORDERED_FACES (U, R, F, D, L, then B),
+// corners in the order URF, UFL, ULB, UBR, DFR, DLF, DBL, DRB, and edges in
+// the order UR, UF, UL, UB, DR, DF, DL, DB, FR, FL, BL, BR
+
+// the facelets of each corner, clockwise from its U or D facelet
+pub(crate) const CORNER_FACELETS: [[usize; 3]; 8] = [
+    [8, 9, 20],
+    [6, 18, 38],
+    [0, 36, 47],
+    [2, 45, 11],
+    [29, 26, 15],
+    [27, 44, 24],
+    [33, 53, 42],
+    [35, 17, 51],
+];
+// the faces of each corner, in the same order as its facelets
+const CORNER_FACES: [[usize; 3]; 8] = [
+    [0, 1, 2],
+    [0, 2, 4],
+    [0, 4, 5],
+    [0, 5, 1],
+    [3, 2, 1],
+    [3, 4, 2],
+    [3, 5, 4],
+    [3, 1, 5],
+];
+// the facelets of each edge, starting with its U or D facelet, or else its F
+// or B facelet
+pub(crate) const EDGE_FACELETS: [[usize; 2]; 12] = [
+    [5, 10],
+    [7, 19],
+    [3, 37],
+    [1, 46],
+    [32, 16],
+    [28, 25],
+    [30, 43],
+    [34, 52],
+    [23, 12],
+    [21, 41],
+    [50, 39],
+    [48, 14],
+];
+const EDGE_FACES: [[usize; 2]; 12] = [
+    [0, 1],
+    [0, 2],
+    [0, 4],
+    [0, 5],
+    [3, 1],
+    [3, 2],
+    [3, 4],
+    [3, 5],
+    [2, 1],
+    [2, 4],
+    [5, 4],
+    [5, 1],
+];
+
+// a clockwise quarter turn of each face, as the cube it turns a solved cube into
+const FACE_TURNS: [CubieCube; 6] = [
+    CubieCube {
+        cp: [3, 0, 1, 2, 4, 5, 6, 7],
+        co: [0; 8],
+        ep: [3, 0, 1, 2, 4, 5, 6, 7, 8, 9, 10, 11],
+        eo: [0; 12],
+    },
+    CubieCube {
+        cp: [4, 1, 2, 0, 7, 5, 6, 3],
+        co: [2, 0, 0, 1, 1, 0, 0, 2],
+        ep: [8, 1, 2, 3, 11, 5, 6, 7, 4, 9, 10, 0],
+        eo: [0; 12],
+    },
+    CubieCube {
+        cp: [1, 5, 2, 3, 0, 4, 6, 7],
+        co: [1, 2, 0, 0, 2, 1, 0, 0],
+        ep: [0, 9, 2, 3, 4, 8, 6, 7, 1, 5, 10, 11],
+        eo: [0, 1, 0, 0, 0, 1, 0, 0, 1, 1, 0, 0],
+    },
+    CubieCube {
+        cp: [0, 1, 2, 3, 5, 6, 7, 4],
+        co: [0; 8],
+        ep: [0, 1, 2, 3, 5, 6, 7, 4, 8, 9, 10, 11],
+        eo: [0; 12],
+    },
+    CubieCube {
+        cp: [0, 2, 6, 3, 4, 1, 5, 7],
+        co: [0, 1, 2, 0, 0, 2, 1, 0],
+        ep: [0, 1, 10, 3, 4, 5, 9, 7, 8, 2, 6, 11],
+        eo: [0; 12],
+    },
+    CubieCube {
+        cp: [0, 1, 3, 7, 4, 5, 2, 6],
+        co: [0, 0, 1, 2, 0, 0, 2, 1],
+        ep: [0, 1, 2, 11, 4, 5, 6, 10, 8, 9, 3, 7],
+        eo: [0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 1, 1],
+    },
+];
+
+/// Why a cube's stickers can't be a real cube's.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum StateError {
+    #[error("Expected {expected} stickers, found {found}")]
+    WrongLength { expected: usize, found: usize },
+    #[error("Expected {expected} {face} stickers, found {found}")]
+    WrongColorCount {
+        face: Face,
+        expected: usize,
+        found: usize,
+    },
+    #[error("Every center must be a different colour")]
+    SameCenters,
+    #[error("A corner has colours no corner has, or is repeated")]
+    InvalidCorner,
+    #[error("An edge has colours no edge has, or is repeated")]
+    InvalidEdge,
+    #[error("A corner is twisted")]
+    TwistedCorner,
+    #[error("An edge is flipped")]
+    FlippedEdge,
+    #[error("Two pieces are swapped")]
+    SwappedPieces,
+}
+
+/// Checks that the stickers of an NxN cube, ordered like
+/// [facelets](crate::GCube::facelets), could be a real cube's. Every cube
+/// needs N * N stickers of each colour, and a 3x3's pieces must also be
+/// solvable.
+pub fn check_state(size: usize, facelets: &[Face]) -> Result<(), StateError> {
+    let expected = 6 * size * size;
+    if facelets.len() != expected {
+        return Err(StateError::WrongLength {
+            expected,
+            found: facelets.len(),
+        });
+    }
+    check_color_counts(size, facelets)?;
+    if size == 3 {
+        let mut model = FaceletModel::new();
+        model.0.copy_from_slice(facelets);
+        CubieCube::from_facelets(&model)?;
+    }
+    Ok(())
+}
+
+fn check_color_counts(size: usize, facelets: &[Face]) -> Result<(), StateError> {
+    for &face in ORDERED_FACES.iter() {
+        let found = facelets.iter().filter(|&&f| f == face).count();
+        if found != size * size {
+            return Err(StateError::WrongColorCount {
+                face,
+                expected: size * size,
+                found,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// A 3x3 cube as the position and orientation of each of its corner and
+/// edge pieces (cubies), e.g. for solving. Corners are numbered in the
+/// order URF, UFL, ULB, UBR, DFR, DLF, DBL, DRB and edges in the order UR,
+/// UF, UL, UB, DR, DF, DL, DB, FR, FL, BL, BR, like Kociemba's.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CubieCube {
+    /// the corner at each corner position
+    pub cp: [usize; 8],
+    /// how twisted each corner position's corner is, clockwise from 0 to 2
+    pub co: [u8; 8],
+    /// the edge at each edge position
+    pub ep: [usize; 12],
+    /// whether each edge position's edge is flipped
+    pub eo: [u8; 12],
+}
+
+impl Default for CubieCube {
+    fn default() -> Self {
+        Self {
+            cp: [0, 1, 2, 3, 4, 5, 6, 7],
+            co: [0; 8],
+            ep: [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+            eo: [0; 12],
+        }
+    }
+}
+
+impl CubieCube {
+    /// Returns the cubies of a 3x3's facelets, or why they aren't a
+    /// solvable cube's. Colours are matched to the faces by the centers, so
+    /// the cube may be in any orientation.
+    pub fn from_facelets(facelets: &FaceletModel) -> Result<Self, StateError> {
+        check_color_counts(3, &facelets.0)?;
+        let centers: Vec<Face> = (0..6).map(|f| facelets[9 * f + 4]).collect();
+        if (1..6).any(|i| centers[..i].contains(&centers[i])) {
+            return Err(StateError::SameCenters);
+        }
+        // the face each sticker's colour is the center of
+        let f: Vec<usize> = facelets
+            .0
+            .iter()
+            .map(|color| centers.iter().position(|c| c == color).unwrap())
+            .collect();
+        let mut cube = CubieCube::default();
+        for (i, facelets) in CORNER_FACELETS.iter().enumerate() {
+            let ori = (0..3)
+                .find(|&o| f[facelets[o]] == 0 || f[facelets[o]] == 3)
+                .ok_or(StateError::InvalidCorner)?;
+            let (a, b) = (f[facelets[(ori + 1) % 3]], f[facelets[(ori + 2) % 3]]);
+            let corner = CORNER_FACES
+                .iter()
+                .position(|faces| faces[1] == a && faces[2] == b)
+                .ok_or(StateError::InvalidCorner)?;
+            cube.cp[i] = corner;
+            cube.co[i] = ori as u8;
+        }
+        for (i, facelets) in EDGE_FACELETS.iter().enumerate() {
+            let (a, b) = (f[facelets[0]], f[facelets[1]]);
+            let (edge, flip) = EDGE_FACES
+                .iter()
+                .enumerate()
+                .find_map(|(j, faces)| match *faces {
+                    [x, y] if (x, y) == (a, b) => Some((j, 0)),
+                    [x, y] if (x, y) == (b, a) => Some((j, 1)),
+                    _ => None,
+                })
+                .ok_or(StateError::InvalidEdge)?;
+            cube.ep[i] = edge;
+            cube.eo[i] = flip;
+        }
+        cube.verify()?;
+        Ok(cube)
+    }
+
+    /// Returns a random state, every solvable state being as likely, e.g.
+    /// for random state scrambles or sampling how far states are from
+    /// solved.
+    pub fn random(rng: &mut Rng) -> Self {
+        let mut cube = CubieCube::default();
+        shuffle(&mut cube.cp, rng);
+        shuffle(&mut cube.ep, rng);
+        // the pieces' swaps must cancel out
+        if parity(&cube.cp) != parity(&cube.ep) {
+            cube.ep.swap(0, 1);
+        }
+        for o in cube.co[..7].iter_mut() {
+            *o = rng.below(3) as u8;
+        }
+        cube.co[7] = (3 - cube.co[..7].iter().sum::<u8>() % 3) % 3;
+        for o in cube.eo[..11].iter_mut() {
+            *o = rng.below(2) as u8;
+        }
+        cube.eo[11] = cube.eo[..11].iter().sum::<u8>() % 2;
+        cube
+    }
+
+    /// Returns the facelets of the cube, with its centers on their faces.
+    pub fn to_facelets(&self) -> FaceletModel {
+        let mut facelets = FaceletModel::new();
+        for i in 0..8 {
+            let (corner, ori) = (self.cp[i], self.co[i] as usize);
+            for n in 0..3 {
+                let face = ORDERED_FACES[CORNER_FACES[corner][n]];
+                facelets[CORNER_FACELETS[i][(n + ori) % 3]] = face;
+            }
+        }
+        for i in 0..12 {
+            let (edge, flip) = (self.ep[i], self.eo[i] as usize);
+            for n in 0..2 {
+                let face = ORDERED_FACES[EDGE_FACES[edge][n]];
+                facelets[EDGE_FACELETS[i][(n + flip) % 2]] = face;
+            }
+        }
+        facelets
+    }
+
+    /// Checks that the cube could be solved, i.e. every piece is there once,
+    /// and the corners' twists, the edges' flips and the pieces' swaps
+    /// cancel out.
+    pub fn verify(&self) -> Result<(), StateError> {
+        if (0..8).any(|c| !self.cp.contains(&c)) || self.co.iter().any(|&o| o > 2) {
+            return Err(StateError::InvalidCorner);
+        }
+        if (0..12).any(|e| !self.ep.contains(&e)) || self.eo.iter().any(|&o| o > 1) {
+            return Err(StateError::InvalidEdge);
+        }
+        if self.co.iter().map(|&o| o as usize).sum::<usize>() % 3 != 0 {
+            return Err(StateError::TwistedCorner);
+        }
+        if self.eo.iter().map(|&o| o as usize).sum::<usize>() % 2 != 0 {
+            return Err(StateError::FlippedEdge);
+        }
+        if parity(&self.cp) != parity(&self.ep) {
+            return Err(StateError::SwappedPieces);
+        }
+        Ok(())
+    }
+
+    pub fn is_solved(&self) -> bool {
+        *self == CubieCube::default()
+    }
+
+    /// Turns a face, which must be U, R, F, D, L or B.
+    pub fn turn(&mut self, face: Face, turn: Turn) {
+        let f = ORDERED_FACES.iter().position(|&f| f == face).unwrap();
+        for _ in 0..turn as usize {
+            self.multiply(&FACE_TURNS[f]);
+        }
+    }
+
+    /// Returns the number of F2L pairs solved, on any face: a corner in
+    /// place (and not twisted) next to an edge in place (and not flipped),
+    /// which is the pair of the face opposite the edge's.
+    pub fn solved_pairs(&self) -> usize {
+        let is_edge_solved = |e: usize| self.ep[e] == e && self.eo[e] == 0;
+        (0..8)
+            .filter(|&c| self.cp[c] == c && self.co[c] == 0)
+            .map(|c| {
+                (0..12)
+                    .filter(|&e| EDGE_FACES[e].iter().all(|f| CORNER_FACES[c].contains(f)))
+                    .filter(|&e| is_edge_solved(e))
+                    .count()
+            })
+            .sum()
+    }
+
+    /// Returns the number of F2L pairs solved for a cross on face, out of
+    /// its 4.
+    pub fn solved_pairs_on(&self, face: Face) -> usize {
+        let f = ORDERED_FACES.iter().position(|&o| o == face).unwrap();
+        (0..8)
+            .filter(|&c| CORNER_FACES[c].contains(&f) && self.cp[c] == c && self.co[c] == 0)
+            .filter(|&c| {
+                // the edge between the corner's other two faces
+                let e = (0..12)
+                    .find(|&e| {
+                        !EDGE_FACES[e].contains(&f)
+                            && EDGE_FACES[e].iter().all(|x| CORNER_FACES[c].contains(x))
+                    })
+                    .unwrap();
+                self.ep[e] == e && self.eo[e] == 0
+            })
+            .count()
+    }
+
+    /// Returns whether a 1x2x3 block on face is solved, like Roux's first
+    /// block (on D and L): for a side next to face, the two corners on both,
+    /// and the side's edges but the one opposite face.
+    pub fn is_block_solved(&self, face: Face) -> bool {
+        let f = ORDERED_FACES.iter().position(|&o| o == face).unwrap();
+        let opposite = (f + 3) % 6;
+        (0..6).filter(|&s| s != f && s != opposite).any(|s| {
+            let is_corner_solved = |c: usize| self.cp[c] == c && self.co[c] == 0;
+            let is_edge_solved = |e: usize| self.ep[e] == e && self.eo[e] == 0;
+            (0..8)
+                .filter(|&c| CORNER_FACES[c].contains(&f) && CORNER_FACES[c].contains(&s))
+                .all(is_corner_solved)
+                && (0..12)
+                    .filter(|&e| EDGE_FACES[e].contains(&s) && !EDGE_FACES[e].contains(&opposite))
+                    .all(is_edge_solved)
+        })
+    }
+
+    /// Returns the fewest face turns that solve the cross on face (its
+    /// four edges), or None if it takes more than max_moves.
+    pub fn cross_moves(&self, face: Face, max_moves: usize) -> Option<usize> {
+        let f = ORDERED_FACES.iter().position(|&o| o == face).unwrap();
+        let index = cross_edges(f).iter().fold(0, |index, &e| {
+            let position = self.ep.iter().position(|&p| p == e).unwrap();
+            24 * index + 2 * position + self.eo[position] as usize
+        });
+        let depth = cross_depths()[f][index] as usize;
+        (depth <= max_moves).then_some(depth)
+    }
+
+    /// Twists the corner at a corner position in place, clockwise by
+    /// amount thirds of a turn. Twisting one corner makes the cube
+    /// unsolvable, unless another is twisted back, e.g. for twist drills.
+    pub fn twist_corner(&mut self, position: usize, amount: u8) {
+        self.co[position] = (self.co[position] + amount) % 3;
+    }
+
+    /// Flips the edge at an edge position in place. Flipping one edge makes
+    /// the cube unsolvable, unless another is flipped too.
+    pub fn flip_edge(&mut self, position: usize) {
+        self.eo[position] ^= 1;
+    }
+
+    // applies the movement that turns a solved cube into other
+    pub(crate) fn multiply(&mut self, other: &CubieCube) {
+        let (cp, co, ep, eo) = (self.cp, self.co, self.ep, self.eo);
+        for i in 0..8 {
+            self.cp[i] = cp[other.cp[i]];
+            self.co[i] = (co[other.cp[i]] + other.co[i]) % 3;
+        }
+        for i in 0..12 {
+            self.ep[i] = ep[other.ep[i]];
+            self.eo[i] = (eo[other.ep[i]] + other.eo[i]) % 2;
+        }
+    }
+
+    // turns face (numbered like ORDERED_FACES) clockwise, quarter_turns times
+    pub(crate) fn turn_face(&mut self, face: usize, quarter_turns: usize) {
+        for _ in 0..quarter_turns {
+            self.multiply(&FACE_TURNS[face]);
+        }
+    }
+}
+
+// the edges of the cross on face f
+fn cross_edges(f: usize) -> Vec<usize> {
+    (0..12).filter(|&e| EDGE_FACES[e].contains(&f)).collect()
+}
+
+// the fewest face turns solving each face's cross, indexed by where its four
+// edges are and whether they're flipped (each 2 * position + flip, as the
+// digits of a base 24 number), tabulated by searching breadth first from
+// solved, on first use
+fn cross_depths() -> &'static [Vec<u8>; 6] {
+    static DEPTHS: OnceLock<[Vec<u8>; 6]> = OnceLock::new();
+    DEPTHS.get_or_init(|| {
+        // where a quarter turn of each face takes an edge at each position
+        // and flip
+        let mut turned = [[0; 24]; 6];
+        for (face, turn) in FACE_TURNS.iter().enumerate() {
+            for (edge, to) in turned[face].iter_mut().enumerate() {
+                let i = turn.ep.iter().position(|&p| p == edge / 2).unwrap();
+                *to = 2 * i + (edge % 2 + turn.eo[i] as usize) % 2;
+            }
+        }
+        [0, 1, 2, 3, 4, 5].map(|f| {
+            let edges = cross_edges(f);
+            let solved = edges.iter().fold(0, |index, &e| 24 * index + 2 * e);
+            let mut depths = vec![u8::MAX; 24usize.pow(4)];
+            depths[solved] = 0;
+            let mut frontier = vec![solved];
+            let mut depth = 0;
+            while !frontier.is_empty() {
+                depth += 1;
+                let mut next = vec![];
+                for &index in frontier.iter() {
+                    for turn in turned.iter() {
+                        let mut digits =
+                            [index / 13824, index / 576 % 24, index / 24 % 24, index % 24];
+                        for _ in 0..3 {
+                            digits = digits.map(|edge| turn[edge]);
+                            let j = digits.iter().fold(0, |j, &edge| 24 * j + edge);
+                            if depths[j] == u8::MAX {
+                                depths[j] = depth;
+                                next.push(j);
+                            }
+                        }
+                    }
+                }
+                frontier = next;
+            }
+            depths
+        })
+    })
+}
+
+// a Fisher-Yates shuffle
+fn shuffle(values: &mut [usize], rng: &mut Rng) {
+    for i in (1..values.len()).rev() {
+        values.swap(i, rng.below(i + 1));
+    }
+}
+
+// whether a permutation takes an odd number of swaps
+fn parity(perm: &[usize]) -> bool {
+    let mut inversions = 0;
+    for i in 0..perm.len() {
+        inversions += perm[i + 1..].iter().filter(|&&p| p < perm[i]).count();
+    }
+    inversions % 2 == 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{scramble_to_movements, GCube};
+
+    #[test]
+    fn face_turns_match_the_geometry_model() {
+        let mut gcube = GCube::new(3);
+        gcube.apply_movements(&scramble_to_movements("R U' F2 D L B' R2 U F' L2 D' B").unwrap());
+        let mut cube = CubieCube::default();
+        let turns = [
+            (Face::R, Turn::Single),
+            (Face::U, Turn::Inverse),
+            (Face::F, Turn::Double),
+            (Face::D, Turn::Single),
+            (Face::L, Turn::Single),
+            (Face::B, Turn::Inverse),
+            (Face::R, Turn::Double),
+            (Face::U, Turn::Single),
+            (Face::F, Turn::Inverse),
+            (Face::L, Turn::Double),
+            (Face::D, Turn::Inverse),
+            (Face::B, Turn::Single),
+        ];
+        for &(face, turn) in turns.iter() {
+            cube.turn(face, turn);
+        }
+        assert_eq!(
+            CubieCube::from_facelets(&gcube.to_facelet_model()),
+            Ok(cube)
+        );
+        assert_eq!(cube.to_facelets(), gcube.to_facelet_model());
+
+        // every face turn four times is no turn at all
+        for &face in ORDERED_FACES.iter() {
+            let mut c = cube;
+            c.turn(face, Turn::Double);
+            c.turn(face, Turn::Double);
+            assert_eq!(c, cube);
+        }
+    }
+
+    #[test]
+    fn random_states_are_solvable() {
+        let mut rng = Rng::new(3);
+        let states: Vec<CubieCube> = (0..100).map(|_| CubieCube::random(&mut rng)).collect();
+        assert!(states.iter().all(|c| c.verify().is_ok()));
+        // with both parities, and corners all over
+        assert!(states.iter().any(|c| parity(&c.cp)) && states.iter().any(|c| !parity(&c.cp)));
+        assert!((0..8).all(|i| states.iter().any(|c| c.cp[i] == 7)));
+    }
+
+    #[test]
+    fn pairs_and_crosses() {
+        let solved = CubieCube::default();
+        // every corner with each of its 3 edges
+        assert_eq!(solved.solved_pairs(), 24);
+        assert_eq!(solved.cross_moves(Face::D, 3), Some(0));
+
+        // R U R' keeps the D cross, but takes 3 moves to undo on U
+        let mut cube = CubieCube::default();
+        cube.turn(Face::R, Turn::Single);
+        cube.turn(Face::U, Turn::Single);
+        cube.turn(Face::R, Turn::Inverse);
+        assert_eq!(cube.cross_moves(Face::D, 3), Some(0));
+        assert_eq!(cube.cross_moves(Face::U, 3), Some(3));
+        assert_eq!(cube.cross_moves(Face::F, 3), Some(2));
+        let mut cube = CubieCube::default();
+        cube.turn(Face::F, Turn::Double);
+        cube.turn(Face::R, Turn::Single);
+        assert_eq!(cube.cross_moves(Face::D, 1), None);
+        assert_eq!(cube.cross_moves(Face::D, 3), Some(2));
+        assert!(cube.solved_pairs() < 24);
+        assert_eq!(solved.solved_pairs_on(Face::D), 4);
+        // F2 R leaves D's back left pair
+        assert_eq!(cube.solved_pairs_on(Face::D), 1);
+
+        // U R leaves D's block on L, and L breaks it
+        assert!(solved.is_block_solved(Face::D));
+        let mut cube = CubieCube::default();
+        cube.turn(Face::U, Turn::Single);
+        cube.turn(Face::R, Turn::Single);
+        assert!(cube.is_block_solved(Face::D) && cube.is_block_solved(Face::L));
+        assert!(!cube.is_block_solved(Face::U));
+        cube.turn(Face::L, Turn::Single);
+        assert!(!cube.is_block_solved(Face::D));
+
+        let mut gcube = GCube::new(3);
+        gcube.apply_movements(
+            &scramble_to_movements("D2 F2 U' B2 R2 B2 R2 L B' D' F D2 F' L2 F2 U' R' U' B L'")
+                .unwrap(),
+        );
+        let scrambled = CubieCube::from_facelets(&gcube.to_facelet_model()).unwrap();
+        assert_eq!(scrambled.solved_pairs(), 0);
+        assert_eq!(scrambled.cross_moves(Face::D, 3), None);
+    }
+
+    #[test]
+    fn impossible_states_are_found() {
+        let mut cube = CubieCube::default();
+        cube.twist_corner(0, 1);
+        assert_eq!(cube.verify(), Err(StateError::TwistedCorner));
+        let facelets = cube.to_facelets();
+        assert_eq!(
+            CubieCube::from_facelets(&facelets),
+            Err(StateError::TwistedCorner)
+        );
+        let mut cube = CubieCube::default();
+        cube.flip_edge(5);
+        assert_eq!(
+            check_state(3, &cube.to_facelets().0),
+            Err(StateError::FlippedEdge)
+        );
+        // but twisting or flipping pieces back makes a real cube
+        cube.flip_edge(7);
+        assert_eq!(cube.verify(), Ok(()));
+        let mut cube = CubieCube::default();
+        cube.twist_corner(2, 1);
+        cube.twist_corner(3, 2);
+        assert_eq!(check_state(3, &cube.to_facelets().0), Ok(()));
+        cube.twist_corner(3, 1);
+        assert_eq!(cube.co[3], 0);
+        let mut cube = CubieCube::default();
+        cube.ep.swap(0, 1);
+        assert_eq!(
+            check_state(3, &cube.to_facelets().0),
+            Err(StateError::SwappedPieces)
+        );
+        // swapping two edges' stickers between themselves makes impossible edges
+        let mut facelets = FaceletModel::new();
+        facelets.0.swap(1, 10);
+        assert_eq!(check_state(3, &facelets.0), Err(StateError::InvalidEdge));
+        let mut facelets = FaceletModel::new();
+        facelets.0.swap(4, 9);
+        assert_eq!(check_state(3, &facelets.0), Err(StateError::SameCenters));
+
+        let mut facelets = GCube::new(4).facelets();
+        facelets[0] = Face::R;
+        assert_eq!(
+            check_state(4, &facelets),
+            Err(StateError::WrongColorCount {
+                face: Face::U,
+                expected: 16,
+                found: 15
+            })
+        );
+        assert_eq!(check_state(4, &GCube::new(4).facelets()), Ok(()));
+        assert!(check_state(4, &facelets[1..]).is_err());
+    }
+}
@@ -0,0 +1,142 @@
+use std::fmt::Write;
+
+use crate::{random_scramble, CubieCube, GCube, Rng, Solver};
+
+/// Where sampled 3x3 states come from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StateSource {
+    /// random states, every solvable state being as likely
+    RandomState,
+    /// random move scrambles of a number of moves, e.g. to check that
+    /// scrambles of that length are as far from solved as random states
+    Scramble(usize),
+}
+
+impl StateSource {
+    /// Returns a random state from the source.
+    pub fn sample(self, rng: &mut Rng) -> CubieCube {
+        match self {
+            StateSource::RandomState => CubieCube::random(rng),
+            StateSource::Scramble(length) => {
+                let mut gcube = GCube::new(3);
+                gcube.apply_movements(random_scramble(3, length, rng).movements());
+                CubieCube::from_facelets(&gcube.to_facelet_model()).unwrap()
+            }
+        }
+    }
+}
+
+/// How many sampled states had solutions of each length, in HTM. Solutions
+/// are the solver's near optimal ones (searching longer, with more tries,
+/// gets closer to optimal), so lengths are an upper bound on the states'
+/// distances from solved.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DistanceDistribution {
+    /// the number of states with a solution of each length, by length
+    pub counts: Vec<usize>,
+}
+
+impl DistanceDistribution {
+    /// Samples states from source, solving each with solver, searching for
+    /// shorter solutions until max_tries phase 1 solutions don't lead to one.
+    pub fn sample(
+        solver: &Solver,
+        source: StateSource,
+        samples: usize,
+        max_tries: usize,
+        rng: &mut Rng,
+    ) -> Self {
+        let mut distribution = Self::default();
+        for _ in 0..samples {
+            let cube = source.sample(rng);
+            distribution.add(solver.solve(&cube, max_tries).len());
+        }
+        distribution
+    }
+
+    /// Counts a state with a solution of length moves.
+    pub fn add(&mut self, length: usize) {
+        if self.counts.len() <= length {
+            self.counts.resize(length + 1, 0);
+        }
+        self.counts[length] += 1;
+    }
+
+    /// Returns the number of states sampled.
+    pub fn samples(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    /// Returns the mean solution length, or None if nothing was sampled.
+    pub fn mean(&self) -> Option<f64> {
+        let total: usize = self.counts.iter().enumerate().map(|(l, n)| l * n).sum();
+        (self.samples() > 0).then(|| total as f64 / self.samples() as f64)
+    }
+
+    /// Returns the distribution as CSV, a row for each length from the
+    /// shortest to the longest, with the share of states (as a percentage).
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("Length,States,Share\n");
+        for (length, count) in self.rows() {
+            let share = count as f64 / self.samples() as f64 * 100.;
+            writeln!(csv, "{},{},{:.1}", length, count, share).unwrap();
+        }
+        csv
+    }
+
+    /// Returns the distribution as a text histogram, a bar of up to width
+    /// `#`s for each length, e.g. `18 |######### 31`.
+    pub fn histogram(&self, width: usize) -> String {
+        let most = self.counts.iter().max().copied().unwrap_or(0).max(1);
+        let mut text = String::new();
+        for (length, count) in self.rows() {
+            let bar = "#".repeat((count * width).div_ceil(most));
+            writeln!(text, "{:>2} |{} {}", length, bar, count).unwrap();
+        }
+        text
+    }
+
+    // the lengths from the shortest sampled to the longest, with their counts
+    fn rows(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let first = self.counts.iter().position(|&n| n > 0).unwrap_or(0);
+        self.counts.iter().copied().enumerate().skip(first)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_scrambles_are_close_to_solved() {
+        let solver = Solver::shared();
+        let mut rng = Rng::new(5);
+        let distribution =
+            DistanceDistribution::sample(solver, StateSource::Scramble(2), 6, 50, &mut rng);
+        assert_eq!(distribution.samples(), 6);
+        // two moves can't cancel, see random_scramble
+        assert_eq!(distribution.counts, [0, 0, 6]);
+        assert_eq!(distribution.mean(), Some(2.));
+
+        let random = StateSource::RandomState.sample(&mut rng);
+        assert!(solver.solve(&random, 50).len() >= 15);
+    }
+
+    #[test]
+    fn csv_and_histogram() {
+        let mut distribution = DistanceDistribution::default();
+        assert_eq!(distribution.mean(), None);
+        for length in [17, 18, 18, 18, 19] {
+            distribution.add(length);
+        }
+        assert_eq!(distribution.mean(), Some(18.));
+        assert_eq!(
+            distribution.to_csv(),
+            "Length,States,Share\n17,1,20.0\n18,3,60.0\n19,1,20.0\n"
+        );
+        assert_eq!(
+            distribution.histogram(6),
+            "17 |## 1\n18 |###### 3\n19 |## 1\n"
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use std::{cell::RefCell, ops::Deref, rc::Rc};
+
+use crate::{Alg, GCube, Movement};
+
+/// Reacts to what happens to an ObservedCube, e.g. a timer, logger or sound
+/// effect. Every method does nothing by default, so observers only implement
+/// the events they're interested in.
+pub trait CubeObserver {
+    /// Called after a movement is applied, with the cube it was applied to.
+    fn on_move(&mut self, _movement: Movement, _gcube: &GCube) {}
+
+    /// Called after a movement solves the cube.
+    fn on_solved(&mut self, _gcube: &GCube) {}
+
+    /// Called after a scramble is applied, instead of on_move for each of
+    /// its movements.
+    fn on_scramble(&mut self, _scramble: &Alg, _gcube: &GCube) {}
+
+    /// Called after movements are applied at once, e.g. seeking through the
+    /// history, instead of on_move for each of them.
+    fn on_seek(&mut self, _movements: &[Movement], _gcube: &GCube) {}
+}
+
+/// A shared observer, so its owner can still reach it once it's subscribed,
+/// e.g. to change a sound effect's volume.
+impl<T: CubeObserver> CubeObserver for Rc<RefCell<T>> {
+    fn on_move(&mut self, movement: Movement, gcube: &GCube) {
+        self.borrow_mut().on_move(movement, gcube);
+    }
+
+    fn on_solved(&mut self, gcube: &GCube) {
+        self.borrow_mut().on_solved(gcube);
+    }
+
+    fn on_scramble(&mut self, scramble: &Alg, gcube: &GCube) {
+        self.borrow_mut().on_scramble(scramble, gcube);
+    }
+
+    fn on_seek(&mut self, movements: &[Movement], gcube: &GCube) {
+        self.borrow_mut().on_seek(movements, gcube);
+    }
+}
+
+/// Identifies a subscribed observer, to unsubscribe it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ObserverId(usize);
+
+/// A cube that notifies its observers of every movement applied to it. It
+/// reads as the GCube it wraps, but can only be changed through its own
+/// methods, so no movement goes unnoticed.
+pub struct ObservedCube {
+    gcube: GCube,
+    observers: Vec<(ObserverId, Box<dyn CubeObserver>)>,
+    next_id: usize,
+}
+
+impl ObservedCube {
+    pub fn new(gcube: GCube) -> Self {
+        Self {
+            gcube,
+            observers: vec![],
+            next_id: 0,
+        }
+    }
+
+    pub fn gcube(&self) -> &GCube {
+        &self.gcube
+    }
+
+    pub fn subscribe(&mut self, observer: Box<dyn CubeObserver>) -> ObserverId {
+        let id = ObserverId(self.next_id);
+        self.next_id += 1;
+        self.observers.push((id, observer));
+        id
+    }
+
+    /// Removes and returns a subscribed observer.
+    pub fn unsubscribe(&mut self, id: ObserverId) -> Option<Box<dyn CubeObserver>> {
+        let index = self.observers.iter().position(|(i, _)| *i == id)?;
+        Some(self.observers.remove(index).1)
+    }
+
+    pub fn apply_movement(&mut self, movement: Movement) {
+        let was_solved = self.gcube.is_solved();
+        self.gcube.apply_movement(&movement);
+        let is_solved = self.gcube.is_solved();
+        for (_, observer) in self.observers.iter_mut() {
+            observer.on_move(movement, &self.gcube);
+            if is_solved && !was_solved {
+                observer.on_solved(&self.gcube);
+            }
+        }
+    }
+
+    pub fn apply_movements(&mut self, movements: &[Movement]) {
+        for &movement in movements {
+            self.apply_movement(movement);
+        }
+    }
+
+    pub fn apply_scramble(&mut self, scramble: &Alg) {
+        self.gcube.apply_movements(scramble.movements());
+        for (_, observer) in self.observers.iter_mut() {
+            observer.on_scramble(scramble, &self.gcube);
+        }
+    }
+
+    /// Applies movements at once, e.g. to go back to a position in the
+    /// history.
+    pub fn seek(&mut self, movements: &[Movement]) {
+        self.gcube.apply_movements(movements);
+        for (_, observer) in self.observers.iter_mut() {
+            observer.on_seek(movements, &self.gcube);
+        }
+    }
+
+    /// Replaces the cube, e.g. with a new one of another size, returning the
+    /// old one. Observers aren't notified, as no movement was applied.
+    pub fn replace(&mut self, gcube: GCube) -> GCube {
+        std::mem::replace(&mut self.gcube, gcube)
+    }
+}
+
+impl Deref for ObservedCube {
+    type Target = GCube;
+
+    fn deref(&self) -> &GCube {
+        &self.gcube
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    // records events as strings, e.g. "move R"
+    struct Recorder(Rc<RefCell<Vec<String>>>);
+
+    impl CubeObserver for Recorder {
+        fn on_move(&mut self, movement: Movement, _gcube: &GCube) {
+            self.0.borrow_mut().push(format!("move {}", movement));
+        }
+
+        fn on_solved(&mut self, _gcube: &GCube) {
+            self.0.borrow_mut().push("solved".to_string());
+        }
+
+        fn on_scramble(&mut self, scramble: &Alg, _gcube: &GCube) {
+            self.0.borrow_mut().push(format!("scramble {}", scramble));
+        }
+
+        fn on_seek(&mut self, movements: &[Movement], _gcube: &GCube) {
+            self.0
+                .borrow_mut()
+                .push(format!("seek {}", Alg::new(movements.to_vec())));
+        }
+    }
+
+    #[test]
+    fn observers_are_notified() {
+        let events = Rc::new(RefCell::new(vec![]));
+        let mut cube = ObservedCube::new(GCube::new(3));
+        let id = cube.subscribe(Box::new(Recorder(events.clone())));
+        cube.apply_scramble(&Alg::from_str("R U").unwrap());
+        cube.apply_movements(Alg::from_str("U' R' R").unwrap().movements());
+        assert_eq!(
+            *events.borrow(),
+            ["scramble R U", "move U'", "move R'", "solved", "move R"]
+        );
+        assert!(cube.unsubscribe(id).is_some());
+        cube.apply_movement(Movement::from_str("R'").unwrap());
+        assert_eq!(events.borrow().len(), 5);
+        assert!(cube.gcube().is_solved());
+    }
+
+    #[test]
+    fn shared_observers_are_notified() {
+        let events = Rc::new(RefCell::new(vec![]));
+        let recorder = Rc::new(RefCell::new(Recorder(events.clone())));
+        let mut cube = ObservedCube::new(GCube::new(3));
+        cube.subscribe(Box::new(recorder.clone()));
+        cube.seek(Alg::from_str("R U").unwrap().movements());
+        cube.replace(GCube::new(2));
+        assert_eq!(*recorder.borrow().0.borrow(), ["seek R U"]);
+        assert_eq!(cube.size, 2);
+    }
+}
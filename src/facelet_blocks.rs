@@ -0,0 +1,101 @@
+use crate::{Facelet, ORDERED_FACES};
+
+/// A rectangle of facelets on a face that are all the same colour, e.g. to
+/// draw with a single quad.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FaceletBlock<T> {
+    /// the block's top left facelet
+    pub facelet: Facelet,
+    pub width: usize,
+    pub height: usize,
+    pub color: T,
+}
+
+impl<T> FaceletBlock<T> {
+    /// Returns the block's bottom right facelet, on an NxN cube.
+    pub fn last(&self, size: usize) -> Facelet {
+        let index = self.facelet.index + (self.height - 1) * size + self.width - 1;
+        Facelet::new(self.facelet.face, index)
+    }
+}
+
+/// Merges the facelets of an NxN cube, coloured in the order of
+/// [facelets](crate::GCube::facelets), into blocks of the same colour,
+/// leaving out facelets without one. Blocks are as wide as they can be, then
+/// as tall, going from left to right, then top to bottom across each face.
+pub fn facelet_blocks<T: Copy + PartialEq>(
+    colors: &[Option<T>],
+    size: usize,
+) -> Vec<FaceletBlock<T>> {
+    let mut blocks = vec![];
+    let mut is_merged = vec![false; colors.len()];
+    for (f, &face) in ORDERED_FACES.iter().enumerate() {
+        let cell = |row: usize, col: usize| (f * size + row) * size + col;
+        for row in 0..size {
+            for col in 0..size {
+                let i = cell(row, col);
+                let color = match colors[i] {
+                    Some(color) if !is_merged[i] => color,
+                    _ => continue,
+                };
+                let matches = |j: usize| !is_merged[j] && colors[j] == Some(color);
+                let width = (col..size).take_while(|&c| matches(cell(row, c))).count();
+                let height = (row..size)
+                    .take_while(|&r| (col..col + width).all(|c| matches(cell(r, c))))
+                    .count();
+                for r in row..row + height {
+                    for c in col..col + width {
+                        is_merged[cell(r, c)] = true;
+                    }
+                }
+                blocks.push(FaceletBlock {
+                    facelet: Facelet::new(face, i - f * size * size),
+                    width,
+                    height,
+                    color,
+                });
+            }
+        }
+    }
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{scramble_to_movements, Face, GCube};
+
+    #[test]
+    fn same_coloured_facelets_are_merged() {
+        let solved = GCube::new(5).facelets();
+        let blocks = facelet_blocks(&solved.iter().map(|&f| Some(f)).collect::<Vec<_>>(), 5);
+        assert_eq!(blocks.len(), 6);
+        assert!(blocks.iter().all(|b| b.width == 5 && b.height == 5));
+        assert_eq!(blocks[1].last(5), Facelet::new(Face::R, 24));
+
+        // R on a 3x3 leaves U with a column of F's colour, and B's colour on D
+        let mut gcube = GCube::new(3);
+        gcube.apply_movements(&scramble_to_movements("R").unwrap());
+        let mut colors: Vec<Option<Face>> = gcube.facelets().into_iter().map(Some).collect();
+        let blocks = facelet_blocks(&colors, 3);
+        let on_u: Vec<_> = blocks
+            .iter()
+            .filter(|b| b.facelet.face == Face::U)
+            .collect();
+        assert_eq!(on_u.len(), 2);
+        assert_eq!(
+            (on_u[0].width, on_u[0].height, on_u[0].color),
+            (2, 3, Face::U)
+        );
+        assert_eq!((on_u[1].facelet.index, on_u[1].color), (2, Face::F));
+
+        // facelets without a colour are left out, splitting the blocks around them
+        colors[4] = None;
+        let on_u: Vec<_> = facelet_blocks(&colors, 3)
+            .into_iter()
+            .filter(|b| b.facelet.face == Face::U)
+            .collect();
+        let area: usize = on_u.iter().map(|b| b.width * b.height).sum();
+        assert_eq!(area, 8);
+    }
+}
@@ -0,0 +1,157 @@
+//! C API for embedding a cube in C, C++ or C# apps, enabled by the `ffi`
+//! feature. Cubes are opaque handles, created with `cubedesu_cube_new` and
+//! freed with `cubedesu_cube_free`, see `include/cubedesu.h`.
+
+use std::{
+    ffi::{CStr, CString},
+    os::raw::{c_char, c_int},
+    ptr,
+    str::FromStr,
+};
+
+use crate::{solve, Alg, GCube};
+
+/// A cube, as seen from C.
+pub struct Cube {
+    gcube: GCube,
+}
+
+/// Returns a new solved NxN cube, to be freed with `cubedesu_cube_free`.
+#[no_mangle]
+pub extern "C" fn cubedesu_cube_new(size: usize) -> *mut Cube {
+    Box::into_raw(Box::new(Cube {
+        gcube: GCube::new(size),
+    }))
+}
+
+/// # Safety
+/// cube must be null, or a cube from `cubedesu_cube_new` that hasn't been
+/// freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn cubedesu_cube_free(cube: *mut Cube) {
+    if !cube.is_null() {
+        drop(Box::from_raw(cube));
+    }
+}
+
+/// Applies an alg, e.g. "R U R' U'". Returns 0 on success, or -1 if the alg
+/// is invalid, in which case nothing is applied.
+///
+/// # Safety
+/// cube must be a live cube, and alg a nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn cubedesu_cube_apply_alg(cube: *mut Cube, alg: *const c_char) -> c_int {
+    let cube = &mut *cube;
+    let alg = match CStr::from_ptr(alg).to_str().map(Alg::from_str) {
+        Ok(Ok(alg)) => alg,
+        _ => return -1,
+    };
+    cube.gcube.apply_movements(alg.movements());
+    0
+}
+
+/// Writes the colour of every sticker as a face letter (U, R, F, D, L or B),
+/// ordered by U, R, F, D, L, then B face, followed by a nul. Returns the
+/// number of letters, 6 * size * size, writing nothing if len (including
+/// the nul) is too short, so out can be null to ask for the length.
+///
+/// # Safety
+/// cube must be a live cube, and out null or valid for len bytes.
+#[no_mangle]
+pub unsafe extern "C" fn cubedesu_cube_facelets(
+    cube: *const Cube,
+    out: *mut c_char,
+    len: usize,
+) -> usize {
+    let facelets: String = (*cube)
+        .gcube
+        .facelets()
+        .iter()
+        .map(|f| f.to_string())
+        .collect();
+    if !out.is_null() && len > facelets.len() {
+        ptr::copy_nonoverlapping(facelets.as_ptr() as *const c_char, out, facelets.len());
+        *out.add(facelets.len()) = 0;
+    }
+    facelets.len()
+}
+
+/// # Safety
+/// cube must be a live cube.
+#[no_mangle]
+pub unsafe extern "C" fn cubedesu_cube_is_solved(cube: *const Cube) -> bool {
+    (*cube).gcube.is_solved()
+}
+
+/// Returns an alg that solves a 3x3 from its state, found by the solver, to
+/// be freed with `cubedesu_string_free`. Returns null for cubes of other
+/// sizes.
+///
+/// # Safety
+/// cube must be a live cube.
+#[no_mangle]
+pub unsafe extern "C" fn cubedesu_cube_solution(cube: *const Cube) -> *mut c_char {
+    let gcube = &(*cube).gcube;
+    if gcube.dims != [3; 3] {
+        return ptr::null_mut();
+    }
+    // a cube only turned by algs is always solvable
+    let solution = solve(&gcube.to_facelet_model()).unwrap();
+    // algs never contain nuls
+    CString::new(solution.to_string()).unwrap().into_raw()
+}
+
+/// # Safety
+/// s must be null, or a string returned by cubedesu that hasn't been freed.
+#[no_mangle]
+pub unsafe extern "C" fn cubedesu_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solves_the_state() {
+        unsafe {
+            let cube = cubedesu_cube_new(3);
+            // a rotation and slice, which the solver's solution can't just undo
+            let alg = CString::new("R U R' U' x M F2").unwrap();
+            assert_eq!(cubedesu_cube_apply_alg(cube, alg.as_ptr()), 0);
+            let invalid = CString::new("R Q").unwrap();
+            assert_eq!(cubedesu_cube_apply_alg(cube, invalid.as_ptr()), -1);
+            assert!(!cubedesu_cube_is_solved(cube));
+
+            let solution = cubedesu_cube_solution(cube);
+            let moves = CStr::from_ptr(solution).to_str().unwrap();
+            assert!(moves.split(' ').all(|m| "URFDLB".contains(&m[..1])));
+            assert_eq!(cubedesu_cube_apply_alg(cube, solution), 0);
+            cubedesu_string_free(solution);
+            assert!(cubedesu_cube_is_solved(cube));
+            cubedesu_cube_free(cube);
+
+            let cube = cubedesu_cube_new(4);
+            assert!(cubedesu_cube_solution(cube).is_null());
+            cubedesu_cube_free(cube);
+        }
+    }
+
+    #[test]
+    fn exports_facelets() {
+        unsafe {
+            let cube = cubedesu_cube_new(2);
+            assert_eq!(cubedesu_cube_facelets(cube, ptr::null_mut(), 0), 24);
+            let mut out = [0 as c_char; 25];
+            assert_eq!(
+                cubedesu_cube_facelets(cube, out.as_mut_ptr(), out.len()),
+                24
+            );
+            let facelets = CStr::from_ptr(out.as_ptr()).to_str().unwrap();
+            assert_eq!(facelets, "UUUURRRRFFFFDDDDLLLLBBBB");
+            cubedesu_cube_free(cube);
+        }
+    }
+}
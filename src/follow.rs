@@ -0,0 +1,106 @@
+use std::{io, net::ToSocketAddrs};
+
+use crate::{Command, CubeEvent, Face, GCube, WebSocketClient};
+
+/// Follows a teacher's cube, broadcast by another instance's
+/// BroadcastServer, keeping a copy of it.
+pub struct Follower {
+    client: WebSocketClient,
+    gcube: Option<GCube>, // once the teacher's state is received
+}
+
+impl Follower {
+    /// Connects to the teacher's broadcast at addr, e.g. "192.168.1.2:9001".
+    pub fn connect(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Self {
+            client: WebSocketClient::connect(addr)?,
+            gcube: None,
+        })
+    }
+
+    /// Returns the teacher's cube, once it's been received.
+    pub fn gcube(&self) -> Option<&GCube> {
+        self.gcube.as_ref()
+    }
+
+    /// Whether the teacher has stopped broadcasting.
+    pub fn is_closed(&self) -> bool {
+        self.client.is_closed()
+    }
+
+    /// Handles the teacher's events since the last update, returning the
+    /// teacher's movements and scrambles as commands, to mirror them. Call
+    /// once a frame.
+    pub fn update(&mut self) -> Vec<Command> {
+        let mut commands = vec![];
+        for json in self.client.poll() {
+            match serde_json::from_str(&json) {
+                Ok(CubeEvent::Move { movement }) => {
+                    if let Some(gcube) = self.gcube.as_mut() {
+                        gcube.apply_movement(&movement);
+                    }
+                    commands.push(Command::Move { movement });
+                }
+                Ok(CubeEvent::Scramble { scramble }) => commands.push(Command::Scramble {
+                    scramble: Some(scramble),
+                }),
+                // the teacher's state is the source of truth, e.g. after scrambles
+                Ok(CubeEvent::State { size, facelets, .. }) => {
+                    let facelets: Option<Vec<_>> =
+                        facelets.chars().map(Face::from_letter).collect();
+                    if let Some(gcube) = facelets.and_then(|f| GCube::from_facelets(size, &f)) {
+                        self.gcube = Some(gcube);
+                    }
+                }
+                _ => {}
+            }
+        }
+        commands
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Alg, BroadcastServer, Movement, ObservedCube, ServerEvent};
+    use std::{str::FromStr, thread, time::Duration};
+
+    #[test]
+    fn students_follow_the_teacher() {
+        let mut teacher = BroadcastServer::bind("127.0.0.1:0").unwrap();
+        let addr = teacher.server().local_addr().unwrap();
+        let mut gcube = GCube::new(3);
+        gcube.apply_movements(&[Movement::from_str("F").unwrap()]);
+        let student = thread::spawn(move || Follower::connect(addr).unwrap());
+        let is_connected = |e: &ServerEvent| matches!(e, ServerEvent::Connected(_));
+        while !teacher.update(&gcube).iter().any(is_connected) {
+            thread::sleep(Duration::from_millis(10));
+        }
+        let mut student = student.join().unwrap();
+
+        let mut teacher_cube = ObservedCube::new(gcube.clone());
+        teacher_cube.subscribe(Box::new(teacher));
+        let alg = Alg::from_str("R U R'").unwrap();
+        teacher_cube.apply_movements(alg.movements());
+
+        let mut commands = vec![];
+        for _ in 0..500 {
+            commands.extend(student.update());
+            if commands.len() == 3 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        let movements: Vec<Command> = alg
+            .movements()
+            .iter()
+            .map(|&movement| Command::Move { movement })
+            .collect();
+        assert_eq!(commands, movements);
+        // the student's copy starts from the teacher's state when connecting
+        assert_eq!(
+            student.gcube().unwrap().facelets(),
+            teacher_cube.gcube().facelets()
+        );
+    }
+}
@@ -1,36 +1,34 @@
-use crate::{Axis, Face, FaceletModel, Move, Movement, Point3, Turn, ORDERED_FACES, TOTAL_FACES};
-use std::{cmp::Ordering, convert::TryInto};
+use crate::{Axis, Face, FaceletModel, Move, Movement, Point3, Turn, ORDERED_FACES};
+use std::{collections::HashMap, convert::TryInto, iter::FromIterator};
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Sticker {
-    pub initial: Point3, // describes the sticker's initial position
-    pub current: Point3, // describes the sticker's current position
-    pub size: usize,
+    pub initial: Point3,  // describes the sticker's initial position
+    pub current: Point3,  // describes the sticker's current position
+    pub dims: [usize; 3], // number of layers of its cube along the x, y and z axes
 }
 
 impl Sticker {
-    pub fn new(size: usize, initial: Point3, current: Point3) -> Self {
+    pub fn new(dims: [usize; 3], initial: Point3, current: Point3) -> Self {
         Self {
-            size,
+            dims,
             initial,
             current,
         }
     }
 
-    pub fn from_point(size: usize, point: Point3) -> Self {
-        Self::new(size, point, point)
+    pub fn from_point(dims: [usize; 3], point: Point3) -> Self {
+        Self::new(dims, point, point)
     }
 
     pub fn apply_gmove(sticker: Self, gmove: GMove) -> Self {
-        if (gmove.predicate)(sticker) {
-            let Movement(_, turn) = gmove.movement;
-            let turns = if gmove.is_clockwise {
-                turn as i16
-            } else {
-                -(turn as i16)
-            };
+        if gmove.affects(sticker) {
             Sticker {
-                current: Point3::rotate_around_axis(sticker.current, gmove.axis, turns),
+                current: Point3::rotate_around_axis(
+                    sticker.current,
+                    gmove.axis,
+                    gmove.quarter_turns(),
+                ),
                 ..sticker
             }
         } else {
@@ -44,14 +42,18 @@ impl Sticker {
 /// Angle is based off the Movement's Turn component.
 /// The rotation direction around the axis is based off the is_clockwise flag.
 ///
-/// E.g. GMove(_, Axis::Y, |pos| pos.y >= 0) represents a geometric move around
+/// The predicate is also given the Movement's depth, for turning inner layers.
+/// It should only look at the coordinate along the axis, as a GMove turns
+/// whole layers, which lets a GCube turn them in bulk.
+///
+/// E.g. GMove(_, Axis::Y, |pos, _| pos.y >= 0) represents a geometric move around
 /// the y axis, that should only affect Point3s that have a y value >= 0
 #[derive(Copy, Clone, Debug)]
 pub struct GMove {
     movement: Movement,
     axis: Axis,
     is_clockwise: bool, // whether rotation around the axis is clockwise
-    predicate: fn(Sticker) -> bool,
+    predicate: fn(Sticker, u8) -> bool,
 }
 
 impl GMove {
@@ -59,7 +61,7 @@ impl GMove {
         movement: Movement,
         axis: Axis,
         is_clockwise: bool,
-        predicate: fn(Sticker) -> bool,
+        predicate: fn(Sticker, u8) -> bool,
     ) -> Self {
         Self {
             movement,
@@ -68,15 +70,227 @@ impl GMove {
             predicate,
         }
     }
+
+    pub fn movement(&self) -> Movement {
+        self.movement
+    }
+
+    pub fn axis(&self) -> Axis {
+        self.axis
+    }
+
+    /// Whether the rotation around the axis is clockwise, looking from the
+    /// axis' positive end towards the origin.
+    pub fn is_clockwise(&self) -> bool {
+        self.is_clockwise
+    }
+
+    /// Returns the number of clockwise quarter turns around the axis,
+    /// negative if the rotation is anticlockwise.
+    pub fn quarter_turns(&self) -> i16 {
+        let Movement(_, turn, _) = self.movement;
+        if self.is_clockwise {
+            turn as i16
+        } else {
+            -(turn as i16)
+        }
+    }
+
+    /// Whether the sticker is moved by this GMove.
+    pub fn affects(&self, sticker: Sticker) -> bool {
+        (self.predicate)(sticker, self.movement.depth())
+    }
+}
+
+/// A position where two cubes show different colours, i.e. the sticker at
+/// `GCube::sticker(index)` is at `position` and shows `actual`, whereas the other
+/// cube shows `expected` there.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct StickerDiff {
+    pub index: usize,
+    pub position: Point3,
+    pub actual: Face,
+    pub expected: Face,
+}
+
+/// A piece (cubie) of a cube, e.g. a corner, and the stickers on it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Piece {
+    /// where the center of the piece currently is
+    pub center: Point3,
+    /// indices of the piece's stickers, into GCube::stickers
+    pub stickers: Vec<usize>,
+}
+
+/// A direction along a face's middle center piece, turned with the piece, as
+/// its sticker alone doesn't show which way the piece is turned, e.g. for
+/// drawing a shape mod whose centers aren't symmetric.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CenterMark {
+    /// index of the center's sticker, into GCube::stickers
+    pub sticker: usize,
+    pub initial: Point3,
+    pub current: Point3,
+}
+
+/// A rotation of a whole cube, as where its x, y and z axes have been
+/// turned to, e.g. by the x, y and z rotations of a reconstruction.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Orientation {
+    pub axes: [Point3; 3],
+}
+
+impl Default for Orientation {
+    fn default() -> Self {
+        Self {
+            axes: [
+                Point3::new(1, 0, 0),
+                Point3::new(0, 1, 0),
+                Point3::new(0, 0, 1),
+            ],
+        }
+    }
+}
+
+impl Orientation {
+    /// Returns where the point at p before the cube was rotated is now.
+    pub fn apply(&self, p: Point3) -> Point3 {
+        self.axes[0] * p.x + self.axes[1] * p.y + self.axes[2] * p.z
+    }
+
+    /// Returns where the point now at p was before the cube was rotated.
+    pub fn unapply(&self, p: Point3) -> Point3 {
+        let [x, y, z] = self.axes.map(|axis| Point3::dot(axis, p));
+        Point3::new(x, y, z)
+    }
+
+    /// Returns the orientation after the cube is turned quarter_turns around
+    /// axis, see Point3::rotate_around_axis.
+    pub fn turned(self, axis: Axis, quarter_turns: i16) -> Self {
+        Self {
+            axes: self
+                .axes
+                .map(|a| Point3::rotate_around_axis(a, axis, quarter_turns)),
+        }
+    }
+
+    // returns the axis that's now along axis, and -1 if it's turned to point
+    // the other way
+    fn unapply_axis(&self, axis: Axis) -> (Axis, i16) {
+        let coord = |p: Point3| [p.x, p.y, p.z][axis as usize];
+        let i = (0..3).find(|&i| coord(self.axes[i]) != 0).unwrap();
+        ([Axis::X, Axis::Y, Axis::Z][i], coord(self.axes[i]))
+    }
+}
+
+// positions kept an axis per array, rather than a Point3 per sticker, so the
+// coordinates of many stickers can be read and turned together
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Points {
+    x: Vec<i16>,
+    y: Vec<i16>,
+    z: Vec<i16>,
+}
+
+impl Points {
+    fn len(&self) -> usize {
+        self.x.len()
+    }
+
+    fn get(&self, i: usize) -> Point3 {
+        Point3::new(self.x[i], self.y[i], self.z[i])
+    }
+
+    fn set(&mut self, i: usize, p: Point3) {
+        self.x[i] = p.x;
+        self.y[i] = p.y;
+        self.z[i] = p.z;
+    }
+
+    // returns the coordinates along axis, then along the next two axes
+    fn split_axis(&mut self, axis: Axis) -> (&[i16], &mut [i16], &mut [i16]) {
+        match axis {
+            Axis::X => (&self.x, &mut self.y, &mut self.z),
+            Axis::Y => (&self.y, &mut self.z, &mut self.x),
+            Axis::Z => (&self.z, &mut self.x, &mut self.y),
+        }
+    }
+}
+
+// the number of stickers turned together by GCube::turn_layers, a chunk at a
+// time, which the compiler turns into SIMD instructions where it can
+const LANES: usize = 16;
+
+// turns the coordinates us and vs, along the two axes after the one turned
+// around, of every sticker whose coordinate along it is in lo..=hi, by the
+// matrix taking a unit u to (uu, uv) and a unit v to (vu, vv)
+fn turn_lanes(
+    along: &[i16],
+    us: &mut [i16],
+    vs: &mut [i16],
+    (lo, hi): (i16, i16),
+    [uu, uv, vu, vv]: [i16; 4],
+) {
+    for ((&a, u), v) in along.iter().zip(us.iter_mut()).zip(vs.iter_mut()) {
+        let is_turned = lo <= a && a <= hi;
+        let (turned_u, turned_v) = (*u * uu + *v * vu, *u * uv + *v * vv);
+        *u = if is_turned { turned_u } else { *u };
+        *v = if is_turned { turned_v } else { *v };
+    }
+}
+
+impl FromIterator<Point3> for Points {
+    fn from_iter<I: IntoIterator<Item = Point3>>(points: I) -> Self {
+        let mut all = Self::default();
+        for p in points {
+            all.x.push(p.x);
+            all.y.push(p.y);
+            all.z.push(p.z);
+        }
+        all
+    }
 }
 
 // length of each cubic piece is 2 units, with cube origin at (0, 0, 0)
 // e.g. the U center piece is centered at (0, 2, 0),
 // and the U center sticker is on the surface, at (0, 3, 0)
-#[derive(Clone, Debug, PartialEq, Eq)]
+//
+// cuboids have a different number of layers along each axis, e.g. a 3x3x2
+// has stickers out to x = 3, y = 2 and z = 3
+#[derive(Clone, Debug)]
 pub struct GCube {
+    /// the number of layers along each axis of a cube, or the most along
+    /// any axis of a cuboid
     pub size: usize,
-    pub stickers: Vec<Sticker>,
+    /// the number of layers along the x (R), y (U) and z (F) axes
+    pub dims: [usize; 3],
+    pub center_marks: Vec<CenterMark>,
+    // where each sticker started, which is its colour, and is now, see
+    // stickers, before the whole cube was rotated to orientation, so that
+    // rotating it doesn't move every sticker
+    initial: Points,
+    current: Points,
+    orientation: Orientation,
+    // the colour of every facelet, ordered like facelets, before the whole
+    // cube was rotated, updated where stickers move rather than found again
+    // from every sticker
+    facelets: Vec<Face>,
+}
+
+// cubes are the same however their centers are turned, like on a real cube
+impl PartialEq for GCube {
+    fn eq(&self, other: &Self) -> bool {
+        self.dims == other.dims && self.stickers().eq(other.stickers())
+    }
+}
+
+impl Eq for GCube {}
+
+// returns the layer of a coordinate along an axis, counting from 1 at the
+// positive end, e.g. on a 3x3 x = 3 (R stickers) and x = 2 are in layer 1
+fn layer(coord: i16, size: usize) -> u8 {
+    let n = size as i16;
+    ((n - coord) / 2 + 1).min(n) as u8
 }
 
 impl GCube {
@@ -87,33 +301,120 @@ impl GCube {
     }
 
     // creates a solved cube
-    pub fn new(mut size: usize) -> Self {
-        if size == 0 {
-            size = 1;
-        }
-        let mut stickers: Vec<Sticker> = vec![];
-        // each sticker is on a face
-        let n = size as i16;
-        for face in [-n, n] {
-            // and the other 2 coordinates describe its position on that face
-            // e.g. 0, 0 for the center sticker of that face
-            for coord1 in Self::range(size) {
-                for coord2 in Self::range(size) {
-                    stickers.push(Sticker::from_point(size, Point3::new(face, coord1, coord2)));
-                    stickers.push(Sticker::from_point(size, Point3::new(coord1, face, coord2)));
-                    stickers.push(Sticker::from_point(size, Point3::new(coord1, coord2, face)));
+    pub fn new(size: usize) -> Self {
+        Self::cuboid([size; 3])
+    }
+
+    /// Creates a solved cuboid with dims layers along the x, y and z axes,
+    /// e.g. [3, 2, 3] for a 3x3x2. Its faces that aren't square only turn
+    /// half turns, see [allowed_movement](GCube::allowed_movement).
+    pub fn cuboid(dims: [usize; 3]) -> Self {
+        let dims = dims.map(|d| d.max(1));
+        let [x, y, z] = dims.map(|d| d as i16);
+        let mut points: Vec<Point3> = vec![];
+        let mut push = |point| points.push(point);
+        // each sticker is on a face, and the other 2 coordinates describe its
+        // position on that face, e.g. 0, 0 for the center sticker of that face
+        for sign in [-1, 1] {
+            for coord1 in Self::range(dims[1]) {
+                for coord2 in Self::range(dims[2]) {
+                    push(Point3::new(sign * x, coord1, coord2));
+                }
+            }
+            for coord1 in Self::range(dims[0]) {
+                for coord2 in Self::range(dims[2]) {
+                    push(Point3::new(coord1, sign * y, coord2));
+                }
+            }
+            for coord1 in Self::range(dims[0]) {
+                for coord2 in Self::range(dims[1]) {
+                    push(Point3::new(coord1, coord2, sign * z));
                 }
             }
         }
-        Self { size, stickers }
+        // faces with an odd number of layers both ways have a middle center,
+        // marked along the next axis, e.g. R's along y
+        let center_marks = points
+            .iter()
+            .enumerate()
+            .filter_map(|(i, point)| {
+                let p = [point.x, point.y, point.z];
+                let axis =
+                    (0..3).find(|&a| p[a] != 0 && p.iter().filter(|&&c| c == 0).count() == 2)?;
+                let mut direction = [0; 3];
+                direction[(axis + 1) % 3] = 1;
+                let direction = Point3::new(direction[0], direction[1], direction[2]);
+                Some(CenterMark {
+                    sticker: i,
+                    initial: direction,
+                    current: direction,
+                })
+            })
+            .collect();
+        let mut gcube = Self {
+            size: *dims.iter().max().unwrap(),
+            dims,
+            center_marks,
+            initial: points.iter().cloned().collect(),
+            current: points.into_iter().collect(),
+            orientation: Orientation::default(),
+            facelets: vec![],
+        };
+        gcube.facelets = gcube.facelets_from_stickers();
+        gcube
+    }
+
+    /// Returns every sticker, where it started and is now, e.g. for drawing
+    /// them. Only apply_gmove turns them and paint colours them, keeping
+    /// facelets in step.
+    pub fn stickers(&self) -> impl ExactSizeIterator<Item = Sticker> + '_ {
+        (0..self.current.len()).map(move |i| self.sticker(i))
+    }
+
+    /// Returns the sticker at index, ordered like stickers.
+    pub fn sticker(&self, index: usize) -> Sticker {
+        let current = self.orientation.apply(self.current.get(index));
+        Sticker::new(self.dims, self.initial.get(index), current)
+    }
+
+    /// Whether every axis has as many layers, i.e. it's not a cuboid.
+    pub fn is_cube(&self) -> bool {
+        self.dims == [self.size; 3]
     }
 
     pub fn change_size(&mut self, size: usize) {
-        if size != self.size && size > 0 {
+        if [size; 3] != self.dims && size > 0 {
             *self = Self::new(size);
         }
     }
 
+    /// Returns the cube as a size x size cube in the same state where it can
+    /// be, its outer layers copied and its inner layers stretched or
+    /// squeezed to fit, e.g. a 3x3 as a reduced 4x4, or the 3x3 a reduced
+    /// 5x5 is solved as. Returns None for a cuboid, or for a 2x2 or smaller
+    /// if the new size has inner layers, as there are none to stretch.
+    pub fn resized(&self, size: usize) -> Option<GCube> {
+        let n = self.size;
+        if !self.is_cube() || size == 0 || (n <= 2 && size > 2) {
+            return None;
+        }
+        // the row or column of this cube's faces that row or column i of the
+        // resized cube's is copied from, the middle of its share of them
+        let from = |i: usize| match i {
+            0 => 0,
+            i if i == size - 1 => n - 1,
+            i => 1 + (2 * (i - 1) + 1) * (n - 2) / (2 * (size - 2)),
+        };
+        let facelets = self.facelets();
+        let resized: Vec<Face> = (0..6 * size * size)
+            .map(|i| {
+                let (face, row, col) = (i / (size * size), i / size % size, i % size);
+                facelets[(face * n + from(row)) * n + from(col)]
+            })
+            .collect();
+        GCube::from_facelets(size, &resized)
+    }
+
     // increases cube size by 1
     pub fn grow(&mut self) {
         self.change_size(self.size + 1);
@@ -127,54 +428,54 @@ impl GCube {
     }
 
     // create the GMove that corresponds to the given Movement
-    fn create_gmove(movement: Movement) -> GMove {
-        let Movement(m, _) = movement;
+    pub fn create_gmove(movement: Movement) -> GMove {
+        let Movement(m, _, _) = movement;
         match m {
-            // typical moves
-            Move::U => GMove::new(movement, Axis::Y, true, |s| {
-                s.current.y >= (s.size as i16) - 2
+            // typical moves, turning the layer at depth d, or d layers for wide moves
+            Move::U => GMove::new(movement, Axis::Y, true, |s, d| {
+                layer(s.current.y, s.dims[1]) == d
             }),
-            Move::Uw => GMove::new(movement, Axis::Y, true, |s| {
-                s.current.y >= (s.size as i16) - 2 * 2
+            Move::Uw => GMove::new(movement, Axis::Y, true, |s, d| {
+                layer(s.current.y, s.dims[1]) <= d
             }),
-            Move::L => GMove::new(movement, Axis::X, false, |s| {
-                s.current.x <= -(s.size as i16) + 2
+            Move::L => GMove::new(movement, Axis::X, false, |s, d| {
+                layer(-s.current.x, s.dims[0]) == d
             }),
-            Move::Lw => GMove::new(movement, Axis::X, false, |s| {
-                s.current.x <= -(s.size as i16) + 2 * 2
+            Move::Lw => GMove::new(movement, Axis::X, false, |s, d| {
+                layer(-s.current.x, s.dims[0]) <= d
             }),
-            Move::F => GMove::new(movement, Axis::Z, true, |s| {
-                s.current.z >= (s.size as i16) - 2
+            Move::F => GMove::new(movement, Axis::Z, true, |s, d| {
+                layer(s.current.z, s.dims[2]) == d
             }),
-            Move::Fw => GMove::new(movement, Axis::Z, true, |s| {
-                s.current.z >= (s.size as i16) - 2 * 2
+            Move::Fw => GMove::new(movement, Axis::Z, true, |s, d| {
+                layer(s.current.z, s.dims[2]) <= d
             }),
-            Move::R => GMove::new(movement, Axis::X, true, |s| {
-                s.current.x >= (s.size as i16) - 2
+            Move::R => GMove::new(movement, Axis::X, true, |s, d| {
+                layer(s.current.x, s.dims[0]) == d
             }),
-            Move::Rw => GMove::new(movement, Axis::X, true, |s| {
-                s.current.x >= (s.size as i16) - 2 * 2
+            Move::Rw => GMove::new(movement, Axis::X, true, |s, d| {
+                layer(s.current.x, s.dims[0]) <= d
             }),
-            Move::B => GMove::new(movement, Axis::Z, false, |s| {
-                s.current.z <= -(s.size as i16) + 2
+            Move::B => GMove::new(movement, Axis::Z, false, |s, d| {
+                layer(-s.current.z, s.dims[2]) == d
             }),
-            Move::Bw => GMove::new(movement, Axis::Z, false, |s| {
-                s.current.z <= -(s.size as i16) + 2 * 2
+            Move::Bw => GMove::new(movement, Axis::Z, false, |s, d| {
+                layer(-s.current.z, s.dims[2]) <= d
             }),
-            Move::D => GMove::new(movement, Axis::Y, false, |s| {
-                s.current.y <= -(s.size as i16) + 2
+            Move::D => GMove::new(movement, Axis::Y, false, |s, d| {
+                layer(-s.current.y, s.dims[1]) == d
             }),
-            Move::Dw => GMove::new(movement, Axis::Y, false, |s| {
-                s.current.y <= -(s.size as i16) + 2 * 2
+            Move::Dw => GMove::new(movement, Axis::Y, false, |s, d| {
+                layer(-s.current.y, s.dims[1]) <= d
             }),
             // slice moves
-            Move::E => GMove::new(movement, Axis::Y, false, |s| s.current.y == 0),
-            Move::M => GMove::new(movement, Axis::X, false, |s| s.current.x == 0),
-            Move::S => GMove::new(movement, Axis::Z, true, |s| s.current.z == 0),
+            Move::E => GMove::new(movement, Axis::Y, false, |s, _| s.current.y == 0),
+            Move::M => GMove::new(movement, Axis::X, false, |s, _| s.current.x == 0),
+            Move::S => GMove::new(movement, Axis::Z, true, |s, _| s.current.z == 0),
             // rotations
-            Move::X => GMove::new(movement, Axis::X, true, |_| true),
-            Move::Y => GMove::new(movement, Axis::Y, true, |_| true),
-            Move::Z => GMove::new(movement, Axis::Z, true, |_| true),
+            Move::X => GMove::new(movement, Axis::X, true, |_, _| true),
+            Move::Y => GMove::new(movement, Axis::Y, true, |_, _| true),
+            Move::Z => GMove::new(movement, Axis::Z, true, |_, _| true),
         }
     }
 
@@ -185,9 +486,111 @@ impl GCube {
             .collect()
     }
 
+    /// Turns the stickers gmove affects, as a half turn if it would quarter
+    /// turn a face that isn't square, see allowed_movement.
     pub fn apply_gmove(&mut self, gmove: GMove) {
-        for sticker in self.stickers.iter_mut() {
-            *sticker = Sticker::apply_gmove(*sticker, gmove);
+        let gmove = self.allowed_gmove(gmove);
+        let len = self.dims[gmove.axis as usize] as i16;
+        match self.turned_coords(gmove) {
+            // rotating the whole cube only turns its orientation
+            Some(coords) if coords == (-len, len) => {
+                self.turn_center_marks(gmove);
+                self.orientation = self.orientation.turned(gmove.axis, gmove.quarter_turns());
+            }
+            Some(coords) => self.turn_layers(gmove, coords),
+            None => self.apply_gmove_per_sticker(gmove),
+        }
+    }
+
+    /// Turns the stickers gmove affects one at a time, as apply_gmove does
+    /// if the layers it turns aren't next to each other, e.g. for comparing
+    /// against turning them in bulk.
+    pub fn apply_gmove_per_sticker(&mut self, gmove: GMove) {
+        let gmove = self.allowed_gmove(gmove);
+        self.turn_center_marks(gmove);
+        // the moved stickers land on each other's positions, so only their
+        // facelets change
+        for i in 0..self.current.len() {
+            let sticker = self.sticker(i);
+            if gmove.affects(sticker) {
+                let current =
+                    Point3::rotate_around_axis(sticker.current, gmove.axis, gmove.quarter_turns());
+                let current = self.orientation.unapply(current);
+                self.current.set(i, current);
+                let index = self.facelet_index(current);
+                self.facelets[index] = self.get_initial_face(sticker);
+            }
+        }
+    }
+
+    fn turn_center_marks(&mut self, gmove: GMove) {
+        for m in 0..self.center_marks.len() {
+            if gmove.affects(self.sticker(self.center_marks[m].sticker)) {
+                let mark = &mut self.center_marks[m];
+                mark.current =
+                    Point3::rotate_around_axis(mark.current, gmove.axis, gmove.quarter_turns());
+            }
+        }
+    }
+
+    // returns the lowest and highest coordinates along gmove's axis of the
+    // layers it turns, found by asking it about a point at each, or None if
+    // there are gaps between them or it turns none
+    fn turned_coords(&self, gmove: GMove) -> Option<(i16, i16)> {
+        let axis = gmove.axis as usize;
+        let len = self.dims[axis] as i16;
+        let turned: Vec<i16> = (-len..=len)
+            .filter(|&c| {
+                let mut p = [0; 3];
+                p[axis] = c;
+                gmove.affects(Sticker::from_point(
+                    self.dims,
+                    Point3::new(p[0], p[1], p[2]),
+                ))
+            })
+            .collect();
+        let (&lo, &hi) = (turned.first()?, turned.last()?);
+        ((hi - lo) as usize + 1 == turned.len()).then_some((lo, hi))
+    }
+
+    // turns every sticker whose coordinate along gmove's axis is in coords
+    // together, LANES at a time, rather than asking gmove about each
+    fn turn_layers(&mut self, gmove: GMove, (lo, hi): (i16, i16)) {
+        self.turn_center_marks(gmove);
+        // the layers as they were before the whole cube was rotated
+        let (turned_axis, sign) = self.orientation.unapply_axis(gmove.axis);
+        let (lo, hi) = if sign > 0 { (lo, hi) } else { (-hi, -lo) };
+        let quarter_turns = sign * gmove.quarter_turns();
+        // where the unit vectors along the next two axes, u then v, turn to
+        let axis = turned_axis as usize;
+        let turned = |a: usize| {
+            let mut p = [0; 3];
+            p[a] = 1;
+            let p = Point3::rotate_around_axis(
+                Point3::new(p[0], p[1], p[2]),
+                turned_axis,
+                quarter_turns,
+            );
+            [p.x, p.y, p.z]
+        };
+        let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+        let matrix = [turned(u)[u], turned(u)[v], turned(v)[u], turned(v)[v]];
+        let (along, us, vs) = self.current.split_axis(turned_axis);
+        let mut along = along.chunks_exact(LANES);
+        let (mut us, mut vs) = (us.chunks_exact_mut(LANES), vs.chunks_exact_mut(LANES));
+        for ((a, u), v) in (&mut along).zip(&mut us).zip(&mut vs) {
+            turn_lanes(a, u, v, (lo, hi), matrix);
+        }
+        let (a, u, v) = (along.remainder(), us.into_remainder(), vs.into_remainder());
+        turn_lanes(a, u, v, (lo, hi), matrix);
+        // turning around an axis doesn't change coordinates along it
+        for i in 0..self.current.len() {
+            let current = self.current.get(i);
+            let a = [current.x, current.y, current.z][axis];
+            if lo <= a && a <= hi {
+                let index = self.facelet_index(current);
+                self.facelets[index] = self.get_face(self.initial.get(i));
+            }
         }
     }
 
@@ -197,6 +600,36 @@ impl GCube {
         }
     }
 
+    /// Returns movement, or a half turn instead of a quarter turn of a face
+    /// that isn't square, which would change the shape of a cuboid, e.g. R2
+    /// instead of R on a 3x3x2. It's what apply_gmove turns, e.g. for
+    /// recording the movement as it was turned.
+    pub fn allowed_movement(&self, movement: Movement) -> Movement {
+        self.allowed_turn(movement, Self::create_gmove(movement).axis())
+    }
+
+    // returns gmove, turning half turns instead of quarter turns of a face
+    // that isn't square, so turning never changes the cuboid's shape
+    fn allowed_gmove(&self, gmove: GMove) -> GMove {
+        GMove {
+            movement: self.allowed_turn(gmove.movement, gmove.axis),
+            ..gmove
+        }
+    }
+
+    // returns movement, turning around axis, as a half turn if the faces
+    // across axis aren't square
+    fn allowed_turn(&self, movement: Movement, axis: Axis) -> Movement {
+        let axis = axis as usize;
+        let is_square = self.dims[(axis + 1) % 3] == self.dims[(axis + 2) % 3];
+        match movement {
+            Movement(m, Turn::Single | Turn::Inverse, depth) if !is_square => {
+                Movement(m, Turn::Double, depth)
+            }
+            _ => movement,
+        }
+    }
+
     pub fn apply_movement(&mut self, movement: &Movement) {
         self.apply_gmoves(&[Self::create_gmove(*movement)]);
     }
@@ -206,70 +639,347 @@ impl GCube {
     }
 
     fn get_face(&self, pos: Point3) -> Face {
-        let n = self.size as i16;
-        if pos.x == n {
+        let [x, y, z] = self.dims.map(|d| d as i16);
+        if pos.x == x {
             Face::R
-        } else if pos.x == -n {
+        } else if pos.x == -x {
             Face::L
-        } else if pos.y == n {
+        } else if pos.y == y {
             Face::U
-        } else if pos.y == -n {
+        } else if pos.y == -y {
             Face::D
-        } else if pos.z == n {
+        } else if pos.z == z {
             Face::F
-        } else if pos.z == -n {
+        } else if pos.z == -z {
             Face::B
         } else {
             Face::X
         }
     }
 
+    /// Returns the 3x3 facelet model of the cube, which must be a 3x3.
     pub fn to_facelet_model(&self) -> FaceletModel {
-        let mut facelet_stickers: Vec<Face> =
-            Vec::with_capacity(self.size * self.size * TOTAL_FACES);
-
-        // assumes stickers are on the F face
-        let mut set_face = |mut stickers: Vec<Sticker>, mut index: usize| {
-            // sort the stickers for insertion into the facelet model,
-            // where facelets per face are ordered from left to right,
-            // then top to bottom. On the F face, top left sticker has the
-            // smallest x value (x axis points right),
-            // and the highest y value (y axis points up)
-            stickers.sort_by(|a, b| {
-                match a.current.y > b.current.y
-                    || (a.current.y == b.current.y && a.current.x < b.current.x)
-                {
-                    true => Ordering::Less,
-                    false => Ordering::Greater,
+        FaceletModel(self.facelets().try_into().unwrap())
+    }
+
+    /// Returns the colour of every sticker as the face it started on, ordered
+    /// by U, R, F, D, L, then B face, and on each face from left to right,
+    /// then top to bottom, like the facelet model but for any size.
+    pub fn facelets(&self) -> Vec<Face> {
+        if self.orientation == Orientation::default() {
+            return self.facelets.clone();
+        }
+        // the cube's rotations move each face's facelets onto another face
+        // together, so only where a face's first facelet and its neighbours
+        // are turned to is found, and the rest follow along rows and columns
+        let mut facelets = vec![Face::X; self.facelets.len()];
+        let turned = |i| self.facelet_index(self.orientation.apply(self.facelet_position(i)));
+        let mut start = 0;
+        for face in 0..6 {
+            let (rows, cols) = self.face_grid(face);
+            let first = turned(start) as isize;
+            let step = |i, n: usize| match n > 1 {
+                true => turned(i) as isize - first,
+                false => 0,
+            };
+            let (row_step, col_step) = (step(start + cols, rows), step(start + 1, cols));
+            for row in 0..rows {
+                for col in 0..cols {
+                    let index = first + row as isize * row_step + col as isize * col_step;
+                    facelets[index as usize] = self.facelets[start + row * cols + col];
                 }
-            });
-            for sticker in stickers {
-                facelet_stickers[index] = self.get_face(sticker.initial);
-                index += 1;
             }
+            start += rows * cols;
+        }
+        facelets
+    }
+
+    // finds facelets from every sticker, rather than where they've moved
+    fn facelets_from_stickers(&self) -> Vec<Face> {
+        self.facelet_order()
+            .iter()
+            .map(|&i| self.get_initial_face(self.sticker(i)))
+            .collect()
+    }
+
+    /// Returns an NxN cube coloured like facelets, ordered like
+    /// [facelets](GCube::facelets), e.g. one received over the network, or
+    /// None if there aren't 6 * N * N facelets.
+    pub fn from_facelets(size: usize, facelets: &[Face]) -> Option<Self> {
+        let mut gcube = GCube::new(size);
+        if facelets.len() != gcube.current.len() {
+            return None;
+        }
+        for (&i, &face) in gcube.facelet_order().iter().zip(facelets) {
+            gcube.initial.set(i, face_center(face, gcube.dims)?);
+        }
+        gcube.facelets = facelets.to_vec();
+        Some(gcube)
+    }
+
+    /// Colours the sticker at index (into stickers) like face, e.g. when
+    /// entering the state of a physical cube. Painting with X does nothing.
+    pub fn paint(&mut self, index: usize, face: Face) {
+        if let Some(center) = face_center(face, self.dims) {
+            self.initial.set(index, center);
+            let facelet = self.facelet_index(self.current.get(index));
+            self.facelets[facelet] = face;
+        }
+    }
+
+    /// Returns the index of the sticker at the facelet at index, ordered
+    /// like facelets.
+    pub fn facelet_sticker(&self, index: usize) -> usize {
+        self.facelet_order()[index]
+    }
+
+    // returns the index of every sticker, ordered like facelets
+    fn facelet_order(&self) -> Vec<usize> {
+        let mut order = vec![0; self.current.len()];
+        for i in 0..self.current.len() {
+            order[self.facelet_index(self.sticker(i).current)] = i;
+        }
+        order
+    }
+
+    // returns the index of the facelet at pos, a sticker's current position,
+    // ordered like facelets
+    fn facelet_index(&self, pos: Point3) -> usize {
+        let [x_len, y_len, z_len] = self.dims;
+        // facelets per face are ordered from left to right, then top to
+        // bottom, as seen looking at the face, e.g. on F the top left
+        // sticker has the smallest x value (x axis points right) and the
+        // highest y value (y axis points up), and U is seen with B on top
+        let nth = |coord: i16, len: usize| ((coord + len as i16 - 1) / 2) as usize;
+        let Point3 { x, y, z } = pos;
+        let (face, row, col, row_len) = match self.get_face(pos) {
+            Face::U => (0, nth(z, z_len), nth(x, x_len), x_len),
+            Face::R => (1, nth(-y, y_len), nth(-z, z_len), z_len),
+            Face::F => (2, nth(-y, y_len), nth(x, x_len), x_len),
+            Face::D => (3, nth(-z, z_len), nth(x, x_len), x_len),
+            Face::L => (4, nth(-y, y_len), nth(z, z_len), z_len),
+            _ => (5, nth(-y, y_len), nth(-x, x_len), x_len),
         };
+        // the number of facelets on U, R, F, D, L then B
+        let face_lens = [x_len * z_len, y_len * z_len, x_len * y_len];
+        let before: usize = (0..face).map(|f| face_lens[f % 3]).sum();
+        before + row * row_len + col
+    }
 
-        for (pos, face) in ORDERED_FACES.iter().enumerate() {
-            let mut c = self.clone();
-            // move the current face to the F face, then transfer the face data
-            match face {
-                Face::U => c.apply_movement(&Movement(Move::X, Turn::Inverse)),
-                Face::R => c.apply_movement(&Movement(Move::Y, Turn::Single)),
-                Face::L => c.apply_movement(&Movement(Move::Y, Turn::Inverse)),
-                Face::B => c.apply_movement(&Movement(Move::Y, Turn::Double)),
-                Face::D => c.apply_movement(&Movement(Move::X, Turn::Single)),
-                _ => {}
-            };
-            let v: Vec<Sticker> = c
-                .stickers
-                .iter()
-                .cloned()
-                .filter(|s| self.get_face(s.current) == Face::F)
-                .collect();
-            // guaranteed to be 9 stickers on the F face
-            set_face(v, pos * self.size * self.size);
+    // returns the number of rows and columns of facelets on the face at
+    // index (U, R, F, D, L, then B), ordered like facelets
+    fn face_grid(&self, face: usize) -> (usize, usize) {
+        let [x_len, y_len, z_len] = self.dims;
+        match face % 3 {
+            0 => (z_len, x_len),
+            1 => (y_len, z_len),
+            _ => (y_len, x_len),
+        }
+    }
+
+    // returns the position of the facelet at index, the inverse of
+    // facelet_index
+    fn facelet_position(&self, index: usize) -> Point3 {
+        let len = |face| {
+            let (rows, cols) = self.face_grid(face);
+            rows * cols
+        };
+        let (mut face, mut index) = (0, index);
+        while index >= len(face) {
+            index -= len(face);
+            face += 1;
+        }
+        let cols = self.face_grid(face).1;
+        let (row, col) = (index / cols, index % cols);
+        let [x_len, y_len, z_len] = self.dims;
+        let [x, y, z] = self.dims.map(|d| d as i16);
+        // the inverse of facelet_index's nth
+        let coord = |n: usize, len: usize| (2 * n + 1) as i16 - len as i16;
+        match face {
+            0 => Point3::new(coord(col, x_len), y, coord(row, z_len)),
+            1 => Point3::new(x, -coord(row, y_len), -coord(col, z_len)),
+            2 => Point3::new(coord(col, x_len), -coord(row, y_len), z),
+            3 => Point3::new(coord(col, x_len), -y, -coord(row, z_len)),
+            4 => Point3::new(-x, -coord(row, y_len), coord(col, z_len)),
+            _ => Point3::new(-coord(col, x_len), -coord(row, y_len), -z),
+        }
+    }
+
+    /// Returns every sticker whose colour differs from the colour at the same
+    /// position on other. Cubes of different sizes differ at every sticker.
+    pub fn diff(&self, other: &GCube) -> Vec<StickerDiff> {
+        let other_colors: HashMap<Point3, Face> = other
+            .stickers()
+            .map(|s| (s.current, other.get_initial_face(s)))
+            .collect();
+        self.stickers()
+            .enumerate()
+            .filter_map(|(index, s)| {
+                let actual = self.get_initial_face(s);
+                let expected = match other.dims == self.dims {
+                    true => other_colors.get(&s.current).cloned().unwrap_or(Face::X),
+                    false => Face::X,
+                };
+                match actual == expected {
+                    true => None,
+                    false => Some(StickerDiff {
+                        index,
+                        position: s.current,
+                        actual,
+                        expected,
+                    }),
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the stickers that differ from a solved cube of the same size
+    /// in the cube's orientation, see solved_in_orientation.
+    pub fn unsolved_stickers(&self) -> Vec<StickerDiff> {
+        self.diff(&self.solved_in_orientation())
+    }
+
+    /// Whether two movements can be turned at the same time, i.e. they turn
+    /// around the same axis and share no layers, like R and L'.
+    pub fn can_turn_together(&self, a: Movement, b: Movement) -> bool {
+        let (a, b) = (Self::create_gmove(a), Self::create_gmove(b));
+        // the set of sticker positions is the same in any state, so this
+        // doesn't depend on how the cube is scrambled
+        a.axis() == b.axis() && !self.stickers().any(|s| a.affects(s) && b.affects(s))
+    }
+
+    /// Whether the cube is solved, in any orientation.
+    pub fn is_solved(&self) -> bool {
+        self.unsolved_stickers().is_empty()
+    }
+
+    /// Returns the cube solved, but turned to its orientation, e.g. to show
+    /// the state it's being solved to after rotations.
+    pub fn solved_in_orientation(&self) -> GCube {
+        let orientation = self.orientation();
+        let mut solved = GCube::cuboid(self.dims);
+        solved.orientation = orientation;
+        for mark in solved.center_marks.iter_mut() {
+            mark.current = orientation.apply(mark.initial);
+        }
+        solved
+    }
+
+    /// Returns the rotation of the whole cube: the one turning a solved
+    /// cube's faces to the colours of this one's middle centers, or of its
+    /// faces when each is one colour, e.g. after a solution ending in
+    /// rotations, and otherwise (as for an even cube's scrambled centers, or
+    /// a cuboid) the whole cube turns it's been turned by, e.g. x and y.
+    pub fn orientation(&self) -> Orientation {
+        let colors = match self.face_colors() {
+            Some(colors) if self.solved_colors(self.orientation) != colors => colors,
+            _ => return self.orientation,
+        };
+        // every orientation is some x, y then z rotations from standard
+        (0..64)
+            .map(|i: usize| {
+                let axes = [Axis::X, Axis::Y, Axis::Z].iter().enumerate();
+                axes.fold(Orientation::default(), |orientation, (n, &axis)| {
+                    orientation.turned(axis, (i / 4usize.pow(n as u32) % 4) as i16)
+                })
+            })
+            .find(|&orientation| self.solved_colors(orientation) == colors)
+            .unwrap_or(self.orientation)
+    }
+
+    // returns the colour of each face, ordered like ORDERED_FACES, as its
+    // middle center's or, if it has none, the whole face's if it's one
+    // colour, or None if a face has neither or it's a cuboid
+    fn face_colors(&self) -> Option<Vec<Face>> {
+        let n = self.size;
+        if !self.is_cube() {
+            return None;
+        }
+        let facelets = self.facelets();
+        facelets
+            .chunks(n * n)
+            .map(|face| match n % 2 {
+                1 => Some(face[n * n / 2]),
+                _ => face.iter().all(|&f| f == face[0]).then(|| face[0]),
+            })
+            .collect()
+    }
+
+    // returns the colour of each face of the cube solved in orientation,
+    // ordered like ORDERED_FACES
+    fn solved_colors(&self, orientation: Orientation) -> Vec<Face> {
+        ORDERED_FACES
+            .iter()
+            .map(|&face| {
+                let center = face_center(face, self.dims).unwrap();
+                self.get_face(orientation.unapply(center))
+            })
+            .collect()
+    }
+
+    /// Returns the pieces on the outside of the cube, with their stickers,
+    /// ordered by where they currently are.
+    pub fn pieces(&self) -> Vec<Piece> {
+        let mut pieces: HashMap<Point3, Vec<usize>> = HashMap::new();
+        for i in 0..self.current.len() {
+            pieces
+                .entry(self.piece_center(self.sticker(i).current))
+                .or_default()
+                .push(i);
         }
-        FaceletModel(facelet_stickers.try_into().unwrap())
+        let mut pieces: Vec<Piece> = pieces
+            .into_iter()
+            .map(|(center, stickers)| Piece { center, stickers })
+            .collect();
+        pieces.sort_by_key(|p| (p.center.x, p.center.y, p.center.z));
+        pieces
+    }
+
+    // the center of the piece a sticker at p is on, as a sticker is on the
+    // surface, 1 unit out from its piece's center
+    fn piece_center(&self, p: Point3) -> Point3 {
+        let [x, y, z] = self.dims.map(|d| d as i16);
+        let inward = |c: i16, n: i16| match c {
+            c if c == n => c - 1,
+            c if c == -n => c + 1,
+            c => c,
+        };
+        Point3::new(inward(p.x, x), inward(p.y, y), inward(p.z, z))
+    }
+
+    /// Returns the center the piece started at, and where the unit x, y and
+    /// z vectors are turned to by the turns that moved it there, or None if
+    /// that's unknown, i.e. for centers without a [CenterMark].
+    pub fn piece_orientation(&self, piece: &Piece) -> Option<(Point3, [Point3; 3])> {
+        let first = self.sticker(*piece.stickers.first()?);
+        let home = self.piece_center(first.initial);
+        // the directions out of the piece it started with, and has now
+        let mut turned: Vec<(Point3, Point3)> = piece
+            .stickers
+            .iter()
+            .map(|&i| {
+                let s = self.sticker(i);
+                (s.initial - home, s.current - piece.center)
+            })
+            .collect();
+        turned.extend(
+            self.center_marks
+                .iter()
+                .filter(|mark| piece.stickers.contains(&mark.sticker))
+                .map(|mark| (mark.initial, mark.current)),
+        );
+        let (a0, a1) = turned[0];
+        let &(b0, b1) = turned.iter().find(|(b0, _)| Point3::dot(a0, *b0) == 0)?;
+        let (c0, c1) = (Point3::cross(a0, b0), Point3::cross(a1, b1));
+        // each axis is a sum of the 3 (perpendicular) directions, which turn
+        // to the same sum of where they are now
+        let axes = [
+            Point3::new(1, 0, 0),
+            Point3::new(0, 1, 0),
+            Point3::new(0, 0, 1),
+        ]
+        .map(|e| a1 * Point3::dot(e, a0) + b1 * Point3::dot(e, b0) + c1 * Point3::dot(e, c0));
+        Some((home, axes))
     }
 
     pub fn get_curr_face(&self, sticker: Sticker) -> Face {
@@ -281,14 +991,226 @@ impl GCube {
     }
 }
 
+// the center of a face of a cube with dims layers, as only the face a sticker
+// starts on matters for its colour
+fn face_center(face: Face, dims: [usize; 3]) -> Option<Point3> {
+    let [x, y, z] = dims.map(|d| d as i16);
+    Some(match face {
+        Face::U => Point3::new(0, y, 0),
+        Face::D => Point3::new(0, -y, 0),
+        Face::R => Point3::new(x, 0, 0),
+        Face::L => Point3::new(-x, 0, 0),
+        Face::F => Point3::new(0, 0, z),
+        Face::B => Point3::new(0, 0, -z),
+        Face::X => return None,
+    })
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::scramble_to_movements;
+    use crate::{scramble_to_movements, Alg};
 
     use super::*;
-    use crate::Turn;
+    use crate::{any_alg, any_gcube, Turn};
+    use proptest::{prop_assert_eq, proptest};
+    use std::str::FromStr;
     use strum::IntoEnumIterator;
 
+    #[test]
+    fn facelets() {
+        assert_eq!(GCube::new(3).to_facelet_model(), FaceletModel::new());
+        let mut gcube = GCube::new(2);
+        gcube.apply_movements(&scramble_to_movements("R").unwrap());
+        let facelets: String = gcube.facelets().iter().map(|f| f.to_string()).collect();
+        assert_eq!(facelets, "UFUFRRRRFDFDDBDBLLLLUBUB");
+    }
+
+    #[test]
+    fn cubes_are_made_from_facelets() {
+        let mut gcube = GCube::new(4);
+        gcube.apply_movements(&scramble_to_movements("R 2U' Fw2 L").unwrap());
+        let copy = GCube::from_facelets(4, &gcube.facelets()).unwrap();
+        assert_eq!(copy.facelets(), gcube.facelets());
+        assert!(!copy.is_solved());
+        assert!(GCube::from_facelets(3, &GCube::new(3).facelets())
+            .unwrap()
+            .is_solved());
+        assert_eq!(GCube::from_facelets(3, &gcube.facelets()), None);
+
+        // painting a sticker only changes its colour
+        let mut painted = copy.clone();
+        let index = painted.stickers().position(|s| s.current.y == 4).unwrap();
+        painted.paint(index, Face::B);
+        painted.paint(0, Face::X);
+        assert_eq!(painted.get_curr_face(painted.sticker(index)), Face::U);
+        assert_eq!(painted.get_initial_face(painted.sticker(index)), Face::B);
+        let changed = painted
+            .facelets()
+            .iter()
+            .zip(copy.facelets())
+            .filter(|(a, b)| **a != *b)
+            .count();
+        assert_eq!(
+            changed,
+            usize::from(copy.get_initial_face(copy.sticker(index)) != Face::B)
+        );
+    }
+
+    #[test]
+    fn pieces_know_which_way_theyre_turned() {
+        let mut gcube = GCube::new(3);
+        gcube.apply_movements(&scramble_to_movements("U").unwrap());
+        let orientation = |gcube: &GCube, center: Point3| {
+            let piece = gcube
+                .pieces()
+                .into_iter()
+                .find(|p| p.center == center)
+                .unwrap();
+            gcube.piece_orientation(&piece)
+        };
+        // U turns x to z, for UR (now at UF) and the U center, whose sticker
+        // alone can't tell
+        let (x, y, z) = (
+            Point3::new(1, 0, 0),
+            Point3::new(0, 1, 0),
+            Point3::new(0, 0, 1),
+        );
+        assert_eq!(orientation(&gcube, y * 2), Some((y * 2, [z, y, -x])));
+        assert_eq!(
+            orientation(&gcube, (y + z) * 2),
+            Some(((x + y) * 2, [z, y, -x]))
+        );
+        assert_eq!(orientation(&gcube, z * 2), Some((z * 2, [x, y, z])));
+        // turned centers don't make the cube unsolved
+        gcube.apply_movements(&scramble_to_movements("U'").unwrap());
+        gcube.center_marks[0].current = -gcube.center_marks[0].current;
+        assert_eq!(gcube, GCube::new(3));
+        assert_eq!(GCube::new(4).center_marks.len(), 0);
+    }
+
+    #[test]
+    fn cuboids_only_half_turn_faces_that_arent_square() {
+        // a 3x3x2, only 2 layers tall
+        let mut gcube = GCube::cuboid([3, 2, 3]);
+        assert_eq!(gcube.size, 3);
+        assert!(!gcube.is_cube() && GCube::new(4).is_cube());
+        assert_eq!(gcube.stickers().len(), 2 * (3 * 3 + 2 * 3 + 2 * 3));
+        assert_eq!(gcube.pieces().len(), 18);
+        let movements: Vec<Movement> = scramble_to_movements("R U F' D2 x y'")
+            .unwrap()
+            .into_iter()
+            .map(|m| gcube.allowed_movement(m))
+            .collect();
+        assert_eq!(Alg::new(movements.clone()).to_string(), "R2 U F2 D2 X2 Y'");
+        gcube.apply_movements(&movements);
+        assert!(!gcube.is_solved());
+        // every sticker is still on a face of the same shape
+        assert!(gcube.stickers().all(|s| gcube.get_curr_face(s) != Face::X));
+        assert_eq!(gcube.pieces().len(), 18);
+        assert_eq!(gcube.facelets(), gcube.facelets_from_stickers());
+        gcube.apply_movements(Alg::new(movements).inverse().movements());
+        assert!(gcube.is_solved());
+        // quarter turns of faces that aren't square turn half turns, however
+        // they reach the cube
+        let turned = |alg: &str| {
+            let mut gcube = GCube::cuboid([3, 2, 3]);
+            gcube.apply_movements(&scramble_to_movements(alg).unwrap());
+            gcube
+        };
+        assert_eq!(turned("R"), turned("R2"));
+        assert_eq!(turned("x'"), turned("x2"));
+        assert_eq!(turned("x").facelets(), turned("x2").facelets());
+        assert_eq!(
+            turned("x R").facelets(),
+            turned("x R").facelets_from_stickers()
+        );
+        let mut per_sticker = GCube::cuboid([3, 2, 3]);
+        per_sticker.apply_gmove_per_sticker(GCube::create_gmove("F'".parse().unwrap()));
+        assert_eq!(per_sticker, turned("F2"));
+        // a layer's depth counts along its own axis, so 2F2 turns an inner
+        // layer of a 2x2x4, swapping the 2 stickers on each side of it
+        let mut gcube = GCube::cuboid([2, 2, 4]);
+        gcube.apply_movement(&"2F2".parse().unwrap());
+        assert_eq!(gcube.unsolved_stickers().len(), 4 * 2);
+        gcube.apply_movement(&"3F2".parse().unwrap());
+        gcube.apply_movement(&"4F2".parse().unwrap());
+        gcube.apply_movement(&"F2".parse().unwrap());
+        assert_eq!(gcube, {
+            let mut z2 = GCube::cuboid([2, 2, 4]);
+            z2.apply_movement(&"z2".parse().unwrap());
+            z2
+        });
+    }
+
+    #[test]
+    fn stickers_are_grouped_into_pieces() {
+        let mut gcube = GCube::new(3);
+        let count = |gcube: &GCube, n_stickers| {
+            gcube
+                .pieces()
+                .iter()
+                .filter(|p| p.stickers.len() == n_stickers)
+                .count()
+        };
+        assert_eq!(gcube.pieces().len(), 26);
+        assert_eq!(
+            (count(&gcube, 1), count(&gcube, 2), count(&gcube, 3)),
+            (6, 12, 8)
+        );
+        // the stickers of a piece stay together
+        let initial_faces = |gcube: &GCube| -> Vec<Vec<Face>> {
+            let mut pieces: Vec<Vec<Face>> = gcube
+                .pieces()
+                .iter()
+                .map(|p| {
+                    let mut faces: Vec<Face> = p
+                        .stickers
+                        .iter()
+                        .map(|&i| gcube.get_initial_face(gcube.sticker(i)))
+                        .collect();
+                    faces.sort_by_key(|f| *f as usize);
+                    faces
+                })
+                .collect();
+            pieces.sort_by_key(|faces| faces.iter().map(|&f| f as usize).collect::<Vec<_>>());
+            pieces
+        };
+        let solved = initial_faces(&gcube);
+        gcube.apply_movements(&scramble_to_movements("R U F' M2 D").unwrap());
+        assert_eq!(initial_faces(&gcube), solved);
+        assert_eq!(GCube::new(1).pieces()[0].stickers.len(), 6);
+        assert_eq!(GCube::new(4).pieces().len(), 56);
+    }
+
+    #[test]
+    fn inner_layers_make_up_wide_moves() {
+        let apply = |size, alg: &str| {
+            let mut gcube = GCube::new(size);
+            gcube.apply_movements(&scramble_to_movements(alg).unwrap());
+            gcube
+        };
+        assert_eq!(apply(4, "R 2R"), apply(4, "Rw"));
+        assert_eq!(apply(5, "Rw 3R"), apply(5, "3Rw"));
+        assert_eq!(apply(5, "3R'"), apply(5, "M"));
+        assert_eq!(apply(4, "3U"), apply(4, "Uw' 3Uw"));
+        assert_ne!(apply(4, "2R"), GCube::new(4));
+    }
+
+    #[test]
+    fn opposite_layers_turn_together() {
+        let gcube = GCube::new(3);
+        let m = |s: &str| Movement::from_str(s).unwrap();
+        assert!(gcube.can_turn_together(m("R"), m("L'")));
+        assert!(gcube.can_turn_together(m("U"), m("E")));
+        assert!(gcube.can_turn_together(m("r"), m("L")));
+        assert!(!gcube.can_turn_together(m("R"), m("U")));
+        assert!(!gcube.can_turn_together(m("R"), m("R")));
+        assert!(!gcube.can_turn_together(m("r"), m("M")));
+        assert!(!gcube.can_turn_together(m("R"), m("x")));
+        // a 1x1 only has one layer
+        assert!(!GCube::new(1).can_turn_together(m("R"), m("L")));
+    }
+
     #[test]
     fn gcube_test_with_my_epic_roux_solutions() {
         let mut gcube = GCube::new(3);
@@ -354,21 +1276,190 @@ mod tests {
         assert_eq!(gcube, GCube::new(3));
     }
 
+    #[test]
+    fn solved_in_any_orientation() {
+        // a Roux solve, without its last rotation back to the standard
+        // orientation
+        let mut gcube = GCube::new(3);
+        let solve = "
+        F2 R' U' B2 L2 D' L2 F2 U B2 U' L2 R2 D2 F' L2 R D' L2 D U
+        y' x
+        D' r' D U2 F2 U' F
+        r' U' M' R' U' R
+        U r U' r2 D' r U r' D r2 U r'
+        U2 M U M' U2 M U' M U' M2 U' M' U2 M U2 M2
+        ";
+        gcube.apply_movements(&scramble_to_movements(solve).unwrap());
+        assert!(gcube.is_solved() && gcube.unsolved_stickers().is_empty());
+        assert_ne!(gcube, GCube::new(3));
+        assert_eq!(gcube.orientation(), {
+            let mut oriented = GCube::new(3);
+            oriented.apply_movements(&scramble_to_movements("z2 y'").unwrap());
+            oriented.orientation
+        });
+        // slice moves turn the centers, so an even cube's orientation is
+        // found from its faces once they're each one colour
+        let mut gcube = GCube::new(4);
+        gcube.apply_movements(&scramble_to_movements("Rw 2L'").unwrap());
+        assert!(!gcube.is_solved());
+        gcube.apply_movements(&scramble_to_movements("L'").unwrap());
+        assert!(gcube.is_solved());
+        assert_eq!(gcube.orientation(), {
+            let mut x = GCube::new(4);
+            x.apply_movements(&scramble_to_movements("x").unwrap());
+            x.orientation()
+        });
+        assert_eq!(gcube.solved_in_orientation().facelets(), gcube.facelets());
+    }
+
+    #[test]
+    fn diff_lists_moved_stickers() {
+        let mut gcube = GCube::new(3);
+        assert!(gcube.unsolved_stickers().is_empty());
+        // R moves 12 stickers onto differently coloured faces
+        gcube.apply_movements(&scramble_to_movements("R").unwrap());
+        assert_eq!(gcube.unsolved_stickers().len(), 12);
+        gcube.apply_movements(&scramble_to_movements("U").unwrap());
+        let solved = GCube::new(3);
+        assert_eq!(gcube.diff(&solved).len(), solved.diff(&gcube).len());
+        for d in gcube.unsolved_stickers() {
+            assert_eq!(gcube.sticker(d.index).current, d.position);
+            assert_eq!(d.expected, gcube.get_face(d.position));
+            assert_ne!(d.actual, d.expected);
+        }
+        assert_eq!(GCube::new(3).diff(&GCube::new(2)).len(), 54);
+    }
+
     #[test]
     fn gcube_test() {
         let mut gcube = GCube::new(3);
         for m in Move::iter() {
             // apply normal move
             let turn = Turn::Single;
-            gcube.apply_movement(&Movement(m, turn));
+            gcube.apply_movement(&Movement::new(m, turn));
             // apply inverse
             let turn = Turn::Inverse;
-            gcube.apply_movement(&Movement(m, turn));
+            gcube.apply_movement(&Movement::new(m, turn));
             // apply double twice
             let turn = Turn::Double;
-            gcube.apply_movement(&Movement(m, turn));
-            gcube.apply_movement(&Movement(m, turn));
+            gcube.apply_movement(&Movement::new(m, turn));
+            gcube.apply_movement(&Movement::new(m, turn));
         }
         assert_eq!(gcube, GCube::new(3));
     }
+
+    #[test]
+    fn solved_in_the_orientation_of_the_centers() {
+        let mut gcube = GCube::new(3);
+        gcube.apply_movements(&scramble_to_movements("R U x y'").unwrap());
+        let mut rotated = GCube::new(3);
+        rotated.apply_movements(&scramble_to_movements("x y'").unwrap());
+        assert_eq!(gcube.solved_in_orientation().facelets(), rotated.facelets());
+        // an even cube's scrambled centers don't have an orientation
+        let mut gcube = GCube::new(4);
+        gcube.apply_movements(&scramble_to_movements("2R U 2R'").unwrap());
+        assert_eq!(gcube.solved_in_orientation(), GCube::new(4));
+    }
+
+    proptest! {
+        #[test]
+        fn facelets_are_kept_in_step_with_stickers(
+            gcube in any_gcube(1..=6, 30),
+            alg in any_alg(30, 6),
+            painted in 0..6usize,
+        ) {
+            let mut gcube = gcube;
+            prop_assert_eq!(gcube.facelets(), gcube.facelets_from_stickers());
+            let index = painted * gcube.stickers().len() / 6;
+            gcube.paint(index, Face::F);
+            gcube.apply_movements(alg.movements());
+            prop_assert_eq!(gcube.facelets(), gcube.facelets_from_stickers());
+        }
+
+        #[test]
+        fn layers_turn_the_same_in_bulk(gcube in any_gcube(1..=6, 10), alg in any_alg(30, 6)) {
+            let mut bulk = gcube.clone();
+            let mut per_sticker = gcube;
+            for gmove in GCube::create_gmoves(alg.movements()) {
+                bulk.apply_gmove(gmove);
+                per_sticker.apply_gmove_per_sticker(gmove);
+            }
+            prop_assert_eq!(bulk.facelets(), per_sticker.facelets());
+            prop_assert_eq!(&bulk.center_marks, &per_sticker.center_marks);
+            prop_assert_eq!(bulk, per_sticker);
+        }
+    }
+
+    #[test]
+    fn resizing_keeps_the_outer_layers() {
+        let turned = |size, alg: &str| {
+            let mut gcube = GCube::new(size);
+            gcube.apply_movements(&scramble_to_movements(alg).unwrap());
+            gcube
+        };
+        // a 3x3 grows into a reduced 4x4, its middle slices both inner ones,
+        // and back
+        let gcube = turned(3, "R U' F2 x M");
+        let grown = gcube.resized(4).unwrap();
+        assert_eq!(grown.facelets(), turned(4, "R U' F2 x 2L 3L").facelets());
+        assert_eq!(grown.resized(3).unwrap().facelets(), gcube.facelets());
+        assert_eq!(gcube.resized(3).unwrap().facelets(), gcube.facelets());
+        // a 5x5's outer layers and middle slices make its 3x3
+        let reduced = turned(5, "R 3Rw U2 3Uw' 2F").resized(3).unwrap();
+        assert_eq!(reduced.facelets(), turned(3, "R R M' U2 U' E").facelets());
+        // a 2x2 has no centers to grow, but a 3x3's corners make one
+        assert_eq!(turned(2, "R").resized(3), None);
+        assert_eq!(
+            gcube.resized(2).unwrap().facelets(),
+            turned(2, "R U' F2 x").facelets()
+        );
+        assert_eq!(GCube::cuboid([3, 2, 3]).resized(3), None);
+    }
+
+    #[test]
+    fn rotations_only_turn_the_orientation() {
+        let mut gcube = GCube::new(4);
+        gcube.apply_movements(&scramble_to_movements("x y' z2").unwrap());
+        assert_eq!(gcube.current, GCube::new(4).current);
+        assert_ne!(gcube.orientation, Orientation::default());
+        let point = Point3::new(1, -3, 4);
+        assert_eq!(
+            gcube.orientation.unapply(gcube.orientation.apply(point)),
+            point
+        );
+        // layers are turned as they're seen after the rotations
+        let movements = scramble_to_movements("x y' R z2 2U Fw'").unwrap();
+        let mut gcube = GCube::new(4);
+        gcube.apply_movements(&movements);
+        let mut per_sticker = GCube::new(4);
+        for gmove in GCube::create_gmoves(&movements) {
+            per_sticker.apply_gmove_per_sticker(gmove);
+        }
+        assert_eq!(per_sticker.orientation, Orientation::default());
+        assert_eq!(gcube, per_sticker);
+        assert_eq!(gcube.facelets(), per_sticker.facelets());
+        // a cuboid's rotations move its faces onto others of the same shape
+        let mut cuboid = GCube::cuboid([3, 2, 4]);
+        cuboid.apply_movements(&scramble_to_movements("R2 x2 U2 y2 F2 D2").unwrap());
+        assert_ne!(cuboid.orientation, Orientation::default());
+        assert_eq!(cuboid.facelets(), cuboid.facelets_from_stickers());
+    }
+
+    #[test]
+    fn gmoves_turning_layers_with_gaps_turn_per_sticker() {
+        let movement = Movement::new(Move::Y, Turn::Single);
+        // U and D turned together, without E
+        let gmove = GMove::new(movement, Axis::Y, true, |s, _| s.current.y != 0);
+        let mut gcube = GCube::new(3);
+        assert_eq!(gcube.turned_coords(gmove), None);
+        gcube.apply_gmove(gmove);
+        let mut expected = GCube::new(3);
+        expected.apply_movements(&scramble_to_movements("U D'").unwrap());
+        assert_eq!(gcube, expected);
+        assert_eq!(gcube.facelets(), expected.facelets());
+        assert_eq!(
+            GCube::new(3).turned_coords(GCube::create_gmove("2R".parse().unwrap())),
+            Some((0, 1))
+        );
+    }
 }
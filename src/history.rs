@@ -0,0 +1,234 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Alg, Movement};
+
+/// Record of the movements applied to a cube, supporting undo and redo.
+/// Undone movements are kept until a new movement is pushed.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct History {
+    movements: Vec<Movement>,
+    position: usize,                  // number of movements currently applied
+    scramble: Option<(usize, usize)>, // start and end positions of the last scramble
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a newly applied movement, discarding any undone movements.
+    pub fn push(&mut self, movement: Movement) {
+        if matches!(self.scramble, Some((_, end)) if end > self.position) {
+            self.scramble = None;
+        }
+        self.movements.truncate(self.position);
+        self.movements.push(movement);
+        self.position += 1;
+    }
+
+    /// Records the movements of a newly applied scramble, remembering where
+    /// the scramble ends so the cube can later be reverted to it.
+    pub fn push_scramble(&mut self, scramble: &Alg) {
+        let start = self.position;
+        for &movement in scramble.movements() {
+            self.push(movement);
+        }
+        self.scramble = Some((start, self.position));
+    }
+
+    /// Records a newly applied setup movement, e.g. a pre-AUF or rotation
+    /// before drilling a case. Setups extend the scramble (or start one, if
+    /// the last movement wasn't part of one), so they're reverted to with
+    /// it and aren't part of the solve.
+    pub fn push_setup(&mut self, movement: Movement) {
+        let start = match self.scramble {
+            Some((start, end)) if end == self.position => start,
+            _ => self.position,
+        };
+        self.push(movement);
+        self.scramble = Some((start, self.position));
+    }
+
+    /// Returns the last scramble, unless it has since been discarded.
+    pub fn scramble(&self) -> Option<Alg> {
+        let (start, end) = self.scramble?;
+        Some(Alg::new(self.movements[start..end].to_vec()))
+    }
+
+    /// Returns the position just after the last scramble.
+    pub fn scramble_end(&self) -> Option<usize> {
+        self.scramble.map(|(_, end)| end)
+    }
+
+    /// Moves to the given position (clamped to the recorded movements),
+    /// returning the alg that takes the cube there from the current position.
+    pub fn seek(&mut self, position: usize) -> Alg {
+        let position = position.min(self.movements.len());
+        let alg = if position < self.position {
+            Alg::new(self.movements[position..self.position].to_vec()).inverse()
+        } else {
+            Alg::new(self.movements[self.position..position].to_vec())
+        };
+        self.position = position;
+        alg
+    }
+
+    /// Steps back one movement, returning the movement that undoes it.
+    pub fn undo(&mut self) -> Option<Movement> {
+        if self.position == 0 {
+            return None;
+        }
+        self.position -= 1;
+        Some(self.movements[self.position].inverse())
+    }
+
+    /// Steps forward one movement, returning the movement to reapply.
+    pub fn redo(&mut self) -> Option<Movement> {
+        let movement = *self.movements.get(self.position)?;
+        self.position += 1;
+        Some(movement)
+    }
+
+    /// Returns every recorded movement, including undone ones.
+    pub fn movements(&self) -> &[Movement] {
+        &self.movements
+    }
+
+    /// Returns the movements currently applied, in order.
+    pub fn applied(&self) -> &[Movement] {
+        &self.movements[..self.position]
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn len(&self) -> usize {
+        self.movements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.movements.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Returns an alg that brings the cube back to the state it was in before
+    /// any recorded movement, i.e. the simplified inverse of the applied
+    /// movements.
+    pub fn solution(&self) -> Alg {
+        Alg::new(self.applied().to_vec()).inverse().simplified()
+    }
+
+    /// Returns the first movement of the solution, if not already solved.
+    pub fn hint(&self) -> Option<Movement> {
+        self.solution().movements().first().cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{scramble_to_movements, GCube};
+    use std::str::FromStr;
+
+    fn history_of(alg: &str) -> History {
+        let mut history = History::new();
+        for movement in scramble_to_movements(alg).unwrap() {
+            history.push(movement);
+        }
+        history
+    }
+
+    #[test]
+    fn undo_redo() {
+        let mut history = history_of("R U F'");
+        assert_eq!(history.undo(), Some("F".parse().unwrap()));
+        assert_eq!(history.undo(), Some("U'".parse().unwrap()));
+        assert_eq!(history.applied(), &scramble_to_movements("R").unwrap()[..]);
+        assert_eq!(history.redo(), Some("U".parse().unwrap()));
+        assert_eq!(history.position(), 2);
+        assert_eq!(history.len(), 3);
+        // pushing discards the undone F'
+        history.push("D".parse().unwrap());
+        assert_eq!(
+            history.applied(),
+            &scramble_to_movements("R U D").unwrap()[..]
+        );
+        assert_eq!(history.redo(), None);
+        history.clear();
+        assert_eq!(history.undo(), None);
+        assert!(history.is_empty());
+    }
+
+    #[test]
+    fn seek_and_revert_to_scramble() {
+        let mut history = history_of("x");
+        let scramble = Alg::from_str("R U F").unwrap();
+        history.push_scramble(&scramble);
+        history.push("D".parse().unwrap());
+        history.push("L".parse().unwrap());
+        assert_eq!(history.scramble(), Some(scramble));
+        assert_eq!(history.scramble_end(), Some(4));
+
+        assert_eq!(history.seek(4), Alg::from_str("L' D'").unwrap());
+        assert_eq!(history.seek(6), Alg::from_str("D L").unwrap());
+        assert_eq!(history.seek(100), Alg::default());
+        // back to solved
+        assert_eq!(history.seek(0), Alg::from_str("L' D' F' U' R' x'").unwrap());
+        assert_eq!(history.position(), 0);
+
+        // the scramble is kept until its movements are overwritten
+        history.seek(4);
+        history.push("B".parse().unwrap());
+        assert!(history.scramble().is_some());
+        history.seek(2);
+        history.push("B".parse().unwrap());
+        assert_eq!(history.scramble(), None);
+    }
+
+    #[test]
+    fn setups_extend_the_scramble() {
+        let mut history = History::new();
+        history.push_scramble(&Alg::from_str("R U").unwrap());
+        history.push_setup("y".parse().unwrap());
+        history.push_setup("U2".parse().unwrap());
+        assert_eq!(history.scramble(), Some(Alg::from_str("R U y U2").unwrap()));
+        history.push("F".parse().unwrap());
+        assert_eq!(history.scramble_end(), Some(4));
+        // without a scramble just before, setups start one
+        history.push_setup("D".parse().unwrap());
+        assert_eq!(history.scramble(), Some(Alg::from_str("D").unwrap()));
+        let mut history = history_of("L");
+        history.push_setup("x".parse().unwrap());
+        assert_eq!(history.scramble(), Some(Alg::from_str("x").unwrap()));
+        assert_eq!(history.solution(), Alg::from_str("x' L'").unwrap());
+    }
+
+    #[test]
+    fn solution_solves() {
+        let scramble = "R U R' U' r2 x D' M2 F F' F2";
+        let history = history_of(scramble);
+        let mut gcube = GCube::new(3);
+        gcube.apply_movements(&scramble_to_movements(scramble).unwrap());
+        gcube.apply_movements(history.solution().movements());
+        assert_eq!(gcube, GCube::new(3));
+        assert_eq!(history.solution().len(), 9);
+    }
+
+    #[test]
+    fn hint_advances_with_solution() {
+        let mut history = history_of("R U2 F");
+        for expected in ["F'", "U2", "R'"] {
+            let hint = history.hint().unwrap();
+            assert_eq!(hint, expected.parse().unwrap());
+            history.push(hint);
+        }
+        assert_eq!(history.hint(), None);
+        // moving away from the hint extends the solution
+        history.push("D".parse().unwrap());
+        assert_eq!(history.hint(), Some("D'".parse().unwrap()));
+    }
+}
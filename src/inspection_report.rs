@@ -0,0 +1,88 @@
+use crate::{ColorScheme, CubieCube, Face, FaceletModel, StateError, ORDERED_FACES};
+
+/// What's worth seeing of a scrambled 3x3 during inspection, to train
+/// planning the start of a solve: how long each colour's cross is, the F2L
+/// pairs already solved, and how many edges are misoriented.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InspectionReport {
+    /// each cross's colour (the face whose stickers it is) with its length
+    /// in face turns, shortest first
+    pub crosses: Vec<(Face, usize)>,
+    /// the F2L pairs solved for the shortest cross
+    pub solved_pairs: usize,
+    /// the edges misoriented for the F/B axis, i.e. needing an F or B
+    /// quarter turn, as in ZZ's EO
+    pub bad_edges: usize,
+}
+
+impl InspectionReport {
+    /// Analyses a 3x3's facelets, or returns why they aren't a solvable
+    /// cube's.
+    pub fn new(facelets: &FaceletModel) -> Result<Self, StateError> {
+        let cube = CubieCube::from_facelets(facelets)?;
+        // a cross is at most 8 moves
+        let mut crosses: Vec<(Face, Face, usize)> = ORDERED_FACES
+            .iter()
+            .enumerate()
+            .map(|(f, &face)| {
+                let color = facelets[9 * f + 4];
+                (color, face, cube.cross_moves(face, 8).unwrap())
+            })
+            .collect();
+        crosses.sort_by_key(|&(_, _, moves)| moves);
+        let (_, best_face, _) = crosses[0];
+        Ok(Self {
+            crosses: crosses
+                .iter()
+                .map(|&(color, _, moves)| (color, moves))
+                .collect(),
+            solved_pairs: cube.solved_pairs_on(best_face),
+            bad_edges: cube.eo.iter().filter(|&&o| o == 1).count(),
+        })
+    }
+
+    /// Returns the report as lines of text, naming colours by scheme, e.g.
+    /// `Cross: yellow 4, white 5, red 5, ...`.
+    pub fn lines(&self, scheme: ColorScheme) -> Vec<String> {
+        let crosses: Vec<String> = self
+            .crosses
+            .iter()
+            .map(|&(color, moves)| format!("{} {}", scheme.color_name(color), moves))
+            .collect();
+        vec![
+            format!("Cross: {}", crosses.join(", ")),
+            format!("Solved pairs: {}", self.solved_pairs),
+            format!("Bad edges (F/B): {}", self.bad_edges),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{scramble_to_movements, GCube};
+
+    #[test]
+    fn reports_crosses_pairs_and_edges() {
+        let mut gcube = GCube::new(3);
+        gcube.apply_movements(&scramble_to_movements("F").unwrap());
+        let report = InspectionReport::new(&gcube.to_facelet_model()).unwrap();
+        assert_eq!(report.crosses[0], (Face::B, 0));
+        assert_eq!(report.crosses.len(), 6);
+        assert!(report.crosses.windows(2).all(|w| w[0].1 <= w[1].1));
+        // F leaves B's cross and pairs solved, flipping the edges it turns
+        assert_eq!(report.solved_pairs, 4);
+        assert_eq!(report.bad_edges, 4);
+        assert_eq!(
+            report.lines(ColorScheme::Standard)[1..],
+            ["Solved pairs: 4", "Bad edges (F/B): 4"]
+        );
+        assert!(report.lines(ColorScheme::Standard)[0].starts_with("Cross: blue 0, "));
+
+        // the colours are the centers', wherever they've been turned to
+        gcube.apply_movements(&scramble_to_movements("y2").unwrap());
+        let report = InspectionReport::new(&gcube.to_facelet_model()).unwrap();
+        assert_eq!(report.crosses[0], (Face::B, 0));
+        assert_eq!(report.bad_edges, 4);
+    }
+}
@@ -0,0 +1,375 @@
+use std::{collections::HashMap, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter};
+
+use crate::{Alg, Move, Movement, Turn};
+
+// the keys of a keyboard, row by row, on the US QWERTY and Dvorak layouts
+const QWERTY_ROWS: [&str; 4] = ["1234567890", "qwertyuiop", "asdfghjkl;", "zxcvbnm,./"];
+const DVORAK_ROWS: [&str; 4] = ["1234567890", "',.pyfgcrl", "aoeuidhtns", ";qjkxbmwvz"];
+// symbols typed with and without shift held, on a US keyboard
+const UNSHIFTED: &str = "1234567890;,./'";
+const SHIFTED: &str = "!@#$%^&*():<>?\"";
+
+/// The characters typed by the viewer's hotkeys (those without ctrl), which
+/// no preset binds, so pressing one never turns the cube as well: - and =
+/// resize, ` and ~ set up and open the console, \ and | the memo and preview,
+/// [ { ] } the trainers and net, 0 to 4 reset and toggle views (on cubes up
+/// to 3x3), and space scrambles.
+pub const VIEWER_HOTKEYS: &str = "-=`~\\|[{]}01234 ";
+
+/// Named keymaps to choose from, all based on csTimer's virtual cube layout.
+/// Lefty variants mirror every key to the other hand, e.g. R is turned with
+/// the left hand.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, EnumIter, Display, Serialize, Deserialize)]
+pub enum KeymapPreset {
+    #[strum(serialize = "csTimer")]
+    CsTimer,
+    #[strum(serialize = "csTimer (lefty)")]
+    CsTimerLefty,
+    /// csTimer's layout, with keys in the same places on a Dvorak keyboard
+    Dvorak,
+    #[strum(serialize = "Dvorak (lefty)")]
+    DvorakLefty,
+}
+
+/// A recorded alg, played with one press of ctrl and key, e.g. an alg being
+/// drilled or the setup of a case.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyMacro {
+    pub key: char,
+    pub alg: Alg,
+}
+
+// returns the key in the same place as key, from rows of one layout in
+// another, or key itself if it's in neither
+fn translate(key: char, from: &[&str], to: &[&str]) -> char {
+    from.iter()
+        .zip(to)
+        .find_map(|(from, to)| from.find(key).map(|i| to.chars().nth(i).unwrap()))
+        .unwrap_or(key)
+}
+
+// returns the key mirrored to the other side of a QWERTY keyboard, e.g. i to e
+fn mirror(key: char) -> char {
+    let mirrored: Vec<String> = QWERTY_ROWS
+        .iter()
+        .map(|r| r.chars().rev().collect())
+        .collect();
+    let mirrored: Vec<&str> = mirrored.iter().map(|r| r.as_str()).collect();
+    translate(key, &QWERTY_ROWS, &mirrored)
+}
+
+// returns the key typed without shift, and whether shift was held
+fn unshift(key: char) -> (char, bool) {
+    match SHIFTED.find(key) {
+        Some(i) => (UNSHIFTED.chars().nth(i).unwrap(), true),
+        None => (key.to_ascii_lowercase(), key.is_ascii_uppercase()),
+    }
+}
+
+// returns the wide version of a face turn, or the slice turning the same way
+// as a rotation, e.g. R to Rw and x to M'
+fn widen(movement: Movement) -> Movement {
+    let Movement(m, turn, _) = movement;
+    match m {
+        Move::U => Movement::new(Move::Uw, turn),
+        Move::L => Movement::new(Move::Lw, turn),
+        Move::F => Movement::new(Move::Fw, turn),
+        Move::R => Movement::new(Move::Rw, turn),
+        Move::B => Movement::new(Move::Bw, turn),
+        Move::D => Movement::new(Move::Dw, turn),
+        // M follows L, E follows D, and S follows F
+        Move::X => Movement::new(Move::M, turn).inverse(),
+        Move::Y => Movement::new(Move::E, turn).inverse(),
+        Move::Z => Movement::new(Move::S, turn),
+        _ => movement,
+    }
+}
+
+/// Maps typed keys to movements, see the [keymap](../keymap.png). Keys are
+/// the characters typed, rather than key codes, as typed characters are
+/// reported in the order they were pressed.
+///
+/// Modifiers reach the movements without a key of their own: holding shift
+/// turns a double turn, and holding alt turns a face wide, or turns the
+/// slice of a rotation, e.g. alt + x turns M'. On cubes bigger than 3x3,
+/// number keys choose the depth face keys turn at, e.g. 2 then R turns 2R.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Keymap(HashMap<char, Movement>);
+
+/// What's changing the movements keys turn, besides the keys themselves.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Modifiers {
+    pub is_alt_down: bool,
+    /// the layer face keys turn, or the number of layers for wide turns,
+    /// chosen with number keys
+    pub depth: u8,
+}
+
+impl Default for Modifiers {
+    fn default() -> Self {
+        Self {
+            is_alt_down: false,
+            depth: 1,
+        }
+    }
+}
+
+impl Keymap {
+    pub fn new(bindings: HashMap<char, Movement>) -> Self {
+        Self(bindings)
+    }
+
+    /// Returns the movement bound to a typed key, doubled if it was typed
+    /// with shift, widened if alt is held, and at the chosen depth.
+    pub fn movement(&self, key: char, modifiers: Modifiers) -> Option<Movement> {
+        let (key, is_shift_down) = unshift(key);
+        let mut movement = *self.0.get(&key)?;
+        if modifiers.is_alt_down {
+            movement = widen(movement);
+        }
+        if modifiers.depth > 1 {
+            // rotations and slices have no depth
+            movement = movement.with_depth(modifiers.depth).unwrap_or(movement);
+        }
+        if is_shift_down {
+            movement = Movement(movement.0, Turn::Double, movement.2);
+        }
+        Some(movement)
+    }
+
+    /// Returns the movements of every bound key, in the order the keys were
+    /// pressed, skipping unbound keys. On an NxN cube bigger than 3x3, number
+    /// keys (1 to N - 1) change the depth of the modifiers instead.
+    pub fn movements(
+        &self,
+        keys: impl IntoIterator<Item = char>,
+        modifiers: &mut Modifiers,
+        size: usize,
+    ) -> Vec<Movement> {
+        let mut movements = vec![];
+        for key in keys {
+            match key.to_digit(10) {
+                Some(depth) if size > 3 => {
+                    if depth >= 1 && (depth as usize) < size {
+                        modifiers.depth = depth as u8;
+                    }
+                }
+                _ => movements.extend(self.movement(key, *modifiers)),
+            }
+        }
+        movements
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::from(KeymapPreset::CsTimer)
+    }
+}
+
+impl From<KeymapPreset> for Keymap {
+    fn from(preset: KeymapPreset) -> Self {
+        let place = |key: char| match preset {
+            KeymapPreset::CsTimer => key,
+            KeymapPreset::CsTimerLefty => mirror(key),
+            KeymapPreset::Dvorak => translate(key, &QWERTY_ROWS, &DVORAK_ROWS),
+            KeymapPreset::DvorakLefty => translate(mirror(key), &QWERTY_ROWS, &DVORAK_ROWS),
+        };
+        let bindings = [
+            ('i', "R"),
+            ('k', "R'"),
+            ('w', "B"),
+            ('o', "B'"),
+            ('s', "D"),
+            ('l', "D'"),
+            ('d', "L"),
+            ('e', "L'"),
+            ('j', "U"),
+            ('f', "U'"),
+            ('h', "F"),
+            ('g', "F'"),
+            (';', "y"),
+            ('a', "y'"),
+            ('u', "r"),
+            ('r', "l'"),
+            ('m', "r'"),
+            ('v', "l"),
+            ('t', "x"),
+            ('y', "x"),
+            ('n', "x'"),
+            ('b', "x'"),
+            ('.', "M'"),
+            ('x', "M"),
+            ('5', "M"),
+            ('6', "M"),
+            ('p', "z"),
+            ('q', "z'"),
+            ('z', "d"),
+            ('c', "u'"),
+            (',', "u"),
+            ('/', "d'"),
+        ];
+        Self(
+            bindings
+                .iter()
+                .map(|&(key, s)| (place(key), Movement::from_str(s).unwrap()))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{scramble_to_movements, Alg};
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn keys_map_to_movements_in_order() {
+        let keymap = Keymap::default();
+        assert_eq!(
+            keymap.movements("ijkf".chars(), &mut Modifiers::default(), 3),
+            scramble_to_movements("R U R' U'").unwrap()
+        );
+        assert_eq!(
+            keymap.movements("fkji".chars(), &mut Modifiers::default(), 3),
+            scramble_to_movements("U' R' U R").unwrap()
+        );
+    }
+
+    #[test]
+    fn unbound_keys_are_skipped() {
+        let keymap = Keymap::default();
+        assert_eq!(keymap.movement('1', Modifiers::default()), None);
+        assert_eq!(
+            keymap.movements("i 1\rk".chars(), &mut Modifiers::default(), 3),
+            scramble_to_movements("R R'").unwrap()
+        );
+    }
+
+    #[test]
+    fn presets_move_keys() {
+        let r = Movement::from_str("R").unwrap();
+        let movement = |preset, key| Keymap::from(preset).movement(key, Modifiers::default());
+        assert_eq!(movement(KeymapPreset::CsTimer, 'i'), Some(r));
+        assert_eq!(movement(KeymapPreset::CsTimerLefty, 'e'), Some(r));
+        assert_eq!(movement(KeymapPreset::Dvorak, 'c'), Some(r));
+        assert_eq!(movement(KeymapPreset::DvorakLefty, '.'), Some(r));
+    }
+
+    #[test]
+    fn presets_bind_the_same_movements() {
+        let mut expected: Vec<Movement> = Keymap::default().0.into_values().collect();
+        expected.sort_by_key(|m| m.to_string());
+        for preset in KeymapPreset::iter() {
+            let keymap = Keymap::from(preset);
+            assert_eq!(keymap.0.len(), Keymap::default().0.len());
+            let mut movements: Vec<Movement> = keymap.0.into_values().collect();
+            movements.sort_by_key(|m| m.to_string());
+            assert_eq!(movements, expected);
+        }
+    }
+
+    #[test]
+    fn viewer_hotkeys_turn_nothing() {
+        let alt = Modifiers {
+            is_alt_down: true,
+            ..Modifiers::default()
+        };
+        for preset in KeymapPreset::iter() {
+            let keymap = Keymap::from(preset);
+            for key in VIEWER_HOTKEYS.chars() {
+                for modifiers in [Modifiers::default(), alt] {
+                    assert_eq!(
+                        keymap.movement(key, modifiers),
+                        None,
+                        "{} in {}",
+                        key,
+                        preset
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn modifiers_change_movements() {
+        let keymap = Keymap::default();
+        let movement = |key, is_alt_down| {
+            let modifiers = Modifiers {
+                is_alt_down,
+                ..Modifiers::default()
+            };
+            keymap.movement(key, modifiers).unwrap().to_string()
+        };
+        assert_eq!(movement('I', false), "R2");
+        assert_eq!(movement(':', false), "Y2");
+        assert_eq!(movement('i', true), "Rw");
+        assert_eq!(movement('G', true), "Fw2");
+        assert_eq!(movement('t', true), "M'");
+        assert_eq!(movement('a', true), "E");
+        assert_eq!(movement('p', true), "S");
+        assert_eq!(movement('u', true), "Rw");
+    }
+
+    #[test]
+    fn every_movement_is_reachable() {
+        let keymap = Keymap::default();
+        let alt = Modifiers {
+            is_alt_down: true,
+            ..Modifiers::default()
+        };
+        let keys: Vec<char> = keymap
+            .0
+            .keys()
+            .flat_map(|&k| vec![k, k.to_ascii_uppercase(), unshift_inverse(k)])
+            .collect();
+        let reachable: Vec<Movement> = keys
+            .iter()
+            .flat_map(|&k| {
+                vec![
+                    keymap.movement(k, Modifiers::default()),
+                    keymap.movement(k, alt),
+                ]
+            })
+            .flatten()
+            .collect();
+        for m in Move::iter() {
+            for turn in Turn::iter() {
+                assert!(
+                    reachable.contains(&Movement::new(m, turn)),
+                    "{:?}",
+                    (m, turn)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn number_keys_choose_depth_on_big_cubes() {
+        let keymap = Keymap::default();
+        let mut modifiers = Modifiers::default();
+        let movements = |keys: &str, modifiers: &mut Modifiers, size| {
+            let movements = keymap.movements(keys.chars(), modifiers, size);
+            Alg::new(movements).to_string()
+        };
+        // 5 and 6 are M on small cubes
+        assert_eq!(movements("5i", &mut modifiers, 3), "M R");
+        assert_eq!(movements("2iu3ix", &mut modifiers, 5), "2R Rw 3R M");
+        modifiers.is_alt_down = true;
+        assert_eq!(movements("j", &mut modifiers, 5), "3Uw");
+        // there's no 5th layer from a face of a 5x5
+        assert_eq!(movements("5k", &mut modifiers, 5), "3Rw'");
+        assert_eq!(movements("1k", &mut modifiers, 5), "Rw'");
+    }
+
+    fn unshift_inverse(key: char) -> char {
+        match UNSHIFTED.find(key) {
+            Some(i) => SHIFTED.chars().nth(i).unwrap(),
+            None => key,
+        }
+    }
+}
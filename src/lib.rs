@@ -1,19 +1,149 @@
 use std::{fmt, str::FromStr};
 use strum_macros::{Display, EnumIter, EnumString};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
 
+mod alg;
+pub use alg::*;
+mod animation;
+pub use animation::*;
+mod autosave;
+pub use autosave::*;
+mod annotation;
+pub use annotation::*;
+mod bld;
+pub use bld::*;
+#[cfg(feature = "net")]
+mod broadcast;
+#[cfg(feature = "net")]
+pub use broadcast::*;
+mod case_trainer;
+pub use case_trainer::*;
+#[cfg(feature = "chat")]
+mod chat;
+#[cfg(feature = "chat")]
+pub use chat::*;
+mod console;
+pub use console::*;
+mod cubie_model;
+pub use cubie_model::*;
+mod distance;
+pub use distance::*;
+mod events;
+pub use events::*;
+mod facelet_blocks;
+pub use facelet_blocks::*;
 mod facelet_model;
 pub use facelet_model::*;
+#[cfg(feature = "net")]
+mod follow;
+#[cfg(feature = "net")]
+pub use follow::*;
 mod vec3;
-use vec3::*;
+pub use vec3::Axis;
 pub type Point3 = vec3::Vec3;
 mod geometry_model;
 pub use geometry_model::*;
+mod history;
+pub use history::*;
+mod inspection_report;
+pub use inspection_report::*;
+mod keymap;
+pub use keymap::*;
+mod mesh;
+pub use mesh::*;
+mod metronome;
+pub use metronome::*;
+mod midi;
+pub use midi::*;
+mod model_check;
+pub use model_check::*;
+mod move_cost;
+pub use move_cost::*;
+mod move_log;
+pub use move_log::*;
+mod net_view;
+pub use net_view::*;
+mod neutrality;
+pub use neutrality::*;
+#[cfg(feature = "photo")]
+mod photo;
+#[cfg(feature = "photo")]
+pub use photo::*;
+mod picking;
+pub use picking::*;
+mod piece_mask;
+pub use piece_mask::*;
+mod premoves;
+pub use premoves::*;
+mod preview;
+pub use preview::*;
+#[cfg(feature = "net")]
+mod race;
+#[cfg(feature = "net")]
+pub use race::*;
+mod reconstruction;
+pub use reconstruction::*;
+mod reduction;
+pub use reduction::*;
+mod relay;
+pub use relay::*;
+#[cfg(feature = "net")]
+mod remote;
+#[cfg(feature = "net")]
+pub use remote::*;
+mod replay;
+pub use replay::*;
+mod results;
+pub use results::*;
+mod scramble;
+pub use scramble::*;
+mod scramble_filter;
+pub use scramble_filter::*;
+mod scramble_sheet;
+pub use scramble_sheet::*;
+mod session;
+pub use session::*;
+mod session_import;
+pub use session_import::*;
+mod settings;
+pub use settings::*;
+mod shape_mod;
+pub use shape_mod::*;
+mod solver;
+pub use solver::*;
+mod sound;
+pub use sound::*;
+mod speech;
+pub use speech::*;
+mod stdio_server;
+pub use stdio_server::*;
+#[cfg(any(test, feature = "test-util"))]
+mod test_util;
+#[cfg(any(test, feature = "test-util"))]
+pub use test_util::*;
+mod thumbnail;
+pub use thumbnail::*;
+mod timed_alg;
+pub use timed_alg::*;
+mod timer;
+pub use timer::*;
+mod turn_stats;
+pub use turn_stats::*;
+#[cfg(feature = "net")]
+mod websocket;
+#[cfg(feature = "net")]
+pub use websocket::*;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 pub const TOTAL_FACES: usize = 6;
 pub const ORDERED_FACES: [Face; TOTAL_FACES] =
     [Face::U, Face::R, Face::F, Face::D, Face::L, Face::B];
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Display, Serialize, Deserialize)]
 pub enum Face {
     U,
     L,
@@ -24,6 +154,16 @@ pub enum Face {
     X,
 }
 
+impl Face {
+    /// Returns the face named by a letter of a facelet string, e.g. U for 'U'.
+    pub fn from_letter(letter: char) -> Option<Face> {
+        ORDERED_FACES
+            .iter()
+            .copied()
+            .find(|f| f.to_string() == letter.to_string())
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, EnumIter, EnumString, Display)]
 
 pub enum Move {
@@ -70,17 +210,239 @@ pub enum Turn {
              // or three normal turns
 }
 
+impl Move {
+    /// Whether the move is a turn of one face's layer, like R.
+    pub fn is_face(self) -> bool {
+        matches!(
+            self,
+            Move::U | Move::L | Move::F | Move::R | Move::B | Move::D
+        )
+    }
+
+    /// Whether the move turns a face along with the layers under it, like
+    /// Rw.
+    pub fn is_wide(self) -> bool {
+        matches!(
+            self,
+            Move::Uw | Move::Lw | Move::Fw | Move::Rw | Move::Bw | Move::Dw
+        )
+    }
+
+    /// Returns the face a face or wide move turns, e.g. R for Rw, or None
+    /// for slices and rotations.
+    pub fn face(self) -> Option<Face> {
+        match self {
+            Move::U | Move::Uw => Some(Face::U),
+            Move::L | Move::Lw => Some(Face::L),
+            Move::F | Move::Fw => Some(Face::F),
+            Move::R | Move::Rw => Some(Face::R),
+            Move::B | Move::Bw => Some(Face::B),
+            Move::D | Move::Dw => Some(Face::D),
+            _ => None,
+        }
+    }
+
+    // the depth of the move without a layer prefix, e.g. 2 for Rw (same as 2Rw)
+    fn default_depth(self) -> u8 {
+        if self.is_wide() {
+            2
+        } else {
+            1
+        }
+    }
+}
+
+/// A Move with a Turn, and how deep into the cube it turns: the layer turned
+/// for face moves, e.g. 2 for 2R (the layer next to R), or the number of
+/// layers turned for wide moves, e.g. 3 for 3Rw.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
-pub struct Movement(Move, Turn);
+pub struct Movement(Move, Turn, u8);
 
-#[derive(Debug, Clone)]
+/// The part of a movement that failed to parse.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Error)]
+pub enum ParseMovementErrorKind {
+    #[error("Empty movement")]
+    Empty,
+    #[error("Failed to parse Move part")]
+    InvalidMove,
+    #[error("Failed to parse Turn part")]
+    InvalidTurn,
+    #[error("Failed to parse layer depth")]
+    InvalidDepth,
+    #[error("Failed to parse timestamp")]
+    InvalidTimestamp,
+}
+
+/// Error for a token that isn't a valid movement, locating the token by its
+/// byte offset into the parsed input, e.g. for highlighting in a text field.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+#[error("{kind} in {token} (at byte {offset}){}", suggestion_text(.suggestion))]
 pub struct ParseMovementError {
-    message: String,
+    pub kind: ParseMovementErrorKind,
+    pub token: String,
+    pub offset: usize,
+    pub suggestion: Option<String>,
+}
+
+fn suggestion_text(suggestion: &Option<String>) -> String {
+    match suggestion {
+        Some(s) => format!(", did you mean {}?", s),
+        None => String::new(),
+    }
+}
+
+impl ParseMovementError {
+    pub fn new(kind: ParseMovementErrorKind, token: &str) -> Self {
+        Self {
+            kind,
+            token: token.to_string(),
+            offset: 0,
+            suggestion: suggest_movement(kind, token),
+        }
+    }
+
+    /// Shifts the error's offset, for when the token was parsed from a
+    /// substring starting `n` bytes into the original input.
+    pub fn offset_by(self, n: usize) -> Self {
+        Self {
+            offset: self.offset + n,
+            ..self
+        }
+    }
+}
+
+// guesses the movement intended by an invalid token, e.g. E' for e'
+fn suggest_movement(kind: ParseMovementErrorKind, token: &str) -> Option<String> {
+    if kind != ParseMovementErrorKind::InvalidMove {
+        return None;
+    }
+    let (depth_part, token) = split_depth(token);
+    let (move_part, turn_part) = split_movement(token)?;
+    let movement = Movement::new(
+        Move::from_str(&move_part.to_ascii_uppercase()).ok()?,
+        parse_turn(turn_part)?,
+    );
+    Some(format!("{}{}", depth_part, movement))
+}
+
+// parses the Turn part of a movement token, also accepting a number of
+// turns (e.g. R3 for R', as some generated algs and bandaging tools write
+// them) optionally inverted (e.g. U'2 or U2' for U2), or None if it turns a
+// whole number of times around (e.g. R4), which no Turn is
+fn parse_turn(s: &str) -> Option<Turn> {
+    if let Ok(turn) = Turn::from_str(s) {
+        return Some(turn);
+    }
+    let (is_inverse, count) = match (s.strip_prefix('\''), s.strip_suffix('\'')) {
+        (Some(count), _) | (_, Some(count)) => (true, count),
+        _ => (false, s),
+    };
+    if count.is_empty() || !count.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    // only the last two digits matter, as 100 is a multiple of 4
+    let count: u32 = count[count.len().saturating_sub(2)..].parse().ok()?;
+    let quarter_turns = match is_inverse {
+        true => (4 - count % 4) % 4,
+        false => count % 4,
+    };
+    match quarter_turns {
+        1 => Some(Turn::Single),
+        2 => Some(Turn::Double),
+        3 => Some(Turn::Inverse),
+        _ => None,
+    }
 }
 
-impl fmt::Display for ParseMovementError {
+// splits the leading digits, a layer depth, off a movement token, e.g. 3Rw'
+fn split_depth(s: &str) -> (&str, &str) {
+    s.split_at(s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len()))
+}
+
+// splits a movement token into its Move and Turn parts (Move is 1 or 2 characters)
+fn split_movement(s: &str) -> Option<(&str, &str)> {
+    let turn_start_index = if s.len() > 1 && s.as_bytes()[1].is_ascii_alphabetic() {
+        2
+    } else {
+        1
+    };
+    Some((s.get(..turn_start_index)?, s.get(turn_start_index..)?))
+}
+
+// yields each whitespace separated token of s, along with its byte offset
+pub(crate) fn tokens(s: &str) -> impl Iterator<Item = (usize, &str)> {
+    s.split_whitespace()
+        .map(move |token| (token.as_ptr() as usize - s.as_ptr() as usize, token))
+}
+
+impl Movement {
+    /// Returns the movement of the outer layer(s), e.g. R rather than 2R.
+    pub fn new(m: Move, turn: Turn) -> Self {
+        Movement(m, turn, m.default_depth())
+    }
+
+    /// Returns the movement turning deeper into the cube, e.g. 2R for the
+    /// layer next to R, or 3Rw for the 3 layers at R. Only face and wide
+    /// moves have depth, and wide moves are at least 2 deep.
+    pub fn with_depth(self, depth: u8) -> Option<Self> {
+        let Movement(m, turn, _) = self;
+        let is_valid = (m.is_face() && depth >= 1) || (m.is_wide() && depth >= 2);
+        if is_valid {
+            Some(Movement(m, turn, depth))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the Move turned, e.g. Rw for 3Rw2.
+    pub fn move_type(self) -> Move {
+        self.0
+    }
+
+    pub fn turn(self) -> Turn {
+        self.1
+    }
+
+    pub fn depth(self) -> u8 {
+        self.2
+    }
+
+    /// Returns the face turned, for face and wide moves, see Move::face.
+    pub fn face(self) -> Option<Face> {
+        self.0.face()
+    }
+
+    /// Returns the movement that undoes this movement.
+    pub fn inverse(self) -> Self {
+        let Movement(m, turn, depth) = self;
+        match turn {
+            Turn::Single => Movement(m, Turn::Inverse, depth),
+            Turn::Double => Movement(m, Turn::Double, depth),
+            Turn::Inverse => Movement(m, Turn::Single, depth),
+        }
+    }
+}
+
+impl fmt::Display for Movement {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.message)
+        if self.2 != self.0.default_depth() {
+            write!(f, "{}", self.2)?;
+        }
+        write!(f, "{}{}", self.0, self.1)
+    }
+}
+
+// movements are serialized in standard notation, e.g. "R'"
+impl Serialize for Movement {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Movement {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Movement::from_str(&s).map_err(de::Error::custom)
     }
 }
 
@@ -88,33 +450,37 @@ impl FromStr for Movement {
     type Err = ParseMovementError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use ParseMovementErrorKind::*;
         if s.is_empty() {
-            return Err(ParseMovementError {
-                message: "Empty movement.".to_string(),
-            });
+            return Err(ParseMovementError::new(Empty, s));
         }
-        // adjust where Turn is expected to start (Move is 1 or 2 characters)
-        let turn_start_index = if s.len() > 1 && s.as_bytes()[1].is_ascii_alphabetic() {
-            2
-        } else {
-            1
-        };
+        let (depth_part, rest) = split_depth(s);
+        let (move_part, turn_part) =
+            split_movement(rest).ok_or_else(|| ParseMovementError::new(InvalidMove, s))?;
         let move_type =
-            Move::from_str(&s[0..turn_start_index]).map_err(|_| ParseMovementError {
-                message: format!("Failed to parse Move part in {}", s),
-            })?;
-        let turn_type = Turn::from_str(&s[turn_start_index..]).map_err(|_| ParseMovementError {
-            message: format!("Failed to parse Turn part in {}", s),
-        })?;
-        Ok(Movement(move_type, turn_type))
+            Move::from_str(move_part).map_err(|_| ParseMovementError::new(InvalidMove, s))?;
+        let turn_type =
+            parse_turn(turn_part).ok_or_else(|| ParseMovementError::new(InvalidTurn, s))?;
+        let movement = Movement::new(move_type, turn_type);
+        if depth_part.is_empty() {
+            return Ok(movement);
+        }
+        depth_part
+            .parse()
+            .ok()
+            .and_then(|depth| movement.with_depth(depth))
+            .ok_or_else(|| ParseMovementError::new(InvalidDepth, s))
     }
 }
 
+/// Lazily parses each whitespace separated movement of an alg, without
+/// allocating unless a movement fails to parse.
+pub fn parse_iter(alg: &str) -> impl Iterator<Item = Result<Movement, ParseMovementError>> + '_ {
+    tokens(alg).map(|(offset, token)| Movement::from_str(token).map_err(|e| e.offset_by(offset)))
+}
+
 pub fn scramble_to_movements(scramble: &str) -> Result<Vec<Movement>, ParseMovementError> {
-    scramble
-        .split_whitespace()
-        .map(Movement::from_str)
-        .collect()
+    parse_iter(scramble).collect()
 }
 
 #[cfg(test)]
@@ -127,10 +493,10 @@ mod tests {
         // test all valid string inputs
         for m in Move::iter() {
             for t in Turn::iter() {
-                let movement_string = format!("{}{}", m.to_string(), t.to_string());
+                let movement_string = format!("{}{}", m, t);
                 assert_eq!(
                     Movement::from_str(&movement_string).unwrap(),
-                    Movement(m, t)
+                    Movement::new(m, t)
                 );
             }
         }
@@ -143,12 +509,12 @@ mod tests {
         assert_eq!(
             movements,
             vec![
-                Movement(Move::Fw, Turn::Single),
-                Movement(Move::L, Turn::Single),
-                Movement(Move::U, Turn::Double),
-                Movement(Move::D, Turn::Inverse),
-                Movement(Move::Rw, Turn::Single),
-                Movement(Move::S, Turn::Single),
+                Movement::new(Move::Fw, Turn::Single),
+                Movement::new(Move::L, Turn::Single),
+                Movement::new(Move::U, Turn::Double),
+                Movement::new(Move::D, Turn::Inverse),
+                Movement::new(Move::Rw, Turn::Single),
+                Movement::new(Move::S, Turn::Single),
             ]
         );
     }
@@ -156,16 +522,116 @@ mod tests {
     #[test]
     fn invalid_scramble_to_movements() {
         let invalid_scrambles = [
-            "f L U2 D' r3 S",
+            "f L U2 D' r4 S",
             "FF",
-            "u2'",
+            "u2''",
             "2",
             "F2 D2  D2 d e",
-            "2D F2 Z2",
-            "Z' z' X' M'2",
+            "2M F2 Z2",
+            "0R",
+            "1Rw",
+            "Z' z' X' M'4",
         ];
         for scramble in invalid_scrambles {
             assert!(scramble_to_movements(scramble).is_err());
         }
     }
+
+    #[test]
+    fn layer_depths() {
+        let movements = scramble_to_movements("2R 3Rw' 3r2 1R 2Rw").unwrap();
+        assert_eq!(
+            movements,
+            vec![
+                Movement(Move::R, Turn::Single, 2),
+                Movement(Move::Rw, Turn::Inverse, 3),
+                Movement(Move::Rw, Turn::Double, 3),
+                Movement::new(Move::R, Turn::Single),
+                Movement::new(Move::Rw, Turn::Single),
+            ]
+        );
+        let strings: Vec<String> = movements.iter().map(|m| m.to_string()).collect();
+        assert_eq!(strings, ["2R", "3Rw'", "3Rw2", "R", "Rw"]);
+
+        let err = Movement::from_str("2x").unwrap_err();
+        assert_eq!(err.kind, ParseMovementErrorKind::InvalidDepth);
+        assert_eq!(Movement::from_str("3R3").unwrap().to_string(), "3R'");
+    }
+
+    #[test]
+    fn repeated_turns_are_normalized() {
+        let parse = |s: &str| Movement::from_str(s).map(|m| m.to_string());
+        assert_eq!(parse("R3"), Ok("R'".to_string()));
+        assert_eq!(parse("U'2"), Ok("U2".to_string()));
+        assert_eq!(parse("U2'"), Ok("U2".to_string()));
+        assert_eq!(parse("F5"), Ok("F".to_string()));
+        assert_eq!(parse("x'3"), Ok("X".to_string()));
+        assert_eq!(parse("Rw1"), Ok("Rw".to_string()));
+        assert_eq!(parse("2L6"), Ok("2L2".to_string()));
+        assert_eq!(parse("D102"), Ok("D2".to_string()));
+        // a whole number of times around isn't a movement
+        for s in ["R4", "R'0", "R''", "R2x", "R-1"] {
+            assert_eq!(
+                Movement::from_str(s).unwrap_err().kind,
+                ParseMovementErrorKind::InvalidTurn
+            );
+        }
+    }
+
+    #[test]
+    fn movements_can_be_inspected() {
+        let movement = Movement::from_str("3Rw2").unwrap();
+        assert_eq!(movement.move_type(), Move::Rw);
+        assert_eq!(movement.turn(), Turn::Double);
+        assert_eq!(movement.depth(), 3);
+        assert_eq!(movement.face(), Some(Face::R));
+        assert_eq!(Movement::from_str("M'").unwrap().face(), None);
+        let gmove = GCube::create_gmove(Movement::from_str("D").unwrap());
+        assert_eq!(gmove.movement(), Movement::new(Move::D, Turn::Single));
+        assert_eq!(gmove.axis(), Axis::Y);
+        assert!(!gmove.is_clockwise());
+    }
+
+    #[test]
+    fn parse_iter_is_lazy() {
+        let mut movements = parse_iter("R U' x2 Q R");
+        assert_eq!(
+            movements.next(),
+            Some(Ok(Movement::new(Move::R, Turn::Single)))
+        );
+        assert_eq!(
+            movements.next(),
+            Some(Ok(Movement::new(Move::U, Turn::Inverse)))
+        );
+        assert_eq!(
+            movements.next(),
+            Some(Ok(Movement::new(Move::X, Turn::Double)))
+        );
+        // the invalid token doesn't stop iteration
+        assert_eq!(movements.next().unwrap().unwrap_err().offset, 8);
+        assert_eq!(
+            movements.next(),
+            Some(Ok(Movement::new(Move::R, Turn::Single)))
+        );
+        assert_eq!(movements.next(), None);
+    }
+
+    #[test]
+    fn error_locates_token() {
+        let err = scramble_to_movements("R U  r4 S").unwrap_err();
+        assert_eq!(err.kind, ParseMovementErrorKind::InvalidTurn);
+        assert_eq!(err.token, "r4");
+        assert_eq!(err.offset, 5);
+        assert_eq!(err.suggestion, None);
+
+        let err = scramble_to_movements("F2 e'").unwrap_err();
+        assert_eq!(err.kind, ParseMovementErrorKind::InvalidMove);
+        assert_eq!(err.offset, 3);
+        assert_eq!(err.suggestion.as_deref(), Some("E'"));
+
+        let err = scramble_to_movements("R é").unwrap_err();
+        assert_eq!(err.kind, ParseMovementErrorKind::InvalidMove);
+        assert_eq!(err.offset, 2);
+        assert_eq!(err.suggestion, None);
+    }
 }
@@ -1,143 +1,2334 @@
-use std::str::FromStr;
-
 use cubedesu::*;
-use macroquad::{input::KeyCode, math::Quat, prelude::*};
+use macroquad::{
+    audio::{load_sound_from_bytes, play_sound, PlaySoundParams, Sound},
+    input::KeyCode,
+    math::Quat,
+    models::{Mesh, Vertex},
+    prelude::*,
+    ui::{hash, root_ui, Ui},
+};
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use strum::IntoEnumIterator;
+
+const FLASH_DURATION: f32 = 0.3; // seconds the layers of the last move are highlighted for
+const P_LEN: f32 = 1.96; // side length of each piece, and its faces when stickerless
+const SESSION_PATH: &str = "cubedesu_session.json";
+const RESULTS_PATH: &str = "cubedesu_results.csv";
+const SETTINGS_PATH: &str = "cubedesu_settings.json";
+const LAST_REPLAY_PATH: &str = "cubedesu_last_solve.cdr";
+const AUTOSAVE_PATH: &str = "cubedesu_autosave.json";
+const TURN_STATS_PATH: &str = "cubedesu_turn_stats.csv";
+const CASE_SETS_PATH: &str = "cubedesu_cases.json";
+const THUMBNAIL_SIZE: usize = 120; // width of thumbnails of states, in pixels before scaling with the UI
+const CAMERA_DISTANCE: f32 = 3.; // starting distance of the camera from the cube's center, in widths of the cube
+const UI_HEIGHT: f32 = 720.; // window height that text and panels are sized for
+
+// how stickers are drawn, as set in the settings
+#[derive(Copy, Clone)]
+struct StickerShape {
+    len: f32,      // side length
+    rounding: f32, // radius of the corners, as a fraction of half the side length
+    is_shaded: bool, // whether stickers are darker the more they face away from the light
+}
+
+// triangles drawn together as meshes, each as big as macroquad draws at once,
+// e.g. to draw every sticker of a big cube in a few draw calls
+#[derive(Default)]
+struct MeshBatch {
+    vertices: Vec<Vertex>,
+    indices: Vec<u16>,
+}
+
+impl MeshBatch {
+    // macroquad's limits for a single draw call
+    const MAX_VERTICES: usize = 10000;
+    const MAX_INDICES: usize = 5000;
+
+    // adds a convex polygon, as a fan of triangles from its first point
+    fn push_polygon(&mut self, points: impl ExactSizeIterator<Item = Vec3>, color: Color) {
+        let n = points.len();
+        if n < 3 {
+            return;
+        }
+        if self.vertices.len() + n >= Self::MAX_VERTICES || self.indices.len() + (n - 2) * 3 >= Self::MAX_INDICES {
+            self.draw();
+        }
+        let first = self.vertices.len() as u16;
+        self.vertices.extend(points.map(|position| Vertex { position, uv: Vec2::ZERO, color }));
+        self.indices.extend((first + 1..first + n as u16 - 1).flat_map(|i| [first, i, i + 1]));
+    }
+
+    // draws the polygons added since the last draw
+    fn draw(&mut self) {
+        if self.vertices.is_empty() {
+            return;
+        }
+        let mesh = Mesh {
+            vertices: std::mem::take(&mut self.vertices),
+            indices: std::mem::take(&mut self.indices),
+            texture: None,
+        };
+        draw_mesh(&mesh);
+        // keeping the buffers for the next polygons
+        self.vertices = mesh.vertices;
+        self.indices = mesh.indices;
+        self.vertices.clear();
+        self.indices.clear();
+    }
+}
+
+// the outlines of a cube's stickers and their mirrors, kept between frames and
+// only rebuilt for stickers that moved, i.e. the layers turned since
+#[derive(Default)]
+struct StickerOutlines {
+    key: ([usize; 3], [u32; 4]), // the cube's dimensions, and bits of the sticker size, rounding, explosion gap and mirror distance
+    stickers: Vec<Sticker>,
+    outlines: Vec<[(Vec3, Vec<Vec3>); 2]>, // the center and outline of each sticker, then its mirror
+}
+
+impl StickerOutlines {
+    fn update(&mut self, gcube: &GCube, shape: StickerShape, gap: f32, mirror_distance: f32) {
+        let key = (gcube.dims, [shape.len, shape.rounding, gap, mirror_distance].map(f32::to_bits));
+        if key != self.key {
+            self.key = key;
+            self.stickers.clear();
+            self.outlines.clear();
+        }
+        let dims_f = dims_to_vec3(gcube.dims);
+        for (i, sticker) in gcube.stickers().enumerate() {
+            if self.stickers.get(i) == Some(&sticker) {
+                continue;
+            }
+            let curr = point3_to_vec3(sticker.current);
+            let mut mirr = curr;
+            if mirr.x.abs() == dims_f.x { mirr.x *= mirror_distance }
+            else if mirr.y.abs() == dims_f.y { mirr.y *= mirror_distance }
+            else { mirr.z *= mirror_distance }
+            let offset = explode_offset(curr, dims_f, gap);
+            let face = gcube.get_curr_face(sticker);
+            let outlines = [curr + offset, mirr + offset].map(|pos| (pos, sticker_outline(pos, face, shape)));
+            if i < self.stickers.len() {
+                self.stickers[i] = sticker;
+                self.outlines[i] = outlines;
+            } else {
+                self.stickers.push(sticker);
+                self.outlines.push(outlines);
+            }
+        }
+    }
+}
 
-const F_LEN: f32 = 1.8; // side length of each facelet
-const F_DEPTH: f32 = 0.; // thickness/depth of each facelet
+fn main() {
+    // `cubedesu replay <file>` watches a replay in the viewer
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let replay = match args.first().map(String::as_str) {
+        Some("replay") => {
+            let path = args.get(1).unwrap_or_else(|| {
+                eprintln!("usage: cubedesu replay <file>");
+                std::process::exit(1)
+            });
+            match Replay::load(path) {
+                Ok(replay) => Some(replay),
+                Err(e) => {
+                    eprintln!("Failed to load {}: {}", path, e);
+                    std::process::exit(1)
+                }
+            }
+        }
+        _ => None,
+    };
+    macroquad::Window::new("cubedesu", run(replay));
+}
 
-#[macroquad::main("cubedesu")]
-async fn main() {
-    let mut gcube = GCube::new(3);
+async fn run(replay: Option<Replay>) {
+    let mut settings = Settings::load(SETTINGS_PATH).unwrap_or_default();
+    // an autosave is only left by an unclean exit, e.g. a crash, so practice picks up where it was
+    let autosave = if replay.is_none() { Autosave::load(AUTOSAVE_PATH) } else { None };
+    // a replay's or autosave's cube is shown without being saved as the cube size
+    if let Some(dims) = replay.as_ref().map(|r| r.dims).or(autosave.as_ref().map(|a| a.dims)) {
+        settings.set_dims(dims);
+    }
+    // quitting cleanly removes the autosave
+    prevent_quit();
+    // the settings as last saved, to know when to save again
+    let mut saved_settings = settings.clone();
+    let mut settings_watcher = SettingsWatcher::new(SETTINGS_PATH, get_time());
+    let mut is_menu_open = false;
+    // every movement goes through the cube, for its sounds, speech, broadcast and log
+    let mut gcube = ObservedCube::new(GCube::cuboid(settings.dims()));
     let mut size_f = gcube.size as f32;
-    let mut has_mirrors = true;
+    let mut keymap = Keymap::from(settings.keymap);
+    let mut modifiers = Modifiers::default();
+    // a MIDI controller pressing csTimer's keys, whichever keymap the keyboard uses
+    let mut midi = settings.midi_device.as_ref().and_then(|path| MidiInput::open(path).ok());
+    let midi_keymap = Keymap::default();
+    // the touch swiping a sticker, the sticker's index, and where the touch started
+    let mut swipe: Option<(u64, usize, Vec2)> = None;
+    let mut two_finger_center: Option<Vec2> = None;
+    // where the mouse was pressed on a sticker of the net view, until it's dragged far enough to turn
+    let mut net_drag: Option<[f32; 2]> = None;
+    let mut mask = PieceMask::All;
+    let mut history = History::new();
+    let mut animator = Animator::new(settings.turn_duration);
+    let mut playback: Option<Playback> = None;
+    // a replay being watched, its moves made as their time comes
+    let mut replay_player: Option<ReplayPlayer> = None;
+    if let Some(r) = replay.as_ref() {
+        gcube.replace(r.scrambled());
+        history.push_scramble(&r.scramble);
+        replay_player = Some(ReplayPlayer::new(r, get_time()));
+    }
+    let mut has_hints = false;
+    // the solution hints are taken from, kept while it's followed
+    let mut solution_hint = SolutionHint::new();
+    // solves the editor's and the auto-solve's 3x3s, building its tables from the start
+    let solver = SolverThread::new();
+    // the 3x3 the solver's solving, its solution played back if the cube's still in that state
+    let mut solving: Option<CubieCube> = None;
+    let mut rng = Rng::new((miniquad::date::now() * 1000.) as u64);
+    // the last scramble, shown until the first move is made
+    let mut scramble: Option<Alg> = None;
+    // the day of the scramble of the day, when that's the last scramble
+    let mut daily: Option<u64> = None;
+    // the relay being solved, switching to the next leg's cube as each is solved
+    let mut relay: Option<Relay> = None;
+    // arrows over the cube, e.g. showing a cycle of pieces, set by remote commands
+    let mut arrows: Vec<Arrow> = vec![];
+    // whether clicks paint stickers, to enter the state of a physical cube
+    let mut is_editing = false;
+    // the moves of a macro being recorded, then the macro until it's bound to a key
+    let mut recording: Option<Vec<Movement>> = None;
+    let mut unbound_macro: Option<Alg> = None;
+    // whether moves are setups, e.g. a pre-AUF before drilling a case, which
+    // extend the scramble rather than being timed as part of the solve
+    let mut is_setting_up = false;
+    // a blindfolded memo being typed, to set the cube up with
+    let mut memo: Option<String> = None;
+    // a candidate alg being typed, previewed on a copy of the cube until it's applied
+    let mut preview: Option<String> = None;
+    // a command being typed into the console, with the console's earlier commands and output
+    let mut console: Option<String> = None;
+    let mut console_log: Vec<String> = vec![];
+    // the console's scrambles and turns, held back until the cube's been resized for them
+    let mut console_commands: Vec<Command> = vec![];
+    // thumbnails of states shown this session, by colours
+    let mut thumbnails: HashMap<(Vec<Face>, ColorScheme), Texture2D> = HashMap::new();
+    let mut outlines = StickerOutlines::default();
+    let mut session = Session::load(SESSION_PATH).unwrap_or_default();
+    let mut has_history_panel = true;
+    // how often each move's turned over the session's solves, charted until toggled off
+    let mut turn_stats: Option<TurnStats> = None;
+    // timing the cross (or first block) on a colour chosen for each 3x3 scramble
+    let mut drill: Option<NeutralityDrill> = None;
+    // timing a big cube's centers (or edge pairing) on its own, the rest masked
+    let mut reduction: Option<ReductionStep> = None;
+    // when a timed big cube solve's centers were solved, and it was reduced
+    let mut reduction_splits = ReductionSplits::default();
+    // drilling a set's cases, e.g. the PLLs, weak cases coming up most
+    let mut case_trainer: Option<CaseTrainer> = None;
+    let mut timer = Timer::new();
+    // whether the timer starts on the next move, i.e. just after scrambling
+    let mut is_timer_armed = false;
+    // inspection runs while the timer is armed, warning as time runs out
+    let mut inspection: Option<Inspection> = None;
+    // the analysis of a 3x3 being inspected, only while it's inspected
+    let mut inspection_report: Option<InspectionReport> = None;
+    // the penalty for how long the current solve was inspected
+    let mut penalty = Penalty::None;
+    if let Some(a) = autosave {
+        gcube.replace(a.gcube());
+        history = a.history;
+        scramble = history.scramble().filter(|_| history.scramble_end() == Some(history.position()));
+        timer = Timer::restore(&a.timer, get_time());
+        is_timer_armed = a.is_timer_armed;
+        penalty = a.penalty;
+    }
+    let mut autosaved_at = get_time();
+    // every move, scramble and solve logged for analysis, when there's a file to log to
+    let move_log = settings
+        .move_log
+        .as_ref()
+        .and_then(|path| MoveLog::open(path, miniquad::date::now(), gcube.size).ok())
+        .map(|log| Rc::new(RefCell::new(MoveLogger { log, solve_start: None })));
+    if let Some(logger) = move_log.as_ref() {
+        gcube.subscribe(Box::new(logger.clone()));
+    }
+    let sounds = Rc::new(RefCell::new(Sounds {
+        effects: load_sounds(settings.sound_theme).await,
+        volume: settings.volume,
+    }));
+    gcube.subscribe(Box::new(sounds.clone()));
+    // reading out moves and scrambles, when turned on in the settings
+    let mut speech = settings.speech.then(|| gcube.subscribe(Box::new(Speech::new())));
+    // pacing turns to a beat, when turned on in the settings
+    let mut metronome: Option<Metronome> = None;
+    // stream overlays follow the cube over WebSocket, when there's an address to broadcast on
+    let broadcast = settings
+        .broadcast_address
+        .as_ref()
+        .and_then(|addr| BroadcastServer::bind(addr).ok())
+        .map(|b| Rc::new(RefCell::new(b)));
+    if let Some(b) = broadcast.as_ref() {
+        gcube.subscribe(Box::new(b.clone()));
+    }
+    // racing other players over the network, when set up in the settings
+    let mut race = settings.race.as_ref().and_then(|r| match r.is_host {
+        true => Race::host(&r.name, &r.address).ok(),
+        false => Race::join(&r.name, &r.address).ok(),
+    });
+    // a race round to scramble for, once the cube is the round's size
+    let mut race_round: Option<Round> = None;
+    // following a teacher's broadcast, when set up in the settings
+    let mut follower = settings
+        .follow_address
+        .as_ref()
+        .and_then(|addr| Follower::connect(addr).ok());
+    // a Twitch chat turning the cube, when set up in the settings
+    #[cfg(feature = "chat")]
+    let mut chat = settings.chat.as_ref().and_then(|c| ChatSolve::connect(c).ok());
     let mut camera = Camera3D {
-        position: vec3(0., size_f * 3.5, size_f * 5.),
+        position: vec3(0., 3.5, 5.).normalize() * size_f * 2. * CAMERA_DISTANCE,
         up: vec3(0., 1., 0.),
         target: vec3(0., 0., 0.),
         ..Default::default()
     };
     let desu_gray = Color::new(35. / 255., 39. / 255., 42. / 255., 1.);
+    // when a key, click or touch last came in, for showcasing the cube when idle
+    let mut last_input_at = get_time();
+    let mut screen_size = vec2(screen_width(), screen_height());
 
     loop {
+        if is_quit_requested() {
+            if let Some(logger) = move_log.as_ref() {
+                logger.borrow_mut().log.log(LogEvent::SessionEnd, miniquad::date::now()).ok();
+            }
+            session.save(SESSION_PATH).ok();
+            Autosave::clear(AUTOSAVE_PATH).ok();
+            return;
+        }
+        // text, panels and swipes are sized by the window, in points rather
+        // than pixels on high DPI screens
+        let ui_scale = settings.ui_scale * (screen_height() / UI_HEIGHT).clamp(0.5, 3.);
+        let panel_width = 160. * ui_scale;
+        let is_resized = vec2(screen_width(), screen_height()) != screen_size;
+        screen_size = vec2(screen_width(), screen_height());
+        let is_input = get_last_key_pressed().is_some()
+            || !touches().is_empty()
+            || is_mouse_button_down(MouseButton::Left);
+        if is_input {
+            last_input_at = get_time();
+        }
+        let is_showcasing = settings.showcase && get_time() - last_input_at > settings.showcase_delay as f64;
+        // commands from WebSocket clients, handled along with this frame's input
+        let mut commands = vec![];
+        if let Some(b) = broadcast.as_ref() {
+            let mut b = b.borrow_mut();
+            let events = b.update(&gcube);
+            if settings.remote_control {
+                commands = b.commands(&events);
+            }
+        }
+        if let Some(f) = follower.as_mut() {
+            let teacher_commands = f.update();
+            if settings.follow_style == FollowStyle::Mirror {
+                commands.extend(teacher_commands);
+            }
+        }
+        #[cfg(feature = "chat")]
+        if let Some(c) = chat.as_mut() {
+            commands.extend(c.update(get_time()));
+        }
+        if let Some(round) = race.as_mut().and_then(|r| r.update()) {
+            settings.cube_size = round.size;
+            settings.cuboid = None;
+            race_round = Some(round);
+        }
+        if race_round.as_ref().is_some_and(|r| r.size == gcube.size) {
+            let round = race_round.take().unwrap();
+            commands.push(Command::Scramble { scramble: Some(round.scramble) });
+        }
+        if !console_commands.is_empty() && settings.dims() == gcube.dims {
+            commands.append(&mut console_commands);
+        }
+        // ctrl and a key plays a macro, rather than turning the key's move
+        let is_ctrl_down = is_key_down(KeyCode::LeftControl) || is_key_down(KeyCode::RightControl);
+        let is_shift_down = is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift);
         if let Some(key) = get_last_key_pressed() {
-            if key == KeyCode::Minus { gcube.shrink() } 
-            else if key == KeyCode::Equal { gcube.grow() }
-            else if key == KeyCode::Key1 { has_mirrors = !has_mirrors }
-            else if let Some(movement) = key_to_movement(key) {
-                gcube.apply_movement(&movement);
+            // number keys choose the depth of face keys on big cubes, so F6-F9 toggle instead
+            let toggles = |number: KeyCode, f: KeyCode| key == f || (key == number && gcube.size <= 3);
+            if memo.is_some() {
+                // keys type the memo, Enter sets the cube up from solved with it
+                match key {
+                    KeyCode::Escape => memo = None,
+                    KeyCode::Backspace => {
+                        memo.as_mut().unwrap().pop();
+                    }
+                    KeyCode::Enter if gcube.dims == [3; 3] => {
+                        if let Some(setup) = memo.as_ref().and_then(|m| m.parse::<Memo>().ok()?.setup().ok()) {
+                            memo = None;
+                            animator.finish(&mut gcube);
+                            gcube.replace(GCube::new(3));
+                            history.clear();
+                            commands.push(Command::Scramble { scramble: Some(setup) });
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            else if preview.is_some() {
+                // keys type the alg, Enter applies it to the cube
+                match key {
+                    KeyCode::Escape => preview = None,
+                    KeyCode::Backspace => {
+                        preview.as_mut().unwrap().pop();
+                    }
+                    KeyCode::Enter => {
+                        if let Some(p) = preview.as_ref().and_then(|text| Preview::parse(&gcube, text).ok()) {
+                            preview = None;
+                            commands.push(Command::Alg { alg: p.alg });
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            else if console.is_some() {
+                // keys type the command, Enter runs it and Esc closes the console
+                match key {
+                    KeyCode::Escape => console = None,
+                    KeyCode::Backspace => {
+                        console.as_mut().unwrap().pop();
+                    }
+                    KeyCode::Enter => {
+                        let line = console.replace(String::new()).unwrap();
+                        console_log.push(format!("> {}", line));
+                        let applied = Alg::new(history.applied().to_vec());
+                        match run_console_line(&line, &gcube, &applied, &mut session, &mut rng) {
+                            Ok(effects) => {
+                                for effect in effects {
+                                    match effect {
+                                        ConsoleEffect::Reset(size) => {
+                                            settings.cube_size = size;
+                                            settings.cuboid = None;
+                                            // otherwise the cube's replaced once it's been resized
+                                            if settings.dims() == gcube.dims {
+                                                animator.finish(&mut gcube);
+                                                gcube.replace(GCube::new(size));
+                                                history.clear();
+                                                playback = None;
+                                                scramble = None;
+                                                daily = None;
+                                                relay = None;
+                                                timer.reset();
+                                                is_timer_armed = false;
+                                            }
+                                        }
+                                        ConsoleEffect::Scramble(alg) => console_commands.push(Command::Scramble { scramble: Some(alg) }),
+                                        ConsoleEffect::Turn(alg) => console_commands.push(Command::Alg { alg }),
+                                        ConsoleEffect::SaveSession => {
+                                            session.save(SESSION_PATH).ok();
+                                        }
+                                        ConsoleEffect::Print(output) => console_log.push(output),
+                                    }
+                                }
+                            }
+                            Err(e) => console_log.push(e.to_string()),
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            else if key == KeyCode::GraveAccent && is_shift_down { console = Some(String::new()) }
+            else if key == KeyCode::Backslash && is_shift_down { preview = Some(String::new()) }
+            else if key == KeyCode::Backslash { memo = Some(String::new()) }
+            else if is_ctrl_down && key == KeyCode::Space {
+                // start recording a macro, or stop to bind it to the next ctrl + key
+                match recording.take() {
+                    Some(movements) => unbound_macro = Some(Alg::new(movements)).filter(|a| !a.is_empty()),
+                    None => {
+                        recording = Some(vec![]);
+                        unbound_macro = None;
+                    }
+                }
+            }
+            else if let Some(c) = key_to_macro_key(key).filter(|_| is_ctrl_down) {
+                match unbound_macro.take() {
+                    Some(alg) => settings.bind_macro(c, alg),
+                    None => commands.extend(settings.macro_alg(c).map(|alg| Command::Alg { alg: alg.clone() })),
+                }
+            }
+            else if key == KeyCode::Minus || key == KeyCode::Equal {
+                settings.cube_size = if key == KeyCode::Minus { (gcube.size - 1).max(1) } else { gcube.size + 1 };
+                settings.cuboid = None;
+            }
+            else if key == KeyCode::F10 { is_menu_open = !is_menu_open }
+            else if key == KeyCode::GraveAccent { is_setting_up = !is_setting_up }
+            else if key == KeyCode::Insert {
+                animator.finish(&mut gcube);
+                playback = None;
+                is_editing = !is_editing;
+            }
+            else if key == KeyCode::Enter && is_editing {
+                // solve from the painted state, when it's a real 3x3's
+                if let Some(cube) = solver_cube(&gcube) {
+                    solver.solve(cube, settings.solve_preference, &settings.move_costs);
+                    solving = Some(cube);
+                }
+            }
+            else if toggles(KeyCode::Key1, KeyCode::F6) { settings.show_mirrors = !settings.show_mirrors }
+            else if toggles(KeyCode::Key2, KeyCode::F7) { mask = mask.next() }
+            else if toggles(KeyCode::Key3, KeyCode::F8) { has_hints = !has_hints }
+            else if toggles(KeyCode::Key4, KeyCode::F9) { has_history_panel = !has_history_panel }
+            else if key == KeyCode::PageUp || key == KeyCode::PageDown {
+                // step through the history, one animated movement at a time
+                playback = None;
+                let position = match key {
+                    KeyCode::PageUp => history.position().saturating_sub(1),
+                    _ => history.position() + 1,
+                };
+                for &movement in history.seek(position).movements() {
+                    animator.push(movement);
+                }
+            }
+            else if key == KeyCode::F5 { settings.metric = settings.metric.next() }
+            else if key == KeyCode::LeftBracket && is_shift_down {
+                // off, then each set, then off again
+                let sets = CaseSet::load_all(CASE_SETS_PATH).unwrap_or_else(|_| vec![CaseSet::pll()]);
+                let next = match case_trainer.as_ref() {
+                    Some(t) => sets.iter().position(|s| s.name == t.set.name).map_or(sets.len(), |i| i + 1),
+                    None => 0,
+                };
+                case_trainer = sets
+                    .into_iter()
+                    .nth(next)
+                    .filter(|s| !s.cases.is_empty())
+                    .map(|set| CaseTrainer::new(set, &session.case_attempts, &mut rng));
+            }
+            else if key == KeyCode::LeftBracket && gcube.size >= 4 && gcube.is_cube() {
+                // off, then each step, then off again
+                reduction = match reduction {
+                    None => Some(ReductionStep::Centers),
+                    Some(ReductionStep::Centers) => Some(ReductionStep::Edges),
+                    Some(ReductionStep::Edges) => None,
+                };
+                mask = reduction.map_or(PieceMask::All, |step| step.mask());
+            }
+            else if key == KeyCode::LeftBracket {
+                // off, then each step, then off again
+                drill = match drill.as_ref().map(|d| d.step) {
+                    None => Some(NeutralityDrill::new(DrillStep::Cross, &mut rng)),
+                    Some(DrillStep::Cross) => Some(NeutralityDrill::new(DrillStep::FirstBlock, &mut rng)),
+                    Some(DrillStep::FirstBlock) => None,
+                };
+            }
+            else if key == KeyCode::RightBracket && is_shift_down { settings.show_net = !settings.show_net }
+            else if key == KeyCode::RightBracket {
+                turn_stats = match turn_stats {
+                    Some(_) => None,
+                    None => {
+                        let stats = TurnStats::new(session.results.iter().map(|r| &r.solution));
+                        std::fs::write(TURN_STATS_PATH, stats.to_csv()).ok();
+                        Some(stats)
+                    }
+                };
+            }
+            else if key == KeyCode::Delete {
+                if let Some(result) = session.results.last_mut() {
+                    result.penalty = result.penalty.next();
+                    save_results(&session);
+                }
+            }
+            else if key == KeyCode::Home || key == KeyCode::End {
+                animator.finish(&mut gcube);
+                playback = None;
+                let position = if key == KeyCode::Home { 0 } else { history.len() };
+                gcube.seek(history.seek(position).movements());
+            }
+            else if key == KeyCode::Enter {
+                // start solving, or pause/resume a solve that's playing
+                match playback.as_mut() {
+                    Some(p) => p.paused = !p.paused,
+                    None if solving.is_some() => {}
+                    None => {
+                        // solving the state the cube's turning to, by undoing its history if the solver can't
+                        animator.finish(&mut gcube);
+                        match solver_cube(&gcube) {
+                            Some(cube) => {
+                                solver.solve(cube, settings.solve_preference, &settings.move_costs);
+                                solving = Some(cube);
+                            }
+                            None => playback = Some(Playback::new(history.solution())),
+                        }
+                    }
+                }
+            }
+            else if key == KeyCode::Tab && animator.is_idle() {
+                if let Some(movement) = playback.as_mut().and_then(|p| p.step()) {
+                    animator.push(movement);
+                    history.push(movement);
+                }
+            }
+            else if key == KeyCode::Escape { playback = None; solving = None }
+            else if let Some(trainer) = case_trainer.as_mut().filter(|_| key == KeyCode::Space && gcube.dims == [3; 3]) {
+                // skipping a case part way through fails it
+                if timer.is_running() {
+                    let now = get_time();
+                    session.case_attempts.push(trainer.attempt(now - timer.elapsed(now), timer.elapsed(now), None));
+                    session.save(SESSION_PATH).ok();
+                    trainer.next_case(&mut rng);
+                }
+                let setup = trainer.setup(get_time(), &mut rng);
+                animator.finish(&mut gcube);
+                gcube.replace(GCube::new(3));
+                history.clear();
+                commands.push(Command::Scramble { scramble: Some(setup) });
+            }
+            else if key == KeyCode::Space { commands.push(Command::Scramble { scramble: None }) }
+            else if key == KeyCode::F11 {
+                let alg = daily_scramble(day_of(miniquad::date::now()), gcube.size);
+                commands.push(Command::Scramble { scramble: Some(alg) });
+            }
+            else if key == KeyCode::F12 {
+                let r = Relay::new(&settings.relay_sizes, &mut rng);
+                if let Some(first) = r.cubes().first() {
+                    animator.finish(&mut gcube);
+                    playback = None;
+                    gcube.replace(first.clone());
+                    settings.cube_size = gcube.size;
+                    settings.cuboid = None;
+                    modifiers.depth = 1;
+                    history.clear();
+                    history.push_scramble(r.scramble(0));
+                    scramble = Some(r.scramble(0).clone());
+                    daily = None;
+                    timer.reset();
+                    is_timer_armed = true;
+                    inspection = None;
+                    relay = Some(r);
+                }
+            }
+            else if key == KeyCode::Key0 || key == KeyCode::Backspace {
+                // reset to solved, or revert to just after the last scramble
+                animator.finish(&mut gcube);
+                playback = None;
+                let position = match key {
+                    KeyCode::Key0 => Some(0),
+                    _ => history.scramble_end(),
+                };
+                if let Some(position) = position {
+                    gcube.seek(history.seek(position).movements());
+                    scramble = if position == 0 { None } else { history.scramble() };
+                    relay = None;
+                    timer.reset();
+                    is_timer_armed = position != 0;
+                }
+            }
+            else if let Some(slot) = key_to_bookmark_slot(key) {
+                animator.finish(&mut gcube);
+                if is_shift_down {
+                    let alg = Alg::new(history.applied().to_vec());
+                    session.set_bookmark(slot, gcube.size, alg);
+                    session.save(SESSION_PATH).ok();
+                } else if let Some(bookmark) = session.bookmark(slot) {
+                    playback = None;
+                    scramble = None;
+                    gcube.replace(bookmark.to_gcube());
+                    relay = None;
+                    timer.reset();
+                    is_timer_armed = false;
+                    history.clear();
+                    for &movement in bookmark.alg.movements() {
+                        history.push(movement);
+                    }
+                }
+            }
+        }
+        // every key typed this frame, as a fast solver can type several
+        let mut keys = vec![];
+        while let Some(c) = get_char_pressed() {
+            keys.push(c);
+        }
+        // characters are popped from last typed to first typed
+        keys.reverse();
+        if let Some(text) = memo.as_mut() {
+            text.extend(keys.drain(..).filter(|c| c.is_ascii_alphabetic() || *c == ' ' || *c == '/'));
+        }
+        if let Some(text) = preview.as_mut() {
+            text.extend(keys.drain(..).filter(|c| c.is_ascii_alphanumeric() || " '()".contains(*c)));
+        }
+        if let Some(text) = console.as_mut() {
+            text.extend(keys.drain(..).filter(|c| c.is_ascii_alphanumeric() || " '()-_".contains(*c)));
+        }
+        if is_ctrl_down {
+            keys.clear();
+        }
+        modifiers.is_alt_down = is_key_down(KeyCode::LeftAlt) || is_key_down(KeyCode::RightAlt);
+        let mut movements = keymap.movements(keys, &mut modifiers, gcube.size);
+        if let Some(m) = midi.as_mut() {
+            let keys = midi_keys(&settings.midi_bindings, &m.poll());
+            if memo.is_none() && preview.is_none() && console.is_none() {
+                movements.extend(midi_keymap.movements(keys, &mut modifiers, gcube.size));
+            }
+        }
+
+        // the net view replaces the 3D view, and handles the mouse itself
+        let is_net_view = settings.render_mode == RenderMode::Net && gcube.is_cube();
+        let is_panel_shown = has_history_panel && !history.is_empty();
+        let net_layout = net_view_layout(if is_panel_shown { screen_width() - panel_width } else { screen_width() }, ui_scale);
+        // the sticker clicked while editing
+        let mut painted = None;
+
+        // touch: swipe a sticker across its face to turn its layer, or drag
+        // with two fingers to move the camera
+        let touches = if is_net_view { vec![] } else { touches() };
+        let mut two_finger_drag = Vec2::ZERO;
+        if touches.len() >= 2 {
+            swipe = None;
+            let center = (touches[0].position + touches[1].position) / 2.;
+            if let Some(last_center) = two_finger_center {
+                two_finger_drag = center - last_center;
+            }
+            two_finger_center = Some(center);
+        } else if let Some(touch) = touches.first() {
+            two_finger_center = None;
+            match touch.phase {
+                TouchPhase::Started => {
+                    let (origin, direction) = screen_ray(&camera, touch.position);
+                    let hit = gcube.raycast(origin.into(), direction.into());
+                    if is_editing {
+                        painted = hit.map(|(index, _)| index);
+                    } else {
+                        swipe = hit.map(|(index, _)| (touch.id, index, touch.position));
+                    }
+                }
+                TouchPhase::Moved => {
+                    if let Some((id, index, start)) = swipe {
+                        // wait for the swipe to be long enough to have a direction
+                        if id == touch.id && start.distance(touch.position) > 30. * ui_scale {
+                            let sticker = gcube.sticker(index);
+                            let drag = face_point(&camera, touch.position, &gcube, sticker)
+                                - face_point(&camera, start, &gcube, sticker);
+                            movements.extend(drag_movement(&gcube, sticker, drag.into()));
+                            swipe = None;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        } else {
+            swipe = None;
+            two_finger_center = None;
+        }
+        // the net view: drag a sticker across its face to turn its layer
+        if is_net_view && !is_menu_open {
+            let (x, y) = mouse_position();
+            if is_mouse_button_pressed(MouseButton::Left) {
+                match net_layout.facelet_at(gcube.size, [x, y]) {
+                    Some(facelet) if is_editing => painted = Some(gcube.facelet_sticker(facelet)),
+                    Some(_) => net_drag = Some([x, y]),
+                    None => {}
+                }
+            }
+            if let Some(from) = net_drag {
+                // wait for the drag to be long enough to have a direction
+                if !is_mouse_button_down(MouseButton::Left) {
+                    net_drag = None;
+                } else if vec2(from[0], from[1]).distance(vec2(x, y)) > 20. * ui_scale {
+                    movements.extend(net_layout.drag_movement(&gcube, from, [x, y]));
+                    net_drag = None;
+                }
+            }
+        } else {
+            net_drag = None;
+        }
+        // each click paints the sticker the next colour
+        if let Some(index) = painted {
+            let face = gcube.get_initial_face(gcube.sticker(index));
+            let i = ORDERED_FACES.iter().position(|&f| f == face).unwrap();
+            let mut painted = gcube.clone();
+            painted.paint(index, ORDERED_FACES[(i + 1) % TOTAL_FACES]);
+            gcube.replace(painted);
+            history.clear();
+            scramble = None;
+            relay = None;
+            timer.reset();
+            is_timer_armed = false;
+        }
+
+        for command in commands {
+            match command {
+                Command::Move { movement } => movements.push(movement),
+                Command::Alg { alg } => movements.extend_from_slice(alg.movements()),
+                Command::Arrows { arrows: a } => arrows = a,
+                // scrambles are applied at once, before the frame's movements
+                Command::Scramble { scramble: alg } => {
+                    animator.finish(&mut gcube);
+                    playback = None;
+                    replay_player = None;
+                    relay = None;
+                    let alg = alg.unwrap_or_else(|| {
+                        if gcube.dims == [3; 3] && settings.scramble_filter.is_active() {
+                            settings.scramble_filter.scramble(&mut rng)
+                        } else if let Some(step) = reduction.filter(|_| gcube.size >= 4 && gcube.is_cube()) {
+                            step.scramble(gcube.size, &mut rng)
+                        } else {
+                            random_scramble(gcube.size, scramble_length(gcube.size), &mut rng)
+                        }
+                    });
+                    // cuboids can only half turn their faces that aren't square
+                    let alg: Alg = alg.movements().iter().map(|&m| gcube.allowed_movement(m)).collect::<Vec<_>>().into();
+                    gcube.apply_scramble(&alg);
+                    history.push_scramble(&alg);
+                    // however it was scrambled, e.g. by a remote command
+                    let today = day_of(miniquad::date::now());
+                    daily = Some(today).filter(|&day| alg == daily_scramble(day, gcube.size));
+                    if let Some(r) = race.as_mut() {
+                        r.start_round(gcube.size, alg.clone());
+                    }
+                    scramble = Some(alg);
+                    timer.reset();
+                    // edge pairing is only timed from solved centers
+                    let step = reduction.filter(|_| gcube.size >= 4 && gcube.is_cube());
+                    is_timer_armed = step.is_none_or(|step| step.can_start(&gcube));
+                    inspection = None;
+                    if let Some(m) = metronome.as_mut() {
+                        m.reset_score();
+                    }
+                }
+            }
+        }
+        let movements: Vec<Movement> = movements.into_iter().map(|m| gcube.allowed_movement(m)).collect();
+        if !movements.is_empty() {
+            // manual moves take over from the auto-solve, or a replay
+            playback = None;
+            replay_player = None;
+            if is_setting_up {
+                // the solve is timed from the first move after the setup
+                timer.reset();
+                is_timer_armed = true;
+            } else {
+                scramble = None;
+                if is_timer_armed {
+                    penalty = inspection.map_or(Penalty::None, |i| Penalty::for_inspection(i.elapsed(get_time())));
+                    timer.start(get_time());
+                    is_timer_armed = false;
+                    reduction_splits = ReductionSplits::default();
+                    sounds.borrow().play(SoundEffect::TimerStart);
+                }
+                // a chord of keys pressed together is scored as one turn
+                if let Some(m) = metronome.as_mut() {
+                    m.record_turn(get_time());
+                }
+            }
+            if let Some(r) = recording.as_mut() {
+                r.extend_from_slice(&movements);
+            }
+            // keys pressed together turn together, e.g. R and L'
+            animator.push_together(&movements, &gcube);
+            for &movement in movements.iter() {
+                if is_setting_up {
+                    history.push_setup(movement);
+                } else {
+                    timer.record(movement, get_time());
+                    history.push(movement);
+                }
             }
-            if size_f != gcube.size as f32 {
-                camera.position *= gcube.size as f32 / size_f;
-                size_f = gcube.size as f32;
+            if is_setting_up {
+                scramble = history.scramble();
             }
         }
-        if is_key_down(KeyCode::Up) { camera.position.y += size_f / 7.; }
-        if is_key_down(KeyCode::Down) { camera.position.y -= size_f / 7.; }
+        if settings.dims() != gcube.dims {
+            animator.finish(&mut gcube);
+            let resized = match settings.cuboid {
+                None if settings.resize_keeps_state => gcube.resized(settings.cube_size),
+                _ => None,
+            };
+            let resized = resized.unwrap_or_else(|| GCube::cuboid(settings.dims()));
+            gcube.replace(resized);
+            modifiers.depth = 1;
+            history.clear();
+            playback = None;
+            replay_player = None;
+            scramble = None;
+            daily = None;
+            relay = None;
+            timer.reset();
+            is_timer_armed = false;
+        }
+        if size_f != gcube.size as f32 {
+            camera.position *= gcube.size as f32 / size_f;
+            size_f = gcube.size as f32;
+        }
+        // the settings file edited beside the running app, e.g. while tuning colours or turn speed
+        let reloaded = settings_watcher.changed(get_time());
+        let is_reloaded = reloaded.is_some();
+        if let Some(changed) = reloaded {
+            settings = changed;
+        }
+        animator.turn_duration = settings.turn_duration;
+        animator.easing = settings.easing;
+        animator.turn_style = settings.turn_style;
+        if settings.keymap != saved_settings.keymap {
+            keymap = Keymap::from(settings.keymap);
+        }
+        if settings.sound_theme != saved_settings.sound_theme {
+            let effects = load_sounds(settings.sound_theme).await;
+            sounds.borrow_mut().effects = effects;
+        }
+        sounds.borrow_mut().volume = settings.volume;
+        match speech {
+            Some(id) if !settings.speech => {
+                gcube.unsubscribe(id);
+                speech = None;
+            }
+            None if settings.speech => speech = Some(gcube.subscribe(Box::new(Speech::new()))),
+            _ => {}
+        }
+        // the metronome starts over when its tempo changes
+        if !settings.metronome {
+            metronome = None;
+        } else if metronome.as_ref().map(|m| m.bpm()) != Some(settings.bpm as f64) {
+            metronome = Some(Metronome::new(settings.bpm as f64, get_time()));
+        }
+        if metronome.as_mut().is_some_and(|m| m.tick(get_time())) {
+            sounds.borrow().play(SoundEffect::Beat);
+        }
+        // between turns, so the cube's state matches its history
+        if get_time() - autosaved_at >= AUTOSAVE_INTERVAL && animator.is_idle() {
+            autosaved_at = get_time();
+            let autosave = Autosave::new(&gcube, &history, timer.snapshot(get_time()), is_timer_armed, penalty);
+            autosave.save(AUTOSAVE_PATH).ok();
+            session.save(SESSION_PATH).ok();
+        }
+        if is_reloaded {
+            // already as in the file, which isn't rewritten under the editor
+            saved_settings = settings.clone();
+        } else if settings != saved_settings {
+            // the settings file is only a convenience, e.g. there's no file system on web
+            settings.save(SETTINGS_PATH).ok();
+            settings_watcher.saved();
+            saved_settings = settings.clone();
+        }
+        let camera_speed = settings.camera_speed;
+        if is_key_down(KeyCode::Up) { camera.position.y += size_f / 7. * camera_speed; }
+        if is_key_down(KeyCode::Down) { camera.position.y -= size_f / 7. * camera_speed; }
         let mut angle = 0.0;
-        if is_key_down(KeyCode::Left) { angle = 0.05; }
-        if is_key_down(KeyCode::Right) { angle = -0.05; }
-        camera.position.y = clamp(camera.position.y, size_f * -3.5, size_f * 3.5);
+        if is_key_down(KeyCode::Left) { angle = 0.05 * camera_speed; }
+        // a slow orbit, whatever the frame rate
+        if is_showcasing { angle = 0.3 * get_frame_time(); }
+        if is_key_down(KeyCode::Right) { angle = -0.05 * camera_speed; }
+        camera.position.y += two_finger_drag.y * size_f / 70. * camera_speed;
+        angle -= two_finger_drag.x * 0.01 * camera_speed;
+        // looking down or up at the cube at most as steeply as at the start
+        let max_y = vec2(camera.position.x, camera.position.z).length() * 0.7;
+        camera.position.y = clamp(camera.position.y, -max_y, max_y);
         if angle != 0.0 {
             camera.position = Quat::from_rotation_y(angle).mul_vec3(camera.position);
         }
+        // the mouse wheel zooms in and out, by moving a perspective camera or
+        // by zooming an orthographic view
+        let wheel = if is_menu_open { 0. } else { mouse_wheel().1.signum() };
+        if wheel != 0. && settings.projection == CameraProjection::Orthographic {
+            settings.orthographic_zoom = (settings.orthographic_zoom * 1.1f32.powf(wheel)).clamp(0.5, 3.);
+        } else if wheel != 0. {
+            camera.position /= 1.1f32.powf(wheel);
+        }
+        let distance = camera.position.length();
+        let min_distance = settings.min_distance * size_f * 2.;
+        let max_distance = settings.max_distance.max(settings.min_distance) * size_f * 2.;
+        camera.position *= distance.clamp(min_distance, max_distance) / distance;
+        // a second view from the opposite corner, showing the hidden faces
+        let (width, height) = (screen_width(), screen_height());
+        let is_panel_shown = has_history_panel && !history.is_empty();
+        let second_viewport = match settings.view_layout {
+            ViewLayout::Single => None,
+            ViewLayout::PictureInPicture => {
+                let right = if is_panel_shown { width - panel_width } else { width };
+                Some((right - width / 3. - 10., 10., width / 3., height / 3.))
+            }
+            ViewLayout::Split => Some((width / 2., 0., width / 2., height)),
+        };
+        let (projection, fovy) = match settings.projection {
+            CameraProjection::Perspective => (Projection::Perspective, settings.fov.to_radians()),
+            // the height of the view, in the cube's units, fitting the cube and its mirrors
+            CameraProjection::Orthographic => (Projection::Orthographics, size_f * 6. / settings.orthographic_zoom),
+        };
+        camera.projection = projection;
+        camera.fovy = fovy;
+        let is_split = settings.view_layout == ViewLayout::Split;
+        camera.viewport = if is_split { Some(to_pixels((0., 0., width / 2., height))) } else { None };
+        camera.aspect = if is_split { Some(width / 2. / height) } else { None };
+        let second_camera = second_viewport.map(|(x, y, w, h)| Camera3D {
+            position: -camera.position,
+            up: camera.up,
+            target: camera.target,
+            aspect: Some(w / h),
+            projection,
+            fovy,
+            viewport: Some(to_pixels((x, y, w, h))),
+            ..Default::default()
+        });
         set_camera(&camera);
 
+        for (cube, solution) in solver.poll() {
+            // played back unless the cube's been turned (or painted) since it was sent to the solver
+            if solving == Some(cube) {
+                solving = None;
+                if animator.is_idle() && solver_cube(&gcube) == Some(cube) {
+                    is_editing = false;
+                    playback = Some(Playback::new(solution));
+                }
+            }
+        }
+        if let Some(p) = playback.as_mut() {
+            if animator.is_idle() {
+                if let Some(movement) = p.next_movement() {
+                    animator.push(movement);
+                    history.push(movement);
+                } else if p.is_finished() {
+                    playback = None;
+                }
+            }
+        }
+        if let Some(p) = replay_player.as_mut() {
+            for movement in p.due(get_time()) {
+                animator.push(movement);
+                history.push(movement);
+            }
+            if p.is_finished() && p.elapsed(get_time()) >= p.duration() {
+                replay_player = None;
+            }
+        }
+        // moves are logged with the time into the solve they land at
+        if let Some(logger) = move_log.as_ref() {
+            logger.borrow_mut().solve_start = Some(get_time() - timer.elapsed(get_time())).filter(|_| timer.is_running());
+        }
+        animator.update(&mut gcube, get_frame_time());
+        if let Some(r) = race.as_mut() {
+            r.send_state(&gcube);
+        }
+        if !is_timer_armed {
+            inspection = None;
+        } else if inspection.is_none() {
+            inspection = Some(Inspection::new(get_time()));
+        }
+        if inspection.is_none() || !settings.inspection_report || gcube.dims != [3; 3] {
+            inspection_report = None;
+        } else if inspection_report.is_none() {
+            inspection_report = InspectionReport::new(&gcube.to_facelet_model()).ok();
+        }
+        if let Some(n) = inspection.as_mut().and_then(|i| i.warning(get_time())) {
+            sounds.borrow().play(SoundEffect::InspectionWarning(n));
+        }
+        // a drill's step is timed on its own, rather than the whole solve
+        if let Some(d) = drill.as_mut() {
+            let is_step_done = || d.step.is_solved(&gcube.to_facelet_model(), d.color);
+            if timer.is_running() && animator.is_idle() && gcube.dims == [3; 3] && relay.is_none() && is_step_done() {
+                timer.stop(get_time());
+                sounds.borrow().play(SoundEffect::TimerStop);
+                session.step_times.push(StepTime { step: d.step, color: d.color, time: timer.elapsed(get_time()) });
+                session.save(SESSION_PATH).ok();
+                d.next_color(&mut rng);
+            }
+        }
+        // as is a big cube trainer's
+        if let Some(step) = reduction.filter(|_| gcube.size >= 4 && gcube.is_cube()) {
+            if timer.is_running() && animator.is_idle() && relay.is_none() && step.is_solved(&gcube) {
+                timer.stop(get_time());
+                sounds.borrow().play(SoundEffect::TimerStop);
+                session.reduction_times.push(ReductionTime { size: gcube.size, step, time: timer.elapsed(get_time()) });
+                session.save(SESSION_PATH).ok();
+            }
+        }
+        // a whole big cube solve is split at each stage of reduction
+        if timer.is_running() && animator.is_idle() && reduction.is_none() {
+            reduction_splits.update(&gcube, timer.elapsed(get_time()));
+        }
+        // and a case trainer's attempts are kept by case, rather than as solves
+        if let Some(t) = case_trainer.as_mut().filter(|_| gcube.dims == [3; 3]) {
+            if timer.is_running() && animator.is_idle() && relay.is_none() && gcube.is_solved() {
+                let now = get_time();
+                timer.stop(now);
+                sounds.borrow().play(SoundEffect::TimerStop);
+                let attempt = t.attempt(now - timer.elapsed(now), timer.elapsed(now), Some(timer.movements().len()));
+                session.case_attempts.push(attempt);
+                session.save(SESSION_PATH).ok();
+                t.next_case(&mut rng);
+            }
+        }
+        let is_solve_finished = timer.is_running() && animator.is_idle() && gcube.is_solved();
+        // relays move on to the next leg's cube, the timer running on
+        let next_leg = match relay.as_mut() {
+            Some(r) if is_solve_finished => r.finish_leg(&gcube, timer.elapsed(get_time())),
+            _ => None,
+        };
+        if let Some(next) = next_leg {
+            let leg = relay.as_ref().map_or(0, |r| r.current());
+            let alg = relay.as_ref().unwrap().scramble(leg).clone();
+            gcube.replace(next);
+            settings.cube_size = gcube.size;
+            settings.cuboid = None;
+            modifiers.depth = 1;
+            history.clear();
+            history.push_scramble(&alg);
+            scramble = Some(alg);
+        } else if is_solve_finished {
+            timer.stop(get_time());
+            sounds.borrow().play(SoundEffect::TimerStop);
+            if let Some(day) = daily {
+                if session.record_daily_time(day, gcube.size, timer.elapsed(get_time())) {
+                    session.save(SESSION_PATH).ok();
+                }
+            }
+            if let Some(r) = race.as_mut() {
+                r.finish(timer.elapsed(get_time()));
+            }
+            // a relay's time is of several cubes, not a solve of one
+            if relay.is_none() {
+                session.results.push(SolveResult {
+                    size: gcube.size,
+                    time: timer.elapsed(get_time()),
+                    penalty,
+                    scramble: history.scramble().unwrap_or_default(),
+                    date: Some(miniquad::date::now()),
+                    solution: Alg::new(timer.movements().to_vec()),
+                });
+                save_results(&session);
+                if let Some(logger) = move_log.as_ref() {
+                    let event = LogEvent::Solve { size: gcube.size, time: timer.elapsed(get_time()), penalty };
+                    logger.borrow_mut().log.log(event, miniquad::date::now()).ok();
+                }
+                let metadata = ReplayMetadata {
+                    date: Some(miniquad::date::now()),
+                    time: Some(timer.elapsed(get_time())),
+                    penalty,
+                    ..Default::default()
+                };
+                let replay = Replay::new(gcube.dims, history.scramble().unwrap_or_default(), timer.timed_alg(), metadata);
+                replay.save(LAST_REPLAY_PATH).ok();
+            }
+        }
+        // the movements currently turning, and how far they've turned
+        let (turning, progress) = match animator.current() {
+            Some((movements, progress)) => (GCube::create_gmoves(movements), progress),
+            None => (vec![], 0.),
+        };
+
         clear_background(desu_gray);
-        for sticker in gcube.stickers.iter() {
-            let curr = point3_to_vec3(sticker.current);
-            let mut mirr = curr;
-            if mirr.x.abs() == size_f { mirr.x *= 2.4 }
-            else if mirr.y.abs() == size_f { mirr.y *= 2.4 }
-            else { mirr.z *= 2.4 }
-            let mirr_vec = curr - mirr;
-            // only render the sticker if it's visible
-            if (curr - camera.position).dot(mirr_vec) > 0. {
-                draw_cube(
-                    curr,
-                    face_to_dimensions(gcube.get_curr_face(*sticker)),
-                    None,
-                    face_to_color(gcube.get_initial_face(*sticker)),
-                );
-            }
-            // only draw the mirror's side that's closer to the cube 
-            if !has_mirrors || (mirr - camera.position).dot(mirr_vec) > 0. { continue }
-            draw_cube(
-                mirr,
-                face_to_dimensions(gcube.get_curr_face(*sticker)),
-                None,
-                face_to_color(gcube.get_initial_face(*sticker)),
-            );
+        // between turns, so the hint's for the cube as shown; the solver's, unless the cube isn't a real 3x3
+        let hint = match has_hints && playback.is_none() && animator.is_idle() {
+            true => solution_hint.next_move(&gcube).unwrap_or_else(|_| history.hint()),
+            false => None,
+        };
+        // the layers of the last move flash, fading out
+        let flash = match animator.last_turned() {
+            Some((movements, since)) if settings.flash_last_move && since < FLASH_DURATION => {
+                Some((GCube::create_gmoves(movements), 1. - since / FLASH_DURATION))
+            }
+            _ => None,
+        };
+        let is_stickerless = settings.piece_style == PieceStyle::Stickerless;
+        // stickerless pieces are coloured all over their outer faces
+        let shape = StickerShape {
+            len: if is_stickerless { P_LEN } else { settings.sticker_size * 2. },
+            rounding: settings.sticker_rounding,
+            is_shaded: settings.shading,
+        };
+        let [r, g, b] = settings.shell_color;
+        let shell = Color::from_rgba(r, g, b, 255);
+        let gap = if settings.exploded_view { settings.explode_gap } else { 0. };
+        let dims_f = dims_to_vec3(gcube.dims);
+        // a 3x3 drawn as a shape mod has no stickers, just its pieces' faces
+        let shape_mod = if gcube.dims == [3; 3] { settings.shape_mod } else { ShapeMod::Off };
+        let is_shape_mod = shape_mod != ShapeMod::Off;
+        // exploded layers are drawn as separate pieces, the shell would hide them
+        let has_pieces = (is_stickerless || gap > 0.) && !is_shape_mod;
+        let pieces = if has_pieces { gcube.pieces() } else { vec![] };
+        let shape_pieces: Vec<(Vec<ShapeFace>, Vec3, Option<Quat>)> = if is_shape_mod {
+            gcube
+                .pieces()
+                .iter()
+                .map(|piece| {
+                    let rotation = turning
+                        .iter()
+                        .find(|gmove| gmove.affects(gcube.sticker(piece.stickers[0])))
+                        .map(|&gmove| turn_rotation(gmove, progress));
+                    let offset = explode_offset(point3_to_vec3(piece.center), dims_f, gap);
+                    (shape_mod.piece_faces(&gcube, piece), offset, rotation)
+                })
+                .collect()
+        } else {
+            vec![]
+        };
+        // the cube may have been resized to a cuboid since
+        let is_net_view = settings.render_mode == RenderMode::Net && gcube.is_cube();
+        let views: Vec<&Camera3D> = match is_net_view {
+            true => vec![],
+            false => std::iter::once(&camera).chain(second_camera.as_ref()).collect(),
+        };
+        let mut n_drawn = 0;
+        outlines.update(&gcube, shape, gap, settings.mirror_distance);
+        let is_mirrored = |face: Face| {
+            settings.show_mirrors && settings.mirror_style == MirrorStyle::Floating && settings.mirror_faces.contains(&face)
+        };
+        let mirror_color = |color: Color| Color { a: color.a * settings.mirror_opacity, ..color };
+        let colors: Vec<Color> = gcube
+            .stickers()
+            .map(|sticker| {
+                let color = if mask.contains(sticker) {
+                    face_to_color(gcube.get_initial_face(sticker), settings.color_scheme)
+                } else {
+                    DARKGRAY
+                };
+                match flash.as_ref() {
+                    Some((gmoves, strength)) if gmoves.iter().any(|g| g.affects(sticker)) => {
+                        lighten(color, strength * 0.6)
+                    }
+                    _ => color,
+                }
+            })
+            .collect();
+        let rotations: Vec<Option<Quat>> = gcube
+            .stickers()
+            .map(|sticker| {
+                turning
+                    .iter()
+                    .find(|gmove| gmove.affects(sticker))
+                    .map(|&gmove| turn_rotation(gmove, progress))
+            })
+            .collect();
+        // very big cubes have their still stickers merged into blocks of the same colour
+        let is_merged = gcube.size >= settings.merge_size && gap == 0. && gcube.is_cube() && !is_shape_mod;
+        let blocks = if is_merged {
+            let n = gcube.size;
+            let mut grid = vec![None; 6 * n * n];
+            for ((sticker, &color), rotation) in gcube.stickers().zip(colors.iter()).zip(rotations.iter()) {
+                if let (Some(facelet), None) = (Facelet::at(sticker.current, n), rotation) {
+                    let face_index = ORDERED_FACES.iter().position(|&f| f == facelet.face).unwrap();
+                    grid[face_index * n * n + facelet.index] = Some(color);
+                }
+            }
+            facelet_blocks(&grid, n)
+        } else {
+            vec![]
+        };
+        for (i, view) in views.into_iter().enumerate() {
+            // the second view needs no mirrors, it shows the hidden faces
+            let is_second = i > 0;
+            if is_second {
+                clear_view(view, desu_gray);
+            }
+            set_camera(view);
+            let frustum = frustum_planes(view);
+            let mut batch = MeshBatch::default();
+            for (i, [(curr, outline), (mirr, mirr_outline)]) in outlines.outlines.iter().enumerate().filter(|_| !is_shape_mod) {
+                let (color, rotation) = (colors[i], rotations[i]);
+                // merged stickers are drawn with their block
+                if is_merged && rotation.is_none() { continue }
+                let face = gcube.get_curr_face(gcube.sticker(i));
+                let (curr, mirr) = match rotation {
+                    Some(rotation) => (rotation.mul_vec3(*curr), rotation.mul_vec3(*mirr)),
+                    None => (*curr, *mirr),
+                };
+                let mirr_vec = curr - mirr;
+                // only render the sticker if it's visible
+                if (curr - view.position).dot(mirr_vec) > 0. && is_in_frustum(&frustum, curr, shape.len) {
+                    draw_sticker(&mut batch, outline, face, rotation, color, shape);
+                    n_drawn += 1;
+                }
+                // only draw the mirror's side that's closer to the cube 
+                if !is_mirrored(face) || is_second || (mirr - view.position).dot(mirr_vec) > 0. { continue }
+                if is_in_frustum(&frustum, mirr, shape.len) {
+                    draw_sticker(&mut batch, mirr_outline, face, rotation, mirror_color(color), shape);
+                    n_drawn += 1;
+                }
+            }
+            for (faces, offset, rotation) in shape_pieces.iter() {
+                let rotation = rotation.unwrap_or(Quat::IDENTITY);
+                for f in faces {
+                    let corners: Vec<Vec3> = f.corners.iter().map(|&c| rotation.mul_vec3(Vec3::from(c) + *offset)).collect();
+                    let normal = (corners[1] - corners[0]).cross(corners[2] - corners[0]).normalize_or_zero();
+                    let color = f.face.map_or(shell, |face| {
+                        let [r, g, b] = shape_mod.color(face, settings.color_scheme);
+                        Color::from_rgba(r, g, b, 255)
+                    });
+                    let color = if shape.is_shaded { shade(color, normal) } else { color };
+                    batch.push_polygon(corners.into_iter(), color);
+                }
+                n_drawn += 1;
+            }
+            for block in blocks.iter() {
+                let face = block.facelet.face;
+                let (from, to) = match (block.facelet.position(gcube.size), block.last(gcube.size).position(gcube.size)) {
+                    (Some(from), Some(to)) => (point3_to_vec3(from), point3_to_vec3(to)),
+                    _ => continue,
+                };
+                let center = (from + to) / 2.;
+                let outline = block_outline(from, to, face, shape.len);
+                let radius = (outline[0] - center).length();
+                let normal = face_to_normal(face);
+                if (center - view.position).dot(normal) < 0. && is_in_frustum(&frustum, center, radius) {
+                    draw_sticker(&mut batch, &outline, face, None, block.color, shape);
+                    n_drawn += 1;
+                }
+                let mirr_offset = normal * size_f * (settings.mirror_distance - 1.);
+                let mirr = center + mirr_offset;
+                if !is_mirrored(face) || is_second || (mirr - view.position).dot(normal) < 0. { continue }
+                if is_in_frustum(&frustum, mirr, radius) {
+                    draw_sticker(&mut batch, &outline.map(|point| point + mirr_offset), face, None, mirror_color(block.color), shape);
+                    n_drawn += 1;
+                }
+            }
+            batch.draw();
+            if settings.follow_style == FollowStyle::Ghost {
+                if let Some(ghost) = follower.as_ref().and_then(|f| f.gcube()) {
+                    let offset = vec3((size_f + ghost.size as f32) * 1.3, 0., 0.);
+                    draw_still_cube(ghost, offset, view.position, settings.color_scheme, shape, 0.4, PieceMask::All);
+                }
+            }
+            // what the step being trained solves to, only the step's pieces, to compare against
+            if settings.ghost_target && (mask != PieceMask::All || case_trainer.is_some()) {
+                let target = gcube.solved_in_orientation();
+                let offset = vec3(-(size_f + target.size as f32) * 1.3, 0., 0.);
+                draw_still_cube(&target, offset, view.position, settings.color_scheme, shape, 0.4, mask);
+            }
+            if let Some(r) = relay.as_ref() {
+                draw_relay(r, gcube.size, view.position, settings.color_scheme, shape, shell);
+            }
+            // pieces turn with their layer, unlike the shell the stickers float on
+            for piece in pieces.iter() {
+                let rotation = turning
+                    .iter()
+                    .find(|gmove| gmove.affects(gcube.sticker(piece.stickers[0])))
+                    .map(|&gmove| turn_rotation(gmove, progress));
+                let center = point3_to_vec3(piece.center);
+                let center = center + explode_offset(center, dims_f, gap);
+                let turned = rotation.unwrap_or(Quat::IDENTITY).mul_vec3(center);
+                if is_in_frustum(&frustum, turned, P_LEN) {
+                    draw_piece(center, rotation, shell);
+                }
+            }
+            if !has_pieces && !is_shape_mod {
+                let scale = if gcube.size >= 14 { 1.96 } else { 1.99 };
+                draw_cube(vec3(0., 0., 0.), dims_f * scale, None, shell);
+            }
+            if let Some(movement) = hint {
+                draw_hint_arrow(&gcube, GCube::create_gmove(movement), view.position);
+            }
+            // facelets are only numbered on cubes
+            for arrow in arrows.iter().filter(|_| gcube.is_cube()) {
+                if let Some((from, to)) = arrow.positions(gcube.size) {
+                    let (from, to) = (point3_to_vec3(from), point3_to_vec3(to));
+                    let from = (from + explode_offset(from, dims_f, gap), face_to_normal(arrow.from.face));
+                    let to = (to + explode_offset(to, dims_f, gap), face_to_normal(arrow.to.face));
+                    draw_facelet_arrow(from, to);
+                }
+            }
+        }
+
+        set_default_camera();
+        if is_net_view {
+            let is_panel_shown = has_history_panel && !history.is_empty();
+            let layout = net_view_layout(if is_panel_shown { screen_width() - panel_width } else { screen_width() }, ui_scale);
+            draw_net(&gcube.facelets(), gcube.size, layout.x, layout.y, layout.face_size, settings.color_scheme);
+        }
+        if let Some(m) = metronome.as_ref() {
+            // a dot that flashes on every beat, fading until the next
+            let fade = 1. - m.phase(get_time()) as f32;
+            let radius = (8. + 8. * fade) * ui_scale;
+            draw_circle(screen_width() / 2., 30. * ui_scale, radius, Color::new(1., 1., 1., fade));
+        }
+        if has_history_panel && !history.is_empty() {
+            let (clicked, hovered) = draw_history_panel(&history, ui_scale);
+            if let Some(position) = clicked {
+                animator.finish(&mut gcube);
+                playback = None;
+                gcube.seek(history.seek(position).movements());
+            }
+            // a thumbnail of the hovered position's state, beside the panel
+            if let Some(position) = hovered {
+                let mut state = gcube.clone();
+                state.apply_movements(history.clone().seek(position).movements());
+                if let Some(texture) = thumbnail_texture(&mut thumbnails, &state, settings.color_scheme) {
+                    let (width, height) = (texture.width() * ui_scale, texture.height() * ui_scale);
+                    let y = (mouse_position().1 - height / 2.).clamp(0., (screen_height() - height).max(0.));
+                    draw_texture_scaled(texture, screen_width() - panel_width - width - 10. * ui_scale, y, width, height);
+                }
+            }
+        }
+        // the bookmarks to restore or overwrite, while shift's held to save one
+        if is_shift_down && memo.is_none() && preview.is_none() && console.is_none() {
+            let mut x = 10. * ui_scale;
+            for key in [KeyCode::F1, KeyCode::F2, KeyCode::F3, KeyCode::F4] {
+                let slot = key_to_bookmark_slot(key).unwrap();
+                let bookmark = session.bookmark(slot);
+                let texture = bookmark.and_then(|b| thumbnail_texture(&mut thumbnails, &b.to_gcube(), settings.color_scheme));
+                let y = screen_height() - 200. * ui_scale;
+                draw_text(&format!("F{}", slot), x, y - 5. * ui_scale, 20. * ui_scale, WHITE);
+                match texture {
+                    Some(t) => draw_texture_scaled(t, x, y, t.width() * ui_scale, t.height() * ui_scale),
+                    None => draw_text("empty", x, y + 20. * ui_scale, 20. * ui_scale, GRAY),
+                }
+                x += (THUMBNAIL_SIZE as f32 + 10.) * ui_scale;
+            }
+        }
+        if let Some(stats) = turn_stats.as_ref() {
+            draw_turn_chart(stats, ui_scale);
+        }
+        if let Some(r) = race.as_ref() {
+            let is_panel_shown = has_history_panel && !history.is_empty();
+            let right = if is_panel_shown { screen_width() - panel_width } else { screen_width() };
+            draw_opponents(r, settings.color_scheme, right, ui_scale);
+        }
+        if let Some(p) = preview.as_ref().and_then(|text| Preview::parse(&gcube, text).ok()).filter(|p| p.gcube.is_cube()) {
+            let is_panel_shown = has_history_panel && !history.is_empty();
+            let right = if is_panel_shown { screen_width() - panel_width } else { screen_width() };
+            draw_preview(&p, settings.color_scheme, right, ui_scale);
+        }
+        if settings.show_mirrors && settings.mirror_style == MirrorStyle::Panel && gcube.is_cube() && !is_net_view {
+            let is_panel_shown = has_history_panel && !history.is_empty();
+            let right = if is_panel_shown { screen_width() - panel_width } else { screen_width() };
+            let faces: Vec<Face> = ORDERED_FACES.iter().copied().filter(|f| settings.mirror_faces.contains(f)).collect();
+            draw_mirror_panel(&gcube, &faces, settings.color_scheme, right, ui_scale);
+        }
+        if settings.show_net && gcube.is_cube() && !is_net_view {
+            let is_panel_shown = has_history_panel && !history.is_empty();
+            let right = if is_panel_shown { screen_width() - panel_width } else { screen_width() };
+            // below the second view when it's in the corner, and the opponents' nets
+            let mut top = match settings.view_layout {
+                ViewLayout::PictureInPicture => second_viewport.map_or(0., |(_, y, _, h)| y + h),
+                _ => 0.,
+            };
+            if race.is_some() {
+                top += 30. * ui_scale + 3. * 36. * ui_scale;
+            }
+            draw_net_overlay(&gcube, settings.color_scheme, right, top, ui_scale);
+        }
+        if let Some(alg) = scramble.as_ref() {
+            draw_wrapped_text(&alg.to_string(), 10., screen_height() - 60. * ui_scale, 30. * ui_scale);
+        }
+        let mut hud_lines: Vec<String> = vec![];
+        if settings.show_hud && (is_timer_armed || timer.is_running() || timer.is_stopped()) {
+            let now = get_time();
+            hud_lines.push(format!("{:.2}", timer.elapsed(now)));
+            let n_moves = timer.move_count(settings.metric);
+            hud_lines.push(format!("{} moves ({})", n_moves, settings.metric));
+            hud_lines.push(format!("{:.2} TPS", timer.tps(now, settings.metric)));
+            if let Some(result) = session.results.last().filter(|_| timer.is_stopped() && relay.is_none() && drill.is_none() && reduction.is_none() && case_trainer.is_none()) {
+                hud_lines.push(format!("Counted: {} {}", format_centiseconds(result.counted()), result.penalty));
+            }
+            let stats: Vec<String> = [("mo3", 3), ("ao5", 5), ("ao12", 12), ("ao100", 100)]
+                .iter()
+                .filter_map(|&(name, n)| {
+                    let times = session.last_times(gcube.size, n)?;
+                    let stat = if n == 3 { mean(&times) } else { average(&times) };
+                    Some(format!("{} {}", name, format_centiseconds(stat)))
+                })
+                .collect();
+            if !stats.is_empty() {
+                hud_lines.push(stats.join(", "));
+            }
+            if let Some(i) = inspection.as_ref() {
+                hud_lines.push(format!("Inspection: {:.0}s", i.elapsed(now).floor()));
+            }
+            if let Some(report) = inspection_report.as_ref() {
+                hud_lines.extend(report.lines(settings.color_scheme));
+            }
+            if let Some(d) = drill.as_ref() {
+                let scheme = settings.color_scheme;
+                hud_lines.push(format!("Drill: {} on {} ([ to change)", d.step, scheme.color_name(d.color)));
+                let stats = color_stats(&session.step_times, d.step);
+                let means: Vec<String> = stats
+                    .iter()
+                    .filter_map(|s| Some(format!("{} {:.2}", scheme.color_name(s.color), s.mean?)))
+                    .collect();
+                if !means.is_empty() {
+                    hud_lines.push(format!("Means: {}", means.join(", ")));
+                }
+                let lagging: Vec<&str> = lagging_colors(&stats).into_iter().map(|c| scheme.color_name(c)).collect();
+                if !lagging.is_empty() {
+                    hud_lines.push(format!("Lagging: {}", lagging.join(", ")));
+                }
+            }
+            if let Some(step) = reduction {
+                hud_lines.push(format!("Trainer: {} ([ to change)", step));
+                if !timer.is_running() && !step.can_start(&gcube) {
+                    hud_lines.push("Solve the centers to time edge pairing".to_string());
+                }
+                if let Some(mean) = reduction_mean(&session.reduction_times, gcube.size, step) {
+                    hud_lines.push(format!("{}x{} mean: {:.2}", gcube.size, gcube.size, mean));
+                }
+            }
+            if let Some(t) = case_trainer.as_ref() {
+                hud_lines.push(format!("Cases: {} (Shift + [ to change)", t.set.name));
+                // the last case's name only, so the current one is recognised unaided
+                if let Some(last) = session.case_attempts.iter().rev().find(|a| a.set == t.set.name) {
+                    let stats = CaseStats::new(&session.case_attempts, &last.set, &last.case);
+                    let result = if last.success { "" } else { ", failed" };
+                    hud_lines.push(format!("{}: {:.2} + {:.2}{}", last.case, last.recognition, last.execution, result));
+                    if let (Some(rate), Some(recognition), Some(execution)) = (stats.success_rate(), stats.recognition, stats.execution) {
+                        hud_lines.push(format!("{} mean: {:.2} + {:.2}, {:.0}% of {}", last.case, recognition, execution, rate * 100., stats.attempts));
+                    }
+                }
+            }
+            if let Some(day) = daily {
+                let best = match session.daily_best(day, gcube.size) {
+                    Some(time) => format!(", best {:.2}", time),
+                    None => String::new(),
+                };
+                hud_lines.push(format!("Daily scramble {}{}", date_of_day(day), best));
+            }
+        }
+        if modifiers.depth > 1 {
+            hud_lines.push(format!("Depth: {}", modifiers.depth));
+        }
+        if mask != PieceMask::All {
+            hud_lines.push(format!("Mask: {}", mask));
+        }
+        if let Some(text) = memo.as_ref() {
+            let state = match text.parse::<Memo>().map(|m| m.to_cubie_cube()) {
+                Ok(Ok(_)) if gcube.dims == [3; 3] => "Enter to set up".to_string(),
+                Ok(Ok(_)) => "only for 3x3".to_string(),
+                Ok(Err(e)) => e.to_string(),
+                Err(_) => "expected Speffz letters, not on the buffers".to_string(),
+            };
+            hud_lines.push(format!("Memo (corners / edges): {}_ ({})", text, state));
+        }
+        if let Some(text) = preview.as_ref() {
+            let state = match Preview::parse(&gcube, text) {
+                Ok(p) if p.unsolved_stickers() == 0 => "solves it, Enter to apply".to_string(),
+                Ok(p) => format!("{} stickers unsolved, Enter to apply", p.unsolved_stickers()),
+                Err(e) => e.to_string(),
+            };
+            hud_lines.push(format!("Preview: {}_ ({})", text, state));
+        }
+        if is_setting_up {
+            hud_lines.push("Setup moves (` to finish)".to_string());
+        }
+        if let Some(r) = recording.as_ref() {
+            hud_lines.push(format!("Recording macro: {} moves, Ctrl + Space to stop", r.len()));
+        }
+        if let Some(alg) = unbound_macro.as_ref() {
+            hud_lines.push(format!("Ctrl + a letter or number to bind {}", alg));
+        }
+        if is_editing {
+            let state = match gcube.is_cube().then(|| check_state(gcube.size, &gcube.facelets())) {
+                Some(Ok(())) if gcube.dims == [3; 3] => "valid, Enter to solve".to_string(),
+                Some(Ok(())) => "valid".to_string(),
+                Some(Err(e)) => e.to_string(),
+                None => "cuboids aren't checked".to_string(),
+            };
+            hud_lines.push(format!("Editing: {}", state));
+        }
+        if let Some(p) = playback.as_ref() {
+            let status = if p.paused { " (paused)" } else { "" };
+            hud_lines.push(format!("Solving: {}/{}{}", p.position(), p.alg().len(), status));
+        } else if solving.is_some() {
+            hud_lines.push("Solving: finding a solution".to_string());
+        }
+        if let Some(p) = replay_player.as_ref() {
+            let elapsed = p.elapsed(get_time()).min(p.duration());
+            hud_lines.push(format!("Replay: {:.2} / {:.2}", elapsed, p.duration()));
+        }
+        if let Some(score) = metronome.as_ref().and_then(|m| m.consistency()) {
+            hud_lines.push(format!("On beat: {:.0}% at {} BPM", score * 100., settings.bpm));
+        }
+        if let Some(movement) = hint {
+            hud_lines.push(format!("Hint: {}", movement));
+        }
+        if (timer.is_running() || timer.is_stopped()) && reduction.is_none() {
+            if let Some(time) = reduction_splits.centers {
+                hud_lines.push(format!("Centers solved at {:.2}", time));
+            }
+            if let Some(time) = reduction_splits.reduced {
+                hud_lines.push(format!("Reduced at {:.2}", time));
+            }
+        }
+        if let Some(r) = relay.as_ref() {
+            let leg = (r.current() + 1).min(r.leg_count());
+            hud_lines.push(format!("Relay leg {}/{}", leg, r.leg_count()));
+            for (cube, split) in r.cubes().iter().zip(r.splits()) {
+                hud_lines.push(format!("{0}x{0} solved at {1:.2}", cube.size, split));
+            }
+        }
+        if let Some(round) = race.as_ref().and_then(|r| r.round()) {
+            hud_lines.push(format!("Race round {}", round.number));
+            for (i, (player, time)) in race.as_ref().unwrap().results().iter().enumerate() {
+                hud_lines.push(format!("{}. {} {:.2}", i + 1, player, time));
+            }
+        }
+        #[cfg(feature = "chat")]
+        if let Some(c) = chat.as_ref() {
+            if c.is_closed() {
+                hud_lines.push("Chat disconnected".to_string());
+            } else if let Some((tally, left)) = c.control.tally(get_time()) {
+                let votes: Vec<String> = tally.iter().map(|(m, n)| format!("{} {}", m, n)).collect();
+                hud_lines.push(format!("Chat vote: {} ({:.0}s left)", votes.join(", "), left.ceil()));
+            }
+        }
+        if settings.show_fps {
+            let drawn = if is_merged { "stickers and blocks" } else { "stickers" };
+            hud_lines.push(format!("{} FPS, {} {} drawn", get_fps(), n_drawn, drawn));
+        }
+        for (i, line) in hud_lines.iter().enumerate() {
+            draw_text(line, 10., 30. * ui_scale * (i + 1) as f32, 30. * ui_scale, WHITE);
+        }
+        if let Some(text) = console.as_ref() {
+            draw_console(text, &console_log, ui_scale);
+        }
+        if is_menu_open {
+            draw_settings_menu(&mut settings, is_resized);
         }
-        let scale = if gcube.size >= 14 { 1.96 } else { 1.99 };
-        draw_cube(vec3(0., 0., 0.), 
-            vec3(size_f * scale, size_f * scale, size_f * scale), 
-            None, 
-            desu_gray);
         next_frame().await
     }
 }
 
-fn face_to_dimensions(face: Face) -> Vec3 {
+// converts a part of the screen from points (as screen_width is in) to pixels
+// (as viewports are in), which differ on high DPI screens
+fn to_pixels((x, y, w, h): (f32, f32, f32, f32)) -> (i32, i32, i32, i32) {
+    let dpi = unsafe { get_internal_gl() }.quad_context.dpi_scale();
+    ((x * dpi) as i32, (y * dpi) as i32, (w * dpi) as i32, (h * dpi) as i32)
+}
+
+// converts a part of the screen from pixels back to points
+fn to_points((x, y, w, h): (i32, i32, i32, i32)) -> (f32, f32, f32, f32) {
+    let dpi = unsafe { get_internal_gl() }.quad_context.dpi_scale();
+    (x as f32 / dpi, y as f32 / dpi, w as f32 / dpi, h as f32 / dpi)
+}
+
+// returns the ray from the camera through a point on the screen
+fn screen_ray(camera: &Camera3D, point: Vec2) -> (Vec3, Vec3) {
+    // the camera's part of the screen, from the bottom left like OpenGL
+    let (x, y, w, h) = match camera.viewport {
+        Some(viewport) => to_points(viewport),
+        None => (0., 0., screen_width(), screen_height()),
+    };
+    let ndc = vec2((point.x - x) / w * 2. - 1., (screen_height() - point.y - y) / h * 2. - 1.);
+    let inverse = camera.matrix().inverse();
+    let near = inverse.project_point3(vec3(ndc.x, ndc.y, -1.));
+    let far = inverse.project_point3(vec3(ndc.x, ndc.y, 1.));
+    (near, (far - near).normalize())
+}
+
+// returns the planes bounding what the camera sees, facing inwards, as the
+// normal and distance from the origin of each
+fn frustum_planes(camera: &Camera3D) -> [Vec4; 6] {
+    let matrix = camera.matrix();
+    let (x, y, z, w) = (matrix.row(0), matrix.row(1), matrix.row(2), matrix.row(3));
+    [w + x, w - x, w + y, w - y, w + z, w - z].map(|plane| plane / plane.truncate().length())
+}
+
+// whether any of a sphere at center is in the frustum, to skip drawing what's off screen
+fn is_in_frustum(frustum: &[Vec4; 6], center: Vec3, radius: f32) -> bool {
+    frustum.iter().all(|plane| plane.truncate().dot(center) + plane.w >= -radius)
+}
+
+// returns where the ray through a point on the screen meets the plane of the sticker's face
+fn face_point(camera: &Camera3D, point: Vec2, gcube: &GCube, sticker: Sticker) -> Vec3 {
+    let (origin, direction) = screen_ray(camera, point);
+    let normal = face_to_normal(gcube.get_curr_face(sticker));
+    let t = (point3_to_vec3(sticker.current) - origin).dot(normal) / direction.dot(normal);
+    origin + direction * t
+}
+
+// darkens color by how far a surface facing normal faces away from a light
+// above the cube, to the front right, so faces differ in shade as well as hue
+fn shade(color: Color, normal: Vec3) -> Color {
+    let light = vec3(0.4, 0.8, 0.45).normalize();
+    let level = 0.55 + 0.45 * normal.dot(light).max(0.);
+    Color::new(color.r * level, color.g * level, color.b * level, color.a)
+}
+
+// mixes color with white, by amount from 0 to 1
+fn lighten(color: Color, amount: f32) -> Color {
+    let mix = |c: f32| c + (1. - c) * amount;
+    Color::new(mix(color.r), mix(color.g), mix(color.b), color.a)
+}
+
+fn face_to_normal(face: Face) -> Vec3 {
+    match face {
+        Face::U => Vec3::Y,
+        Face::D => -Vec3::Y,
+        Face::R => Vec3::X,
+        Face::L => -Vec3::X,
+        Face::F => Vec3::Z,
+        Face::B => -Vec3::Z,
+        _ => Vec3::ZERO,
+    }
+}
+
+// draws a combo box for choosing any variant of an enum, returns the chosen one
+fn enum_combo_box<T>(ui: &mut Ui, id: u64, label: &str, selected: T) -> T
+where
+    T: IntoEnumIterator + PartialEq + ToString + Copy,
+{
+    let variants: Vec<T> = T::iter().collect();
+    let names: Vec<String> = variants.iter().map(|v| v.to_string()).collect();
+    let names: Vec<&str> = names.iter().map(|s| s.as_str()).collect();
+    let mut index = variants.iter().position(|&v| v == selected).unwrap_or(0);
+    ui.combo_box(id, label, &names, &mut index);
+    variants[index]
+}
+
+// draws a window for changing the settings, which are updated in place
+// draws the settings window, centered again when the window's been resized
+fn draw_settings_menu(settings: &mut Settings, is_resized: bool) {
+    let id = hash!();
+    let size = vec2(320., 920.);
+    // keeping the title in view on short screens
+    let position = vec2((screen_width() - size.x) / 2., ((screen_height() - size.y) / 2.).max(0.));
+    if is_resized {
+        root_ui().move_window(id, position);
+    }
+    root_ui().window(id, position, size, |ui| {
+        ui.label(None, "Settings (F10 to close)");
+        let mut cube_size = settings.cube_size as f32;
+        ui.slider(hash!(), "Cube size", 1.0..20.0, &mut cube_size);
+        settings.cube_size = cube_size.round() as usize;
+        ui.checkbox(hash!(), "Keep state on resize", &mut settings.resize_keeps_state);
+        let mut is_cuboid = settings.cuboid.is_some();
+        ui.checkbox(hash!(), "Cuboid", &mut is_cuboid);
+        settings.cuboid = match settings.cuboid {
+            Some(dims) if is_cuboid => {
+                let mut dims = dims.map(|d| d as f32);
+                ui.slider(hash!(), "Width", 1.0..10.0, &mut dims[0]);
+                ui.slider(hash!(), "Height", 1.0..10.0, &mut dims[1]);
+                ui.slider(hash!(), "Depth", 1.0..10.0, &mut dims[2]);
+                Some(dims.map(|d| d.round() as usize))
+            }
+            // starting from the cube shown
+            None if is_cuboid => Some(settings.dims()),
+            _ => None,
+        };
+
+        settings.color_scheme = enum_combo_box(ui, hash!(), "Colours", settings.color_scheme);
+        settings.turn_style = enum_combo_box(ui, hash!(), "Turn style", settings.turn_style);
+        ui.slider(hash!(), "Turn time (s)", 0.0..0.5, &mut settings.turn_duration);
+        settings.easing = enum_combo_box(ui, hash!(), "Easing", settings.easing);
+        settings.keymap = enum_combo_box(ui, hash!(), "Keymap", settings.keymap);
+        settings.solve_preference = enum_combo_box(ui, hash!(), "Solutions", settings.solve_preference);
+        ui.checkbox(hash!(), "Inspection report", &mut settings.inspection_report);
+        ui.checkbox(hash!(), "Target ghost", &mut settings.ghost_target);
+        // rejecting easy 3x3 scrambles
+        let filter = &mut settings.scramble_filter;
+        ui.checkbox(hash!(), "No solved pairs", &mut filter.no_solved_pairs);
+        let mut min_cross_moves = filter.min_cross_moves as f32;
+        ui.slider(hash!(), "Min cross moves", 0.0..6.0, &mut min_cross_moves);
+        filter.min_cross_moves = min_cross_moves.round() as usize;
+        let mut min_solution_length = filter.min_solution_length as f32;
+        ui.slider(hash!(), "Min solution length", 0.0..18.0, &mut min_solution_length);
+        filter.min_solution_length = min_solution_length.round() as usize;
+        if settings.follow_address.is_some() {
+            settings.follow_style = enum_combo_box(ui, hash!(), "Follow", settings.follow_style);
+        }
+        ui.slider(hash!(), "Camera speed", 0.2..3.0, &mut settings.camera_speed);
+        settings.projection = enum_combo_box(ui, hash!(), "Projection", settings.projection);
+        if settings.projection == CameraProjection::Orthographic {
+            ui.slider(hash!(), "Zoom", 0.5..3.0, &mut settings.orthographic_zoom);
+        } else {
+            ui.slider(hash!(), "Field of view", 20.0..100.0, &mut settings.fov);
+        }
+        settings.piece_style = enum_combo_box(ui, hash!(), "Pieces", settings.piece_style);
+        settings.shape_mod = enum_combo_box(ui, hash!(), "Shape mod (3x3)", settings.shape_mod);
+        ui.slider(hash!(), "Sticker size", 0.5..1.0, &mut settings.sticker_size);
+        ui.slider(hash!(), "Rounding", 0.0..1.0, &mut settings.sticker_rounding);
+        settings.render_mode = enum_combo_box(ui, hash!(), "Render", settings.render_mode);
+        ui.checkbox(hash!(), "Mirrors", &mut settings.show_mirrors);
+        if settings.show_mirrors {
+            settings.mirror_style = enum_combo_box(ui, hash!(), "Mirror style", settings.mirror_style);
+            for face in ORDERED_FACES {
+                let was_mirrored = settings.mirror_faces.contains(&face);
+                let mut is_mirrored = was_mirrored;
+                ui.checkbox(hash!("mirror", face as usize), &format!("Mirror {}", face), &mut is_mirrored);
+                match (was_mirrored, is_mirrored) {
+                    (false, true) => settings.mirror_faces.push(face),
+                    (true, false) => settings.mirror_faces.retain(|&f| f != face),
+                    _ => {}
+                }
+            }
+            if settings.mirror_style == MirrorStyle::Floating {
+                ui.slider(hash!(), "Mirror distance", 1.5..4.0, &mut settings.mirror_distance);
+                ui.slider(hash!(), "Mirror opacity", 0.1..1.0, &mut settings.mirror_opacity);
+            }
+        }
+        ui.checkbox(hash!(), "Net overlay", &mut settings.show_net);
+        ui.checkbox(hash!(), "Shading", &mut settings.shading);
+        ui.checkbox(hash!(), "Flash last move", &mut settings.flash_last_move);
+        ui.checkbox(hash!(), "Exploded view", &mut settings.exploded_view);
+        if settings.exploded_view {
+            ui.slider(hash!(), "Gap", 0.0..2.0, &mut settings.explode_gap);
+        }
+        let mut merge_size = settings.merge_size as f32;
+        ui.slider(hash!(), "Merge from size", 2.0..100.0, &mut merge_size);
+        settings.merge_size = merge_size.round() as usize;
+        settings.view_layout = enum_combo_box(ui, hash!(), "Views", settings.view_layout);
+        ui.checkbox(hash!(), "HUD", &mut settings.show_hud);
+        ui.slider(hash!(), "UI scale", 0.5..2.0, &mut settings.ui_scale);
+        ui.checkbox(hash!(), "FPS", &mut settings.show_fps);
+        ui.checkbox(hash!(), "Showcase when idle", &mut settings.showcase);
+        ui.slider(hash!(), "Idle time (s)", 5.0..300.0, &mut settings.showcase_delay);
+        settings.sound_theme = enum_combo_box(ui, hash!(), "Sounds", settings.sound_theme);
+        ui.slider(hash!(), "Volume", 0.0..1.0, &mut settings.volume);
+        ui.checkbox(hash!(), "Read out moves", &mut settings.speech);
+        ui.checkbox(hash!(), "Metronome", &mut settings.metronome);
+        let mut bpm = settings.bpm;
+        ui.slider(hash!(), "BPM", 40.0..240.0, &mut bpm);
+        settings.bpm = bpm.round();
+    });
+}
+
+// saves the session, and its results as CSV for spreadsheets
+fn save_results(session: &Session) {
+    session.save(SESSION_PATH).ok();
+    std::fs::write(RESULTS_PATH, results_csv(&session.results)).ok();
+}
+
+// the cubies of a 3x3 for the solver, or None if the cube's another size or its
+// stickers aren't a real 3x3's
+fn solver_cube(gcube: &GCube) -> Option<CubieCube> {
+    if gcube.dims != [3; 3] {
+        return None;
+    }
+    CubieCube::from_facelets(&gcube.to_facelet_model()).ok()
+}
+
+// synthesizes and loads every sound effect of theme, or none if sound is off
+async fn load_sounds(theme: SoundTheme) -> HashMap<SoundEffect, Sound> {
+    let mut sounds = HashMap::new();
+    if theme == SoundTheme::Off {
+        return sounds;
+    }
+    for effect in SoundEffect::all() {
+        if let Ok(sound) = load_sound_from_bytes(&effect.to_wav(theme)).await {
+            sounds.insert(effect, sound);
+        }
+    }
+    sounds
+}
+
+// the sound effects of the theme in the settings, each movement's played as
+// it lands on the cube
+struct Sounds {
+    effects: HashMap<SoundEffect, Sound>,
+    volume: f32,
+}
+
+impl Sounds {
+    fn play(&self, effect: SoundEffect) {
+        if let Some(&sound) = self.effects.get(&effect) {
+            play_sound(sound, PlaySoundParams { looped: false, volume: self.volume });
+        }
+    }
+}
+
+// a seek through the history is silent, rather than a click for every movement
+impl CubeObserver for Sounds {
+    fn on_move(&mut self, movement: Movement, _gcube: &GCube) {
+        self.play(SoundEffect::of_movement(movement));
+    }
+}
+
+// logs every movement and scramble applied to the cube
+struct MoveLogger {
+    log: MoveLog,
+    // when the solve being timed started, by get_time()
+    solve_start: Option<f64>,
+}
+
+impl MoveLogger {
+    fn log_move(&mut self, movement: Movement, solve_time: Option<f64>) {
+        self.log.log(LogEvent::Move { movement, solve_time }, miniquad::date::now()).ok();
+    }
+}
+
+impl CubeObserver for MoveLogger {
+    fn on_move(&mut self, movement: Movement, _gcube: &GCube) {
+        self.log_move(movement, self.solve_start.map(|start| get_time() - start));
+    }
+
+    fn on_scramble(&mut self, scramble: &Alg, gcube: &GCube) {
+        let event = LogEvent::Scramble { size: gcube.size, scramble: scramble.clone() };
+        self.log.log(event, miniquad::date::now()).ok();
+    }
+
+    // seeks aren't turned, so aren't timed, but are logged to keep the log's
+    // movements adding up to the cube's state
+    fn on_seek(&mut self, movements: &[Movement], _gcube: &GCube) {
+        for &movement in movements {
+            self.log_move(movement, None);
+        }
+    }
+}
+
+// draws the recorded movements down the right side of the screen, returning
+// the history position of the row that was clicked, if any
+fn draw_history_panel(history: &History, scale: f32) -> (Option<usize>, Option<usize>) {
+    let row_height = 22. * scale;
+    let x = screen_width() - 150. * scale;
+    draw_rectangle(x - 10. * scale, 0., 160. * scale, screen_height(), Color::new(0., 0., 0., 0.3));
+    // row i shows the position after i movements, keeping the current position in view
+    let n_rows = history.len() + 1;
+    let max_rows = ((screen_height() - 10.) / row_height) as usize;
+    let first_row = history
+        .position()
+        .saturating_sub(max_rows / 2)
+        .min(n_rows.saturating_sub(max_rows));
+    let (mut clicked, mut hovered) = (None, None);
+    let (mouse_x, mouse_y) = mouse_position();
+    for (i, row) in (first_row..n_rows.min(first_row + max_rows)).enumerate() {
+        let y = (i + 1) as f32 * row_height;
+        let text = match row {
+            0 => "start".to_string(),
+            _ => format!("{}. {}", row, history.movements()[row - 1]),
+        };
+        let color = match row {
+            r if r == history.position() => YELLOW,
+            r if r > history.position() => GRAY,
+            _ => WHITE,
+        };
+        draw_text(&text, x, y, row_height, color);
+        let is_hovered = mouse_x >= x - 10. * scale && mouse_y > y - row_height && mouse_y <= y;
+        if is_hovered {
+            hovered = Some(row);
+            if is_mouse_button_pressed(MouseButton::Left) {
+                clicked = Some(row);
+            }
+        }
+    }
+    (clicked, hovered)
+}
+
+// returns the texture of a thumbnail of gcube's state, cached as textures
+// aren't freed until they're deleted, or None for a cuboid
+fn thumbnail_texture(cache: &mut HashMap<(Vec<Face>, ColorScheme), Texture2D>, gcube: &GCube, scheme: ColorScheme) -> Option<Texture2D> {
+    if cache.len() > 64 {
+        for (_, texture) in cache.drain() {
+            texture.delete();
+        }
+    }
+    let key = (gcube.facelets(), scheme);
+    if let Some(&texture) = cache.get(&key) {
+        return Some(texture);
+    }
+    let thumbnail = render_thumbnail(gcube, scheme, THUMBNAIL_SIZE)?;
+    let texture = Texture2D::from_rgba8(thumbnail.width as u16, thumbnail.height as u16, &thumbnail.pixels);
+    texture.set_filter(FilterMode::Nearest);
+    cache.insert(key, texture);
+    Some(texture)
+}
+
+fn draw_texture_scaled(texture: Texture2D, x: f32, y: f32, width: f32, height: f32) {
+    let params = DrawTextureParams { dest_size: Some(vec2(width, height)), ..Default::default() };
+    draw_texture_ex(texture, x, y, WHITE, params);
+}
+
+// a bar for each move, split into its clockwise, double and anticlockwise
+// turns, at the bottom left above the scramble
+fn draw_turn_chart(stats: &TurnStats, scale: f32) {
+    let row_height = 20. * scale;
+    let bar_width = 200. * scale;
+    let height = (stats.counts.len() + 2) as f32 * row_height;
+    let (x, y) = (10. * scale, screen_height() - 90. * scale - height);
+    draw_rectangle(x - 5. * scale, y - row_height, bar_width + 75. * scale, height + 10. * scale, Color::new(0., 0., 0., 0.5));
+    let title = format!("{} moves in {} solves, {} regrips?", stats.total(), stats.solves, stats.regrip_suspects.len());
+    draw_text(&title, x, y, row_height, WHITE);
+    let most = stats.counts.iter().map(|(_, turns)| turns.iter().sum::<usize>()).max().unwrap_or(1).max(1);
+    let colors = [SKYBLUE, YELLOW, PINK];
+    for (i, (m, turns)) in stats.counts.iter().enumerate() {
+        let row_y = y + (i + 1) as f32 * row_height;
+        draw_text(&m.to_string(), x, row_y, row_height, WHITE);
+        let mut bar_x = x + 30. * scale;
+        for (&count, &color) in turns.iter().zip(colors.iter()) {
+            let w = count as f32 / most as f32 * bar_width;
+            draw_rectangle(bar_x, row_y - row_height * 0.7, w, row_height * 0.6, color);
+            bar_x += w;
+        }
+        draw_text(&turns.iter().sum::<usize>().to_string(), bar_x + 5. * scale, row_y, row_height, GRAY);
+    }
+    let legend_y = y + (stats.counts.len() + 1) as f32 * row_height;
+    for (i, (label, &color)) in ["clockwise", "double", "anticlockwise"].iter().zip(colors.iter()).enumerate() {
+        draw_text(label, x + i as f32 * 85. * scale, legend_y, row_height, color);
+    }
+}
+
+// clears the part of the screen a second view is drawn to, as picture in
+// picture would otherwise be hidden behind the main view's cube
+fn clear_view(view: &Camera3D, color: Color) {
+    if let Some((x, y, w, h)) = view.viewport.map(to_points) {
+        set_default_camera();
+        let top = screen_height() - (y + h);
+        draw_rectangle(x, top, w, h, color);
+        draw_rectangle_lines(x, top, w, h, 2., GRAY);
+        // depth can only be cleared for the whole screen, the main view's
+        // already drawn
+        let mut gl = unsafe { get_internal_gl() };
+        gl.flush();
+        let clear_depth = miniquad::PassAction::Clear { color: None, depth: Some(1.), stencil: None };
+        gl.quad_context.begin_default_pass(clear_depth);
+        gl.quad_context.end_render_pass();
+    }
+}
+
+// draws a cube that isn't turning, moved by offset, e.g. a translucent
+// teacher's cube to follow, with only the stickers of mask's pieces
+fn draw_still_cube(gcube: &GCube, offset: Vec3, camera_position: Vec3, scheme: ColorScheme, shape: StickerShape, alpha: f32, mask: PieceMask) {
+    let mut batch = MeshBatch::default();
+    for sticker in gcube.stickers().filter(|s| mask.contains(*s)) {
+        let pos = point3_to_vec3(sticker.current) + offset;
+        let face = gcube.get_curr_face(sticker);
+        // only the stickers facing the camera, as the others would show through
+        if (camera_position - pos).dot(face_to_normal(face)) > 0. {
+            let mut color = face_to_color(gcube.get_initial_face(sticker), scheme);
+            color.a = alpha;
+            draw_sticker(&mut batch, &sticker_outline(pos, face, shape), face, None, color, shape);
+        }
+    }
+    batch.draw();
+}
+
+// draws every leg's cube of a relay but the one being solved (of size, at
+// the origin), in a row either side of it, the finished legs to the left
+fn draw_relay(relay: &Relay, size: usize, camera_position: Vec3, scheme: ColorScheme, shape: StickerShape, body: Color) {
+    let cubes = relay.cubes();
+    // the last leg stays at the origin once the relay is finished
+    let current = relay.current().min(cubes.len() - 1);
+    let gap = |a: usize, b: usize| (a + b) as f32 * 1.3;
+    let draw_leg = |gcube: &GCube, x: f32| {
+        let n = gcube.size as f32;
+        draw_cube(vec3(x, 0., 0.), vec3(n * 1.99, n * 1.99, n * 1.99), None, body);
+        draw_still_cube(gcube, vec3(x, 0., 0.), camera_position, scheme, shape, 1., PieceMask::All);
+    };
+    let (mut x, mut prev) = (0., size);
+    for gcube in cubes[current + 1..].iter() {
+        x += gap(prev, gcube.size);
+        prev = gcube.size;
+        draw_leg(gcube, x);
+    }
+    let (mut x, mut prev) = (0., size);
+    for gcube in cubes[..current].iter().rev() {
+        x -= gap(prev, gcube.size);
+        prev = gcube.size;
+        draw_leg(gcube, x);
+    }
+}
+
+// draws every opponent's cube as a net along the top of the screen, leftwards from right
+fn draw_opponents(race: &Race, scheme: ColorScheme, right: f32, scale: f32) {
+    let face_size = 36. * scale;
+    let mut x = right - 4. * face_size - 10. * scale;
+    for (player, opponent) in race.opponents() {
+        draw_text(player, x, 20. * scale, 20. * scale, WHITE);
+        draw_net(&opponent.facelets, opponent.size, x, 30. * scale, face_size, scheme);
+        x -= 4. * face_size + 20. * scale;
+    }
+}
+
+// draws a preview's cube as a net in a pane at the middle of the right edge
+fn draw_preview(preview: &Preview, scheme: ColorScheme, right: f32, scale: f32) {
+    let face_size = 30. * scale;
+    let (width, height) = (4. * face_size + 20. * scale, 3. * face_size + 40. * scale);
+    let (x, y) = (right - width - 10. * scale, (screen_height() - height) / 2.);
+    draw_rectangle(x, y, width, height, Color::new(0., 0., 0., 0.6));
+    draw_text("Preview", x + 10. * scale, y + 20. * scale, 20. * scale, WHITE);
+    let gcube = &preview.gcube;
+    draw_net(&gcube.facelets(), gcube.size, x + 10. * scale, y + 30. * scale, face_size, scheme);
+}
+
+// returns where the net view draws the net, as big as fits left of right
+// with room for text around it
+fn net_view_layout(right: f32, scale: f32) -> NetLayout {
+    let margin = 60. * scale;
+    NetLayout::fit(margin, margin, right - 2. * margin, screen_height() - 2. * margin)
+}
+
+// draws an NxN cube as a net in a pane at the top right, below top
+fn draw_net_overlay(gcube: &GCube, scheme: ColorScheme, right: f32, top: f32, scale: f32) {
+    let face_size = 30. * scale;
+    let (width, height) = (4. * face_size + 20. * scale, 3. * face_size + 20. * scale);
+    let (x, y) = (right - width - 10. * scale, top + 10. * scale);
+    draw_rectangle(x, y, width, height, Color::new(0., 0., 0., 0.6));
+    draw_net(&gcube.facelets(), gcube.size, x + 10. * scale, y + 10. * scale, face_size, scheme);
+}
+
+// draws faces of an NxN cube flat in a row, in a pane at the bottom of the
+// right edge, each turned as in a net
+fn draw_mirror_panel(gcube: &GCube, faces: &[Face], scheme: ColorScheme, right: f32, scale: f32) {
+    if faces.is_empty() {
+        return;
+    }
+    let (n, facelets) = (gcube.size, gcube.facelets());
+    let (face_size, gap) = (50. * scale, 10. * scale);
+    let sticker_size = face_size / n as f32;
+    let (width, height) = (faces.len() as f32 * (face_size + gap) + gap, face_size + 40. * scale);
+    let (x, y) = (right - width - 10. * scale, screen_height() - height - 10. * scale);
+    draw_rectangle(x, y, width, height, Color::new(0., 0., 0., 0.6));
+    for (i, &face) in faces.iter().enumerate() {
+        let face_x = x + gap + i as f32 * (face_size + gap);
+        draw_text(&face.to_string(), face_x, y + 20. * scale, 20. * scale, WHITE);
+        let start = ORDERED_FACES.iter().position(|&f| f == face).unwrap() * n * n;
+        for (j, &facelet) in facelets[start..start + n * n].iter().enumerate() {
+            draw_rectangle(
+                face_x + (j % n) as f32 * sticker_size,
+                y + 30. * scale + (j / n) as f32 * sticker_size,
+                sticker_size - 1.,
+                sticker_size - 1.,
+                face_to_color(facelet, scheme),
+            );
+        }
+    }
+}
+
+// draws the console dropped down from the top, its last lines above the
+// command being typed
+fn draw_console(text: &str, log: &[String], scale: f32) {
+    const LINES: usize = 8;
+    let line_height = 24. * scale;
+    let height = (LINES + 1) as f32 * line_height + 10. * scale;
+    draw_rectangle(0., 0., screen_width(), height, Color::new(0., 0., 0., 0.8));
+    let shown = &log[log.len().saturating_sub(LINES)..];
+    for (i, line) in shown.iter().enumerate() {
+        draw_text(line, 10. * scale, (i + 1) as f32 * line_height, 20. * scale, LIGHTGRAY);
+    }
+    let prompt = format!("> {}_", text);
+    draw_text(&prompt, 10. * scale, (LINES + 1) as f32 * line_height, 20. * scale, WHITE);
+}
+
+// draws the facelets of an NxN cube as a net with its top left at x, y
+fn draw_net(facelets: &[Face], n: usize, x: f32, y: f32, face_size: f32, scheme: ColorScheme) {
+    let sticker_size = face_size / n as f32;
+    for (i, &face) in facelets.iter().enumerate() {
+        let (face_x, face_y) = NET_OFFSETS[i / (n * n)];
+        let (row, col) = ((i % (n * n)) / n, i % n);
+        draw_rectangle(
+            x + face_x * face_size + col as f32 * sticker_size,
+            y + face_y * face_size + row as f32 * sticker_size,
+            sticker_size - 1.,
+            sticker_size - 1.,
+            face_to_color(face, scheme),
+        );
+    }
+}
+
+// draws text left aligned at x, wrapping words onto extra lines above y
+// so that the last line stays at y
+fn draw_wrapped_text(text: &str, x: f32, y: f32, font_size: f32) {
+    let max_width = screen_width() - 2. * x;
+    let mut lines: Vec<String> = vec![String::new()];
+    for word in text.split_whitespace() {
+        let line = lines.last_mut().unwrap();
+        let candidate = match line.is_empty() {
+            true => word.to_string(),
+            false => format!("{} {}", line, word),
+        };
+        let width = measure_text(&candidate, None, font_size as u16, 1.).width;
+        if !line.is_empty() && width > max_width {
+            lines.push(word.to_string());
+        } else {
+            *line = candidate;
+        }
+    }
+    let n_lines = lines.len();
+    for (i, line) in lines.iter().enumerate() {
+        draw_text(line, x, y - (n_lines - 1 - i) as f32 * font_size, font_size, WHITE);
+    }
+}
+
+// draws a sticker with outline (from sticker_outline) on the face, optionally rotated mid-turn
+fn draw_sticker(batch: &mut MeshBatch, outline: &[Vec3], face: Face, rotation: Option<Quat>, color: Color, shape: StickerShape) {
+    let rotation = rotation.unwrap_or(Quat::IDENTITY);
+    let color = if shape.is_shaded { shade(color, rotation.mul_vec3(face_to_normal(face))) } else { color };
+    batch.push_polygon(outline.iter().map(|&point| rotation.mul_vec3(point)), color);
+}
+
+// returns the points around a sticker centered at pos on the face, going
+// around each of its rounded corners in turn
+fn sticker_outline(pos: Vec3, face: Face, shape: StickerShape) -> Vec<Vec3> {
+    let (e1, e2) = face_axes(face);
+    let (e1, e2) = (e1 * shape.len, e2 * shape.len);
+    // square corners are a single point each
+    let segments = if shape.rounding <= 0. { 0 } else { 4 };
+    let (half1, half2, radius) = (e1 / 2., e2 / 2., shape.rounding.clamp(0., 1.));
+    let mut outline = vec![];
+    for (corner, (s1, s2)) in [(1., 1.), (-1., 1.), (-1., -1.), (1., -1.)].iter().enumerate() {
+        let corner_center = pos + (half1 * *s1 + half2 * *s2) * (1. - radius);
+        for i in 0..=segments {
+            let angle = (corner as f32 + i as f32 / segments.max(1) as f32) * std::f32::consts::FRAC_PI_2;
+            outline.push(corner_center + (half1 * angle.cos() + half2 * angle.sin()) * radius);
+        }
+    }
+    outline
+}
+
+// returns the corners of a block of merged stickers on the face, from the
+// center of its first sticker to the center of its last
+fn block_outline(from: Vec3, to: Vec3, face: Face, len: f32) -> [Vec3; 4] {
+    let (e1, e2) = face_axes(face);
+    let center = (from + to) / 2.;
+    // half the distance between the centers, and half a sticker
+    let half1 = e1 * ((to - from).dot(e1).abs() + len) / 2.;
+    let half2 = e2 * ((to - from).dot(e2).abs() + len) / 2.;
+    [(1., 1.), (-1., 1.), (-1., -1.), (1., -1.)].map(|(s1, s2)| center + half1 * s1 + half2 * s2)
+}
+
+// returns unit vectors along the edges of the face
+fn face_axes(face: Face) -> (Vec3, Vec3) {
     match face {
-        Face::U | Face::D => vec3(F_LEN, F_DEPTH, F_LEN),
-        Face::L | Face::R => vec3(F_DEPTH, F_LEN, F_LEN),
-        Face::F | Face::B => vec3(F_LEN, F_LEN, F_DEPTH),
-        _ => vec3(0.0, 0.0, 0.0),
+        Face::U | Face::D => (Vec3::X, Vec3::Z),
+        Face::L | Face::R => (Vec3::Y, Vec3::Z),
+        _ => (Vec3::X, Vec3::Y),
     }
 }
 
+// draws the body of a piece centered at pos, optionally rotated mid-turn
+fn draw_piece(pos: Vec3, rotation: Option<Quat>, color: Color) {
+    let rotation = rotation.unwrap_or(Quat::IDENTITY);
+    let [e1, e2, e3] = [Vec3::X, Vec3::Y, Vec3::Z].map(|e| rotation.mul_vec3(e * P_LEN));
+    draw_affine_parallelepiped(rotation.mul_vec3(pos) - (e1 + e2 + e3) / 2., e1, e2, e3, None, color);
+}
+
+// how far an exploded view moves whatever is at pos on a cube of dims_f
+// layers, each layer gap pieces further from the one inside it
+fn explode_offset(pos: Vec3, dims_f: Vec3, gap: f32) -> Vec3 {
+    // the center of the piece at pos, in pieces from the center of the cube
+    let inner = dims_f - Vec3::ONE;
+    pos.clamp(-inner, inner) * gap
+}
+
+fn dims_to_vec3(dims: [usize; 3]) -> Vec3 {
+    vec3(dims[0] as f32, dims[1] as f32, dims[2] as f32)
+}
+
+// rotation of the stickers affected by gmove, after progress (0..1) of its turn
+fn turn_rotation(gmove: GMove, progress: f32) -> Quat {
+    // clockwise turns are negative rotations around the axis
+    let angle = -(gmove.quarter_turns() as f32) * std::f32::consts::FRAC_PI_2 * progress;
+    Quat::from_axis_angle(axis_to_vec3(gmove.axis()), angle)
+}
+
+fn axis_to_vec3(axis: Axis) -> Vec3 {
+    match axis {
+        Axis::X => Vec3::X,
+        Axis::Y => Vec3::Y,
+        Axis::Z => Vec3::Z,
+    }
+}
+
+// draws an arc around the layer turned by gmove, pointing in its turning direction
+fn draw_hint_arrow(gcube: &GCube, gmove: GMove, camera_pos: Vec3) {
+    let axis = axis_to_vec3(gmove.axis());
+    let affected: Vec<Vec3> = gcube
+        .stickers()
+        .filter(|s| gmove.affects(*s))
+        .map(|s| point3_to_vec3(s.current))
+        .collect();
+    if affected.is_empty() {
+        return;
+    }
+    // the arc sits in the plane through the middle of the turning layer,
+    // just outside the cube, on the camera's side
+    let layer = affected.iter().fold(Vec3::ZERO, |acc, p| acc + *p) / affected.len() as f32;
+    let center = axis * layer.dot(axis);
+    let towards_camera = (camera_pos - axis * camera_pos.dot(axis)).normalize_or_zero();
+    let radius = gcube.size as f32 * 1.3;
+    let start = turn_rotation(gmove, -0.5).mul_vec3(towards_camera * radius);
+    let n_segments = 16;
+    let points: Vec<Vec3> = (0..=n_segments)
+        .map(|i| center + turn_rotation(gmove, i as f32 / n_segments as f32).mul_vec3(start))
+        .collect();
+    for segment in points.windows(2) {
+        draw_line_3d(segment[0], segment[1], MAGENTA);
+    }
+    // arrowhead at the end of the arc
+    let tip = points[n_segments];
+    let back = (points[n_segments - 2] - tip).normalize_or_zero() * 0.8;
+    let side = axis * 0.5;
+    draw_line_3d(tip, tip + back + side, MAGENTA);
+    draw_line_3d(tip, tip + back - side, MAGENTA);
+}
+
+// draws an arrow between two facelets, given as their positions and normals,
+// curving out from the cube so it isn't hidden by the stickers
+fn draw_facelet_arrow((from, from_normal): (Vec3, Vec3), (to, to_normal): (Vec3, Vec3)) {
+    let start = from + from_normal * 0.3;
+    let end = to + to_normal * 0.3;
+    let bulge = (from_normal + to_normal).normalize_or_zero() * start.distance(end) * 0.3;
+    let control = (start + end) / 2. + bulge;
+    let n_segments = 12;
+    let points: Vec<Vec3> = (0..=n_segments)
+        .map(|i| {
+            // a quadratic bezier curve
+            let t = i as f32 / n_segments as f32;
+            start * (1. - t) * (1. - t) + control * 2. * t * (1. - t) + end * t * t
+        })
+        .collect();
+    for segment in points.windows(2) {
+        draw_line_3d(segment[0], segment[1], MAGENTA);
+    }
+    let tip = points[n_segments];
+    let back = (points[n_segments - 2] - tip).normalize_or_zero() * 0.8;
+    let side = back.cross(to_normal).normalize_or_zero() * 0.5;
+    draw_line_3d(tip, tip + back + side, MAGENTA);
+    draw_line_3d(tip, tip + back - side, MAGENTA);
+}
+
 fn point3_to_vec3(p: Point3) -> Vec3 {
     vec3(p.x as f32, p.y as f32, p.z as f32)
 }
 
-fn face_to_color(face: Face) -> Color {
-    match face {
-        Face::U => WHITE,
-        Face::R => RED,
-        Face::L => ORANGE,
-        Face::B => BLUE,
-        Face::D => YELLOW,
-        Face::F => GREEN,
-        _ => BLACK,
-    }
-}
-
-fn key_to_movement(key: KeyCode) -> Option<Movement> {
-    let movement_str = match key {
-        KeyCode::I => "R",
-        KeyCode::K => "R'",
-        KeyCode::W => "B",
-        KeyCode::O => "B'",
-        KeyCode::S => "D",
-        KeyCode::L => "D'",
-        KeyCode::D => "L",
-        KeyCode::E => "L'",
-        KeyCode::J => "U",
-        KeyCode::F => "U'",
-        KeyCode::H => "F",
-        KeyCode::G => "F'",
-        KeyCode::Semicolon => "y",
-        KeyCode::A => "y'",
-        KeyCode::U => "r",
-        KeyCode::R => "l'",
-        KeyCode::M => "r'",
-        KeyCode::V => "l",
-        KeyCode::T => "x",
-        KeyCode::Y => "x",
-        KeyCode::N => "x'",
-        KeyCode::B => "x'",
-        KeyCode::Period => "M'",
-        KeyCode::X => "M",
-        KeyCode::Key5 => "M",
-        KeyCode::Key6 => "M",
-        KeyCode::P => "z",
-        KeyCode::Q => "z'",
-        KeyCode::Z => "d",
-        KeyCode::C => "u'",
-        KeyCode::Comma => "u",
-        KeyCode::Slash => "d'",
-        _ => "",
-    };
-    Movement::from_str(movement_str).ok()
+fn face_to_color(face: Face, scheme: ColorScheme) -> Color {
+    let [r, g, b] = scheme.color(face);
+    Color::from_rgba(r, g, b, 255)
+}
+
+// the letter or number of a key, which macros are bound to with ctrl
+fn key_to_macro_key(key: KeyCode) -> Option<char> {
+    match key {
+        KeyCode::A => Some('a'),
+        KeyCode::B => Some('b'),
+        KeyCode::C => Some('c'),
+        KeyCode::D => Some('d'),
+        KeyCode::E => Some('e'),
+        KeyCode::F => Some('f'),
+        KeyCode::G => Some('g'),
+        KeyCode::H => Some('h'),
+        KeyCode::I => Some('i'),
+        KeyCode::J => Some('j'),
+        KeyCode::K => Some('k'),
+        KeyCode::L => Some('l'),
+        KeyCode::M => Some('m'),
+        KeyCode::N => Some('n'),
+        KeyCode::O => Some('o'),
+        KeyCode::P => Some('p'),
+        KeyCode::Q => Some('q'),
+        KeyCode::R => Some('r'),
+        KeyCode::S => Some('s'),
+        KeyCode::T => Some('t'),
+        KeyCode::U => Some('u'),
+        KeyCode::V => Some('v'),
+        KeyCode::W => Some('w'),
+        KeyCode::X => Some('x'),
+        KeyCode::Y => Some('y'),
+        KeyCode::Z => Some('z'),
+        KeyCode::Key0 => Some('0'),
+        KeyCode::Key1 => Some('1'),
+        KeyCode::Key2 => Some('2'),
+        KeyCode::Key3 => Some('3'),
+        KeyCode::Key4 => Some('4'),
+        KeyCode::Key5 => Some('5'),
+        KeyCode::Key6 => Some('6'),
+        KeyCode::Key7 => Some('7'),
+        KeyCode::Key8 => Some('8'),
+        KeyCode::Key9 => Some('9'),
+        _ => None,
+    }
 }
+
+fn key_to_bookmark_slot(key: KeyCode) -> Option<&'static str> {
+    match key {
+        KeyCode::F1 => Some("1"),
+        KeyCode::F2 => Some("2"),
+        KeyCode::F3 => Some("3"),
+        KeyCode::F4 => Some("4"),
+        _ => None,
+    }
+}
+
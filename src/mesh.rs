@@ -0,0 +1,110 @@
+use std::fmt::Write;
+
+use crate::{ColorScheme, GCube};
+
+/// A cube's stickers as triangles, in plain data for other renderers and
+/// engines, e.g. bevy, or Blender through [OBJ](CubeMesh::to_obj). Each
+/// sticker is a square of 4 vertices, facing out of the cube.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CubeMesh {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    /// RGB colour of each vertex
+    pub colors: Vec<[u8; 3]>,
+    /// every 3 indices are a triangle, counter-clockwise seen from outside
+    pub indices: Vec<u32>,
+}
+
+impl CubeMesh {
+    /// Returns the mesh of the cube's stickers, coloured by scheme, each
+    /// covering sticker_size (up to 1) of its piece's face. Positions are in
+    /// the cube's coordinates, where pieces are 2 units wide and the cube is
+    /// centered on the origin.
+    pub fn new(gcube: &GCube, scheme: ColorScheme, sticker_size: f32) -> Self {
+        let mut mesh = CubeMesh::default();
+        for sticker in gcube.stickers() {
+            let p = sticker.current;
+            let center = [p.x as f32, p.y as f32, p.z as f32];
+            // the axis the sticker faces along, and the other two, in order
+            // so that the first crossed with the second points out of the cube
+            let i = (0..3)
+                .find(|&i| [p.x, p.y, p.z][i].abs() == gcube.dims[i] as i16)
+                .unwrap();
+            let sign = center[i].signum();
+            let (a, b) = ((i + 1) % 3, (i + 2) % 3);
+            let first = mesh.positions.len() as u32;
+            for (s1, s2) in [(-1., -1.), (1., -1.), (1., 1.), (-1., 1.)] {
+                let mut position = center;
+                position[a] += s1 * sticker_size;
+                position[b] += s2 * sticker_size * sign;
+                let mut normal = [0.; 3];
+                normal[i] = sign;
+                mesh.positions.push(position);
+                mesh.normals.push(normal);
+                mesh.colors
+                    .push(scheme.color(gcube.get_initial_face(sticker)));
+            }
+            mesh.indices
+                .extend([0, 1, 2, 0, 2, 3].iter().map(|i| first + i));
+        }
+        mesh
+    }
+
+    /// Returns the mesh in Wavefront OBJ format, with vertex colours after
+    /// each position (an extension read by e.g. Blender and MeshLab).
+    pub fn to_obj(&self) -> String {
+        let mut obj = String::from("# cubedesu\n");
+        for ([x, y, z], [r, g, b]) in self.positions.iter().zip(self.colors.iter()) {
+            let [r, g, b] = [r, g, b].map(|c| *c as f32 / 255.);
+            writeln!(obj, "v {} {} {} {} {} {}", x, y, z, r, g, b).unwrap();
+        }
+        for [x, y, z] in self.normals.iter() {
+            writeln!(obj, "vn {} {} {}", x, y, z).unwrap();
+        }
+        // OBJ indices count from 1, each vertex has its own normal
+        for triangle in self.indices.chunks(3) {
+            let [a, b, c] = [triangle[0] + 1, triangle[1] + 1, triangle[2] + 1];
+            writeln!(obj, "f {0}//{0} {1}//{1} {2}//{2}", a, b, c).unwrap();
+        }
+        obj
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stickers_face_out_of_the_cube() {
+        let mesh = CubeMesh::new(&GCube::new(3), ColorScheme::Standard, 0.9);
+        assert_eq!(mesh.positions.len(), 54 * 4);
+        assert_eq!(mesh.indices.len(), 54 * 6);
+        for triangle in mesh.indices.chunks(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| mesh.positions[triangle[i] as usize]);
+            let (u, v) = (
+                [0, 1, 2].map(|i| b[i] - a[i]),
+                [0, 1, 2].map(|i| c[i] - a[i]),
+            );
+            let cross = [
+                u[1] * v[2] - u[2] * v[1],
+                u[2] * v[0] - u[0] * v[2],
+                u[0] * v[1] - u[1] * v[0],
+            ];
+            let normal = mesh.normals[triangle[0] as usize];
+            assert!((0..3).map(|i| cross[i] * normal[i]).sum::<f32>() > 0.);
+            // on the cube's surface, 3 units from its center
+            assert!(a.iter().any(|c| c.abs() == 3.));
+        }
+        let white = mesh
+            .colors
+            .iter()
+            .filter(|&&c| c == [255, 255, 255])
+            .count();
+        assert_eq!(white, 9 * 4);
+
+        let obj = mesh.to_obj();
+        assert_eq!(obj.lines().filter(|l| l.starts_with("v ")).count(), 54 * 4);
+        assert_eq!(obj.lines().filter(|l| l.starts_with("f ")).count(), 54 * 2);
+        assert!(obj.contains("f 1//1 2//2 3//3\n"));
+    }
+}
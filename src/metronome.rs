@@ -0,0 +1,95 @@
+/// Ticks at a steady tempo to pace turns to, scoring how close to its beats
+/// the turns land. Times are passed in by the caller, in seconds from any
+/// fixed point.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Metronome {
+    bpm: f64,
+    started_at: f64,
+    offsets: Vec<f64>, // of each turn from its nearest beat, in beats
+    beats: u64,        // number of beats ticked
+}
+
+impl Metronome {
+    /// Starts ticking at bpm beats per minute, with the first beat at now.
+    pub fn new(bpm: f64, now: f64) -> Self {
+        Self {
+            bpm,
+            started_at: now,
+            offsets: vec![],
+            beats: 0,
+        }
+    }
+
+    pub fn bpm(&self) -> f64 {
+        self.bpm
+    }
+
+    /// Returns how far into the current beat now is, from 0 (on the beat) to
+    /// 1, e.g. to pulse in time with it.
+    pub fn phase(&self, now: f64) -> f64 {
+        ((now - self.started_at) * self.bpm / 60.).rem_euclid(1.)
+    }
+
+    /// Whether a beat has passed since the last call, to tick on.
+    pub fn tick(&mut self, now: f64) -> bool {
+        let beats = ((now - self.started_at) * self.bpm / 60.).floor().max(0.) as u64 + 1;
+        let is_tick = beats > self.beats;
+        self.beats = beats;
+        is_tick
+    }
+
+    /// Records a turn made at now, returning its offset from the nearest
+    /// beat, in beats from -0.5 (early) to 0.5 (late).
+    pub fn record_turn(&mut self, now: f64) -> f64 {
+        let phase = self.phase(now);
+        let offset = if phase < 0.5 { phase } else { phase - 1. };
+        self.offsets.push(offset);
+        offset
+    }
+
+    /// Returns how consistently the recorded turns landed on the beat, from
+    /// 0 (always halfway between beats) to 1 (always on the beat), or None
+    /// before any turns.
+    pub fn consistency(&self) -> Option<f64> {
+        if self.offsets.is_empty() {
+            return None;
+        }
+        let mean = self.offsets.iter().map(|o| o.abs()).sum::<f64>() / self.offsets.len() as f64;
+        Some(1. - mean * 2.)
+    }
+
+    /// Forgets the recorded turns, e.g. to start a new drill.
+    pub fn reset_score(&mut self) {
+        self.offsets.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metronome_ticks_on_the_beat() {
+        let mut metronome = Metronome::new(120., 10.);
+        assert!(metronome.tick(10.));
+        assert!(!metronome.tick(10.4));
+        assert_eq!(metronome.phase(10.25), 0.5);
+        assert!(metronome.tick(10.5));
+        // beats missed by a slow frame tick once
+        assert!(metronome.tick(12.));
+        assert!(!metronome.tick(12.1));
+    }
+
+    #[test]
+    fn turns_are_scored_by_their_offset_from_the_beat() {
+        let mut metronome = Metronome::new(60., 0.);
+        assert_eq!(metronome.consistency(), None);
+        assert_eq!(metronome.record_turn(3.), 0.);
+        assert_eq!(metronome.record_turn(4.25), 0.25);
+        assert_eq!(metronome.record_turn(5.75), -0.25);
+        assert_eq!(metronome.consistency(), Some(1. - 1. / 3.));
+        metronome.reset_score();
+        metronome.record_turn(7.5);
+        assert_eq!(metronome.consistency(), Some(0.));
+    }
+}
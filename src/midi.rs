@@ -0,0 +1,199 @@
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::Path,
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// A press on a MIDI controller: a note played (e.g. a key or pad), or a
+/// control change pushed past halfway (e.g. a button or pedal).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MidiTrigger {
+    /// a note number, e.g. 60 for middle C
+    Note(u8),
+    /// a controller number
+    Control(u8),
+}
+
+/// A press bound to a key of the csTimer keymap, so it turns the cube as
+/// typing the key does, e.g. "I" for R2 or "2" for the depth of big cube
+/// turns.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MidiBinding {
+    pub trigger: MidiTrigger,
+    pub key: char,
+}
+
+/// Returns bindings of the octave from middle C to the face turns, white
+/// keys clockwise and black keys anticlockwise where there's one, then the
+/// next octave's first keys to the rotations: C R, C# R', D U, D# U', E F,
+/// F L, F# L', G D, G# D', A B, A# B', B F', then C x, C# x', D y, D# y'.
+pub fn default_midi_bindings() -> Vec<MidiBinding> {
+    "ikjfhdeslwog"
+        .chars()
+        .chain("tn;a".chars())
+        .enumerate()
+        .map(|(i, key)| MidiBinding {
+            trigger: MidiTrigger::Note(60 + i as u8),
+            key,
+        })
+        .collect()
+}
+
+/// Returns the keys bound to triggers, in the order they were pressed.
+pub fn midi_keys(bindings: &[MidiBinding], triggers: &[MidiTrigger]) -> Vec<char> {
+    triggers
+        .iter()
+        .filter_map(|t| bindings.iter().find(|b| b.trigger == *t).map(|b| b.key))
+        .collect()
+}
+
+/// Parses a stream of MIDI bytes into presses, on any channel, e.g. from a
+/// raw MIDI device.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MidiParser {
+    // the status byte of the message being read, which later messages can
+    // leave out (running status)
+    status: Option<u8>,
+    data: Vec<u8>,
+    // each controller's last value, so a press is only its value rising
+    // past halfway
+    controls: Vec<u8>,
+}
+
+impl Default for MidiParser {
+    fn default() -> Self {
+        Self {
+            status: None,
+            data: vec![],
+            controls: vec![0; 128],
+        }
+    }
+}
+
+impl MidiParser {
+    /// Returns the presses completed by bytes, skipping other messages.
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<MidiTrigger> {
+        let mut triggers = vec![];
+        for &byte in bytes {
+            match byte {
+                // real time messages can come between any bytes
+                0xf8..=0xff => {}
+                // system messages cancel running status
+                0xf0..=0xf7 => {
+                    self.status = None;
+                    self.data.clear();
+                }
+                0x80..=0xef => {
+                    self.status = Some(byte);
+                    self.data.clear();
+                }
+                _ => {
+                    let status = match self.status {
+                        Some(status) => status,
+                        None => continue,
+                    };
+                    self.data.push(byte);
+                    // program and channel pressure changes have one data byte
+                    let length = if (0xc0..0xe0).contains(&status) { 1 } else { 2 };
+                    if self.data.len() == length {
+                        triggers.extend(self.message(status));
+                        self.data.clear();
+                    }
+                }
+            }
+        }
+        triggers
+    }
+
+    fn message(&mut self, status: u8) -> Option<MidiTrigger> {
+        match (status & 0xf0, self.data[0], self.data.get(1).copied()) {
+            // a note on with no velocity is a note off
+            (0x90, note, Some(velocity)) if velocity > 0 => Some(MidiTrigger::Note(note)),
+            (0xb0, controller, Some(value)) => {
+                let last = std::mem::replace(&mut self.controls[controller as usize], value);
+                (last < 64 && value >= 64).then_some(MidiTrigger::Control(controller))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Reads presses from a raw MIDI device, e.g. "/dev/snd/midiC1D0" on Linux,
+/// on a thread of its own.
+pub struct MidiInput {
+    receiver: Receiver<Vec<u8>>,
+    parser: MidiParser,
+}
+
+impl MidiInput {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut buffer = [0; 256];
+            // until the device is unplugged, or the input's dropped
+            while let Ok(n @ 1..) = file.read(&mut buffer) {
+                if sender.send(buffer[..n].to_vec()).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self {
+            receiver,
+            parser: MidiParser::default(),
+        })
+    }
+
+    /// Returns the presses since the last poll, without blocking.
+    pub fn poll(&mut self) -> Vec<MidiTrigger> {
+        let bytes: Vec<u8> = self.receiver.try_iter().flatten().collect();
+        self.parser.feed(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{scramble_to_movements, Keymap, Modifiers};
+
+    #[test]
+    fn parses_presses() {
+        let mut parser = MidiParser::default();
+        // middle C on, with running status for E on then off, on channel 2
+        let notes = [0x91, 60, 100, 64, 90, 64, 0];
+        assert_eq!(
+            parser.feed(&notes),
+            [MidiTrigger::Note(60), MidiTrigger::Note(64)]
+        );
+        // a message split between reads, with a clock tick inside it
+        assert_eq!(parser.feed(&[0x80, 60]), []);
+        assert_eq!(parser.feed(&[0xf8, 0, 0x90, 62]), []);
+        assert_eq!(parser.feed(&[127]), [MidiTrigger::Note(62)]);
+        // a pedal only presses as it goes down
+        let pedal = [0xb0, 64, 0, 64, 127, 64, 100, 64, 0, 64, 127];
+        assert_eq!(
+            parser.feed(&pedal),
+            [MidiTrigger::Control(64), MidiTrigger::Control(64)]
+        );
+        // a program change's single data byte isn't a note
+        assert_eq!(
+            parser.feed(&[0xc0, 5, 0x90, 61, 1]),
+            [MidiTrigger::Note(61)]
+        );
+    }
+
+    #[test]
+    fn presses_turn_bound_keys() {
+        let bindings = default_midi_bindings();
+        let triggers = [60, 62, 61, 63, 72].map(MidiTrigger::Note);
+        let keys = midi_keys(&bindings, &triggers);
+        let movements = Keymap::default().movements(keys, &mut Modifiers::default(), 3);
+        assert_eq!(movements, scramble_to_movements("R U R' U' x").unwrap());
+        assert!(midi_keys(&bindings, &[MidiTrigger::Control(1)]).is_empty());
+    }
+}
@@ -0,0 +1,93 @@
+use thiserror::Error;
+
+use crate::{Alg, CubieCube, Face, GCube, Move, Movement, Turn};
+
+// the face turns a CubieCube can turn, in the order fuzz input bytes pick them
+const FACE_MOVES: [Move; 6] = [Move::U, Move::R, Move::F, Move::D, Move::L, Move::B];
+
+/// Why the geometric model (GCube) and the cubie model (CubieCube) couldn't
+/// be checked against each other, or where they disagree.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum ModelCheckError {
+    #[error("{0} isn't a face turn, which is all a CubieCube can turn")]
+    Unsupported(Movement),
+    #[error("The models disagree after {movements}")]
+    Mismatch {
+        /// the movements from solved up to the first that they disagree
+        /// after
+        movements: Alg,
+        /// each model's facelets after them, ordered like GCube::facelets
+        geometric: Vec<Face>,
+        cubie: Vec<Face>,
+    },
+}
+
+/// Turns a solved 3x3 by movements in both the geometric model, which the
+/// viewer draws, and the cubie model, which the solver searches with,
+/// checking after each movement that their facelets match. Only outer face
+/// turns (e.g. R2, not 2R, Rw, M or x) can be checked.
+pub fn check_models(movements: &[Movement]) -> Result<(), ModelCheckError> {
+    if let Some(&movement) = movements.iter().find(|m| !is_face_turn(**m)) {
+        return Err(ModelCheckError::Unsupported(movement));
+    }
+    let mut gcube = GCube::new(3);
+    let mut cube = CubieCube::default();
+    for (i, &movement) in movements.iter().enumerate() {
+        gcube.apply_movements(&[movement]);
+        cube.turn(movement.face().unwrap(), movement.turn());
+        let (geometric, cubie) = (gcube.facelets(), cube.to_facelets().0.to_vec());
+        if geometric != cubie {
+            return Err(ModelCheckError::Mismatch {
+                movements: Alg::new(movements[..=i].to_vec()),
+                geometric,
+                cubie,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn is_face_turn(movement: Movement) -> bool {
+    movement.move_type().is_face() && movement.depth() == 1
+}
+
+/// Returns the face turns a fuzzer's input stands for, a byte each, so any
+/// input is a valid alg to check_models.
+pub fn movements_from_bytes(bytes: &[u8]) -> Vec<Movement> {
+    bytes
+        .iter()
+        .map(|&b| {
+            let b = b as usize % 18;
+            let turn = [Turn::Single, Turn::Double, Turn::Inverse][b % 3];
+            Movement::new(FACE_MOVES[b / 3], turn)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::{collection::vec, prelude::any, prop_assert_eq, proptest};
+
+    #[test]
+    fn models_agree() {
+        let alg: Alg = "R U R' U' F2 D L' B2".parse().unwrap();
+        assert_eq!(check_models(alg.movements()), Ok(()));
+        let wide: Alg = "R Rw".parse().unwrap();
+        assert_eq!(
+            check_models(wide.movements()),
+            Err(ModelCheckError::Unsupported(wide.movements()[1]))
+        );
+        let bytes: Vec<u8> = (0..18).collect();
+        let movements = movements_from_bytes(&bytes);
+        assert_eq!(Alg::new(movements[..4].to_vec()).to_string(), "U U2 U' R");
+        assert_eq!(movements_from_bytes(&[18]), movements_from_bytes(&[0]));
+    }
+
+    proptest! {
+        #[test]
+        fn models_agree_on_any_face_turns(bytes in vec(any::<u8>(), 0..60)) {
+            prop_assert_eq!(check_models(&movements_from_bytes(&bytes)), Ok(()));
+        }
+    }
+}
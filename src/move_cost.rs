@@ -0,0 +1,175 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Alg, Move, Movement, Turn};
+
+/// What a movement costs to turn, overriding MoveCosts' rules for it.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MoveCost {
+    pub movement: Movement,
+    pub cost: f64,
+}
+
+/// An approximation of how awkward movements are to turn, for ranking
+/// solutions by ergonomics rather than by move count, e.g. R U R' U' over
+/// F' B' D B, which are both 4 moves.
+///
+/// A movement costs what it's listed at in costs, or else what its clockwise
+/// turn is listed at (or default, if that isn't either), times double or
+/// inverse for those turns, and inner_layer for turns of inner layers (e.g.
+/// 2R or 3Rw).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MoveCosts {
+    pub costs: Vec<MoveCost>,
+    pub default: f64,
+    pub double: f64,
+    pub inverse: f64,
+    pub inner_layer: f64,
+}
+
+impl Default for MoveCosts {
+    /// Costs for a right handed solver holding the cube with F facing them:
+    /// R, U and their wide turns are cheapest, then L and D, with F, B, S,
+    /// E and rotations (regrips) the most expensive.
+    fn default() -> Self {
+        let clockwise = [
+            (Move::R, 1.),
+            (Move::U, 1.),
+            (Move::Rw, 1.1),
+            (Move::Uw, 1.2),
+            (Move::L, 1.2),
+            (Move::M, 1.3),
+            (Move::D, 1.4),
+            (Move::Lw, 1.4),
+            (Move::F, 1.5),
+            (Move::Dw, 1.7),
+            (Move::Fw, 1.8),
+            (Move::B, 2.),
+            (Move::S, 2.),
+            (Move::E, 2.),
+            (Move::X, 1.5),
+            (Move::Y, 1.5),
+            (Move::Z, 2.),
+            (Move::Bw, 2.2),
+        ];
+        let mut costs: Vec<MoveCost> = clockwise
+            .iter()
+            .map(|&(m, cost)| MoveCost {
+                movement: Movement::new(m, Turn::Single),
+                cost,
+            })
+            .collect();
+        // F' and D' are pushed with the left hand's index finger, or regripped
+        for &(m, cost) in [(Move::F, 1.8), (Move::D, 1.6)].iter() {
+            costs.push(MoveCost {
+                movement: Movement::new(m, Turn::Inverse),
+                cost,
+            });
+        }
+        Self {
+            costs,
+            default: 1.,
+            double: 1.6,
+            inverse: 1.,
+            inner_layer: 1.2,
+        }
+    }
+}
+
+impl MoveCosts {
+    /// Returns the cost of turning movement.
+    pub fn cost(&self, movement: Movement) -> f64 {
+        let find = |movement| {
+            self.costs
+                .iter()
+                .find(|c| c.movement == movement)
+                .map(|c| c.cost)
+        };
+        if let Some(cost) = find(movement) {
+            return cost;
+        }
+        let Movement(m, turn, depth) = movement;
+        let outer = Movement::new(m, turn);
+        let mut cost = find(outer).unwrap_or_else(|| {
+            let clockwise = find(Movement::new(m, Turn::Single)).unwrap_or(self.default);
+            match turn {
+                Turn::Single => clockwise,
+                Turn::Double => clockwise * self.double,
+                Turn::Inverse => clockwise * self.inverse,
+            }
+        });
+        if depth != outer.depth() {
+            cost *= self.inner_layer;
+        }
+        cost
+    }
+
+    /// Returns the cost of turning every movement.
+    pub fn cost_all(&self, movements: &[Movement]) -> f64 {
+        movements.iter().map(|&m| self.cost(m)).sum()
+    }
+
+    /// Returns algs ordered from cheapest to most expensive, with their
+    /// costs. Algs of equal cost keep their order.
+    pub fn rank(&self, algs: impl IntoIterator<Item = Alg>) -> Vec<(f64, Alg)> {
+        let mut ranked: Vec<(f64, Alg)> = algs
+            .into_iter()
+            .map(|alg| (self.cost_all(alg.movements()), alg))
+            .collect();
+        ranked.sort_by(|a, b| a.0.total_cmp(&b.0));
+        ranked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn movement(s: &str) -> Movement {
+        Movement::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn listed_costs_then_rules() {
+        let costs = MoveCosts::default();
+        assert_eq!(costs.cost(movement("R")), 1.);
+        assert_eq!(costs.cost(movement("R'")), 1.);
+        assert_eq!(costs.cost(movement("R2")), 1.6);
+        assert_eq!(costs.cost(movement("F")), 1.5);
+        assert_eq!(costs.cost(movement("F'")), 1.8);
+        assert_eq!(costs.cost(movement("2R")), 1.2);
+        assert_eq!(costs.cost(movement("3Rw'")), 1.1 * 1.2);
+        assert!(costs.cost(movement("F'")) > costs.cost(movement("R'")));
+
+        let costs = MoveCosts {
+            costs: vec![MoveCost {
+                movement: movement("2R"),
+                cost: 3.,
+            }],
+            ..MoveCosts::default()
+        };
+        assert_eq!(costs.cost(movement("2R")), 3.);
+        assert_eq!(costs.cost(movement("F'")), 1.);
+    }
+
+    #[test]
+    fn ranks_by_cost_not_length() {
+        let algs = ["F' B' D B", "R U R' U' R", "R U R' U'"]
+            .iter()
+            .map(|s| Alg::from_str(s).unwrap());
+        let ranked = MoveCosts::default().rank(algs);
+        let order: Vec<String> = ranked.iter().map(|(_, alg)| alg.to_string()).collect();
+        assert_eq!(order, ["R U R' U'", "R U R' U' R", "F' B' D B"]);
+        assert_eq!(ranked[0].0, 4.);
+    }
+
+    #[test]
+    fn partial_json_keeps_default_rules() {
+        let costs: MoveCosts =
+            serde_json::from_str(r#"{ "costs": [{ "movement": "U'", "cost": 1.2 }] }"#).unwrap();
+        assert_eq!(costs.cost(movement("U'")), 1.2);
+        assert_eq!(costs.cost(movement("U2")), 1.6);
+        assert_eq!(costs.default, 1.);
+    }
+}
@@ -0,0 +1,118 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Alg, Movement, Penalty};
+
+/// What happened, as logged, tagged by its type.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LogEvent {
+    /// the app started, with a cube of size
+    SessionStart {
+        size: usize,
+    },
+    Scramble {
+        size: usize,
+        scramble: Alg,
+    },
+    Move {
+        movement: Movement,
+        /// seconds into the solve, if it's being timed
+        solve_time: Option<f64>,
+    },
+    Solve {
+        size: usize,
+        time: f64,
+        penalty: Penalty,
+    },
+    /// the app quit
+    SessionEnd,
+}
+
+/// A line of the move log, e.g.
+/// `{"session":1790000000000,"at":1790000012.5,"type":"move","movement":"R'","solve_time":1.25}`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LogLine {
+    /// when the session started, in unix milliseconds, which is the same for
+    /// every line of a session
+    pub session: u64,
+    /// the wall clock time, in unix seconds
+    pub at: f64,
+    #[serde(flatten)]
+    pub event: LogEvent,
+}
+
+/// Appends every move, scramble and solve to a file as a line of JSON
+/// (JSONL), for analysis scripts to work out statistics across sessions.
+pub struct MoveLog {
+    file: File,
+    session: u64,
+}
+
+impl MoveLog {
+    /// Opens the log at path to append to, creating it if there's none, and
+    /// starts a session at now (in unix seconds).
+    pub fn open(path: impl AsRef<Path>, now: f64, size: usize) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let mut log = Self {
+            file,
+            session: (now * 1000.) as u64,
+        };
+        log.log(LogEvent::SessionStart { size }, now)?;
+        Ok(log)
+    }
+
+    /// Appends event, which happened at now (in unix seconds).
+    pub fn log(&mut self, event: LogEvent, now: f64) -> io::Result<()> {
+        let line = LogLine {
+            session: self.session,
+            at: now,
+            event,
+        };
+        writeln!(self.file, "{}", serde_json::to_string(&line)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, str::FromStr};
+
+    #[test]
+    fn sessions_append_lines_of_json() {
+        let path = std::env::temp_dir().join("cubedesu_move_log_test.jsonl");
+        fs::remove_file(&path).ok();
+        for start in [100., 200.] {
+            let mut log = MoveLog::open(&path, start, 3).unwrap();
+            let movement = Movement::from_str("R'").unwrap();
+            log.log(
+                LogEvent::Move {
+                    movement,
+                    solve_time: Some(1.25),
+                },
+                start + 1.,
+            )
+            .unwrap();
+            log.log(LogEvent::SessionEnd, start + 2.).unwrap();
+        }
+        let text = fs::read_to_string(&path).unwrap();
+        let lines: Vec<LogLine> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 6);
+        assert_eq!(lines[0].event, LogEvent::SessionStart { size: 3 });
+        assert_eq!(lines[3].session, 200_000);
+        assert!(lines[..3].iter().all(|l| l.session == 100_000));
+        assert_eq!(
+            text.lines().nth(1).unwrap(),
+            r#"{"session":100000,"at":101.0,"type":"move","movement":"R'","solve_time":1.25}"#
+        );
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,112 @@
+use crate::{drag_movement, GCube, Movement};
+
+/// Where each face of a net is, in faces from its top left, ordered by U, R,
+/// F, D, L, then B face: L F R B in a row, with U above F and D below it.
+pub const NET_OFFSETS: [(f32, f32); 6] =
+    [(1., 0.), (2., 1.), (1., 1.), (1., 2.), (0., 1.), (3., 1.)];
+
+// the directions rightwards along a row and downwards along a column of each
+// face of a net, in the cube's coordinates, ordered like NET_OFFSETS
+const NET_AXES: [([f32; 3], [f32; 3]); 6] = [
+    ([1., 0., 0.], [0., 0., 1.]),
+    ([0., 0., -1.], [0., -1., 0.]),
+    ([1., 0., 0.], [0., -1., 0.]),
+    ([1., 0., 0.], [0., 0., -1.]),
+    ([0., 0., 1.], [0., -1., 0.]),
+    ([-1., 0., 0.], [0., -1., 0.]),
+];
+
+/// Where an NxN cube's net is drawn on screen, for finding the facelets
+/// under the mouse.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NetLayout {
+    /// the net's top left corner
+    pub x: f32,
+    pub y: f32,
+    pub face_size: f32,
+}
+
+impl NetLayout {
+    /// Returns the biggest net fitting in a width by height area at x, y,
+    /// centered in it.
+    pub fn fit(x: f32, y: f32, width: f32, height: f32) -> Self {
+        let face_size = (width / 4.).min(height / 3.);
+        Self {
+            x: x + (width - 4. * face_size) / 2.,
+            y: y + (height - 3. * face_size) / 2.,
+            face_size,
+        }
+    }
+
+    /// Returns the index of the facelet of an NxN cube at point, ordered like
+    /// GCube::facelets, or None if it's off the net.
+    pub fn facelet_at(&self, n: usize, point: [f32; 2]) -> Option<usize> {
+        let (x, y) = (
+            (point[0] - self.x) / self.face_size,
+            (point[1] - self.y) / self.face_size,
+        );
+        let face = NET_OFFSETS.iter().position(|&(face_x, face_y)| {
+            (face_x..face_x + 1.).contains(&x) && (face_y..face_y + 1.).contains(&y)
+        })?;
+        let (face_x, face_y) = NET_OFFSETS[face];
+        let col = (((x - face_x) * n as f32) as usize).min(n - 1);
+        let row = (((y - face_y) * n as f32) as usize).min(n - 1);
+        Some(face * n * n + row * n + col)
+    }
+
+    /// Returns the movement turning the layer of the sticker at from the way
+    /// it was dragged to to, as dragging it across the 3D cube's face would.
+    pub fn drag_movement(&self, gcube: &GCube, from: [f32; 2], to: [f32; 2]) -> Option<Movement> {
+        let facelet = self.facelet_at(gcube.size, from)?;
+        let (right, down) = NET_AXES[facelet / (gcube.size * gcube.size)];
+        let (dx, dy) = (to[0] - from[0], to[1] - from[1]);
+        let drag = [0, 1, 2].map(|i| right[i] * dx + down[i] * dy);
+        let sticker = gcube.sticker(gcube.facelet_sticker(facelet));
+        drag_movement(gcube, sticker, drag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Face;
+
+    #[test]
+    fn finds_facelets_on_the_net() {
+        let layout = NetLayout::fit(0., 0., 400., 600.);
+        assert_eq!(layout.face_size, 100.);
+        assert_eq!((layout.x, layout.y), (0., 150.));
+        let gcube = GCube::new(3);
+        let facelets = gcube.facelets();
+        // the top left of U, the bottom right of F, and the middle of B
+        assert_eq!(layout.facelet_at(3, [101., 151.]), Some(0));
+        assert_eq!(layout.facelet_at(3, [199., 349.]), Some(2 * 9 + 8));
+        assert_eq!(layout.facelet_at(3, [350., 300.]), Some(5 * 9 + 4));
+        // beside U, and below the net
+        assert_eq!(layout.facelet_at(3, [50., 200.]), None);
+        assert_eq!(layout.facelet_at(3, [150., 460.]), None);
+        assert_eq!(
+            facelets[layout.facelet_at(3, [10., 300.]).unwrap()],
+            Face::L
+        );
+    }
+
+    #[test]
+    fn drags_on_the_net_turn_layers() {
+        let layout = NetLayout::fit(0., 0., 4., 3.);
+        let gcube = GCube::new(3);
+        let drag = |from: [f32; 2], to: [f32; 2]| {
+            layout.drag_movement(&gcube, from, to).unwrap().to_string()
+        };
+        // up the right of F, and along the top of F to the left
+        assert_eq!(drag([1.9, 1.5], [1.9, 1.2]), "R");
+        assert_eq!(drag([1.5, 1.1], [1.2, 1.1]), "U");
+        // along the front of U, which is its bottom row, and down B's left
+        assert_eq!(drag([1.5, 0.9], [1.8, 0.9]), "F");
+        assert_eq!(drag([3.1, 1.5], [3.1, 1.8]), "R");
+        // along D's back row, its bottom, and across R
+        assert_eq!(drag([1.5, 2.9], [1.8, 2.9]), "B");
+        assert_eq!(drag([2.5, 1.1], [2.8, 1.1]), "U'");
+        assert_eq!(layout.drag_movement(&gcube, [0.5, 0.5], [0.5, 0.9]), None);
+    }
+}
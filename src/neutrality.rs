@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter};
+
+use crate::{CubieCube, Face, FaceletModel, Rng, ORDERED_FACES};
+
+/// How much slower than the mean of every colour's times a colour's mean can
+/// be before it's lagging, e.g. 1.2 for 20% slower.
+pub const LAG_RATIO: f64 = 1.2;
+
+/// The first step of a solve timed by a NeutralityDrill.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, EnumIter, Display, Serialize, Deserialize)]
+pub enum DrillStep {
+    /// the cross, for CFOP
+    Cross,
+    /// a 1x2x3 block on the bottom colour, for Roux
+    #[strum(serialize = "First block")]
+    FirstBlock,
+}
+
+impl DrillStep {
+    /// Returns whether the step is solved on a colour (the face whose
+    /// stickers it is) of a 3x3's facelets, wherever its center is.
+    pub fn is_solved(self, facelets: &FaceletModel, color: Face) -> bool {
+        let cube = match CubieCube::from_facelets(facelets) {
+            Ok(cube) => cube,
+            Err(_) => return false,
+        };
+        let center = (0..6).find(|&f| facelets[9 * f + 4] == color);
+        let face = match center {
+            Some(f) => ORDERED_FACES[f],
+            None => return false,
+        };
+        match self {
+            DrillStep::Cross => cube.cross_moves(face, 0).is_some(),
+            DrillStep::FirstBlock => cube.is_block_solved(face),
+        }
+    }
+}
+
+/// How long a step took on a colour in a NeutralityDrill.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StepTime {
+    pub step: DrillStep,
+    pub color: Face,
+    /// in seconds
+    pub time: f64,
+}
+
+/// The times of a step on a colour.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ColorStats {
+    pub color: Face,
+    pub count: usize,
+    pub mean: Option<f64>,
+    pub best: Option<f64>,
+}
+
+/// A drill for colour neutrality: each scramble's first step is solved on a
+/// colour chosen for it, every colour coming up once (in a random order)
+/// before any comes up again.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NeutralityDrill {
+    pub step: DrillStep,
+    /// the colour to solve the step on
+    pub color: Face,
+    // the colours still to come this round
+    upcoming: Vec<Face>,
+}
+
+impl NeutralityDrill {
+    pub fn new(step: DrillStep, rng: &mut Rng) -> Self {
+        let mut drill = Self {
+            step,
+            color: Face::U,
+            upcoming: vec![],
+        };
+        drill.next_color(rng);
+        drill
+    }
+
+    /// Moves on to the next colour, shuffling the colours into a new round
+    /// once each has come up.
+    pub fn next_color(&mut self, rng: &mut Rng) {
+        if self.upcoming.is_empty() {
+            self.upcoming = ORDERED_FACES.to_vec();
+            for i in (1..self.upcoming.len()).rev() {
+                self.upcoming.swap(i, rng.below(i + 1));
+            }
+        }
+        self.color = self.upcoming.pop().unwrap();
+    }
+}
+
+/// Returns the stats of each colour's times of step, in the order of
+/// ORDERED_FACES.
+pub fn color_stats(times: &[StepTime], step: DrillStep) -> Vec<ColorStats> {
+    ORDERED_FACES
+        .iter()
+        .map(|&color| {
+            let times: Vec<f64> = times
+                .iter()
+                .filter(|t| t.step == step && t.color == color)
+                .map(|t| t.time)
+                .collect();
+            ColorStats {
+                color,
+                count: times.len(),
+                mean: (!times.is_empty()).then(|| times.iter().sum::<f64>() / times.len() as f64),
+                best: times.iter().copied().reduce(f64::min),
+            }
+        })
+        .collect()
+}
+
+/// Returns the colours whose mean is LAG_RATIO times the mean of every
+/// colour's means or slower, slowest first.
+pub fn lagging_colors(stats: &[ColorStats]) -> Vec<Face> {
+    let means: Vec<f64> = stats.iter().filter_map(|s| s.mean).collect();
+    if means.len() < 2 {
+        return vec![];
+    }
+    let overall = means.iter().sum::<f64>() / means.len() as f64;
+    let mut lagging: Vec<&ColorStats> = stats
+        .iter()
+        .filter(|s| s.mean.is_some_and(|m| m >= overall * LAG_RATIO))
+        .collect();
+    lagging.sort_by(|a, b| b.mean.unwrap().total_cmp(&a.mean.unwrap()));
+    lagging.iter().map(|s| s.color).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{scramble_to_movements, GCube};
+
+    #[test]
+    fn every_color_comes_up_each_round() {
+        let mut rng = Rng::new(1);
+        let mut drill = NeutralityDrill::new(DrillStep::Cross, &mut rng);
+        let mut colors = vec![drill.color];
+        for _ in 0..11 {
+            drill.next_color(&mut rng);
+            colors.push(drill.color);
+        }
+        for round in colors.chunks(6) {
+            assert!(ORDERED_FACES.iter().all(|f| round.contains(f)));
+        }
+    }
+
+    #[test]
+    fn steps_are_solved_by_color() {
+        let mut gcube = GCube::new(3);
+        gcube.apply_movements(&scramble_to_movements("U R").unwrap());
+        let facelets = gcube.to_facelet_model();
+        assert!(DrillStep::FirstBlock.is_solved(&facelets, Face::D));
+        assert!(!DrillStep::FirstBlock.is_solved(&facelets, Face::U));
+        assert!(!DrillStep::Cross.is_solved(&facelets, Face::D));
+        gcube.apply_movements(&scramble_to_movements("R'").unwrap());
+        assert!(DrillStep::Cross.is_solved(&gcube.to_facelet_model(), Face::D));
+        // D's cross is still solved with D's center turned to the top
+        gcube.apply_movements(&scramble_to_movements("x2").unwrap());
+        assert!(DrillStep::Cross.is_solved(&gcube.to_facelet_model(), Face::D));
+    }
+
+    #[test]
+    fn slow_colors_lag() {
+        let time = |color, time| StepTime {
+            step: DrillStep::Cross,
+            color,
+            time,
+        };
+        let times = [
+            time(Face::U, 2.),
+            time(Face::U, 3.),
+            time(Face::D, 2.),
+            time(Face::R, 5.),
+            time(Face::F, 6.),
+        ];
+        let stats = color_stats(&times, DrillStep::Cross);
+        assert_eq!(
+            stats[0],
+            ColorStats {
+                color: Face::U,
+                count: 2,
+                mean: Some(2.5),
+                best: Some(2.)
+            }
+        );
+        assert_eq!(stats[4].mean, None);
+        // the mean of means is 3.875
+        assert_eq!(lagging_colors(&stats), [Face::F, Face::R]);
+        assert!(color_stats(&times, DrillStep::FirstBlock)
+            .iter()
+            .all(|s| s.count == 0));
+    }
+}
@@ -0,0 +1,148 @@
+use std::{ops::Range, path::Path};
+
+use image::RgbImage;
+use thiserror::Error;
+
+use crate::{ColorScheme, CubieCube, Face, FaceletModel, StateError, ORDERED_FACES};
+
+/// Why photos couldn't be loaded as a cube's state.
+#[derive(Debug, Error)]
+pub enum PhotoError {
+    #[error("Failed to load photo: {0}")]
+    Image(#[from] image::ImageError),
+    #[error("The photos aren't of a real cube: {0}")]
+    State(#[from] StateError),
+}
+
+/// Returns the average colour of each sticker in a photo of an NxN face,
+/// cropped to the face, ordered left to right, then top to bottom. Only the
+/// middle of each sticker is sampled, so the gaps between them don't count.
+pub fn sample_stickers(photo: &RgbImage, size: usize) -> Vec<[u8; 3]> {
+    let (width, height) = (photo.width() as usize, photo.height() as usize);
+    let mut colors = Vec::with_capacity(size * size);
+    for row in 0..size {
+        for col in 0..size {
+            let (xs, ys) = (
+                middle_half(col, size, width),
+                middle_half(row, size, height),
+            );
+            let mut sum = [0u64; 3];
+            let mut n = 0;
+            for y in ys.clone() {
+                for x in xs.clone() {
+                    let pixel = photo.get_pixel(x as u32, y as u32);
+                    for (s, &c) in sum.iter_mut().zip(pixel.0.iter()) {
+                        *s += c as u64;
+                    }
+                    n += 1;
+                }
+            }
+            colors.push(sum.map(|s| (s / n.max(1)) as u8));
+        }
+    }
+    colors
+}
+
+// the middle half of the ith of n equal parts of 0..len, at least a pixel long
+fn middle_half(i: usize, n: usize, len: usize) -> Range<usize> {
+    let start = (4 * i + 1) * len / (4 * n);
+    start..((4 * i + 3) * len / (4 * n)).max(start + 1)
+}
+
+/// Returns the face whose colour in scheme is nearest to color.
+pub fn classify_color(color: [u8; 3], scheme: ColorScheme) -> Face {
+    let distance = |face: &Face| -> i32 {
+        let c = scheme.color(*face);
+        (0..3).map(|i| (color[i] as i32 - c[i] as i32).pow(2)).sum()
+    };
+    *ORDERED_FACES.iter().min_by_key(|f| distance(f)).unwrap()
+}
+
+/// Returns the facelets of a 3x3 from photos of its faces, in the order U,
+/// R, F, D, L, then B, or why they aren't a real cube's. Each photo is
+/// cropped to its face and held like the facelet model's net: U with B at
+/// the top, D with F at the top, and the others with U at the top.
+pub fn facelets_from_photos(
+    photos: &[RgbImage; 6],
+    scheme: ColorScheme,
+) -> Result<FaceletModel, StateError> {
+    let mut facelets = FaceletModel::new();
+    for (f, photo) in photos.iter().enumerate() {
+        for (i, color) in sample_stickers(photo, 3).into_iter().enumerate() {
+            facelets[9 * f + i] = classify_color(color, scheme);
+        }
+    }
+    CubieCube::from_facelets(&facelets)?;
+    Ok(facelets)
+}
+
+/// Loads the facelets of a 3x3 from image files of its faces, like
+/// [facelets_from_photos].
+pub fn load_photos<P: AsRef<Path>>(
+    paths: &[P; 6],
+    scheme: ColorScheme,
+) -> Result<FaceletModel, PhotoError> {
+    let mut photos: [RgbImage; 6] = Default::default();
+    for (photo, path) in photos.iter_mut().zip(paths.iter()) {
+        *photo = image::open(path)?.to_rgb8();
+    }
+    Ok(facelets_from_photos(&photos, scheme)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{scramble_to_movements, GCube};
+    use image::Rgb;
+
+    // draws a face like a photo of it, with dark gaps between slightly off
+    // coloured stickers
+    fn photo_of(facelets: &[Face], scheme: ColorScheme) -> RgbImage {
+        RgbImage::from_fn(90, 90, |x, y| {
+            if x % 30 < 3 || y % 30 < 3 {
+                return Rgb([20, 20, 20]);
+            }
+            let color = scheme.color(facelets[(y / 30 * 3 + x / 30) as usize]);
+            Rgb(color.map(|c| (c as f32 * 0.85) as u8 + (x % 7) as u8))
+        })
+    }
+
+    #[test]
+    fn photos_are_read_as_facelets() {
+        let scheme = ColorScheme::Japanese;
+        let mut gcube = GCube::new(3);
+        gcube.apply_movements(&scramble_to_movements("R U' F2 D L B'").unwrap());
+        let facelets = gcube.facelets();
+        let mut photos: [RgbImage; 6] = Default::default();
+        for (f, photo) in photos.iter_mut().enumerate() {
+            *photo = photo_of(&facelets[9 * f..9 * f + 9], scheme);
+        }
+        assert_eq!(
+            facelets_from_photos(&photos, scheme),
+            Ok(gcube.to_facelet_model())
+        );
+
+        let dir = std::env::temp_dir();
+        let paths = ORDERED_FACES.map(|f| dir.join(format!("cubedesu_photo_{}.png", f)));
+        for (photo, path) in photos.iter().zip(paths.iter()) {
+            photo.save(path).unwrap();
+        }
+        assert_eq!(
+            load_photos(&paths, scheme).unwrap(),
+            gcube.to_facelet_model()
+        );
+
+        // a twisted corner can't be solved
+        let mut cube = CubieCube::default();
+        cube.twist_corner(0, 1);
+        let facelets = cube.to_facelets();
+        for (f, photo) in photos.iter_mut().enumerate() {
+            *photo = photo_of(&facelets.0[9 * f..9 * f + 9], scheme);
+        }
+        assert_eq!(
+            facelets_from_photos(&photos, scheme),
+            Err(StateError::TwistedCorner)
+        );
+        assert!(load_photos(&["cubedesu_no_photo.png"; 6], scheme).is_err());
+    }
+}
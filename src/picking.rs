@@ -0,0 +1,148 @@
+use crate::{Face, GCube, Move, Movement, Sticker, Turn};
+
+// returns the index (0 for x, 1 for y, 2 for z) and sign of the axis the
+// sticker's face points along, e.g. (0, 1) for a sticker on R
+fn face_normal(sticker: Sticker) -> (usize, f32) {
+    let p = sticker.current;
+    let i = (0..3)
+        .find(|&i| [p.x, p.y, p.z][i].abs() == sticker.dims[i] as i16)
+        .unwrap();
+    (i, [p.x, p.y, p.z][i].signum() as f32)
+}
+
+fn coords(sticker: Sticker) -> [f32; 3] {
+    let p = sticker.current;
+    [p.x as f32, p.y as f32, p.z as f32]
+}
+
+impl GCube {
+    /// Returns the index of the sticker first hit by a ray from origin in
+    /// direction, and the face it's on, in the cube's coordinates, where
+    /// each sticker is a 2x2 square centered on its current position, e.g.
+    /// for the sticker under the mouse.
+    pub fn raycast(&self, origin: [f32; 3], direction: [f32; 3]) -> Option<(usize, Face)> {
+        let mut nearest: Option<(usize, f32)> = None;
+        for (index, sticker) in self.stickers().enumerate() {
+            let (i, sign) = face_normal(sticker);
+            // only the front of a sticker can be hit
+            if direction[i] * sign >= 0. {
+                continue;
+            }
+            let center = coords(sticker);
+            let t = (center[i] - origin[i]) / direction[i];
+            let is_hit = (0..3)
+                .filter(|&j| j != i)
+                .all(|j| (origin[j] + t * direction[j] - center[j]).abs() <= 1.);
+            if t > 0. && is_hit && nearest.is_none_or(|(_, nearest_t)| t < nearest_t) {
+                nearest = Some((index, t));
+            }
+        }
+        nearest.map(|(index, _)| (index, self.get_curr_face(self.sticker(index))))
+    }
+}
+
+/// Returns the movement that turns a sticker's layer the way the sticker was
+/// dragged across its face, e.g. dragging a sticker on F upwards turns the
+/// layer it's in around the x axis, like R or L'. The drag is in the cube's
+/// coordinates, and only its largest component along the face counts.
+pub fn drag_movement(gcube: &GCube, sticker: Sticker, drag: [f32; 3]) -> Option<Movement> {
+    let (normal, normal_sign) = face_normal(sticker);
+    let along = (0..3)
+        .filter(|&j| j != normal)
+        .max_by(|&a, &b| drag[a].abs().partial_cmp(&drag[b].abs()).unwrap())?;
+    if drag[along] == 0. {
+        return None;
+    }
+    // the layer turns around the remaining axis, in the direction of
+    // normal x drag, by the right hand rule
+    let axis = 3 - normal - along;
+    let is_cyclic = (normal + 1) % 3 == along;
+    let sign = normal_sign * drag[along].signum() * if is_cyclic { 1. } else { -1. };
+    // clockwise quarter turns are negative rotations around the axis
+    let quarter_turns = if sign > 0. { -1 } else { 1 };
+
+    // the shallowest movement of the sticker's layer, slices first on the
+    // middle layer of odd cubes
+    let faces = [Move::U, Move::L, Move::F, Move::R, Move::B, Move::D];
+    let slices = [Move::M, Move::E, Move::S]
+        .iter()
+        .map(|&m| Movement::new(m, Turn::Single));
+    let layers = (1..=gcube.size as u8).flat_map(|depth| {
+        faces
+            .iter()
+            .filter_map(move |&m| Movement::new(m, Turn::Single).with_depth(depth))
+    });
+    slices
+        .chain(layers)
+        .flat_map(|movement| [movement, movement.inverse()])
+        .find(|&movement| {
+            let gmove = GCube::create_gmove(movement);
+            gmove.axis() as usize == axis
+                && (gmove.quarter_turns() - quarter_turns).rem_euclid(4) == 0
+                && gmove.affects(sticker)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn sticker_at(gcube: &GCube, x: i16, y: i16, z: i16) -> Sticker {
+        gcube
+            .stickers()
+            .find(|s| (s.current.x, s.current.y, s.current.z) == (x, y, z))
+            .unwrap()
+    }
+
+    #[test]
+    fn picks_nearest_sticker() {
+        let gcube = GCube::new(3);
+        // looking at F, from in front of the cube
+        let (index, face) = gcube.raycast([2.3, -1.5, 10.], [0., 0., -1.]).unwrap();
+        assert_eq!(gcube.sticker(index), sticker_at(&gcube, 2, -2, 3));
+        assert_eq!(face, Face::F);
+        // from behind, B is hit instead
+        let (index, face) = gcube.raycast([0., 0., -10.], [0., 0., 1.]).unwrap();
+        assert_eq!(gcube.sticker(index), sticker_at(&gcube, 0, 0, -3));
+        assert_eq!(face, Face::B);
+        // from above and in front, at an angle, U is hit before F
+        let (_, face) = gcube.raycast([0., 10., 10.], [0., -1., -1.2]).unwrap();
+        assert_eq!(face, Face::U);
+        assert_eq!(gcube.raycast([0., 5., 10.], [0., 0., -1.]), None);
+    }
+
+    #[test]
+    fn drags_turn_layers() {
+        let gcube = GCube::new(3);
+        let drag = |x, y, z, drag| {
+            let movement = drag_movement(&gcube, sticker_at(&gcube, x, y, z), drag).unwrap();
+            movement.to_string()
+        };
+        // dragging up the right of F turns R, down turns R'
+        assert_eq!(drag(2, 0, 3, [0., 1., 0.]), "R");
+        assert_eq!(drag(2, 0, 3, [0.2, -1., 0.]), "R'");
+        // the left and middle columns
+        assert_eq!(drag(-2, 2, 3, [0., 1., 0.]), "L'");
+        assert_eq!(drag(0, 2, 3, [0., -1., 0.]), "M");
+        // dragging left along the top of F turns U
+        assert_eq!(drag(0, 2, 3, [-1., 0., 0.]), "U");
+        assert_eq!(drag(0, -2, 3, [-1., 0., 0.]), "D'");
+        assert_eq!(drag(0, 0, 3, [1., 0., 0.]), "E");
+        // dragging right along the front of U turns F
+        assert_eq!(drag(0, 3, 2, [1., 0., 0.]), "F");
+        // dragging the front along R turns U
+        assert_eq!(drag(3, 2, 0, [0., 0., 1.]), "U");
+        assert_eq!(drag(3, 0, 0, [0., 0., 1.]), "E'");
+        assert_eq!(drag(3, 0, 0, [0., 1., 0.]), "S'");
+    }
+
+    #[test]
+    fn drags_turn_inner_layers() {
+        let gcube = GCube::new(4);
+        let movement = drag_movement(&gcube, sticker_at(&gcube, 1, 3, 4), [0., 1., 0.]);
+        assert_eq!(movement, Some(Movement::from_str("2R").unwrap()));
+        let movement = drag_movement(&gcube, sticker_at(&gcube, -1, 3, 4), [0., 1., 0.]);
+        assert_eq!(movement, Some(Movement::from_str("2L'").unwrap()));
+    }
+}
@@ -0,0 +1,113 @@
+use strum_macros::{Display, EnumIter};
+
+use crate::{Point3, Sticker};
+
+/// Selects the pieces relevant to a step of a solve, e.g. only the cross
+/// pieces. Pieces are identified by their solved position, so a masked piece
+/// stays masked wherever it is moved to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, EnumIter, Display)]
+pub enum PieceMask {
+    All,
+    Cross,
+    F2L,
+    Centers,
+    Edges,
+    Corners,
+}
+
+impl PieceMask {
+    /// Returns the position of the center of the piece that the sticker
+    /// belongs to, e.g. (0, 3, 0) on a 3x3 belongs to the piece at (0, 2, 0)
+    pub fn piece_position(sticker: Sticker) -> Point3 {
+        let [x, y, z] = sticker.dims.map(|d| d as i16);
+        let pull_in = |c: i16, n: i16| match c {
+            c if c == n => c - 1,
+            c if c == -n => c + 1,
+            c => c,
+        };
+        let p = sticker.initial;
+        Point3::new(pull_in(p.x, x), pull_in(p.y, y), pull_in(p.z, z))
+    }
+
+    /// Whether the sticker belongs to a piece selected by the mask.
+    pub fn contains(self, sticker: Sticker) -> bool {
+        // the coordinates of pieces in the outer layers along each axis
+        let outer = sticker.dims.map(|d| d as i16 - 1);
+        let piece = Self::piece_position(sticker);
+        // number of outer layers the piece is in, 3 for corners, 2 for edges
+        let n_outer = (0..3)
+            .filter(|&i| [piece.x, piece.y, piece.z][i].abs() == outer[i])
+            .count();
+        match self {
+            PieceMask::All => true,
+            PieceMask::Cross => piece.y == -outer[1] && n_outer < 3,
+            PieceMask::F2L => piece.y != outer[1],
+            PieceMask::Centers => n_outer == 1,
+            PieceMask::Edges => n_outer == 2,
+            PieceMask::Corners => n_outer == 3,
+        }
+    }
+
+    /// Returns the next mask, wrapping back around to All.
+    pub fn next(self) -> Self {
+        match self {
+            PieceMask::All => PieceMask::Cross,
+            PieceMask::Cross => PieceMask::F2L,
+            PieceMask::F2L => PieceMask::Centers,
+            PieceMask::Centers => PieceMask::Edges,
+            PieceMask::Edges => PieceMask::Corners,
+            PieceMask::Corners => PieceMask::All,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GCube;
+
+    fn count(size: usize, mask: PieceMask) -> usize {
+        GCube::new(size)
+            .stickers()
+            .filter(|s| mask.contains(*s))
+            .count()
+    }
+
+    #[test]
+    fn masks_select_pieces() {
+        assert_eq!(count(3, PieceMask::All), 54);
+        // D center and 4 D edges
+        assert_eq!(count(3, PieceMask::Cross), 1 + 4 * 2);
+        // everything but the U layer
+        assert_eq!(count(3, PieceMask::F2L), 54 - 9 - 12);
+        assert_eq!(count(3, PieceMask::Centers), 6);
+        assert_eq!(count(3, PieceMask::Edges), 24);
+        assert_eq!(count(3, PieceMask::Corners), 24);
+        assert_eq!(count(4, PieceMask::Centers), 24);
+        assert_eq!(count(4, PieceMask::Edges), 48);
+    }
+
+    #[test]
+    fn mask_follows_moved_pieces() {
+        let mut gcube = GCube::new(3);
+        let before: Vec<bool> = gcube
+            .stickers()
+            .map(|s| PieceMask::Cross.contains(s))
+            .collect();
+        gcube.apply_movements(&crate::scramble_to_movements("R U F2 D'").unwrap());
+        let after: Vec<bool> = gcube
+            .stickers()
+            .map(|s| PieceMask::Cross.contains(s))
+            .collect();
+        assert_eq!(before, after);
+    }
+
+    #[test]
+    fn next_cycles_through_all_masks() {
+        let mut mask = PieceMask::All;
+        for _ in 0..6 {
+            mask = mask.next();
+        }
+        assert_eq!(mask, PieceMask::All);
+    }
+}
@@ -0,0 +1,140 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Alg, Movement};
+
+/// Bookkeeping for an FMC attempt using premoves: moves thought of as done
+/// before the scramble, kept apart from the moves found after it, and merged
+/// into one linear solution. Turning the inverse scramble (NISS) is the same
+/// as premoving the inverse turns, so both are tracked as premoves.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Premoves {
+    pub scramble: Alg,
+    premoves: Vec<Movement>, // in the order they're done, so the latest first
+    moves: Vec<Movement>,    // done after the scramble
+}
+
+impl Premoves {
+    pub fn new(scramble: Alg) -> Self {
+        Self {
+            scramble,
+            ..Self::default()
+        }
+    }
+
+    /// Records a move done after the scramble and the moves so far.
+    pub fn push(&mut self, movement: Movement) {
+        self.moves.push(movement);
+    }
+
+    /// Records a premove, done before the scramble and the premoves so far.
+    pub fn push_premove(&mut self, movement: Movement) {
+        self.premoves.insert(0, movement);
+    }
+
+    /// Records a move done to the inverse scramble after the inverse moves
+    /// so far, which premoves its inverse.
+    pub fn push_inverse(&mut self, movement: Movement) {
+        self.push_premove(movement.inverse());
+    }
+
+    /// Forgets the last move, returning it.
+    pub fn pop(&mut self) -> Option<Movement> {
+        self.moves.pop()
+    }
+
+    /// Forgets the latest premove, returning it.
+    pub fn pop_premove(&mut self) -> Option<Movement> {
+        match self.premoves.is_empty() {
+            true => None,
+            false => Some(self.premoves.remove(0)),
+        }
+    }
+
+    /// Returns the premoves, in the order they're done before the scramble.
+    pub fn premoves(&self) -> Alg {
+        Alg::new(self.premoves.clone())
+    }
+
+    /// Returns the moves found after the scramble.
+    pub fn moves(&self) -> Alg {
+        Alg::new(self.moves.clone())
+    }
+
+    /// Returns the alg turning a solved cube to the state being solved: the
+    /// premoves, the scramble, then the moves.
+    pub fn setup(&self) -> Alg {
+        self.premoves()
+            .then(self.scramble.clone())
+            .then(self.moves())
+    }
+
+    /// Returns the alg turning a solved cube to the state being solved on
+    /// the inverse scramble: the moves inverted, the scramble inverted, then
+    /// the inverse moves (the premoves inverted).
+    pub fn inverse_setup(&self) -> Alg {
+        self.setup().inverse()
+    }
+
+    /// Returns the linear solution to the scramble: the moves, then the
+    /// premoves, simplified. Once the setup is solved, so is the scramble
+    /// turned by this.
+    pub fn solution(&self) -> Alg {
+        self.moves().then(self.premoves()).simplified()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GCube;
+
+    fn is_solved_by(scramble: &Alg, solution: &Alg) -> bool {
+        let mut gcube = GCube::new(3);
+        gcube.apply_movements(scramble.movements());
+        gcube.apply_movements(solution.movements());
+        gcube.is_solved()
+    }
+
+    #[test]
+    fn premoves_merge_into_the_solution() {
+        let scramble: Alg = "R U R' F2 D".parse().unwrap();
+        let mut attempt = Premoves::new(scramble.clone());
+        // D' F2 leaves R U R', undone by premoving R U' R', found from the
+        // last premove done to the first
+        for m in ["D'", "F2"] {
+            attempt.push(m.parse().unwrap());
+        }
+        for m in ["R'", "U'", "R"] {
+            attempt.push_premove(m.parse().unwrap());
+        }
+        assert_eq!(attempt.premoves().to_string(), "R U' R'");
+        assert_eq!(attempt.setup().to_string(), "R U' R' R U R' F2 D D' F2");
+        assert_eq!(attempt.solution().to_string(), "D' F2 R U' R'");
+        assert!(is_solved_by(&scramble, &attempt.solution()));
+        assert_eq!(attempt.pop_premove(), Some("R".parse().unwrap()));
+        assert_eq!(attempt.pop(), Some("F2".parse().unwrap()));
+        assert_eq!(attempt.solution().to_string(), "D' U' R'");
+    }
+
+    #[test]
+    fn inverse_moves_are_premoved_inverted() {
+        let scramble: Alg = "F R2 U' L B".parse().unwrap();
+        let mut attempt = Premoves::new(scramble.clone());
+        let normal: Alg = "B' L'".parse().unwrap();
+        let inverse: Alg = "F R2".parse().unwrap();
+        normal.movements().iter().for_each(|&m| attempt.push(m));
+        inverse
+            .movements()
+            .iter()
+            .for_each(|&m| attempt.push_inverse(m));
+        assert_eq!(attempt.premoves(), inverse.inverse());
+        // U is left on the inverse, so U' on the normal scramble
+        assert_eq!(
+            attempt.inverse_setup().to_string(),
+            "L B B' L' U R2 F' F R2"
+        );
+        attempt.push("U".parse().unwrap());
+        assert_eq!(attempt.solution().to_string(), "B' L' U R2 F'");
+        assert!(is_solved_by(&scramble, &attempt.solution()));
+    }
+}
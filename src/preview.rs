@@ -0,0 +1,66 @@
+use crate::{Alg, GCube, ParseMovementError};
+
+/// A candidate alg applied to a copy of a cube, leaving the cube itself as
+/// it is, e.g. to compare algs mid-FMC before committing to one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Preview {
+    pub alg: Alg,
+    /// the copy, with the alg applied
+    pub gcube: GCube,
+}
+
+impl Preview {
+    /// Returns a preview of alg applied to gcube, with its movements made
+    /// allowed on a cuboid as they would be when turned.
+    pub fn new(gcube: &GCube, alg: Alg) -> Self {
+        let alg: Alg = alg
+            .movements()
+            .iter()
+            .map(|&m| gcube.allowed_movement(m))
+            .collect::<Vec<_>>()
+            .into();
+        let mut preview = gcube.clone();
+        preview.apply_movements(alg.movements());
+        Self {
+            alg,
+            gcube: preview,
+        }
+    }
+
+    /// Returns a preview of the alg written in text applied to gcube.
+    pub fn parse(gcube: &GCube, text: &str) -> Result<Self, ParseMovementError> {
+        Ok(Self::new(gcube, text.parse()?))
+    }
+
+    /// Returns how many stickers the alg leaves out of place, e.g. 0 when it
+    /// solves the cube.
+    pub fn unsolved_stickers(&self) -> usize {
+        self.gcube.unsolved_stickers().len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn previews_leave_the_cube_alone() {
+        let mut gcube = GCube::new(3);
+        gcube.apply_movements(&crate::scramble_to_movements("R U").unwrap());
+        let before = gcube.clone();
+        let preview = Preview::parse(&gcube, "U' R'").unwrap();
+        assert_eq!(gcube, before);
+        assert_eq!(preview.unsolved_stickers(), 0);
+        assert!(
+            Preview::parse(&gcube, "R U R'")
+                .unwrap()
+                .unsolved_stickers()
+                > 0
+        );
+        assert!(Preview::parse(&gcube, "R Q").is_err());
+
+        // a 3x3x2 only half turns R
+        let preview = Preview::parse(&GCube::cuboid([3, 2, 3]), "R").unwrap();
+        assert_eq!(preview.alg.to_string(), "R2");
+    }
+}
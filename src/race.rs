@@ -0,0 +1,363 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    io,
+    net::ToSocketAddrs,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Alg, ClientId, Face, GCube, ServerEvent, WebSocketClient, WebSocketServer};
+
+/// What racing players tell each other, as JSON objects tagged by their type.
+/// The host relays every message from a player to every other player.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RaceMessage {
+    /// a new round, started by the host, with the scramble every player solves
+    Round {
+        round: u32,
+        size: usize,
+        scramble: Alg,
+    },
+    /// a player's cube, as faces ordered by U, R, F, D, L, then B face
+    State {
+        player: String,
+        size: usize,
+        facelets: String,
+    },
+    /// a player solved the round's scramble, in time seconds
+    Finished {
+        player: String,
+        round: u32,
+        time: f64,
+    },
+    Left {
+        player: String,
+    },
+}
+
+/// A scramble that every player in a race solves.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Round {
+    pub number: u32,
+    pub size: usize,
+    pub scramble: Alg,
+}
+
+/// Another player's cube, as last sent by them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Opponent {
+    pub size: usize,
+    /// ordered by U, R, F, D, L, then B face
+    pub facelets: Vec<Face>,
+}
+
+enum Peer {
+    Host(WebSocketServer),
+    Guest(WebSocketClient),
+}
+
+/// A race between players on different machines, solving the same
+/// scrambles. One player hosts the race, starting every round, and the others
+/// join it. Players are told apart by name, so names should be unique.
+pub struct Race {
+    name: String,
+    peer: Peer,
+    round: Option<Round>,
+    opponents: BTreeMap<String, Opponent>,
+    players: HashMap<ClientId, String>, // the player on each connection, for the host
+    times: Vec<(String, f64)>,          // of the players who finished the round
+    state: Option<RaceMessage>,         // the last state sent
+}
+
+impl Race {
+    /// Hosts a race at addr, e.g. "0.0.0.0:9002".
+    pub fn host(name: &str, addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Self::new(name, Peer::Host(WebSocketServer::bind(addr)?)))
+    }
+
+    /// Joins the race hosted at addr, blocking until the host accepts.
+    pub fn join(name: &str, addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Self::new(
+            name,
+            Peer::Guest(WebSocketClient::connect(addr)?),
+        ))
+    }
+
+    fn new(name: &str, peer: Peer) -> Self {
+        Self {
+            name: name.to_string(),
+            peer,
+            round: None,
+            opponents: BTreeMap::new(),
+            players: HashMap::new(),
+            times: vec![],
+            state: None,
+        }
+    }
+
+    pub fn is_host(&self) -> bool {
+        matches!(self.peer, Peer::Host(_))
+    }
+
+    pub fn round(&self) -> Option<&Round> {
+        self.round.as_ref()
+    }
+
+    pub fn opponents(&self) -> &BTreeMap<String, Opponent> {
+        &self.opponents
+    }
+
+    /// Returns the times of the players who finished the round, fastest
+    /// first.
+    pub fn results(&self) -> Vec<(String, f64)> {
+        let mut times = self.times.clone();
+        times.sort_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap());
+        times
+    }
+
+    fn send(&mut self, message: &RaceMessage) {
+        let json = serde_json::to_string(message).unwrap();
+        match &mut self.peer {
+            Peer::Host(server) => server.broadcast(&json),
+            Peer::Guest(client) => client.send(&json),
+        }
+    }
+
+    /// Starts a new round with the given scramble, if hosting the race.
+    pub fn start_round(&mut self, size: usize, scramble: Alg) {
+        if !self.is_host() {
+            return;
+        }
+        let number = self.round.as_ref().map_or(1, |r| r.number + 1);
+        self.round = Some(Round {
+            number,
+            size,
+            scramble: scramble.clone(),
+        });
+        self.times.clear();
+        self.send(&RaceMessage::Round {
+            round: number,
+            size,
+            scramble,
+        });
+    }
+
+    /// Sends the player's cube to the other players, if it has changed.
+    pub fn send_state(&mut self, gcube: &GCube) {
+        let state = RaceMessage::State {
+            player: self.name.clone(),
+            size: gcube.size,
+            facelets: gcube.facelets().iter().map(|f| f.to_string()).collect(),
+        };
+        if self.state.as_ref() != Some(&state) {
+            self.send(&state);
+            self.state = Some(state);
+        }
+    }
+
+    /// Tells the other players the round's scramble was solved in time
+    /// seconds, unless it already was.
+    pub fn finish(&mut self, time: f64) {
+        let round = match self.round.as_ref() {
+            Some(round) => round.number,
+            None => return,
+        };
+        if self.times.iter().all(|(player, _)| *player != self.name) {
+            self.times.push((self.name.clone(), time));
+            self.send(&RaceMessage::Finished {
+                player: self.name.clone(),
+                round,
+                time,
+            });
+        }
+    }
+
+    /// Handles what the other players sent since the last update, returning
+    /// the new round if the host started one. Call once a frame.
+    pub fn update(&mut self) -> Option<Round> {
+        let mut messages = vec![];
+        match &mut self.peer {
+            Peer::Host(server) => {
+                for event in server.poll() {
+                    match event {
+                        ServerEvent::Connected(id) => {
+                            // catch the new player up with the round and everyone's cube
+                            let round = self.round.as_ref().map(|r| RaceMessage::Round {
+                                round: r.number,
+                                size: r.size,
+                                scramble: r.scramble.clone(),
+                            });
+                            for message in round.iter().chain(self.state.iter()) {
+                                server.send(id, &serde_json::to_string(message).unwrap());
+                            }
+                            for (player, opponent) in self.opponents.iter() {
+                                let state = RaceMessage::State {
+                                    player: player.clone(),
+                                    size: opponent.size,
+                                    facelets: opponent
+                                        .facelets
+                                        .iter()
+                                        .map(|f| f.to_string())
+                                        .collect(),
+                                };
+                                server.send(id, &serde_json::to_string(&state).unwrap());
+                            }
+                        }
+                        ServerEvent::Message(id, json) => {
+                            let message = match serde_json::from_str(&json) {
+                                Ok(message) => message,
+                                Err(_) => continue,
+                            };
+                            // only the host starts rounds
+                            if matches!(message, RaceMessage::Round { .. }) {
+                                continue;
+                            }
+                            if let RaceMessage::State { player, .. } = &message {
+                                self.players.insert(id, player.clone());
+                            }
+                            server.broadcast(&json);
+                            messages.push(message);
+                        }
+                        ServerEvent::Disconnected(id) => {
+                            if let Some(player) = self.players.remove(&id) {
+                                let message = RaceMessage::Left { player };
+                                server.broadcast(&serde_json::to_string(&message).unwrap());
+                                messages.push(message);
+                            }
+                        }
+                    }
+                }
+            }
+            Peer::Guest(client) => {
+                messages.extend(
+                    client
+                        .poll()
+                        .iter()
+                        .filter_map(|json| serde_json::from_str(json).ok()),
+                );
+            }
+        }
+        let mut new_round = None;
+        for message in messages {
+            new_round = self.handle(message).or(new_round);
+        }
+        new_round
+    }
+
+    fn handle(&mut self, message: RaceMessage) -> Option<Round> {
+        match message {
+            RaceMessage::Round {
+                round,
+                size,
+                scramble,
+            } => {
+                let round = Round {
+                    number: round,
+                    size,
+                    scramble,
+                };
+                self.round = Some(round.clone());
+                self.times.clear();
+                return Some(round);
+            }
+            RaceMessage::State {
+                player,
+                size,
+                facelets,
+            } if player != self.name => {
+                let facelets: Option<Vec<Face>> = facelets.chars().map(Face::from_letter).collect();
+                if let Some(facelets) = facelets.filter(|f| f.len() == 6 * size * size) {
+                    self.opponents.insert(player, Opponent { size, facelets });
+                }
+            }
+            RaceMessage::Finished {
+                player,
+                round,
+                time,
+            } => {
+                let is_current = self.round.as_ref().map(|r| r.number) == Some(round);
+                if is_current && self.times.iter().all(|(p, _)| *p != player) {
+                    self.times.push((player, time));
+                }
+            }
+            RaceMessage::Left { player } => {
+                self.opponents.remove(&player);
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{str::FromStr, thread, time::Duration};
+
+    // updates both sides of a race until f returns true, or panics
+    fn update_until(host: &mut Race, guest: &mut Race, mut f: impl FnMut(&Race, &Race) -> bool) {
+        for _ in 0..500 {
+            host.update();
+            guest.update();
+            if f(host, guest) {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("timed out");
+    }
+
+    #[test]
+    fn players_race_the_same_scramble() {
+        let mut host = Race::host("alice", "127.0.0.1:0").unwrap();
+        let addr = match &host.peer {
+            Peer::Host(server) => server.local_addr().unwrap(),
+            Peer::Guest(_) => unreachable!(),
+        };
+        let guest = thread::spawn(move || Race::join("bob", addr).unwrap());
+        while !guest.is_finished() {
+            host.update();
+            thread::sleep(Duration::from_millis(10));
+        }
+        let mut guest = guest.join().unwrap();
+
+        let scramble = Alg::from_str("R U R' F2").unwrap();
+        host.start_round(3, scramble.clone());
+        let mut round = None;
+        update_until(&mut host, &mut guest, |_, guest| {
+            round = guest.round().cloned();
+            round.is_some()
+        });
+        assert_eq!(round.unwrap().scramble, scramble);
+
+        let mut gcube = GCube::new(3);
+        gcube.apply_movements(scramble.movements());
+        guest.send_state(&gcube);
+        host.send_state(&GCube::new(3));
+        update_until(&mut host, &mut guest, |host, guest| {
+            host.opponents().contains_key("bob") && guest.opponents().contains_key("alice")
+        });
+        assert_eq!(host.opponents()["bob"].facelets, gcube.facelets());
+
+        guest.finish(12.5);
+        host.finish(9.75);
+        update_until(&mut host, &mut guest, |host, guest| {
+            host.results().len() == 2 && guest.results().len() == 2
+        });
+        let expected = vec![("alice".to_string(), 9.75), ("bob".to_string(), 12.5)];
+        assert_eq!(host.results(), expected);
+        assert_eq!(guest.results(), expected);
+
+        // players who leave are forgotten
+        drop(guest);
+        for _ in 0..500 {
+            host.update();
+            if host.opponents().is_empty() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("timed out");
+    }
+}
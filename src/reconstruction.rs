@@ -0,0 +1,115 @@
+use std::str::FromStr;
+
+use crate::{scramble_to_movements, Movement, ParseMovementError};
+
+/// A single line of a reconstruction, made up of the line's movements and
+/// the text of its `//` comment (if any), e.g. `r U' R F' U' F // first block`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Step {
+    pub movements: Vec<Movement>,
+    pub label: Option<String>,
+}
+
+/// Multi-line alg input, such as a scramble followed by a solution, where
+/// each non-blank line is kept as its own labelled Step.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Reconstruction {
+    pub steps: Vec<Step>,
+}
+
+impl Reconstruction {
+    /// Returns all movements of the reconstruction, in order.
+    pub fn movements(&self) -> Vec<Movement> {
+        self.steps
+            .iter()
+            .flat_map(|step| step.movements.iter().cloned())
+            .collect()
+    }
+
+    /// Returns the label of every step that has one, in order.
+    pub fn labels(&self) -> Vec<&str> {
+        self.steps
+            .iter()
+            .filter_map(|step| step.label.as_deref())
+            .collect()
+    }
+}
+
+impl FromStr for Reconstruction {
+    type Err = ParseMovementError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut steps = vec![];
+        for line in s.lines() {
+            let line_offset = line.as_ptr() as usize - s.as_ptr() as usize;
+            // everything after a "//" is the line's label
+            let (alg, label) = match line.find("//") {
+                Some(i) => (&line[..i], Some(line[i + 2..].trim())),
+                None => (line, None),
+            };
+            let label = label.filter(|l| !l.is_empty()).map(str::to_string);
+            let movements = scramble_to_movements(alg).map_err(|e| e.offset_by(line_offset))?;
+            // blank lines don't form steps
+            if movements.is_empty() && label.is_none() {
+                continue;
+            }
+            steps.push(Step { movements, label });
+        }
+        Ok(Self { steps })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Move, Turn};
+
+    #[test]
+    fn parses_comments_and_blank_lines() {
+        let input = "
+        R U R' // insert pair
+
+        // LL
+        F  R U R' U' F' //
+        ";
+        let reconstruction = Reconstruction::from_str(input).unwrap();
+        assert_eq!(reconstruction.steps.len(), 3);
+        assert_eq!(
+            reconstruction.steps[0],
+            Step {
+                movements: vec![
+                    Movement::new(Move::R, Turn::Single),
+                    Movement::new(Move::U, Turn::Single),
+                    Movement::new(Move::R, Turn::Inverse),
+                ],
+                label: Some("insert pair".to_string()),
+            }
+        );
+        assert_eq!(
+            reconstruction.steps[1],
+            Step {
+                movements: vec![],
+                label: Some("LL".to_string()),
+            }
+        );
+        assert_eq!(reconstruction.steps[2].movements.len(), 6);
+        assert_eq!(reconstruction.steps[2].label, None);
+        assert_eq!(reconstruction.labels(), vec!["insert pair", "LL"]);
+        assert_eq!(reconstruction.movements().len(), 9);
+    }
+
+    #[test]
+    fn empty_input_has_no_steps() {
+        assert_eq!(
+            Reconstruction::from_str("\n  \n// \n").unwrap(),
+            Reconstruction::default()
+        );
+    }
+
+    #[test]
+    fn error_offset_is_into_whole_input() {
+        let err = Reconstruction::from_str("R U\n  D Q // bad").unwrap_err();
+        assert_eq!(err.token, "Q");
+        assert_eq!(err.offset, 8);
+    }
+}
@@ -0,0 +1,299 @@
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter};
+
+use crate::{
+    random_scramble, scramble_length, Alg, Face, GCube, Move, Movement, PieceMask, Rng, Turn,
+};
+
+/// A step of solving a big cube (4x4 and up) by reduction to a 3x3, trained
+/// on its own.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, EnumIter, Display, Serialize, Deserialize)]
+pub enum ReductionStep {
+    /// solving each face's centers, from a full scramble
+    Centers,
+    /// pairing the edges' wings, from a scramble that keeps the centers solved
+    #[strum(serialize = "Edge pairing")]
+    Edges,
+}
+
+impl ReductionStep {
+    /// Returns the mask showing the pieces the step solves.
+    pub fn mask(self) -> PieceMask {
+        match self {
+            ReductionStep::Centers => PieceMask::Centers,
+            ReductionStep::Edges => PieceMask::Edges,
+        }
+    }
+
+    /// Returns a scramble of an NxN cube to practise the step from.
+    pub fn scramble(self, size: usize, rng: &mut Rng) -> Alg {
+        match self {
+            ReductionStep::Centers => random_scramble(size, scramble_length(size), rng),
+            ReductionStep::Edges => edge_scramble(size, rng),
+        }
+    }
+
+    /// Returns whether the step is solved on a cube, which for edge pairing
+    /// includes the centers still being solved.
+    pub fn is_solved(self, gcube: &GCube) -> bool {
+        match self {
+            ReductionStep::Centers => are_centers_solved(gcube),
+            ReductionStep::Edges => is_reduced(gcube),
+        }
+    }
+
+    /// Returns whether the step can be timed from a cube's state, which for
+    /// edge pairing needs its centers solved, as its scrambles keep them.
+    pub fn can_start(self, gcube: &GCube) -> bool {
+        match self {
+            ReductionStep::Centers => true,
+            ReductionStep::Edges => are_centers_solved(gcube),
+        }
+    }
+}
+
+/// Whether a big cube's (4x4 and up) centers are solved, each face's one
+/// colour, in any orientation.
+pub fn are_centers_solved(gcube: &GCube) -> bool {
+    let (n, facelets) = match big_cube_facelets(gcube) {
+        Some(big) => big,
+        None => return false,
+    };
+    let at = |f: usize, (row, col): (usize, usize)| facelets[(f * n + row) * n + col];
+    let inner = 1..n - 1;
+    (0..6).all(|f| {
+        let centers = inner
+            .clone()
+            .flat_map(|row| inner.clone().map(move |col| (row, col)));
+        is_uniform(centers.map(|cell| at(f, cell)))
+    }) && is_center_scheme((0..6).map(|f| at(f, (1, 1))).collect())
+}
+
+/// Whether a big cube's edges are paired, each edge's wings showing the same
+/// two colours, whether or not its centers are solved.
+pub fn are_edges_paired(gcube: &GCube) -> bool {
+    let (n, facelets) = match big_cube_facelets(gcube) {
+        Some(big) => big,
+        None => return false,
+    };
+    let at = |f: usize, (row, col): (usize, usize)| facelets[(f * n + row) * n + col];
+    // every edge's wings show the same two colours when the strips along
+    // every face's sides are each one colour
+    (0..6).all(|f| {
+        (0..4).all(|side| {
+            let strip = |i| [(0, i), (n - 1, i), (i, 0), (i, n - 1)][side];
+            is_uniform((1..n - 1).map(|i| at(f, strip(i))))
+        })
+    })
+}
+
+/// Whether a big cube is reduced to a 3x3, its centers solved and edges
+/// paired, so what's left is solved like a 3x3 (and any parity).
+pub fn is_reduced(gcube: &GCube) -> bool {
+    are_centers_solved(gcube) && are_edges_paired(gcube)
+}
+
+// returns the size and facelets of a cube of 4x4 or bigger, which has centers
+// and edges to reduce, or None for smaller cubes and cuboids
+fn big_cube_facelets(gcube: &GCube) -> Option<(usize, Vec<Face>)> {
+    (gcube.is_cube() && gcube.size >= 4).then(|| (gcube.size, gcube.facelets()))
+}
+
+/// When a timed big cube solve first reached each stage of reduction, in
+/// seconds since it started, e.g. to see which stage takes longest.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ReductionSplits {
+    /// when its centers were solved
+    pub centers: Option<f64>,
+    /// when it was reduced to a 3x3, its edges paired with the centers solved
+    pub reduced: Option<f64>,
+}
+
+impl ReductionSplits {
+    /// Records the stages a cube being solved has reached at time, since
+    /// the solve started, the first time it reaches each.
+    pub fn update(&mut self, gcube: &GCube, time: f64) {
+        if self.centers.is_none() && are_centers_solved(gcube) {
+            self.centers = Some(time);
+        }
+        if self.reduced.is_none() && is_reduced(gcube) {
+            self.reduced = Some(time);
+        }
+    }
+}
+
+/// How long a step took on an NxN cube.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReductionTime {
+    pub size: usize,
+    pub step: ReductionStep,
+    /// in seconds
+    pub time: f64,
+}
+
+/// Returns the mean of the times of step on NxN cubes, or None if there
+/// aren't any.
+pub fn reduction_mean(times: &[ReductionTime], size: usize, step: ReductionStep) -> Option<f64> {
+    let times: Vec<f64> = times
+        .iter()
+        .filter(|t| t.size == size && t.step == step)
+        .map(|t| t.time)
+        .collect();
+    (!times.is_empty()).then(|| times.iter().sum::<f64>() / times.len() as f64)
+}
+
+fn is_uniform(mut faces: impl Iterator<Item = Face>) -> bool {
+    let first = faces.next();
+    faces.all(|f| Some(f) == first)
+}
+
+// whether the faces' centers, in the order U, R, F, D, L, B, are coloured
+// like a solved cube in some orientation
+fn is_center_scheme(centers: Vec<Face>) -> bool {
+    let rotations = [Move::X, Move::Y, Move::Z];
+    // every orientation is some x, y then z rotations from solved
+    (0..64).any(|i: usize| {
+        let mut orientation = GCube::new(1);
+        for (axis, &m) in rotations.iter().enumerate() {
+            for _ in 0..i / 4usize.pow(axis as u32) % 4 {
+                orientation.apply_movement(&Movement::new(m, Turn::Single));
+            }
+        }
+        orientation.facelets() == centers
+    })
+}
+
+// a scramble of an NxN cube keeping its centers solved: conjugates of outer
+// turns by inner layer turns, e.g. 2R' (U F R U' F' R') 2R, where the outer
+// turns turn each face whose centers the inner layer moved back to where it
+// was, and then outer turns
+fn edge_scramble(size: usize, rng: &mut Rng) -> Alg {
+    let faces = [Move::U, Move::R, Move::F, Move::D, Move::L, Move::B];
+    let turns = [Turn::Single, Turn::Double, Turn::Inverse];
+    let mut movements = vec![];
+    for _ in 0..3 * size {
+        let m = faces[rng.below(3)];
+        let depth = 2 + rng.below(size / 2 - 1) as u8;
+        let inner = Movement::new(m, turns[rng.below(3)])
+            .with_depth(depth)
+            .unwrap();
+        movements.push(inner);
+        // the quarter turns of each face
+        let mut quarter_turns = [0; 6];
+        for &movement in random_scramble(3, 3 + rng.below(3), rng).movements() {
+            let Movement(face, turn, _) = movement;
+            quarter_turns[faces.iter().position(|&f| f == face).unwrap()] += turn as usize;
+            movements.push(movement);
+        }
+        for (&face, &n) in faces.iter().zip(quarter_turns.iter()) {
+            // faces on the inner layer's axis keep their centers
+            if n % 4 != 0 && !is_on_axis(face, m) {
+                let undo = [Turn::Inverse, Turn::Double, Turn::Single][n % 4 - 1];
+                movements.push(Movement::new(face, undo));
+            }
+        }
+        movements.push(inner.inverse());
+    }
+    movements.extend_from_slice(random_scramble(3, scramble_length(3), rng).movements());
+    Alg::new(movements)
+}
+
+// whether face is turned about the same axis as m, e.g. L and R
+fn is_on_axis(face: Move, m: Move) -> bool {
+    let axis = |m: Move| match m {
+        Move::U | Move::D => 0,
+        Move::R | Move::L => 1,
+        _ => 2,
+    };
+    axis(face) == axis(m)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edge_scrambles_keep_centers_solved() {
+        let mut rng = Rng::new(4);
+        for size in [4, 5, 6] {
+            let mut gcube = GCube::new(size);
+            gcube.apply_movements(ReductionStep::Edges.scramble(size, &mut rng).movements());
+            assert!(ReductionStep::Centers.is_solved(&gcube), "{}", size);
+            assert!(!ReductionStep::Edges.is_solved(&gcube), "{}", size);
+        }
+    }
+
+    #[test]
+    fn steps_are_solved_in_any_orientation() {
+        let mut gcube = GCube::new(4);
+        assert!(ReductionStep::Centers.is_solved(&gcube));
+        assert!(ReductionStep::Edges.is_solved(&gcube));
+        // outer turns keep the centers solved and the edges paired
+        gcube.apply_movements(&crate::scramble_to_movements("R U F' x y2").unwrap());
+        assert!(ReductionStep::Edges.is_solved(&gcube));
+        gcube.apply_movements(&crate::scramble_to_movements("2R").unwrap());
+        assert!(!ReductionStep::Centers.is_solved(&gcube));
+        gcube.apply_movements(&crate::scramble_to_movements("2R'").unwrap());
+        assert!(ReductionStep::Edges.is_solved(&gcube));
+        assert!(!ReductionStep::Centers.is_solved(&GCube::new(3)));
+
+        // the centers of opposite faces swapped aren't solved
+        let mut gcube = GCube::new(4);
+        gcube.apply_movements(&crate::scramble_to_movements("Uw2 3Uw2").unwrap());
+        assert!(!ReductionStep::Centers.is_solved(&gcube));
+    }
+
+    #[test]
+    fn reduction_stages_are_detected() {
+        let solved = GCube::new(5);
+        assert!(is_reduced(&solved));
+        // two center facelets swapped leave the edges paired
+        let mut facelets = solved.facelets();
+        facelets.swap(6, 25 + 6);
+        let centers_swapped = GCube::from_facelets(5, &facelets).unwrap();
+        assert!(!are_centers_solved(&centers_swapped));
+        assert!(are_edges_paired(&centers_swapped));
+        assert!(!is_reduced(&centers_swapped));
+        // and inner slices break both up
+        let mut gcube = GCube::new(4);
+        gcube.apply_movements(&crate::scramble_to_movements("2R U 2R'").unwrap());
+        assert!(!are_centers_solved(&gcube) && !are_edges_paired(&gcube));
+        assert!(!is_reduced(&GCube::new(3)) && !are_edges_paired(&GCube::new(3)));
+        assert!(ReductionStep::Centers.can_start(&gcube));
+        assert!(!ReductionStep::Edges.can_start(&gcube));
+    }
+
+    #[test]
+    fn splits_are_when_each_stage_is_first_reached() {
+        let mut scrambled = GCube::new(4);
+        scrambled.apply_movements(&crate::scramble_to_movements("2R U 2R'").unwrap());
+        let mut centers_solved = GCube::new(4);
+        let scramble = ReductionStep::Edges.scramble(4, &mut Rng::new(1));
+        centers_solved.apply_movements(scramble.movements());
+        let mut splits = ReductionSplits::default();
+        splits.update(&scrambled, 1.);
+        assert_eq!(splits, ReductionSplits::default());
+        splits.update(&centers_solved, 2.);
+        splits.update(&scrambled, 3.);
+        splits.update(&GCube::new(4), 4.);
+        splits.update(&GCube::new(4), 5.);
+        let expected = ReductionSplits {
+            centers: Some(2.),
+            reduced: Some(4.),
+        };
+        assert_eq!(splits, expected);
+    }
+
+    #[test]
+    fn means_by_size_and_step() {
+        let time = |size, step, time| ReductionTime { size, step, time };
+        let times = [
+            time(4, ReductionStep::Centers, 20.),
+            time(4, ReductionStep::Centers, 30.),
+            time(5, ReductionStep::Centers, 60.),
+            time(4, ReductionStep::Edges, 40.),
+        ];
+        assert_eq!(reduction_mean(&times, 4, ReductionStep::Centers), Some(25.));
+        assert_eq!(reduction_mean(&times, 5, ReductionStep::Edges), None);
+    }
+}
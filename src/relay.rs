@@ -0,0 +1,101 @@
+use crate::{random_scramble, scramble_length, Alg, GCube, Rng};
+
+/// A relay of several cubes (e.g. 2x2, 3x3, then 4x4) solved one after
+/// another, timed together. Every leg's cube is scrambled up front.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Relay {
+    cubes: Vec<GCube>,
+    scrambles: Vec<Alg>,
+    splits: Vec<f64>, // the relay's time as each leg was solved
+}
+
+impl Relay {
+    /// Scrambles a cube of each size, in the order they're solved.
+    pub fn new(sizes: &[usize], rng: &mut Rng) -> Self {
+        let scrambles: Vec<Alg> = sizes
+            .iter()
+            .map(|&size| random_scramble(size, scramble_length(size), rng))
+            .collect();
+        let cubes = sizes
+            .iter()
+            .zip(scrambles.iter())
+            .map(|(&size, scramble)| {
+                let mut gcube = GCube::new(size);
+                gcube.apply_movements(scramble.movements());
+                gcube
+            })
+            .collect();
+        Self {
+            cubes,
+            scrambles,
+            splits: vec![],
+        }
+    }
+
+    pub fn leg_count(&self) -> usize {
+        self.cubes.len()
+    }
+
+    /// Returns the index of the leg being solved, or the number of legs once
+    /// the relay is finished.
+    pub fn current(&self) -> usize {
+        self.splits.len()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.current() == self.leg_count()
+    }
+
+    /// Returns every leg's cube, as scrambled, or as solved for the finished
+    /// legs.
+    pub fn cubes(&self) -> &[GCube] {
+        &self.cubes
+    }
+
+    pub fn scramble(&self, leg: usize) -> &Alg {
+        &self.scrambles[leg]
+    }
+
+    /// Returns the relay's time as each finished leg was solved.
+    pub fn splits(&self) -> &[f64] {
+        &self.splits
+    }
+
+    /// Finishes the current leg with its solved cube, at time seconds into
+    /// the relay, returning the next leg's cube to solve, if any.
+    pub fn finish_leg(&mut self, gcube: &GCube, time: f64) -> Option<GCube> {
+        if self.is_finished() {
+            return None;
+        }
+        let leg = self.current();
+        self.cubes[leg] = gcube.clone();
+        self.splits.push(time);
+        self.cubes.get(leg + 1).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legs_are_solved_in_order() {
+        let mut relay = Relay::new(&[2, 3, 4], &mut Rng::new(1));
+        assert_eq!(relay.leg_count(), 3);
+        assert_eq!(relay.scramble(2).len(), scramble_length(4));
+        assert!(relay.cubes().iter().all(|c| !c.is_solved()));
+
+        let next = relay.finish_leg(&GCube::new(2), 4.5).unwrap();
+        assert_eq!(next, relay.cubes()[1]);
+        assert_eq!(next.size, 3);
+        assert!(relay.cubes()[0].is_solved());
+        relay.finish_leg(&GCube::new(3), 15.);
+        assert_eq!(relay.current(), 2);
+        assert_eq!(relay.finish_leg(&GCube::new(4), 60.), None);
+        assert!(relay.is_finished());
+        assert_eq!(relay.splits(), [4.5, 15., 60.]);
+        // nothing left to finish
+        assert_eq!(relay.finish_leg(&GCube::new(4), 70.), None);
+        assert_eq!(relay.splits().len(), 3);
+    }
+}
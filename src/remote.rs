@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Alg, Arrow, BroadcastServer, CubeEvent, Movement, ServerEvent};
+
+/// What a client of a BroadcastServer can ask the cube to do, sent as a JSON
+/// object tagged by its type, e.g. `{"type":"alg","alg":"R U R' U'"}`. Move
+/// and scramble commands look like the events of the same type, so the
+/// events broadcast by one cube can drive another.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Command {
+    Move {
+        movement: Movement,
+    },
+    Alg {
+        alg: Alg,
+    },
+    /// scrambles the cube with the given scramble, or a random one if it's
+    /// missing
+    Scramble {
+        #[serde(default)]
+        scramble: Option<Alg>,
+    },
+    /// draws arrows over the cube, replacing any drawn before, e.g.
+    /// `{"type":"arrows","arrows":["U0U2","U2U8","U8U0"]}`
+    Arrows {
+        arrows: Vec<Arrow>,
+    },
+}
+
+impl BroadcastServer {
+    /// Returns the commands sent by clients, from the server events returned
+    /// by update. Invalid commands are answered with an Error event, sent to
+    /// just the client that sent them.
+    pub fn commands(&mut self, events: &[ServerEvent]) -> Vec<Command> {
+        let mut commands = vec![];
+        for event in events {
+            if let ServerEvent::Message(id, message) = event {
+                match serde_json::from_str(message) {
+                    Ok(command) => commands.push(command),
+                    Err(e) => self.send_to(
+                        *id,
+                        &CubeEvent::Error {
+                            message: e.to_string(),
+                        },
+                    ),
+                }
+            }
+        }
+        commands
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GCube, WebSocketClient};
+    use std::{str::FromStr, thread, time::Duration};
+
+    #[test]
+    fn commands_are_tagged_json() {
+        let command = |json| serde_json::from_str::<Command>(json).unwrap();
+        assert_eq!(
+            command(r#"{"type":"move","movement":"R'"}"#),
+            Command::Move {
+                movement: Movement::from_str("R'").unwrap()
+            }
+        );
+        assert_eq!(
+            command(r#"{"type":"alg","alg":"R U"}"#),
+            Command::Alg {
+                alg: Alg::from_str("R U").unwrap()
+            }
+        );
+        assert_eq!(
+            command(r#"{"type":"scramble"}"#),
+            Command::Scramble { scramble: None }
+        );
+        // broadcast events drive other cubes
+        let scramble = Alg::from_str("R U F").unwrap();
+        let event = serde_json::to_string(&CubeEvent::Scramble {
+            scramble: scramble.clone(),
+        })
+        .unwrap();
+        assert_eq!(
+            command(&event),
+            Command::Scramble {
+                scramble: Some(scramble)
+            }
+        );
+        assert!(serde_json::from_str::<Command>(r#"{"type":"alg","alg":"R Q"}"#).is_err());
+        assert_eq!(
+            command(r#"{"type":"arrows","arrows":["U0U2","U2U8"]}"#),
+            Command::Arrows {
+                arrows: crate::parse_arrows("U0U2,U2U8").unwrap()
+            }
+        );
+    }
+
+    #[test]
+    fn clients_send_commands() {
+        let mut server = BroadcastServer::bind("127.0.0.1:0").unwrap();
+        let addr = server.server().local_addr().unwrap();
+        let client = thread::spawn(move || {
+            let mut client = WebSocketClient::connect(addr).unwrap();
+            client.send(r#"{"type":"turn"}"#);
+            client.send(r#"{"type":"alg","alg":"R U"}"#);
+            client
+        });
+        let mut commands = vec![];
+        for _ in 0..500 {
+            let events = server.update(&GCube::new(3));
+            commands.extend(server.commands(&events));
+            if !commands.is_empty() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(
+            commands,
+            [Command::Alg {
+                alg: Alg::from_str("R U").unwrap()
+            }]
+        );
+
+        // the invalid command is answered with an error, after the state
+        let mut client = client.join().unwrap();
+        let mut messages = vec![];
+        for _ in 0..500 {
+            messages.extend(client.poll());
+            if messages.len() >= 2 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        let event: CubeEvent = serde_json::from_str(&messages[1]).unwrap();
+        assert!(matches!(event, CubeEvent::Error { .. }));
+    }
+}
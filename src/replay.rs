@@ -0,0 +1,394 @@
+use std::{fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use thiserror::Error;
+
+use crate::{Alg, GCube, Move, Movement, Penalty, TimedAlg, TimedNode, Turn};
+
+/// The version of the replay format this version of cubedesu writes, and
+/// the newest it reads.
+pub const REPLAY_VERSION: u32 = 1;
+
+// the start of a binary replay, before its version
+const MAGIC: &[u8; 4] = b"CDR\0";
+// a pause in a binary replay's moves, where a move would be
+const PAUSE: u8 = 0xff;
+// set in a binary movement's turn when a timestamp follows it
+const TIMED: u8 = 0x80;
+
+/// What's known about a replay's solve, besides its moves.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReplayMetadata {
+    /// who solved it
+    pub solver: String,
+    /// when it was finished, in unix seconds
+    pub date: Option<f64>,
+    /// the solve's time in seconds, without the penalty, from when the timer
+    /// started until it stopped (after the last move's turn finished)
+    pub time: Option<f64>,
+    pub penalty: Penalty,
+    pub comment: String,
+}
+
+/// A recorded solve: the puzzle, its scramble and the moves of the solve,
+/// each timestamped in milliseconds since the solve started, so it plays
+/// back the same every time. Saved as `.cdr` files, which are compact
+/// binary, or as JSON, see [Replay::save].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    /// the version of the format the replay was written in
+    pub version: u32,
+    /// the cube's dimensions, see [GCube::cuboid]
+    pub dims: [usize; 3],
+    pub scramble: Alg,
+    pub moves: TimedAlg,
+    #[serde(default)]
+    pub metadata: ReplayMetadata,
+}
+
+/// Why a replay couldn't be loaded.
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("Invalid JSON replay: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Replay version {0} is newer than this version of cubedesu reads")]
+    UnsupportedVersion(u32),
+    #[error("The replay is cut short or corrupted")]
+    Corrupted,
+}
+
+impl Replay {
+    pub fn new(dims: [usize; 3], scramble: Alg, moves: TimedAlg, metadata: ReplayMetadata) -> Self {
+        Self {
+            version: REPLAY_VERSION,
+            dims,
+            scramble,
+            moves,
+            metadata,
+        }
+    }
+
+    /// Returns the cube as scrambled, before the first move.
+    pub fn scrambled(&self) -> GCube {
+        let mut gcube = GCube::cuboid(self.dims);
+        gcube.apply_movements(self.scramble.movements());
+        gcube
+    }
+
+    /// Returns each movement with the milliseconds since the start it's
+    /// made at. A movement without a timestamp is made along with the one
+    /// before it, and pauses are dropped.
+    pub fn timeline(&self) -> Vec<(u32, Movement)> {
+        let mut at = 0;
+        self.moves
+            .0
+            .iter()
+            .filter_map(|node| match *node {
+                TimedNode::Movement(movement, time) => {
+                    at = time.unwrap_or(at);
+                    Some((at, movement))
+                }
+                TimedNode::Pause => None,
+            })
+            .collect()
+    }
+
+    /// Returns the cube as it was ms milliseconds into the solve, with every
+    /// movement made by then applied.
+    pub fn state_at(&self, ms: u32) -> GCube {
+        let mut gcube = self.scrambled();
+        for (_, movement) in self.timeline().into_iter().filter(|&(at, _)| at <= ms) {
+            gcube.apply_movement(&movement);
+        }
+        gcube
+    }
+
+    /// Returns how long the replay lasts in milliseconds: the solve's time if
+    /// it's known, or else until the last movement.
+    pub fn duration(&self) -> u32 {
+        let last = self.timeline().last().map_or(0, |&(at, _)| at);
+        match self.metadata.time {
+            Some(time) => ((time * 1000.).round() as u32).max(last),
+            None => last,
+        }
+    }
+
+    /// Saves the replay to path, as JSON if it ends in `.json`, or else as a
+    /// compact binary `.cdr`.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if path.extension().is_some_and(|e| e == "json") {
+            fs::write(path, serde_json::to_string_pretty(self)?)
+        } else {
+            fs::write(path, self.to_bytes())
+        }
+    }
+
+    /// Loads a replay saved by [Replay::save], as either format.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ReplayError> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    /// Returns the replay as a binary `.cdr`: "CDR\0", then the version,
+    /// dimensions, scramble and moves, and lastly the metadata as JSON.
+    /// Numbers are LEB128 varints, so most take a byte or two, and each
+    /// movement takes 3 bytes (its move, turn and depth), and its timestamp.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        write_varint(&mut bytes, REPLAY_VERSION as u64);
+        for &d in self.dims.iter() {
+            write_varint(&mut bytes, d as u64);
+        }
+        write_varint(&mut bytes, self.scramble.len() as u64);
+        for &movement in self.scramble.movements() {
+            write_movement(&mut bytes, movement, None);
+        }
+        write_varint(&mut bytes, self.moves.0.len() as u64);
+        for node in self.moves.0.iter() {
+            match *node {
+                TimedNode::Movement(movement, time) => write_movement(&mut bytes, movement, time),
+                TimedNode::Pause => bytes.push(PAUSE),
+            }
+        }
+        let metadata = serde_json::to_vec(&self.metadata).unwrap();
+        write_varint(&mut bytes, metadata.len() as u64);
+        bytes.extend(metadata);
+        bytes
+    }
+
+    /// Reads a replay from either a binary `.cdr` or JSON.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ReplayError> {
+        let replay: Self = match bytes.strip_prefix(MAGIC) {
+            Some(rest) => {
+                let mut reader = Reader(rest);
+                let version = reader.varint()? as u32;
+                if version > REPLAY_VERSION {
+                    return Err(ReplayError::UnsupportedVersion(version));
+                }
+                reader.replay(version)?
+            }
+            None => serde_json::from_slice(bytes)?,
+        };
+        if replay.version > REPLAY_VERSION {
+            return Err(ReplayError::UnsupportedVersion(replay.version));
+        }
+        if replay.dims.contains(&0) {
+            return Err(ReplayError::Corrupted);
+        }
+        Ok(replay)
+    }
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        bytes.push(n as u8 | 0x80);
+        n >>= 7;
+    }
+    bytes.push(n as u8);
+}
+
+fn write_movement(bytes: &mut Vec<u8>, Movement(m, turn, depth): Movement, time: Option<u32>) {
+    let m = Move::iter().position(|other| other == m).unwrap();
+    let timed = if time.is_some() { TIMED } else { 0 };
+    bytes.extend([m as u8, turn as u8 | timed, depth]);
+    if let Some(time) = time {
+        write_varint(bytes, time as u64);
+    }
+}
+
+// reads a binary replay after its magic, failing if it runs out
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn byte(&mut self) -> Result<u8, ReplayError> {
+        let (&first, rest) = self.0.split_first().ok_or(ReplayError::Corrupted)?;
+        self.0 = rest;
+        Ok(first)
+    }
+
+    fn varint(&mut self) -> Result<u64, ReplayError> {
+        let mut n = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            n |= ((byte & 0x7f) as u64) << shift;
+            if byte < 0x80 {
+                return Ok(n);
+            }
+        }
+        Err(ReplayError::Corrupted)
+    }
+
+    // a movement, with its timestamp if it has one, or None for a pause
+    fn node(&mut self) -> Result<TimedNode, ReplayError> {
+        let m = self.byte()?;
+        if m == PAUSE {
+            return Ok(TimedNode::Pause);
+        }
+        let m = Move::iter().nth(m as usize).ok_or(ReplayError::Corrupted)?;
+        let turn = self.byte()?;
+        let is_timed = turn & TIMED != 0;
+        let turn = Turn::iter()
+            .find(|&t| t as u8 == turn & !TIMED)
+            .ok_or(ReplayError::Corrupted)?;
+        let depth = self.byte()?;
+        let time = match is_timed {
+            true => Some(self.varint()? as u32),
+            false => None,
+        };
+        Ok(TimedNode::Movement(Movement(m, turn, depth), time))
+    }
+
+    fn replay(&mut self, version: u32) -> Result<Replay, ReplayError> {
+        let mut dims = [0; 3];
+        for d in dims.iter_mut() {
+            *d = self.varint()? as usize;
+        }
+        let mut scramble = vec![];
+        for _ in 0..self.varint()? {
+            match self.node()? {
+                TimedNode::Movement(movement, None) => scramble.push(movement),
+                _ => return Err(ReplayError::Corrupted),
+            }
+        }
+        let mut moves = vec![];
+        for _ in 0..self.varint()? {
+            moves.push(self.node()?);
+        }
+        let length = self.varint()? as usize;
+        let metadata = self.0.get(..length).ok_or(ReplayError::Corrupted)?;
+        Ok(Replay {
+            version,
+            dims,
+            scramble: Alg::new(scramble),
+            moves: TimedAlg(moves),
+            metadata: serde_json::from_slice(metadata)?,
+        })
+    }
+}
+
+/// Plays a replay back in real time, giving its movements as their time
+/// comes. Times are passed in by the caller, in seconds from any fixed point.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReplayPlayer {
+    timeline: Vec<(u32, Movement)>,
+    position: usize,
+    started_at: f64,
+    // in seconds
+    duration: f64,
+}
+
+impl ReplayPlayer {
+    /// Starts playing replay's moves from now, on its scrambled cube.
+    pub fn new(replay: &Replay, now: f64) -> Self {
+        Self {
+            timeline: replay.timeline(),
+            position: 0,
+            started_at: now,
+            duration: replay.duration() as f64 / 1000.,
+        }
+    }
+
+    /// Returns the movements that have come due since the last call.
+    pub fn due(&mut self, now: f64) -> Vec<Movement> {
+        let ms = ((now - self.started_at) * 1000.).max(0.);
+        let due: Vec<Movement> = self.timeline[self.position..]
+            .iter()
+            .take_while(|&&(at, _)| at as f64 <= ms)
+            .map(|&(_, movement)| movement)
+            .collect();
+        self.position += due.len();
+        due
+    }
+
+    /// Returns the seconds since the replay started playing.
+    pub fn elapsed(&self, now: f64) -> f64 {
+        now - self.started_at
+    }
+
+    /// Returns how long the replay lasts in seconds, see [Replay::duration].
+    pub fn duration(&self) -> f64 {
+        self.duration
+    }
+
+    /// Returns whether every movement has been given.
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.timeline.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn replay() -> Replay {
+        Replay::new(
+            [3; 3],
+            Alg::from_str("R U R' U'").unwrap(),
+            TimedAlg::from_str("U@0 R@150 . U'@400 R'@700").unwrap(),
+            ReplayMetadata {
+                solver: "desu".to_string(),
+                date: Some(1_790_000_000.5),
+                time: Some(0.75),
+                penalty: Penalty::PlusTwo,
+                comment: String::new(),
+            },
+        )
+    }
+
+    #[test]
+    fn plays_back_the_same_every_time() {
+        let replay = replay();
+        assert!(!replay.scrambled().is_solved());
+        assert!(!replay.state_at(699).is_solved());
+        assert!(replay.state_at(700).is_solved());
+        assert_eq!(replay.state_at(150), replay.state_at(399));
+        assert_eq!(replay.duration(), 750);
+
+        let mut player = ReplayPlayer::new(&replay, 10.);
+        assert_eq!(player.due(10.), [Movement::from_str("U").unwrap()]);
+        assert_eq!(player.due(10.2), [Movement::from_str("R").unwrap()]);
+        assert_eq!(player.due(10.3), []);
+        // a slow frame gets every movement it missed
+        assert_eq!(player.due(11.).len(), 2);
+        assert!(player.is_finished());
+        assert_eq!(player.duration(), 0.75);
+    }
+
+    #[test]
+    fn round_trips_through_binary_and_json() {
+        let mut replay = replay();
+        // a movement without a timestamp, and a big cube's inner layers
+        replay.dims = [5; 3];
+        replay.moves.0.push(TimedNode::from_str("3Rw2").unwrap());
+        replay.scramble = replay.scramble.then(Alg::from_str("2L' Fw").unwrap());
+
+        let bytes = replay.to_bytes();
+        assert!(bytes.starts_with(b"CDR\0\x01"));
+        assert_eq!(Replay::from_bytes(&bytes).unwrap(), replay);
+        let json = serde_json::to_vec(&replay).unwrap();
+        assert!(bytes.len() < json.len());
+        assert_eq!(Replay::from_bytes(&json).unwrap(), replay);
+
+        for cut in [12, bytes.len() - 1] {
+            assert!(matches!(
+                Replay::from_bytes(&bytes[..cut]),
+                Err(ReplayError::Corrupted)
+            ));
+        }
+        assert!(matches!(
+            Replay::from_bytes(b"CDR\0\x02"),
+            Err(ReplayError::UnsupportedVersion(2))
+        ));
+        assert!(Replay::from_bytes(b"not a replay").is_err());
+
+        let path = std::env::temp_dir().join("cubedesu_replay_test.cdr");
+        replay.save(&path).unwrap();
+        assert_eq!(Replay::load(&path).unwrap(), replay);
+        fs::remove_file(&path).unwrap();
+    }
+}
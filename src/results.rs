@@ -0,0 +1,213 @@
+use std::fmt::{self, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Alg;
+
+/// A penalty added to a solve's time, as in WCA regulations.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Penalty {
+    #[default]
+    None,
+    /// 2 seconds added, e.g. for inspecting a little too long
+    PlusTwo,
+    /// did not finish, the solve doesn't count
+    Dnf,
+}
+
+impl Penalty {
+    /// Returns the penalty for inspecting the cube for seconds: +2 after 15
+    /// seconds, and DNF after 17.
+    pub fn for_inspection(seconds: f64) -> Self {
+        match seconds {
+            s if s > 17. => Penalty::Dnf,
+            s if s > 15. => Penalty::PlusTwo,
+            _ => Penalty::None,
+        }
+    }
+
+    /// Returns the next penalty, wrapping back around to none.
+    pub fn next(self) -> Self {
+        match self {
+            Penalty::None => Penalty::PlusTwo,
+            Penalty::PlusTwo => Penalty::Dnf,
+            Penalty::Dnf => Penalty::None,
+        }
+    }
+}
+
+impl fmt::Display for Penalty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Penalty::None => Ok(()),
+            Penalty::PlusTwo => write!(f, "+2"),
+            Penalty::Dnf => write!(f, "DNF"),
+        }
+    }
+}
+
+/// A finished solve of an NxN cube.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SolveResult {
+    pub size: usize,
+    /// in seconds, without the penalty
+    pub time: f64,
+    #[serde(default)]
+    pub penalty: Penalty,
+    /// empty if the cube wasn't scrambled before the solve, e.g. by hand
+    #[serde(default)]
+    pub scramble: Alg,
+    /// when it was finished, in unix seconds, if that's known
+    #[serde(default)]
+    pub date: Option<f64>,
+    /// the movements of the solve, empty if they aren't known, e.g. for a
+    /// solve imported from another timer
+    #[serde(default)]
+    pub solution: Alg,
+}
+
+impl SolveResult {
+    /// Returns the time the solve counts as in centiseconds, cut off at the
+    /// hundredth like a WCA timer's display, or None for a DNF.
+    pub fn counted(&self) -> Option<u64> {
+        let centiseconds = centiseconds(self.time);
+        match self.penalty {
+            Penalty::None => Some(centiseconds),
+            Penalty::PlusTwo => Some(centiseconds + 200),
+            Penalty::Dnf => None,
+        }
+    }
+}
+
+fn centiseconds(seconds: f64) -> u64 {
+    // e.g. 12.34 is stored as 12.339999..., which is still 12.34
+    (seconds * 100. + 1e-6).max(0.) as u64
+}
+
+/// Returns the average of counted times (None for a DNF) in centiseconds,
+/// the WCA way: the mean of all but the fastest and slowest 5% of them
+/// (rounded up, so 1 each from an average of 5 or 12), rounded to the
+/// nearest hundredth. DNFs count as slower than any time, so when there
+/// are more of them than are trimmed, the average's a DNF (None).
+pub fn average(times: &[Option<u64>]) -> Option<u64> {
+    let trimmed = times.len().div_ceil(20);
+    if times.len() <= 2 * trimmed {
+        return None;
+    }
+    let mut sorted = times.to_vec();
+    // None, a DNF, after every time
+    sorted.sort_by_key(|t| t.unwrap_or(u64::MAX));
+    mean(&sorted[trimmed..times.len() - trimmed])
+}
+
+/// Returns the mean of counted times (None for a DNF) in centiseconds,
+/// rounded to the nearest hundredth, e.g. for a mean of 3. It's a DNF (None)
+/// if any of them are, or there are none.
+pub fn mean(times: &[Option<u64>]) -> Option<u64> {
+    if times.is_empty() {
+        return None;
+    }
+    let sum: u64 = times.iter().copied().sum::<Option<u64>>()?;
+    let n = times.len() as u64;
+    // halves round up
+    Some((2 * sum + n) / (2 * n))
+}
+
+/// Formats a time in centiseconds like the WCA, e.g. 12.34 or 1:02.34, or
+/// DNF for None.
+pub fn format_centiseconds(centiseconds: Option<u64>) -> String {
+    match centiseconds {
+        None => "DNF".to_string(),
+        Some(cs) if cs >= 6000 => format!("{}:{:02}.{:02}", cs / 6000, cs / 100 % 60, cs % 100),
+        Some(cs) => format!("{}.{:02}", cs / 100, cs % 100),
+    }
+}
+
+/// Returns results as CSV, a row for each solve with its number, time
+/// (without the penalty), penalty and scramble, the layout comp simulation
+/// spreadsheets take.
+pub fn results_csv(results: &[SolveResult]) -> String {
+    let mut csv = String::from("Solve,Time,Penalty,Scramble\n");
+    for (i, result) in results.iter().enumerate() {
+        let time = format_centiseconds(Some(centiseconds(result.time)));
+        writeln!(
+            csv,
+            "{},{},{},{}",
+            i + 1,
+            time,
+            result.penalty,
+            result.scramble
+        )
+        .unwrap();
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn averages_trim_the_best_and_worst() {
+        let times = |t: &[i64]| -> Vec<Option<u64>> {
+            t.iter()
+                .map(|&t| Some(t as u64).filter(|_| t >= 0))
+                .collect()
+        };
+        // the middle 3 of 5, rounded to the nearest hundredth
+        assert_eq!(average(&times(&[1000, 1200, 1100, 900, 2000])), Some(1100));
+        assert_eq!(average(&times(&[1000, 1001, 1001, 900, 2000])), Some(1001));
+        assert_eq!(average(&times(&[1000, 1000, 1001, 900, 2000])), Some(1000));
+        // one DNF is trimmed as the worst, two make a DNF
+        assert_eq!(average(&times(&[1000, 1200, -1, 900, 1100])), Some(1100));
+        assert_eq!(average(&times(&[1000, -1, -1, 900, 1100])), None);
+        // 5% of 12 trims 1 each side, 5% of 50 trims 3
+        let mut of_12 = times(&[500; 10]);
+        of_12.extend([Some(100), None]);
+        assert_eq!(average(&of_12), Some(500));
+        let mut of_50 = times(&[500; 47]);
+        of_50.extend([None, None, None]);
+        assert_eq!(average(&of_50), Some(500));
+        of_50[0] = None;
+        assert_eq!(average(&of_50), None);
+        assert_eq!(average(&times(&[1000, 1000])), None);
+
+        assert_eq!(mean(&times(&[1000, 1001, 1001])), Some(1001));
+        assert_eq!(mean(&times(&[1000, 1000, 1001])), Some(1000));
+        assert_eq!(mean(&times(&[1000, -1, 1001])), None);
+        assert_eq!(mean(&[]), None);
+    }
+
+    #[test]
+    fn results_export_as_csv() {
+        let scramble = Alg::from_str("R U R' F2").unwrap();
+        let result = |time, penalty| SolveResult {
+            size: 3,
+            time,
+            penalty,
+            scramble: scramble.clone(),
+            date: None,
+            solution: Alg::default(),
+        };
+        let results = [
+            result(12.34, Penalty::None),
+            result(62.5, Penalty::PlusTwo),
+            result(9.999, Penalty::Dnf),
+        ];
+        assert_eq!(
+            results_csv(&results),
+            "Solve,Time,Penalty,Scramble\n\
+             1,12.34,,R U R' F2\n\
+             2,1:02.50,+2,R U R' F2\n\
+             3,9.99,DNF,R U R' F2\n"
+        );
+        let counted: Vec<_> = results.iter().map(SolveResult::counted).collect();
+        assert_eq!(counted, [Some(1234), Some(6450), None]);
+        assert_eq!(format_centiseconds(mean(&counted[..2])), "38.42");
+
+        assert_eq!(Penalty::for_inspection(15.), Penalty::None);
+        assert_eq!(Penalty::for_inspection(16.), Penalty::PlusTwo);
+        assert_eq!(Penalty::for_inspection(17.5), Penalty::Dnf);
+    }
+}
@@ -0,0 +1,188 @@
+use crate::{Alg, Move, Movement, Turn};
+
+/// Small deterministic pseudo random number generator (SplitMix64), so a
+/// seed always gives the same scramble, on any machine.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in 0..n, n must be positive
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+/// Returns the typical number of movements in a random move scramble of an
+/// NxN cube, e.g. 20 for 3x3, 40 for 4x4.
+pub fn scramble_length(size: usize) -> usize {
+    match size {
+        0..=2 => 10,
+        n => 20 * (n - 2),
+    }
+}
+
+// the moves a scramble is made of, grouped by axis
+fn scramble_moves(size: usize) -> Vec<[Move; 2]> {
+    let mut moves = vec![[Move::U, Move::D], [Move::L, Move::R], [Move::F, Move::B]];
+    if size >= 4 {
+        moves.extend([
+            [Move::Uw, Move::Dw],
+            [Move::Lw, Move::Rw],
+            [Move::Fw, Move::Bw],
+        ]);
+    }
+    moves
+}
+
+/// Generates a random move scramble for an NxN cube, that never turns the
+/// same layer twice in a row or cancels like R L R'.
+pub fn random_scramble(size: usize, length: usize, rng: &mut Rng) -> Alg {
+    let groups = scramble_moves(size);
+    let axis_of = |m: Move| groups.iter().position(|g| g.contains(&m)).unwrap() % 3;
+    let all_moves: Vec<Move> = groups.iter().flatten().cloned().collect();
+    let turns = [Turn::Single, Turn::Double, Turn::Inverse];
+
+    let mut scramble: Vec<Movement> = vec![];
+    // moves made since the last change of axis
+    let mut same_axis: Vec<Move> = vec![];
+    while scramble.len() < length {
+        let m = all_moves[rng.below(all_moves.len())];
+        let axis = same_axis.first().map(|&a| axis_of(a));
+        if axis == Some(axis_of(m)) {
+            // on the same axis, each layer can only be turned once
+            if same_axis.contains(&m) {
+                continue;
+            }
+        } else {
+            same_axis.clear();
+        }
+        same_axis.push(m);
+        scramble.push(Movement::new(m, turns[rng.below(turns.len())]));
+    }
+    Alg::new(scramble)
+}
+
+/// Returns the seed of the scramble of the day for an NxN cube: the day (the
+/// number of days since 1970-01-01, in UTC) times 100, plus the size. Any
+/// app can get the same scramble with this seed, e.g. the 3x3 scramble of
+/// 2026-10-16 (day 20742) is `random_scramble(3, scramble_length(3),
+/// &mut Rng::new(2074203))`.
+pub fn daily_seed(day: u64, size: usize) -> u64 {
+    day * 100 + size as u64
+}
+
+/// Returns the scramble of the day for an NxN cube, the same on every
+/// machine, see daily_seed.
+pub fn daily_scramble(day: u64, size: usize) -> Alg {
+    random_scramble(
+        size,
+        scramble_length(size),
+        &mut Rng::new(daily_seed(day, size)),
+    )
+}
+
+/// Returns the day of unix time seconds, as used by daily_seed.
+pub fn day_of(unix_seconds: f64) -> u64 {
+    (unix_seconds / 86400.).floor().max(0.) as u64
+}
+
+/// Returns the date of a day (as used by daily_seed), e.g. "2026-10-16".
+pub fn date_of_day(day: u64) -> String {
+    // days to the civil calendar, counting from 0000-03-01 so leap days end
+    // each 4/100/400 year cycle
+    let days = day as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day_of_month = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day_of_month)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GCube;
+
+    #[test]
+    fn rng_is_deterministic() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+        assert_ne!(Rng::new(1).next_u64(), Rng::new(2).next_u64());
+    }
+
+    #[test]
+    fn scramble_has_no_redundant_moves() {
+        let mut rng = Rng::new(7);
+        for size in 2..=6 {
+            let scramble = random_scramble(size, scramble_length(size), &mut rng);
+            assert_eq!(scramble.len(), scramble_length(size));
+            // nothing to cancel
+            assert_eq!(scramble.simplified(), scramble);
+            // a layer is only turned again after turning a layer on another axis
+            let groups = scramble_moves(size);
+            let axis_of = |m: Move| groups.iter().position(|g| g.contains(&m)).unwrap() % 3;
+            for w in scramble.movements().windows(3) {
+                if w[0].0 == w[2].0 {
+                    assert_ne!(axis_of(w[0].0), axis_of(w[1].0));
+                }
+            }
+            let mut gcube = GCube::new(size);
+            gcube.apply_movements(scramble.movements());
+            assert_ne!(gcube, GCube::new(size));
+        }
+    }
+
+    #[test]
+    fn small_cubes_only_use_face_moves() {
+        let scramble = random_scramble(3, 200, &mut Rng::new(3));
+        assert!(scramble.movements().iter().all(|m| [
+            Move::U,
+            Move::D,
+            Move::L,
+            Move::R,
+            Move::F,
+            Move::B
+        ]
+        .contains(&m.0)));
+    }
+
+    #[test]
+    fn daily_scrambles_are_the_same_everywhere() {
+        let day = day_of(1_792_160_000.);
+        assert_eq!(day, 20742);
+        assert_eq!(date_of_day(day), "2026-10-16");
+        assert_eq!(date_of_day(0), "1970-01-01");
+        assert_eq!(date_of_day(11016), "2000-02-29");
+        assert_eq!(
+            daily_scramble(day, 3),
+            random_scramble(3, 20, &mut Rng::new(2074203))
+        );
+        assert_eq!(daily_scramble(day, 3), daily_scramble(day, 3));
+        assert_ne!(daily_scramble(day, 3), daily_scramble(day + 1, 3));
+        assert_eq!(daily_scramble(day, 4).len(), 40);
+    }
+}
@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{random_scramble, scramble_length, Alg, CubieCube, GCube, Rng, Solver, ORDERED_FACES};
+
+/// How many scrambles are tried for one that passes a filter, before giving
+/// up and using the last.
+pub const MAX_FILTER_ATTEMPTS: usize = 1000;
+
+/// Constraints on 3x3 scrambles, rejecting the easy ones for serious
+/// practice. Each is off at its default.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScrambleFilter {
+    /// the fewest moves (HTM) a scramble's state can be solved in, checked
+    /// by the solver not finding a shorter solution (which it may miss, so
+    /// very rarely one slips through), or 0 for any
+    pub min_solution_length: usize,
+    /// whether to reject scrambles leaving any F2L pair solved
+    pub no_solved_pairs: bool,
+    /// the fewest moves the cross can be solved in on every face, or 0 for
+    /// any, e.g. 4 to reject crosses of 3 moves or less
+    pub min_cross_moves: usize,
+}
+
+impl ScrambleFilter {
+    /// Returns whether any constraint is on.
+    pub fn is_active(&self) -> bool {
+        *self != Self::default()
+    }
+
+    /// Returns whether a scrambled cube meets every constraint.
+    pub fn accepts(&self, cube: &CubieCube) -> bool {
+        if self.no_solved_pairs && cube.solved_pairs() > 0 {
+            return false;
+        }
+        if let Some(max_easy) = self.min_cross_moves.checked_sub(1) {
+            let is_easy = |&face| cube.cross_moves(face, max_easy).is_some();
+            if ORDERED_FACES.iter().any(is_easy) {
+                return false;
+            }
+        }
+        match self.min_solution_length.checked_sub(1) {
+            Some(max_short) => Solver::shared().solve_within(cube, max_short, 50).is_none(),
+            None => true,
+        }
+    }
+
+    /// Returns a random move scramble of a 3x3 meeting every constraint, or
+    /// the last tried if none of MAX_FILTER_ATTEMPTS do.
+    pub fn scramble(&self, rng: &mut Rng) -> Alg {
+        let mut scramble = Alg::default();
+        for _ in 0..MAX_FILTER_ATTEMPTS {
+            scramble = random_scramble(3, scramble_length(3), rng);
+            let mut gcube = GCube::new(3);
+            gcube.apply_movements(scramble.movements());
+            let cube = CubieCube::from_facelets(&gcube.to_facelet_model()).unwrap();
+            if self.accepts(&cube) {
+                break;
+            }
+        }
+        scramble
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Face, Turn};
+
+    #[test]
+    fn rejects_easy_states() {
+        let mut easy = CubieCube::default();
+        easy.turn(Face::R, Turn::Single);
+        easy.turn(Face::U, Turn::Double);
+        assert!(ScrambleFilter::default().accepts(&easy));
+        let no_pairs = ScrambleFilter {
+            no_solved_pairs: true,
+            ..ScrambleFilter::default()
+        };
+        assert!(!no_pairs.accepts(&easy));
+        let hard_cross = ScrambleFilter {
+            min_cross_moves: 3,
+            ..ScrambleFilter::default()
+        };
+        assert!(!hard_cross.accepts(&easy));
+        let long = ScrambleFilter {
+            min_solution_length: 3,
+            ..ScrambleFilter::default()
+        };
+        assert!(!long.accepts(&easy));
+        assert!(!ScrambleFilter::default().is_active());
+        assert!(long.is_active());
+    }
+
+    #[test]
+    fn filtered_scrambles_pass() {
+        let filter = ScrambleFilter {
+            min_solution_length: 16,
+            no_solved_pairs: true,
+            min_cross_moves: 4,
+        };
+        let mut rng = Rng::new(9);
+        for _ in 0..3 {
+            let scramble = filter.scramble(&mut rng);
+            let mut gcube = GCube::new(3);
+            gcube.apply_movements(scramble.movements());
+            let cube = CubieCube::from_facelets(&gcube.to_facelet_model()).unwrap();
+            assert_eq!(cube.solved_pairs(), 0);
+            assert!(ORDERED_FACES
+                .iter()
+                .all(|&face| cube.cross_moves(face, 3).is_none()));
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use std::fmt::Write;
+
+use crate::{
+    random_scramble, scramble_length, Alg, Arrow, ColorScheme, Face, GCube, Rng, ORDERED_FACES,
+};
+
+// an A4 page's width at 96 DPI, with the scrambles in 2 columns
+const PAGE_WIDTH: f32 = 794.;
+const MARGIN: f32 = 40.;
+const COLUMNS: usize = 2;
+const NET_WIDTH: f32 = 240.;
+const FONT_SIZE: f32 = 12.;
+// how many characters of a scramble fit on a line of its column
+const LINE_LENGTH: usize = 52;
+
+/// A printable sheet of scrambles of an NxN cube, each shown with the net of
+/// the cube it scrambles to, e.g. for a club meetup or mock competition.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScrambleSheet {
+    pub size: usize,
+    pub scrambles: Vec<Alg>,
+    pub scheme: ColorScheme,
+}
+
+impl ScrambleSheet {
+    /// Returns a sheet of count random move scrambles.
+    pub fn random(size: usize, count: usize, scheme: ColorScheme, rng: &mut Rng) -> Self {
+        let scrambles = (0..count)
+            .map(|_| random_scramble(size, scramble_length(size), rng))
+            .collect();
+        Self {
+            size,
+            scrambles,
+            scheme,
+        }
+    }
+
+    /// Returns the sheet as an SVG image, a page wide and as tall as the
+    /// scrambles take.
+    pub fn to_svg(&self) -> String {
+        let column_width = (PAGE_WIDTH - 2. * MARGIN) / COLUMNS as f32;
+        let sticker = NET_WIDTH / (4 * self.size.max(1)) as f32;
+        let lines: Vec<Vec<String>> = self.scrambles.iter().map(wrap).collect();
+        let max_lines = lines.iter().map(Vec::len).max().unwrap_or(0);
+        // the number and scramble above the net, with a line's space between
+        let text_height = (max_lines + 2) as f32 * FONT_SIZE * 1.4;
+        let row_height = text_height + 3. * self.size as f32 * sticker + 2. * FONT_SIZE;
+        let rows = self.scrambles.len().div_ceil(COLUMNS);
+        let height = 2. * MARGIN + rows as f32 * row_height;
+
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = PAGE_WIDTH,
+            h = height
+        )
+        .unwrap();
+        writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#).unwrap();
+        for (i, (scramble, lines)) in self.scrambles.iter().zip(lines.iter()).enumerate() {
+            let x = MARGIN + (i % COLUMNS) as f32 * column_width;
+            let y = MARGIN + (i / COLUMNS) as f32 * row_height;
+            let mut text = format!("{}.", i + 1);
+            for (l, line) in lines.iter().enumerate() {
+                let line_y = y + (l + 2) as f32 * FONT_SIZE * 1.4;
+                write!(text, r#"<tspan x="{}" y="{}">{}</tspan>"#, x, line_y, line).unwrap();
+            }
+            writeln!(
+                svg,
+                r#"<text x="{}" y="{}" font-family="monospace" font-size="{}">{}</text>"#,
+                x,
+                y + FONT_SIZE * 1.4,
+                FONT_SIZE,
+                text
+            )
+            .unwrap();
+            let mut gcube = GCube::new(self.size);
+            gcube.apply_movements(scramble.movements());
+            let net = net_svg(
+                &gcube.facelets(),
+                self.size,
+                self.scheme,
+                &[],
+                (x, y + text_height),
+                sticker,
+            );
+            svg.push_str(&net);
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+// splits a scramble into lines that fit its column, between movements
+fn wrap(scramble: &Alg) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    for movement in scramble.movements() {
+        let movement = movement.to_string();
+        match lines.last_mut() {
+            Some(line) if line.len() + 1 + movement.len() <= LINE_LENGTH => {
+                line.push(' ');
+                line.push_str(&movement);
+            }
+            _ => lines.push(movement),
+        }
+    }
+    lines
+}
+
+// where a face is on the net, in stickers from its top left corner, with L,
+// F, R and B in a row, U above F and D below it
+fn net_offset(face: Face, size: usize) -> (usize, usize) {
+    match face {
+        Face::U => (size, 0),
+        Face::L => (0, size),
+        Face::F => (size, size),
+        Face::R => (2 * size, size),
+        Face::B => (3 * size, size),
+        _ => (size, 2 * size),
+    }
+}
+
+/// Returns SVG elements drawing the net of an NxN cube coloured like
+/// facelets (ordered like [facelets](GCube::facelets)), its top left corner
+/// at x, y and each sticker sticker wide, with arrows drawn between facelets
+/// on top, e.g. to show an alg's cycles.
+pub fn net_svg(
+    facelets: &[Face],
+    size: usize,
+    scheme: ColorScheme,
+    arrows: &[Arrow],
+    (x, y): (f32, f32),
+    sticker: f32,
+) -> String {
+    let mut svg = String::new();
+    let n = size * size;
+    for (f, &face) in ORDERED_FACES.iter().enumerate() {
+        let (col, row) = net_offset(face, size);
+        for (i, &color) in facelets.iter().skip(f * n).take(n).enumerate() {
+            let [r, g, b] = match color {
+                Face::X => [128, 128, 128],
+                face => scheme.color(face),
+            };
+            writeln!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{s}" height="{s}" fill="rgb({},{},{})" stroke="black" stroke-width="{}"/>"#,
+                x + (col + i % size) as f32 * sticker,
+                y + (row + i / size) as f32 * sticker,
+                r,
+                g,
+                b,
+                sticker / 12.,
+                s = sticker
+            )
+            .unwrap();
+        }
+    }
+    // the center of a facelet, if it's on the cube
+    let center = |face: Face, index: usize| {
+        let (col, row) = net_offset(face, size);
+        let is_on_cube = index < n && face != Face::X;
+        Some((
+            x + ((col + index % size) as f32 + 0.5) * sticker,
+            y + ((row + index / size) as f32 + 0.5) * sticker,
+        ))
+        .filter(|_| is_on_cube)
+    };
+    for arrow in arrows {
+        let ((x1, y1), (x2, y2)) = match (
+            center(arrow.from.face, arrow.from.index),
+            center(arrow.to.face, arrow.to.index),
+        ) {
+            (Some(from), Some(to)) => (from, to),
+            _ => continue,
+        };
+        let length = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt().max(1e-3);
+        // along and across the arrow, a head's length
+        let head = sticker / 3.;
+        let (dx, dy) = ((x2 - x1) / length * head, (y2 - y1) / length * head);
+        writeln!(
+            svg,
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="black" stroke-width="{}"/>"#,
+            x1,
+            y1,
+            x2 - dx,
+            y2 - dy,
+            sticker / 10.
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"<polygon points="{},{} {},{} {},{}" fill="black"/>"#,
+            x2,
+            y2,
+            x2 - dx - dy / 2.,
+            y2 - dy + dx / 2.,
+            x2 - dx + dy / 2.,
+            y2 - dy - dx / 2.
+        )
+        .unwrap();
+    }
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_arrows, scramble_to_movements};
+
+    #[test]
+    fn sheets_show_each_scramble_with_its_net() {
+        let sheet = ScrambleSheet::random(3, 5, ColorScheme::Standard, &mut Rng::new(7));
+        assert_eq!(sheet.scrambles.len(), 5);
+        assert_eq!(sheet.scrambles, {
+            ScrambleSheet::random(3, 5, ColorScheme::Standard, &mut Rng::new(7)).scrambles
+        });
+        let svg = sheet.to_svg();
+        assert!(svg.starts_with("<svg") && svg.ends_with("</svg>\n"));
+        // a sticker for every facelet, and the background
+        assert_eq!(svg.matches("<rect").count(), 5 * 54 + 1);
+        for (i, scramble) in sheet.scrambles.iter().enumerate() {
+            assert!(svg.contains(&format!("{}.", i + 1)));
+            for line in wrap(scramble) {
+                assert!(svg.contains(&format!(">{}</tspan>", line)));
+            }
+        }
+
+        // long scrambles wrap between movements
+        let sheet = ScrambleSheet::random(7, 1, ColorScheme::Standard, &mut Rng::new(7));
+        let lines = wrap(&sheet.scrambles[0]);
+        assert!(lines.len() > 1 && lines.iter().all(|l| l.len() <= LINE_LENGTH));
+        assert_eq!(lines.join(" "), sheet.scrambles[0].to_string());
+    }
+
+    #[test]
+    fn nets_are_coloured_like_the_cube() {
+        let mut gcube = GCube::new(2);
+        gcube.apply_movements(&scramble_to_movements("R").unwrap());
+        let arrows = parse_arrows("U0U3,F1F9").unwrap();
+        let svg = net_svg(
+            &gcube.facelets(),
+            2,
+            ColorScheme::Standard,
+            &arrows,
+            (0., 0.),
+            10.,
+        );
+        assert_eq!(svg.matches("<rect").count(), 24);
+        // U's right column is F's green after R, at x = 30 (U starts 2 stickers in)
+        let green = r#"fill="rgb(0,228,48)""#;
+        assert!(svg.contains(&format!(
+            r#"<rect x="30" y="0" width="10" height="10" {}"#,
+            green
+        )));
+        // only the arrow between facelets on the cube
+        assert_eq!(svg.matches("<line").count(), 1);
+        assert_eq!(svg.matches("<polygon").count(), 1);
+    }
+}
@@ -0,0 +1,221 @@
+use std::{fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Alg, CaseAttempt, GCube, ReductionTime, SolveResult, StepTime};
+
+/// A saved cube state, stored as the alg that reaches it from solved.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub name: String,
+    pub size: usize,
+    pub alg: Alg,
+}
+
+impl Bookmark {
+    pub fn to_gcube(&self) -> GCube {
+        let mut gcube = GCube::new(self.size);
+        gcube.apply_movements(self.alg.movements());
+        gcube
+    }
+}
+
+/// The best time for a scramble of the day, see daily_scramble.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DailyBest {
+    pub day: u64,
+    pub size: usize,
+    /// in seconds
+    pub time: f64,
+}
+
+/// Everything kept between runs of the app, saved as JSON.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    #[serde(default)]
+    pub bookmarks: Vec<Bookmark>,
+    #[serde(default)]
+    pub daily_bests: Vec<DailyBest>,
+    /// every finished solve, oldest first
+    #[serde(default)]
+    pub results: Vec<SolveResult>,
+    /// the times of neutrality drills' steps, oldest first
+    #[serde(default)]
+    pub step_times: Vec<StepTime>,
+    /// the times of big cube trainers' steps, oldest first
+    #[serde(default)]
+    pub reduction_times: Vec<ReductionTime>,
+    /// the attempts at case trainers' cases, oldest first
+    #[serde(default)]
+    pub case_attempts: Vec<CaseAttempt>,
+}
+
+impl Session {
+    /// Loads the session at path, or an empty session if there's no file.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    /// Saves the state reached by alg (from solved) under name, replacing any
+    /// bookmark of the same name.
+    pub fn set_bookmark(&mut self, name: &str, size: usize, alg: Alg) {
+        let bookmark = Bookmark {
+            name: name.to_string(),
+            size,
+            alg: alg.simplified(),
+        };
+        match self.bookmarks.iter_mut().find(|b| b.name == name) {
+            Some(existing) => *existing = bookmark,
+            None => self.bookmarks.push(bookmark),
+        }
+    }
+
+    pub fn bookmark(&self, name: &str) -> Option<&Bookmark> {
+        self.bookmarks.iter().find(|b| b.name == name)
+    }
+
+    /// Returns the counted times (see [SolveResult::counted]) of the last n
+    /// solves of an NxN cube, oldest first, or None if there haven't been n,
+    /// e.g. for the current average of 5.
+    pub fn last_times(&self, size: usize, n: usize) -> Option<Vec<Option<u64>>> {
+        let mut times: Vec<Option<u64>> = self
+            .results
+            .iter()
+            .rev()
+            .filter(|r| r.size == size)
+            .take(n)
+            .map(SolveResult::counted)
+            .collect();
+        times.reverse();
+        Some(times).filter(|t| t.len() == n)
+    }
+
+    /// Adds solves imported from another timer (see import_results) after
+    /// the session's own, leaving out any already in it so an export can be
+    /// imported again. Returns how many were added.
+    pub fn import_results(&mut self, imported: Vec<SolveResult>) -> usize {
+        let count = self.results.len();
+        for result in imported {
+            if !self.results.contains(&result) {
+                self.results.push(result);
+            }
+        }
+        self.results.len() - count
+    }
+
+    /// Returns the best time for the scramble of day for an NxN cube.
+    pub fn daily_best(&self, day: u64, size: usize) -> Option<f64> {
+        self.daily_bests
+            .iter()
+            .find(|b| b.day == day && b.size == size)
+            .map(|b| b.time)
+    }
+
+    /// Records a time for the scramble of day for an NxN cube, returning
+    /// whether it's a new best.
+    pub fn record_daily_time(&mut self, day: u64, size: usize, time: f64) -> bool {
+        match self
+            .daily_bests
+            .iter_mut()
+            .find(|b| b.day == day && b.size == size)
+        {
+            Some(best) if best.time <= time => false,
+            Some(best) => {
+                best.time = time;
+                true
+            }
+            None => {
+                self.daily_bests.push(DailyBest { day, size, time });
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Penalty;
+    use std::str::FromStr;
+
+    #[test]
+    fn bookmarks_replace_by_name() {
+        let mut session = Session::default();
+        session.set_bookmark("a", 3, Alg::from_str("R R").unwrap());
+        session.set_bookmark("b", 4, Alg::from_str("r").unwrap());
+        session.set_bookmark("a", 3, Alg::from_str("U").unwrap());
+        assert_eq!(session.bookmarks.len(), 2);
+        assert_eq!(
+            session.bookmark("a").unwrap().alg,
+            Alg::from_str("U").unwrap()
+        );
+        assert_eq!(session.bookmark("c"), None);
+
+        let mut expected = GCube::new(4);
+        expected.apply_movements(&crate::scramble_to_movements("r").unwrap());
+        assert_eq!(session.bookmark("b").unwrap().to_gcube(), expected);
+    }
+
+    #[test]
+    fn daily_bests_keep_the_fastest_time() {
+        let mut session = Session::default();
+        assert!(session.record_daily_time(20742, 3, 15.));
+        assert!(!session.record_daily_time(20742, 3, 16.));
+        assert!(session.record_daily_time(20742, 3, 12.5));
+        assert!(session.record_daily_time(20742, 4, 60.));
+        assert_eq!(session.daily_best(20742, 3), Some(12.5));
+        assert_eq!(session.daily_best(20743, 3), None);
+    }
+
+    #[test]
+    fn last_times_are_of_one_size() {
+        let mut session = Session::default();
+        for (size, time, penalty) in [
+            (3, 10., Penalty::None),
+            (4, 40., Penalty::None),
+            (3, 11., Penalty::PlusTwo),
+            (3, 12., Penalty::Dnf),
+        ] {
+            session.results.push(SolveResult {
+                size,
+                time,
+                penalty,
+                scramble: Alg::default(),
+                date: None,
+                solution: Alg::default(),
+            });
+        }
+        assert_eq!(
+            session.last_times(3, 3),
+            Some(vec![Some(1000), Some(1300), None])
+        );
+        assert_eq!(session.last_times(3, 2), Some(vec![Some(1300), None]));
+        assert_eq!(session.last_times(4, 2), None);
+
+        let imported = crate::import_results("9.5, 9.6", 3).unwrap();
+        assert_eq!(session.import_results(imported.clone()), 2);
+        assert_eq!(session.import_results(imported), 0);
+        assert_eq!(session.last_times(3, 2), Some(vec![Some(950), Some(960)]));
+    }
+
+    #[test]
+    fn save_and_load() {
+        let path = std::env::temp_dir().join("cubedesu_session_test.json");
+        let mut session = Session::default();
+        session.set_bookmark("oll", 3, Alg::from_str("F R U R' U' F'").unwrap());
+        session.record_daily_time(20742, 3, 9.87);
+        session.save(&path).unwrap();
+        assert_eq!(Session::load(&path).unwrap(), session);
+        fs::remove_file(&path).unwrap();
+        // a missing file is an empty session
+        assert_eq!(Session::load(&path).unwrap(), Session::default());
+    }
+}
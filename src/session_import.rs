@@ -0,0 +1,415 @@
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::{Alg, Penalty, SolveResult};
+
+/// Why a line of another timer's export couldn't be imported.
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+#[error("Line {line}: can't read the time {time:?}")]
+pub struct ImportError {
+    /// counting from 1
+    pub line: usize,
+    pub time: String,
+}
+
+/// Reads the solves from another timer's export, oldest first, for an NxN
+/// cube of size unless the export says which puzzle each solve is of. It
+/// takes
+/// - qqTimer's time lists, e.g. `12.34, 13.56+, DNF(11.23)`, and its
+///   statistics, with a numbered line like `2. 13.56+ R U R'` for each solve
+/// - CSV with a header naming its time, scramble, date and penalty columns,
+///   separated by commas or semicolons, e.g. Twisty Timer's backups
+///   (`Puzzle;Category;Time(millis);Date(millis);Scramble;Penalty;Comment`)
+///   or cubedesu's own results_csv
+/// - Twisty Timer's exports for other apps, `time;scramble;date` rows with
+///   no header
+///
+/// A +2 time shown with its penalty (`13.56+`, or a Twisty Timer backup's
+/// +2 time) has the penalty taken back off, and dates become unix seconds.
+/// Solves of puzzles that aren't NxN cubes, e.g. Twisty Timer's pyraminx,
+/// are left out.
+pub fn import_results(text: &str, size: usize) -> Result<Vec<SolveResult>, ImportError> {
+    let lines: Vec<(usize, &str)> = text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .collect();
+    let first = match lines.first() {
+        Some(&(_, line)) => line,
+        None => return Ok(vec![]),
+    };
+    let separator = if first.contains(';') { ';' } else { ',' };
+    let is_csv = separator == ';' || header(first, separator).is_some();
+    if is_csv {
+        import_csv(&lines, separator, size)
+    } else {
+        import_list(&lines, size)
+    }
+}
+
+// which columns of a CSV header are the puzzle, time, date, scramble and
+// penalty, and whether times and dates are in milliseconds
+#[derive(Default)]
+struct Columns {
+    puzzle: Option<usize>,
+    time: usize,
+    date: Option<usize>,
+    scramble: Option<usize>,
+    penalty: Option<usize>,
+    is_millis: bool,
+}
+
+fn header(line: &str, separator: char) -> Option<Columns> {
+    let mut columns = Columns::default();
+    let mut time = None;
+    for (i, name) in split_csv(line, separator).iter().enumerate() {
+        let name = name.to_lowercase();
+        match name.split('(').next().unwrap_or("").trim() {
+            "puzzle" => columns.puzzle = Some(i),
+            "time" => {
+                time = Some(i);
+                columns.is_millis = name.contains("millis");
+            }
+            "date" => columns.date = Some(i),
+            "scramble" => columns.scramble = Some(i),
+            "penalty" => columns.penalty = Some(i),
+            _ => {}
+        }
+    }
+    columns.time = time?;
+    Some(columns)
+}
+
+fn import_csv(
+    lines: &[(usize, &str)],
+    separator: char,
+    size: usize,
+) -> Result<Vec<SolveResult>, ImportError> {
+    let (columns, rows) = match header(lines[0].1, separator) {
+        Some(columns) => (columns, &lines[1..]),
+        // Twisty Timer's export for other apps
+        None => (
+            Columns {
+                time: 0,
+                scramble: Some(1),
+                date: Some(2),
+                ..Columns::default()
+            },
+            lines,
+        ),
+    };
+    let mut results = vec![];
+    for &(line, row) in rows {
+        let fields = split_csv(row, separator);
+        let field =
+            |column: Option<usize>| column.and_then(|c| fields.get(c)).map_or("", |f| f.trim());
+        let size = match columns.puzzle {
+            Some(_) => match puzzle_size(field(columns.puzzle)) {
+                Some(size) => size,
+                None => continue,
+            },
+            None => size,
+        };
+        let error = || ImportError {
+            line,
+            time: field(Some(columns.time)).to_string(),
+        };
+        let (time, mut penalty) = if columns.is_millis {
+            let millis: f64 = field(Some(columns.time)).parse().map_err(|_| error())?;
+            (millis / 1000., Penalty::None)
+        } else {
+            parse_time(field(Some(columns.time))).ok_or_else(error)?
+        };
+        // Twisty Timer's backups store the time with a +2 added
+        let mut time = time;
+        match field(columns.penalty) {
+            "1" => {
+                penalty = Penalty::PlusTwo;
+                time -= 2.;
+            }
+            "2" => penalty = Penalty::Dnf,
+            "+2" => penalty = Penalty::PlusTwo,
+            p if p.eq_ignore_ascii_case("dnf") => penalty = Penalty::Dnf,
+            _ => {}
+        }
+        let date = field(columns.date);
+        let date = if columns.is_millis {
+            // like dates that can't be read, NaN or infinite ones are left out
+            date.parse::<f64>()
+                .ok()
+                .filter(|millis| millis.is_finite())
+                .map(|millis| millis / 1000.)
+        } else {
+            parse_date(date)
+        };
+        results.push(SolveResult {
+            size,
+            time: time.max(0.),
+            penalty,
+            scramble: Alg::from_str(field(columns.scramble)).unwrap_or_default(),
+            date,
+            solution: Alg::default(),
+        });
+    }
+    // Twisty Timer's backups are newest first
+    results.sort_by(|a, b| {
+        let date = |r: &SolveResult| r.date.unwrap_or(f64::MIN);
+        date(a).total_cmp(&date(b))
+    });
+    Ok(results)
+}
+
+fn import_list(lines: &[(usize, &str)], size: usize) -> Result<Vec<SolveResult>, ImportError> {
+    // qqTimer's statistics number each solve, among lines of averages
+    let numbered: Vec<(usize, &str)> = lines
+        .iter()
+        .filter_map(|&(line, text)| {
+            let (number, rest) = text.split_once(". ")?;
+            number.parse::<usize>().ok()?;
+            Some((line, rest.trim()))
+        })
+        .collect();
+    let mut results = vec![];
+    if !numbered.is_empty() {
+        for (line, text) in numbered {
+            let (time, scramble) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+            results.push(list_result(line, time, scramble, size)?);
+        }
+    } else {
+        for &(line, text) in lines {
+            for time in text.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+                results.push(list_result(line, time, "", size)?);
+            }
+        }
+    }
+    Ok(results)
+}
+
+fn list_result(
+    line: usize,
+    time: &str,
+    scramble: &str,
+    size: usize,
+) -> Result<SolveResult, ImportError> {
+    let (time, penalty) = parse_time(time).ok_or_else(|| ImportError {
+        line,
+        time: time.to_string(),
+    })?;
+    Ok(SolveResult {
+        size,
+        time,
+        penalty,
+        scramble: Alg::from_str(scramble.trim()).unwrap_or_default(),
+        date: None,
+        solution: Alg::default(),
+    })
+}
+
+// splits a CSV row at separators outside of quotes, unquoting the fields
+fn split_csv(row: &str, separator: char) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut is_quoted = false;
+    let mut chars = row.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            // "" is a quote inside quotes
+            '"' if is_quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            }
+            '"' => is_quoted = !is_quoted,
+            c if c == separator && !is_quoted => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+// Twisty Timer's puzzles are named like 333 for a 3x3
+fn puzzle_size(puzzle: &str) -> Option<usize> {
+    let first = puzzle.chars().next()?;
+    let is_cube = puzzle.len() == 3 && puzzle.chars().all(|c| c == first);
+    first.to_digit(10).map(|n| n as usize).filter(|_| is_cube)
+}
+
+// reads a time in seconds with its penalty, like 12.34, 1:02.34, 13.56+ (a +2
+// shown with the penalty added) or DNF(11.23)
+fn parse_time(time: &str) -> Option<(f64, Penalty)> {
+    let time = time.trim();
+    if let Some(rest) = strip_prefix_ignore_case(time, "dnf") {
+        let inner = rest.trim().trim_start_matches('(').trim_end_matches(')');
+        let seconds = if inner.is_empty() {
+            0.
+        } else {
+            parse_seconds(inner)?
+        };
+        return Some((seconds, Penalty::Dnf));
+    }
+    match time.strip_suffix('+') {
+        Some(shown) => Some(((parse_seconds(shown)? - 2.).max(0.), Penalty::PlusTwo)),
+        None => Some((parse_seconds(time)?, Penalty::None)),
+    }
+}
+
+fn strip_prefix_ignore_case<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = s.get(..prefix.len())?;
+    Some(&s[prefix.len()..]).filter(|_| head.eq_ignore_ascii_case(prefix))
+}
+
+// e.g. 12.34, 1:02.34 or 1:00:02.34
+fn parse_seconds(time: &str) -> Option<f64> {
+    time.trim().split(':').try_fold(0., |seconds, part| {
+        let part: f64 = part.parse().ok()?;
+        Some(seconds * 60. + part).filter(|_| part >= 0.)
+    })
+}
+
+// reads an ISO 8601 date and time, e.g. 2017-12-03T12:34:56.789-02:00, as
+// unix seconds, taking dates without a time zone to be in UTC
+fn parse_date(date: &str) -> Option<f64> {
+    let (day, time) = date.split_once(['T', ' '])?;
+    let mut ymd = day.splitn(3, '-').map(str::parse::<i64>);
+    let (year, month, day_of_month) = (ymd.next()?.ok()?, ymd.next()?.ok()?, ymd.next()?.ok()?);
+    // the time zone's offset from UTC, in seconds
+    let (time, offset) = match time.find(['Z', '+', '-']) {
+        Some(i) => {
+            let zone = &time[i..];
+            let offset = match zone.get(1..).map(|z| z.replace(':', "")) {
+                Some(hhmm) if hhmm.len() == 4 => {
+                    let hhmm: i64 = hhmm.parse().ok()?;
+                    let sign = if zone.starts_with('-') { -1 } else { 1 };
+                    sign * (hhmm / 100 * 3600 + hhmm % 100 * 60)
+                }
+                _ => 0,
+            };
+            (&time[..i], offset)
+        }
+        None => (time, 0),
+    };
+    let seconds = parse_seconds(time)?;
+    Some((days_from_civil(year, month, day_of_month) * 86400 - offset) as f64 + seconds)
+}
+
+// the inverse of date_of_day, counting from 0000-03-01 so leap days end each
+// 4/100/400 year cycle
+fn days_from_civil(year: i64, month: i64, day_of_month: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_from_march = (month + 9) % 12;
+    let day_of_year = (153 * month_from_march + 2) / 5 + day_of_month - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{date_of_day, day_of};
+
+    fn times(results: &[SolveResult]) -> Vec<(f64, Penalty)> {
+        results
+            .iter()
+            .map(|r| ((r.time * 100.).round() / 100., r.penalty))
+            .collect()
+    }
+
+    #[test]
+    fn imports_qqtimer_lists() {
+        let results = import_results("12.34, 13.56+, DNF(11.23)\n1:02.50, DNF", 4).unwrap();
+        assert_eq!(
+            times(&results),
+            [
+                (12.34, Penalty::None),
+                (11.56, Penalty::PlusTwo),
+                (11.23, Penalty::Dnf),
+                (62.5, Penalty::None),
+                (0., Penalty::Dnf)
+            ]
+        );
+        assert!(results.iter().all(|r| r.size == 4 && r.date.is_none()));
+
+        let statistics = "qqTimer statistics for Sat Dec 02 2017\n\
+                          \n\
+                          average: 12.95\n\
+                          \n\
+                          1. 12.34 R U R' U'\n\
+                          2. 13.56+ F2 D\n";
+        let results = import_results(statistics, 3).unwrap();
+        assert_eq!(
+            times(&results),
+            [(12.34, Penalty::None), (11.56, Penalty::PlusTwo)]
+        );
+        assert_eq!(results[1].scramble, Alg::from_str("F2 D").unwrap());
+
+        assert_eq!(
+            import_results("12.34, abc", 3),
+            Err(ImportError {
+                line: 1,
+                time: "abc".to_string()
+            })
+        );
+        assert_eq!(import_results("\n", 3), Ok(vec![]));
+    }
+
+    #[test]
+    fn imports_twisty_timer_csv() {
+        let backup = "Puzzle;Category;Time(millis);Date(millis);Scramble;Penalty;Comment\n\
+                      \"333\";\"Normal\";\"14000\";\"1512345678000\";\"R U\";\"1\";\"\"\n\
+                      \"pyra\";\"Normal\";\"5000\";\"1512345677000\";\"R U\";\"0\";\"\"\n\
+                      \"444\";\"Normal\";\"61230\";\"1512345600000\";\"Rw U2\";\"0\";\"\"\n\
+                      \"333\";\"OH\";\"20000\";\"1512345700000\";\"F\";\"2\";\"a; b\"\n";
+        let results = import_results(backup, 3).unwrap();
+        // oldest first, without the pyraminx
+        assert_eq!(
+            times(&results),
+            [
+                (61.23, Penalty::None),
+                (12., Penalty::PlusTwo),
+                (20., Penalty::Dnf)
+            ]
+        );
+        assert_eq!(
+            results.iter().map(|r| r.size).collect::<Vec<_>>(),
+            [4, 3, 3]
+        );
+        assert_eq!(results[0].date, Some(1_512_345_600.));
+        assert_eq!(results[1].scramble, Alg::from_str("R U").unwrap());
+        // a date that isn't a number of millis is left out, rather than
+        // stopping the import
+        let nan = "Puzzle;Category;Time(millis);Date(millis);Scramble;Penalty;Comment\n\
+                   \"333\";\"Normal\";\"14000\";\"NaN\";\"R U\";\"0\";\"\"\n\
+                   \"333\";\"Normal\";\"12000\";\"1512345678000\";\"F\";\"0\";\"\"\n";
+        let results = import_results(nan, 3).unwrap();
+        assert_eq!(
+            results.iter().map(|r| r.date).collect::<Vec<_>>(),
+            [None, Some(1_512_345_678.)]
+        );
+
+        let export = "\"12.34\";\"R U R'\";\"2017-12-03T12:34:56.789-02:00\"\n\
+                      \"DNF(9.87)\";\"F\";\"2017-12-03T14:35:00Z\"\n";
+        let results = import_results(export, 3).unwrap();
+        assert_eq!(
+            times(&results),
+            [(12.34, Penalty::None), (9.87, Penalty::Dnf)]
+        );
+        let date = results[0].date.unwrap();
+        assert_eq!(date_of_day(day_of(date)), "2017-12-03");
+        assert!((date - 1_512_311_696.789).abs() < 1e-3);
+        assert_eq!(results[1].date, Some(1_512_311_700.));
+
+        // cubedesu's own export, with the time before its penalty
+        let own = "Solve,Time,Penalty,Scramble\n1,12.34,,R U\n2,1:02.50,+2,F\n3,9.99,DNF,F\n";
+        let results = import_results(own, 3).unwrap();
+        assert_eq!(
+            times(&results),
+            [
+                (12.34, Penalty::None),
+                (62.5, Penalty::PlusTwo),
+                (9.99, Penalty::Dnf)
+            ]
+        );
+    }
+}
@@ -0,0 +1,586 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter};
+
+use crate::{
+    default_midi_bindings, Alg, Easing, Face, KeyMacro, KeymapPreset, Metric, MidiBinding,
+    MoveCosts, ScrambleFilter, ShapeMod, SolvePreference, SoundTheme, TurnStyle, ORDERED_FACES,
+};
+
+/// Which colours go on which faces.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, EnumIter, Display, Serialize, Deserialize)]
+pub enum ColorScheme {
+    /// white opposite yellow, green in front
+    Standard,
+    /// white opposite blue, green in front
+    Japanese,
+    /// Standard's layout in pure, bright colours, with magenta for orange so
+    /// it isn't mistaken for red, e.g. for low vision
+    #[strum(serialize = "High contrast")]
+    HighContrast,
+    /// Standard's layout in Okabe and Ito's colours, told apart with any
+    /// colour vision deficiency, with purple for orange
+    #[strum(serialize = "Colour blind")]
+    ColorBlind,
+}
+
+impl ColorScheme {
+    /// Returns the RGB colour of the stickers of face.
+    pub fn color(self, face: Face) -> [u8; 3] {
+        let (white, red, orange) = ([255, 255, 255], [230, 41, 55], [255, 161, 0]);
+        let (blue, yellow, green) = ([0, 121, 241], [253, 249, 0], [0, 228, 48]);
+        // ordered by U, R, F, D, L, then B face
+        let colors = match self {
+            ColorScheme::Standard => [white, red, green, yellow, orange, blue],
+            ColorScheme::Japanese => [white, red, green, blue, orange, yellow],
+            ColorScheme::HighContrast => [
+                white,
+                [255, 0, 0],
+                [0, 220, 0],
+                [255, 255, 0],
+                [255, 0, 255],
+                [0, 80, 255],
+            ],
+            ColorScheme::ColorBlind => [
+                white,
+                [213, 94, 0],
+                [0, 158, 115],
+                [240, 228, 66],
+                [204, 121, 167],
+                [0, 114, 178],
+            ],
+        };
+        match ORDERED_FACES.iter().position(|&f| f == face) {
+            Some(i) => colors[i],
+            None => [0, 0, 0],
+        }
+    }
+
+    /// Returns the name of the colour of the stickers of face, e.g. white.
+    pub fn color_name(self, face: Face) -> &'static str {
+        match (face, self) {
+            (Face::U, _) => "white",
+            (Face::R, _) => "red",
+            (Face::L, ColorScheme::HighContrast) => "magenta",
+            (Face::L, ColorScheme::ColorBlind) => "purple",
+            (Face::L, _) => "orange",
+            (Face::B, ColorScheme::Japanese) => "yellow",
+            (Face::D, ColorScheme::Japanese) => "blue",
+            (Face::B, _) => "blue",
+            (Face::D, _) => "yellow",
+            (Face::F, _) => "green",
+            _ => "grey",
+        }
+    }
+}
+
+/// How a student follows their teacher's cube.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, EnumIter, Display, Serialize, Deserialize)]
+pub enum FollowStyle {
+    /// the teacher's movements turn the student's cube
+    Mirror,
+    /// the teacher's cube is shown alongside the student's, for the student
+    /// to copy
+    Ghost,
+}
+
+/// How the cube's pieces look.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, EnumIter, Display, Serialize, Deserialize)]
+pub enum PieceStyle {
+    /// stickers floating on a black shell
+    Stickered,
+    /// solid pieces, coloured all over their outer faces
+    Stickerless,
+}
+
+/// How the cube is viewed, with a second view from the opposite corner
+/// showing the hidden faces, or not.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, EnumIter, Display, Serialize, Deserialize)]
+pub enum ViewLayout {
+    Single,
+    /// the second view in a corner of the screen
+    #[strum(serialize = "Picture in picture")]
+    PictureInPicture,
+    /// the screen split between both views
+    Split,
+}
+
+/// How the 3D view is projected onto the screen.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, EnumIter, Display, Serialize, Deserialize)]
+pub enum CameraProjection {
+    /// further away is smaller, like a photo
+    Perspective,
+    /// parallel lines stay parallel, e.g. for isometric screenshots and
+    /// diagrams
+    Orthographic,
+}
+
+/// How the hidden faces are shown when mirrors are on.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, EnumIter, Display, Serialize, Deserialize)]
+pub enum MirrorStyle {
+    /// mirrored stickers floating behind the cube, facing it
+    Floating,
+    /// the faces drawn flat in a panel in a corner of the screen (NxN cubes
+    /// only)
+    Panel,
+}
+
+/// How the cube is drawn.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, EnumIter, Display, Serialize, Deserialize)]
+pub enum RenderMode {
+    #[strum(serialize = "3D")]
+    Cube,
+    /// only the cube's net, flat, which is lighter on the GPU and how many
+    /// online trainers show states (NxN cubes only)
+    #[strum(serialize = "2D net")]
+    Net,
+}
+
+/// How to race other players over the network.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RaceSettings {
+    /// shown to the other players
+    pub name: String,
+    /// where to host the race, or the host's address to join it, e.g.
+    /// "192.168.1.2:9002"
+    pub address: String,
+    /// whether to host the race, scrambling every round, or join it
+    pub is_host: bool,
+}
+
+/// How a Twitch chat turns the cube.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, EnumIter, Display, Serialize, Deserialize)]
+pub enum ChatMode {
+    /// each viewer's move is turned as it's sent, a viewer at most once a
+    /// cooldown
+    Direct,
+    /// viewers vote on the next move, the most voted turned as each vote
+    /// closes
+    Vote,
+}
+
+/// How to let a Twitch channel's chat solve the cube (with the `chat`
+/// feature). Missing fields take their default value.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChatSettings {
+    /// the channel whose chat is read, e.g. "stanleydesu"
+    pub channel: String,
+    pub mode: ChatMode,
+    /// seconds a viewer waits between moves in Direct mode
+    pub cooldown: f32,
+    /// seconds each vote stays open in Vote mode, from its first vote
+    pub vote_duration: f32,
+    /// viewers whose moves aren't rate limited and who can scramble the cube
+    /// with !scramble
+    pub moderators: Vec<String>,
+}
+
+impl Default for ChatSettings {
+    fn default() -> Self {
+        Self {
+            channel: String::new(),
+            mode: ChatMode::Vote,
+            cooldown: 5.,
+            vote_duration: 10.,
+            moderators: vec![],
+        }
+    }
+}
+
+/// User preferences, saved as JSON. Missing fields take their default value.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// size of the cube shown on startup
+    pub cube_size: usize,
+    /// the layers along the x, y and z axes of a cuboid to show instead,
+    /// e.g. [3, 2, 3] for a 3x3x2
+    pub cuboid: Option<[usize; 3]>,
+    /// whether changing the cube size keeps the cube's state, copying its
+    /// outer layers and stretching or squeezing its inner ones, rather than
+    /// starting solved, see GCube::resized
+    pub resize_keeps_state: bool,
+    pub color_scheme: ColorScheme,
+    pub piece_style: PieceStyle,
+    /// the shape mod a 3x3 is drawn as, e.g. mirror blocks
+    pub shape_mod: ShapeMod,
+    /// how much of a piece's face its sticker covers, e.g. 0.9 leaves a
+    /// margin of 0.05 either side
+    pub sticker_size: f32,
+    /// how round the corners of stickers are, from 0 (square) to 1
+    /// (circular)
+    pub sticker_rounding: f32,
+    /// RGB colour of the plastic under the stickers
+    pub shell_color: [u8; 3],
+    /// whether faces are lit from one direction, so they differ in shade
+    pub shading: bool,
+    /// whether the layers of the last move flash briefly, e.g. for viewers
+    /// of a replay or stream
+    pub flash_last_move: bool,
+    /// whether the layers are pulled apart, so every sticker can be seen,
+    /// e.g. for teaching or checking an imported state
+    pub exploded_view: bool,
+    /// how far apart exploded layers are, in pieces
+    pub explode_gap: f32,
+    /// cubes at least this big have their same coloured stickers drawn
+    /// merged into blocks, to keep very big cubes responsive
+    pub merge_size: usize,
+    /// seconds taken to animate a single turn
+    pub turn_duration: f32,
+    pub easing: Easing,
+    pub turn_style: TurnStyle,
+    /// which keys turn which layers
+    pub keymap: KeymapPreset,
+    /// recorded algs, played by pressing ctrl and their key
+    pub macros: Vec<KeyMacro>,
+    /// the raw MIDI device to turn the cube with, e.g. "/dev/snd/midiC1D0",
+    /// or None not to
+    pub midi_device: Option<String>,
+    /// the csTimer keys that MIDI notes and controls press
+    pub midi_bindings: Vec<MidiBinding>,
+    /// whether to show mirrored stickers of the hidden faces
+    pub show_mirrors: bool,
+    pub mirror_style: MirrorStyle,
+    /// the faces mirrored, e.g. only B, L and D for the faces hidden from
+    /// the default camera
+    pub mirror_faces: Vec<Face>,
+    /// how far floating mirrors are from the cube's center, in half widths
+    /// of the cube, so 1 would be on its faces
+    pub mirror_distance: f32,
+    /// how opaque floating mirrors are, from 0 (invisible) to 1
+    pub mirror_opacity: f32,
+    /// whether to show the cube unfolded as a net in the top right corner,
+    /// so every face can be seen at once without distortion
+    pub show_net: bool,
+    pub render_mode: RenderMode,
+    pub view_layout: ViewLayout,
+    pub projection: CameraProjection,
+    /// vertical field of view of a perspective view, in degrees
+    pub fov: f32,
+    /// how close the camera can zoom in to the cube's center, in widths
+    /// of the cube
+    pub min_distance: f32,
+    /// how far the camera can zoom out from the cube's center, in widths
+    /// of the cube
+    pub max_distance: f32,
+    /// how far an orthographic view is zoomed in, 1 fits the cube
+    pub orthographic_zoom: f32,
+    /// how fast the camera moves around the cube
+    pub camera_speed: f32,
+    /// whether to show the timer, move count and TPS while solving
+    pub show_hud: bool,
+    /// how big text and panels are, on top of growing and shrinking with
+    /// the window
+    pub ui_scale: f32,
+    /// whether to show the frame rate, and how many stickers were drawn
+    pub show_fps: bool,
+    /// whether to slowly orbit the camera around the cube after no input
+    /// for showcase_delay seconds, e.g. as a display piece
+    pub showcase: bool,
+    pub showcase_delay: f32,
+    /// how moves are counted in the HUD
+    pub metric: Metric,
+    /// which of the solver's solutions of about the same length to pick
+    pub solve_preference: SolvePreference,
+    /// how awkward each movement is to turn, for the Ergonomic preference
+    pub move_costs: MoveCosts,
+    pub sound_theme: SoundTheme,
+    /// volume of sound effects, from 0 to 1
+    pub volume: f32,
+    /// whether each move and scramble is read out with the system's text to
+    /// speech, e.g. for learning notation with low vision
+    pub speech: bool,
+    /// whether to tick a metronome to pace turns to
+    pub metronome: bool,
+    /// the metronome's tempo, in beats per minute
+    pub bpm: f32,
+    /// whether to show an InspectionReport of a scrambled 3x3 while it's
+    /// inspected, hidden once the solve starts
+    pub inspection_report: bool,
+    /// whether to show a translucent ghost of the state being trained to
+    /// beside the cube, e.g. only the cross pieces, in place
+    pub ghost_target: bool,
+    /// constraints on 3x3 scrambles, e.g. no solved F2L pairs
+    pub scramble_filter: ScrambleFilter,
+    /// the cube sizes of a relay, in the order they're solved
+    pub relay_sizes: Vec<usize>,
+    /// where to broadcast the cube's moves and state to WebSocket clients,
+    /// e.g. "127.0.0.1:9001" for stream overlays, or None not to
+    pub broadcast_address: Option<String>,
+    /// whether clients of the broadcast can turn and scramble the cube
+    pub remote_control: bool,
+    /// the race to host or join on startup, if any
+    pub race: Option<RaceSettings>,
+    /// the address of a teacher's broadcast to follow, if any
+    pub follow_address: Option<String>,
+    pub follow_style: FollowStyle,
+    /// the Twitch chat to let turn the cube, if any
+    pub chat: Option<ChatSettings>,
+    /// the file to append every move, scramble and solve to as lines of
+    /// JSON, e.g. "cubedesu_moves.jsonl" for analysis scripts, or None not to
+    pub move_log: Option<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            cube_size: 3,
+            cuboid: None,
+            resize_keeps_state: false,
+            color_scheme: ColorScheme::Standard,
+            piece_style: PieceStyle::Stickered,
+            shape_mod: ShapeMod::Off,
+            sticker_size: 0.9,
+            sticker_rounding: 0.,
+            shell_color: [35, 39, 42],
+            shading: true,
+            flash_last_move: false,
+            exploded_view: false,
+            explode_gap: 1.,
+            merge_size: 30,
+            turn_duration: 0.15,
+            easing: Easing::EaseOut,
+            turn_style: TurnStyle::Smooth,
+            keymap: KeymapPreset::CsTimer,
+            macros: vec![],
+            midi_device: None,
+            midi_bindings: default_midi_bindings(),
+            show_mirrors: true,
+            mirror_style: MirrorStyle::Floating,
+            mirror_faces: ORDERED_FACES.to_vec(),
+            mirror_distance: 2.4,
+            mirror_opacity: 1.,
+            show_net: false,
+            render_mode: RenderMode::Cube,
+            view_layout: ViewLayout::Single,
+            projection: CameraProjection::Perspective,
+            fov: 60.,
+            min_distance: 1.5,
+            max_distance: 6.,
+            orthographic_zoom: 1.,
+            camera_speed: 1.,
+            show_hud: true,
+            ui_scale: 1.,
+            show_fps: false,
+            showcase: false,
+            showcase_delay: 30.,
+            metric: Metric::Htm,
+            solve_preference: SolvePreference::Ergonomic,
+            move_costs: MoveCosts::default(),
+            sound_theme: SoundTheme::Off,
+            volume: 0.5,
+            speech: false,
+            metronome: false,
+            bpm: 120.,
+            inspection_report: false,
+            ghost_target: false,
+            scramble_filter: ScrambleFilter::default(),
+            relay_sizes: vec![2, 3, 4],
+            broadcast_address: None,
+            remote_control: false,
+            race: None,
+            follow_address: None,
+            follow_style: FollowStyle::Mirror,
+            chat: None,
+            move_log: None,
+        }
+    }
+}
+
+impl Settings {
+    /// Loads the settings at path, or the default settings if there's no file.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    /// Returns the layers along each axis of the cube to show, the cuboid's
+    /// if there is one.
+    pub fn dims(&self) -> [usize; 3] {
+        self.cuboid.unwrap_or([self.cube_size; 3])
+    }
+
+    /// Shows a cube of dims, as the cube size if it's NxN, or else as a
+    /// cuboid.
+    pub fn set_dims(&mut self, dims: [usize; 3]) {
+        match dims {
+            [x, y, z] if x == y && y == z => {
+                self.cube_size = x;
+                self.cuboid = None;
+            }
+            _ => self.cuboid = Some(dims),
+        }
+    }
+
+    /// Binds alg to ctrl and key, replacing the key's macro, or unbinding
+    /// the key if alg is empty.
+    pub fn bind_macro(&mut self, key: char, alg: Alg) {
+        self.macros.retain(|m| m.key != key);
+        if !alg.is_empty() {
+            self.macros.push(KeyMacro { key, alg });
+        }
+    }
+
+    /// Returns the alg bound to ctrl and key, if any.
+    pub fn macro_alg(&self, key: char) -> Option<&Alg> {
+        self.macros.iter().find(|m| m.key == key).map(|m| &m.alg)
+    }
+}
+
+/// Watches a settings file for changes made outside the app, e.g. in a text
+/// editor beside it, so they can be applied without restarting.
+#[derive(Clone, Debug)]
+pub struct SettingsWatcher {
+    path: PathBuf,
+    // when the file was last modified, as last seen
+    modified: Option<SystemTime>,
+    // when the file was last checked, in seconds
+    checked_at: f64,
+}
+
+impl SettingsWatcher {
+    /// How often the file is checked, in seconds.
+    pub const INTERVAL: f64 = 0.5;
+
+    pub fn new(path: impl AsRef<Path>, now: f64) -> Self {
+        let path = path.as_ref().to_path_buf();
+        Self {
+            modified: modified(&path),
+            path,
+            checked_at: now,
+        }
+    }
+
+    /// Returns the settings in the file if it's changed since the last
+    /// check, at most every INTERVAL seconds. Settings that don't parse,
+    /// e.g. while they're half typed, are skipped until the next change.
+    pub fn changed(&mut self, now: f64) -> Option<Settings> {
+        if now - self.checked_at < Self::INTERVAL {
+            return None;
+        }
+        self.checked_at = now;
+        let modified = modified(&self.path);
+        if modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+        let json = fs::read_to_string(&self.path).ok()?;
+        serde_json::from_str(&json).ok()
+    }
+
+    /// Notes that the app saved the file itself, which isn't a change to
+    /// apply.
+    pub fn saved(&mut self) {
+        self.modified = modified(&self.path);
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{collections::HashSet, time::Duration};
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn schemes_color_each_face_differently() {
+        for scheme in ColorScheme::iter() {
+            let colors: HashSet<[u8; 3]> = ORDERED_FACES.iter().map(|&f| scheme.color(f)).collect();
+            let names: HashSet<&str> = ORDERED_FACES
+                .iter()
+                .map(|&f| scheme.color_name(f))
+                .collect();
+            assert_eq!((colors.len(), names.len()), (6, 6), "{}", scheme);
+        }
+        assert_eq!(ColorScheme::Japanese.color_name(Face::D), "blue");
+        assert_eq!(ColorScheme::HighContrast.color_name(Face::L), "magenta");
+    }
+
+    #[test]
+    fn missing_fields_are_defaulted() {
+        let settings: Settings = serde_json::from_str(r#"{ "show_hud": false }"#).unwrap();
+        assert_eq!(
+            settings,
+            Settings {
+                show_hud: false,
+                ..Settings::default()
+            }
+        );
+    }
+
+    #[test]
+    fn save_and_load() {
+        let path = std::env::temp_dir().join("cubedesu_settings_test.json");
+        let mut settings = Settings {
+            cube_size: 5,
+            color_scheme: ColorScheme::Japanese,
+            show_hud: false,
+            metric: Metric::Stm,
+            keymap: KeymapPreset::DvorakLefty,
+            ..Settings::default()
+        };
+        settings.bind_macro('a', Alg::new(vec![]));
+        settings.bind_macro('s', "R U R' U'".parse().unwrap());
+        settings.bind_macro('s', "R' F R F'".parse().unwrap());
+        assert_eq!(settings.macros.len(), 1);
+        assert_eq!(settings.macro_alg('s').unwrap().to_string(), "R' F R F'");
+        settings.save(&path).unwrap();
+        assert_eq!(Settings::load(&path).unwrap(), settings);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(Settings::load(&path).unwrap(), Settings::default());
+    }
+
+    #[test]
+    fn watcher_sees_changes_made_elsewhere() {
+        let path = std::env::temp_dir().join("cubedesu_settings_watch_test.json");
+        // file times can be coarse, so each write is dated a second later
+        let write = |json: &str, seconds: u64| {
+            fs::write(&path, json).unwrap();
+            let at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_800_000_000 + seconds);
+            fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(at)
+                .unwrap();
+        };
+        write("{}", 0);
+        let mut watcher = SettingsWatcher::new(&path, 0.);
+        assert_eq!(watcher.changed(1.), None);
+
+        write(r#"{ "color_scheme": "Japanese" }"#, 1);
+        // not checked again so soon
+        assert_eq!(watcher.changed(1.1), None);
+        let changed = watcher.changed(2.).unwrap();
+        assert_eq!(changed.color_scheme, ColorScheme::Japanese);
+        assert_eq!(watcher.changed(3.), None);
+
+        // half typed, then finished
+        write(r#"{ "color_scheme": "#, 2);
+        assert_eq!(watcher.changed(4.), None);
+        write(r#"{ "turn_duration": 0.05 }"#, 3);
+        assert_eq!(watcher.changed(5.).unwrap().turn_duration, 0.05);
+
+        // the app's own saves aren't changes
+        write("{}", 4);
+        watcher.saved();
+        assert_eq!(watcher.changed(6.), None);
+        fs::remove_file(&path).unwrap();
+    }
+}
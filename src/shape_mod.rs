@@ -0,0 +1,305 @@
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter};
+
+use crate::{ColorScheme, Face, GCube, Piece, Point3};
+
+/// A shape mod of a 3x3, which turns like a 3x3 but has pieces of other
+/// shapes, so it changes shape as it's turned.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, EnumIter, Display, Serialize, Deserialize)]
+pub enum ShapeMod {
+    Off,
+    /// one colour all over, with each layer a different width, so it's
+    /// solved by its shape
+    MirrorBlocks,
+    /// the shell is turned at an angle to the layers, so the cuts between
+    /// them run diagonally across its faces
+    Axis,
+}
+
+/// A flat face of a piece of a shape mod.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShapeFace {
+    /// counter-clockwise seen from outside the piece
+    pub corners: Vec<[f32; 3]>,
+    /// the face of the shell it's on, or None for a cut between layers
+    pub face: Option<Face>,
+}
+
+// where a 3x3's layers are cut apart, with pieces 2 units wide
+const CUT: f32 = 1.;
+// further out than any shell, which cuts the outer pieces down to size
+const FAR: f32 = 20.;
+// the shell's faces, with the unit vectors out of them
+const SHELL_FACES: [(Face, [f32; 3]); 6] = [
+    (Face::R, [1., 0., 0.]),
+    (Face::L, [-1., 0., 0.]),
+    (Face::U, [0., 1., 0.]),
+    (Face::D, [0., -1., 0.]),
+    (Face::F, [0., 0., 1.]),
+    (Face::B, [0., 0., -1.]),
+];
+
+impl ShapeMod {
+    /// Returns the faces of a piece of a 3x3, turned to where it is now, or
+    /// none when the shape mod's off, the cube isn't a 3x3, or which way the
+    /// piece is turned isn't known.
+    pub fn piece_faces(self, gcube: &GCube, piece: &Piece) -> Vec<ShapeFace> {
+        let (home, axes) = match gcube.piece_orientation(piece) {
+            Some(orientation) if self != ShapeMod::Off && gcube.dims == [3; 3] => orientation,
+            _ => return vec![],
+        };
+        let turn = |p: [f32; 3]| {
+            let mut turned = [0.; 3];
+            for (c, axis) in p.iter().zip(axes.iter()) {
+                let axis = [axis.x, axis.y, axis.z];
+                for (t, a) in turned.iter_mut().zip(axis.iter()) {
+                    *t += c * *a as f32;
+                }
+            }
+            turned
+        };
+        self.home_faces(home)
+            .into_iter()
+            .map(|f| ShapeFace {
+                corners: f.corners.into_iter().map(turn).collect(),
+                face: f.face,
+            })
+            .collect()
+    }
+
+    /// Returns the colour of a face of the shell, which is the face's own
+    /// except on mirror blocks.
+    pub fn color(self, face: Face, scheme: ColorScheme) -> [u8; 3] {
+        match self {
+            ShapeMod::MirrorBlocks => [200, 200, 205],
+            _ => scheme.color(face),
+        }
+    }
+
+    // the faces of the piece centered at home on a solved cube: the space
+    // between the cuts around it, cut down to the shell
+    fn home_faces(self, home: Point3) -> Vec<ShapeFace> {
+        let bounds = [home.x, home.y, home.z].map(|c| match c.signum() {
+            -1 => (-FAR, -CUT),
+            0 => (-CUT, CUT),
+            _ => (CUT, FAR),
+        });
+        let mut faces = box_faces(bounds);
+        for (face, normal, distance) in self.shell() {
+            faces = clip(faces, face, normal, distance);
+        }
+        faces
+    }
+
+    // the planes of the shell's faces, as the face, the unit vector out of
+    // it, and how far it is from the center
+    fn shell(self) -> Vec<(Face, [f32; 3], f32)> {
+        match self {
+            ShapeMod::Off => vec![],
+            // e.g. the R layer is thicker than the L layer
+            ShapeMod::MirrorBlocks => SHELL_FACES
+                .iter()
+                .zip([3.5, 2.5, 2.4, 3.6, 3.3, 2.7])
+                .map(|(&(face, normal), distance)| (face, normal, distance))
+                .collect(),
+            // a cube turned 45° about y, then tilted 35° about x
+            ShapeMod::Axis => SHELL_FACES
+                .iter()
+                .map(|&(face, normal)| {
+                    let normal = rotate(normal, 1, std::f32::consts::FRAC_PI_4);
+                    (face, rotate(normal, 0, 35f32.to_radians()), 3.)
+                })
+                .collect(),
+        }
+    }
+}
+
+// the faces of the box between bounds along each axis
+fn box_faces(bounds: [(f32, f32); 3]) -> Vec<ShapeFace> {
+    let mut faces = vec![];
+    for axis in 0..3 {
+        let (a, b) = ((axis + 1) % 3, (axis + 2) % 3);
+        for is_max in [false, true] {
+            let mut corners: Vec<[f32; 3]> = [(0, 0), (1, 0), (1, 1), (0, 1)]
+                .iter()
+                .map(|&(i, j)| {
+                    let mut corner = [0.; 3];
+                    corner[axis] = if is_max {
+                        bounds[axis].1
+                    } else {
+                        bounds[axis].0
+                    };
+                    corner[a] = if i == 0 { bounds[a].0 } else { bounds[a].1 };
+                    corner[b] = if j == 0 { bounds[b].0 } else { bounds[b].1 };
+                    corner
+                })
+                .collect();
+            // the corners go counter-clockwise seen from the max side
+            if !is_max {
+                corners.reverse();
+            }
+            faces.push(ShapeFace {
+                corners,
+                face: None,
+            });
+        }
+    }
+    faces
+}
+
+// cuts away the parts of a convex piece's faces outside the plane of the
+// shell's face, and closes the hole that leaves with a face on the plane
+fn clip(faces: Vec<ShapeFace>, face: Face, normal: [f32; 3], distance: f32) -> Vec<ShapeFace> {
+    let height = |p: [f32; 3]| dot(p, normal) - distance;
+    let mut on_plane = vec![];
+    let mut clipped = vec![];
+    for f in faces {
+        let n = f.corners.len();
+        let mut corners = vec![];
+        for i in 0..n {
+            let (p, q) = (f.corners[i], f.corners[(i + 1) % n]);
+            let (hp, hq) = (height(p), height(q));
+            if hp <= 0. {
+                corners.push(p);
+            }
+            if hp == 0. {
+                on_plane.push(p);
+            }
+            if (hp < 0. && hq > 0.) || (hp > 0. && hq < 0.) {
+                let t = hp / (hp - hq);
+                let cut = [0, 1, 2].map(|k| p[k] + (q[k] - p[k]) * t);
+                corners.push(cut);
+                on_plane.push(cut);
+            }
+        }
+        if corners.len() >= 3 {
+            clipped.push(ShapeFace {
+                corners,
+                face: f.face,
+            });
+        }
+    }
+    let mut hole: Vec<[f32; 3]> = vec![];
+    for p in on_plane {
+        if hole
+            .iter()
+            .all(|h| (0..3).any(|k| (h[k] - p[k]).abs() > 1e-4))
+        {
+            hole.push(p);
+        }
+    }
+    if hole.len() >= 3 {
+        // going around the hole's center counter-clockwise seen from outside,
+        // along u then v, where u x v is the normal
+        let n = hole.len() as f32;
+        let center = [0, 1, 2].map(|k| hole.iter().map(|p| p[k]).sum::<f32>() / n);
+        let helper = if normal[0].abs() < 0.9 {
+            [1., 0., 0.]
+        } else {
+            [0., 1., 0.]
+        };
+        let u = cross(normal, helper);
+        let v = cross(normal, u);
+        let angle = |p: &[f32; 3]| {
+            let d = [0, 1, 2].map(|k| p[k] - center[k]);
+            dot(d, v).atan2(dot(d, u))
+        };
+        hole.sort_by(|p, q| angle(p).partial_cmp(&angle(q)).unwrap());
+        clipped.push(ShapeFace {
+            corners: hole,
+            face: Some(face),
+        });
+    }
+    clipped
+}
+
+// rotates v anticlockwise by angle about the x, y or z axis (0, 1 or 2)
+fn rotate(v: [f32; 3], axis: usize, angle: f32) -> [f32; 3] {
+    let (a, b) = ((axis + 1) % 3, (axis + 2) % 3);
+    let (sin, cos) = angle.sin_cos();
+    let mut rotated = v;
+    rotated[a] = v[a] * cos - v[b] * sin;
+    rotated[b] = v[a] * sin + v[b] * cos;
+    rotated
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    (0..3).map(|k| a[k] * b[k]).sum()
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scramble_to_movements;
+
+    fn faces(shape_mod: ShapeMod, gcube: &GCube) -> Vec<Vec<ShapeFace>> {
+        gcube
+            .pieces()
+            .iter()
+            .map(|piece| shape_mod.piece_faces(gcube, piece))
+            .collect()
+    }
+
+    fn turned(scramble: &str) -> GCube {
+        let mut gcube = GCube::new(3);
+        gcube.apply_movements(&scramble_to_movements(scramble).unwrap());
+        gcube
+    }
+
+    #[test]
+    fn pieces_are_cut_down_to_the_shell() {
+        let gcube = GCube::new(3);
+        for shape_mod in [ShapeMod::MirrorBlocks, ShapeMod::Axis] {
+            let pieces = faces(shape_mod, &gcube);
+            assert_eq!(pieces.len(), 26);
+            for faces in pieces {
+                // closed, with the shell on the outside
+                assert!(faces.len() >= 4);
+                assert!(faces.iter().any(|f| f.face.is_some()));
+                for corner in faces.iter().flat_map(|f| f.corners.iter()) {
+                    for (_, normal, distance) in shape_mod.shell() {
+                        assert!(dot(*corner, normal) <= distance + 1e-4);
+                    }
+                }
+            }
+        }
+        assert!(faces(ShapeMod::Off, &gcube).iter().all(Vec::is_empty));
+        assert!(faces(ShapeMod::Axis, &GCube::new(4))
+            .iter()
+            .all(Vec::is_empty));
+    }
+
+    #[test]
+    fn shape_changes_as_it_turns() {
+        let top = |gcube: &GCube| {
+            faces(ShapeMod::MirrorBlocks, gcube)
+                .iter()
+                .flatten()
+                .flat_map(|f| f.corners.iter().map(|c| c[1]))
+                .fold(f32::MIN, f32::max)
+        };
+        assert_eq!(top(&GCube::new(3)), 2.4);
+        // the R layer's F side comes up
+        assert!((top(&turned("R")) - 3.3).abs() < 1e-4);
+
+        // the U center's shape turns with it on an axis cube
+        let solved = faces(ShapeMod::Axis, &GCube::new(3));
+        assert_ne!(faces(ShapeMod::Axis, &turned("U")), solved);
+        assert_eq!(faces(ShapeMod::Axis, &turned("U U U U")), solved);
+        assert_eq!(
+            faces(
+                ShapeMod::Axis,
+                &turned("R U R' U' R U R' U' R U R' U' R U R' U' R U R' U' R U R' U'")
+            ),
+            solved
+        );
+    }
+}
@@ -0,0 +1,706 @@
+use std::{
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        OnceLock,
+    },
+    thread,
+};
+
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter};
+
+use crate::{Alg, CubieCube, FaceletModel, GCube, Move, MoveCosts, Movement, StateError, Turn};
+
+// moves are numbered 3 * face + quarter turns - 1, with faces numbered like
+// ORDERED_FACES (U, R, F, D, L, then B), e.g. 4 for R2
+const N_MOVES: usize = 18;
+const N_TWIST: usize = 2187; // 3^7 corner twists
+const N_FLIP: usize = 2048; // 2^11 edge flips
+const N_SLICE: usize = 495; // 12 choose 4 positions of the FR, FL, BL and BR edges
+const N_PERM_8: usize = 40320; // 8! permutations of the corners, or of the U and D edges
+const N_PERM_4: usize = 24; // 4! permutations of the FR, FL, BL and BR edges
+
+// whether a move keeps a cube in the subgroup phase 2 solves in, i.e. turns
+// of U and D, and half turns of the other faces
+fn is_phase_2_move(m: usize) -> bool {
+    m / 3 == 0 || m / 3 == 3 || m % 3 == 1
+}
+
+// whether m needn't follow the last move, as it turns the same face, or the
+// face opposite it (which commutes, so only one order is searched)
+fn is_redundant(last: Option<usize>, m: usize) -> bool {
+    last.is_some_and(|last| last / 3 == m / 3 || last / 3 == m / 3 + 3)
+}
+
+fn binomial(n: usize, k: usize) -> usize {
+    if k > n {
+        return 0;
+    }
+    (0..k).fold(1, |c, i| c * (n - i) / (i + 1))
+}
+
+// ranks a permutation of the values offset..offset + perm.len()
+fn perm_coord(perm: &[usize], offset: usize) -> usize {
+    let mut perm = perm.to_vec();
+    let mut coord = 0;
+    for j in (1..perm.len()).rev() {
+        let mut k = 0;
+        while perm[j] != j + offset {
+            perm[..=j].rotate_left(1);
+            k += 1;
+        }
+        coord = (j + 1) * coord + k;
+    }
+    coord
+}
+
+fn set_perm_coord(perm: &mut [usize], offset: usize, mut coord: usize) {
+    for (j, p) in perm.iter_mut().enumerate() {
+        *p = j + offset;
+    }
+    for j in 0..perm.len() {
+        let k = coord % (j + 1);
+        coord /= j + 1;
+        for _ in 0..k {
+            perm[..=j].rotate_right(1);
+        }
+    }
+}
+
+// the coordinates of a cube, each a number for part of its state, in which
+// a solved cube is 0
+impl CubieCube {
+    fn twist(&self) -> usize {
+        self.co[..7].iter().fold(0, |t, &o| 3 * t + o as usize)
+    }
+
+    fn set_twist(&mut self, mut twist: usize) {
+        for i in (0..7).rev() {
+            self.co[i] = (twist % 3) as u8;
+            twist /= 3;
+        }
+        self.co[7] = (3 - self.co[..7].iter().sum::<u8>() % 3) % 3;
+    }
+
+    fn flip(&self) -> usize {
+        self.eo[..11].iter().fold(0, |f, &o| 2 * f + o as usize)
+    }
+
+    fn set_flip(&mut self, mut flip: usize) {
+        for i in (0..11).rev() {
+            self.eo[i] = (flip % 2) as u8;
+            flip /= 2;
+        }
+        self.eo[11] = self.eo[..11].iter().sum::<u8>() % 2;
+    }
+
+    // where the FR, FL, BL and BR edges are, in any order
+    fn slice(&self) -> usize {
+        let (mut slice, mut x) = (0, 0);
+        for j in (0..12).rev() {
+            if self.ep[j] >= 8 {
+                slice += binomial(11 - j, x + 1);
+                x += 1;
+            }
+        }
+        slice
+    }
+
+    fn set_slice(&mut self, mut slice: usize) {
+        let mut is_slice = [false; 12];
+        let mut x = 4;
+        for (j, is_slice) in is_slice.iter_mut().enumerate() {
+            if x > 0 && slice >= binomial(11 - j, x) {
+                *is_slice = true;
+                slice -= binomial(11 - j, x);
+                x -= 1;
+            }
+        }
+        let (mut slice_edges, mut other_edges) = (8..12, 0..8);
+        for (j, &is_slice) in is_slice.iter().enumerate() {
+            let edges = if is_slice {
+                &mut slice_edges
+            } else {
+                &mut other_edges
+            };
+            self.ep[j] = edges.next().unwrap();
+        }
+    }
+}
+
+/// Solves 3x3 cubes with Kociemba's two-phase algorithm: first turning a
+/// cube into one solvable with only U, D and half turns of the other faces,
+/// then solving it with those. Building its tables takes a moment, so a
+/// [shared](Solver::shared) solver is kept.
+pub struct Solver {
+    twist_moves: Vec<u16>,
+    flip_moves: Vec<u16>,
+    slice_moves: Vec<u16>,
+    corner_moves: Vec<u16>,
+    edge_moves: Vec<u16>,       // of the U and D edges, for phase 2 moves only
+    slice_perm_moves: Vec<u16>, // for phase 2 moves only
+    // the fewest moves to get from a pair of coordinates to both being 0
+    twist_slice_depths: Vec<u8>,
+    flip_slice_depths: Vec<u8>,
+    corner_slice_depths: Vec<u8>,
+    edge_slice_depths: Vec<u8>,
+}
+
+impl Solver {
+    pub fn new() -> Self {
+        let twist_moves = move_table(N_TWIST, |c| c.twist(), |c, t| c.set_twist(t), false);
+        let flip_moves = move_table(N_FLIP, |c| c.flip(), |c, f| c.set_flip(f), false);
+        let slice_moves = move_table(N_SLICE, |c| c.slice(), |c, s| c.set_slice(s), false);
+        let corner_moves = move_table(
+            N_PERM_8,
+            |c| perm_coord(&c.cp, 0),
+            |c, p| set_perm_coord(&mut c.cp, 0, p),
+            false,
+        );
+        let edge_moves = move_table(
+            N_PERM_8,
+            |c| perm_coord(&c.ep[..8], 0),
+            |c, p| set_perm_coord(&mut c.ep[..8], 0, p),
+            true,
+        );
+        let slice_perm_moves = move_table(
+            N_PERM_4,
+            |c| perm_coord(&c.ep[8..], 8),
+            |c, p| set_perm_coord(&mut c.ep[8..], 8, p),
+            true,
+        );
+        let all_moves: Vec<usize> = (0..N_MOVES).collect();
+        let phase_2_moves: Vec<usize> = (0..N_MOVES).filter(|&m| is_phase_2_move(m)).collect();
+        Self {
+            twist_slice_depths: depth_table(&twist_moves, &slice_moves, N_SLICE, &all_moves),
+            flip_slice_depths: depth_table(&flip_moves, &slice_moves, N_SLICE, &all_moves),
+            corner_slice_depths: depth_table(
+                &corner_moves,
+                &slice_perm_moves,
+                N_PERM_4,
+                &phase_2_moves,
+            ),
+            edge_slice_depths: depth_table(
+                &edge_moves,
+                &slice_perm_moves,
+                N_PERM_4,
+                &phase_2_moves,
+            ),
+            twist_moves,
+            flip_moves,
+            slice_moves,
+            corner_moves,
+            edge_moves,
+            slice_perm_moves,
+        }
+    }
+
+    /// Returns a solver shared by every caller, built on first use.
+    pub fn shared() -> &'static Solver {
+        static SOLVER: OnceLock<Solver> = OnceLock::new();
+        SOLVER.get_or_init(Solver::new)
+    }
+
+    /// Returns a solution of cube, usually of about 20 to 25 moves, from
+    /// searching for shorter solutions until max_tries phase 1 solutions in
+    /// a row don't lead to one.
+    pub fn solve(&self, cube: &CubieCube, max_tries: usize) -> Alg {
+        self.solutions(cube, max_tries).pop().unwrap()
+    }
+
+    /// Returns the solutions of cube found on the way to the one solve
+    /// returns, each shorter than the last, which ends the list.
+    pub fn solutions(&self, cube: &CubieCube, max_tries: usize) -> Vec<Alg> {
+        let mut solutions = vec![self.solve_within(cube, 30, usize::MAX).unwrap()];
+        while let Some(shorter) = solutions
+            .last()
+            .unwrap()
+            .len()
+            .checked_sub(1)
+            .and_then(|max_length| self.solve_within(cube, max_length, max_tries))
+        {
+            solutions.push(shorter);
+        }
+        solutions
+    }
+
+    /// Returns the solution of cube preference likes best, out of those up
+    /// to PREFERENCE_SLACK moves longer than the shortest found, e.g. one a
+    /// move longer but without B turns. Solutions it likes as much are
+    /// told apart by length.
+    pub fn solve_preferring(
+        &self,
+        cube: &CubieCube,
+        preference: SolvePreference,
+        costs: &MoveCosts,
+        max_tries: usize,
+    ) -> Alg {
+        let mut candidates = self.solutions(cube, max_tries);
+        let shortest = candidates.last().unwrap().len();
+        if preference == SolvePreference::Shortest {
+            return candidates.pop().unwrap();
+        }
+        candidates.extend(self.solve_all_within(
+            cube,
+            shortest + PREFERENCE_SLACK,
+            max_tries,
+            MAX_CANDIDATES,
+        ));
+        let score = |alg: &Alg| match preference {
+            SolvePreference::Shortest => 0.,
+            SolvePreference::Ergonomic => costs.cost_all(alg.movements()),
+            SolvePreference::RufGen => alg
+                .movements()
+                .iter()
+                .filter(|m| !matches!(m.0, Move::R | Move::U | Move::F))
+                .count() as f64,
+        };
+        candidates
+            .into_iter()
+            .map(|alg| (score(&alg), alg))
+            .min_by(|(a, a_alg), (b, b_alg)| a.total_cmp(b).then(a_alg.len().cmp(&b_alg.len())))
+            .unwrap()
+            .1
+    }
+
+    /// Returns a solution of cube of at most max_length moves, or None if
+    /// none was found within max_tries phase 1 solutions. Every cube can be
+    /// solved in 30 moves, but finding solutions under about 20 moves can
+    /// take a while.
+    pub fn solve_within(
+        &self,
+        cube: &CubieCube,
+        max_length: usize,
+        max_tries: usize,
+    ) -> Option<Alg> {
+        self.solve_all_within(cube, max_length, max_tries, 1).pop()
+    }
+
+    /// Returns up to limit solutions of cube of at most max_length moves,
+    /// from the first max_tries phase 1 solutions.
+    pub fn solve_all_within(
+        &self,
+        cube: &CubieCube,
+        max_length: usize,
+        max_tries: usize,
+        limit: usize,
+    ) -> Vec<Alg> {
+        let mut search = Search {
+            solver: self,
+            cube,
+            max_length,
+            tries: max_tries,
+            moves: vec![],
+            found: vec![],
+            limit,
+        };
+        let (twist, flip, slice) = (cube.twist(), cube.flip(), cube.slice());
+        for depth in 0..=max_length {
+            if search.phase_1(twist, flip, slice, depth) || search.tries == 0 {
+                break;
+            }
+        }
+        search
+            .found
+            .iter()
+            .map(|moves| Alg::new(moves.iter().map(|&m| to_movement(m)).collect()))
+            .collect()
+    }
+}
+
+impl Default for Solver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// How many moves longer than the shortest solution found the solutions
+/// SolvePreferences other than Shortest choose from can be.
+pub const PREFERENCE_SLACK: usize = 2;
+
+// how many of the longer solutions are compared
+const MAX_CANDIDATES: usize = 200;
+
+/// Which of the solutions of about the same length the solver picks.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, EnumIter, Display, Serialize, Deserialize)]
+pub enum SolvePreference {
+    /// the shortest
+    Shortest,
+    /// the cheapest by the settings' MoveCosts
+    Ergonomic,
+    /// the one with fewest turns of L, D and B, i.e. mostly R, U and F
+    #[strum(serialize = "<R,U,F>")]
+    RufGen,
+}
+
+/// Returns a solution of a 3x3's facelets, from the [shared](Solver::shared)
+/// solver, or why they aren't a solvable cube's.
+pub fn solve(facelets: &FaceletModel) -> Result<Alg, StateError> {
+    let cube = CubieCube::from_facelets(facelets)?;
+    Ok(Solver::shared().solve(&cube, 50))
+}
+
+/// Hints at the next move of a solution of a 3x3, from the
+/// [shared](Solver::shared) solver. The solution is kept while the cube
+/// follows it, and only solved for again once the cube goes off it.
+#[derive(Clone, Debug, Default)]
+pub struct SolutionHint {
+    solving: Option<(GCube, Alg)>, // the state being solved, and its solution
+}
+
+impl SolutionHint {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the next move of a solution of gcube, None if it's solved, or
+    /// why it isn't a solvable 3x3.
+    pub fn next_move(&mut self, gcube: &GCube) -> Result<Option<Movement>, StateError> {
+        if let Some((state, solution)) = self.solving.as_mut() {
+            // the hint was followed, so the rest of the solution still solves
+            if let Some(&first) = solution.movements().first().filter(|_| state != gcube) {
+                let mut next = state.clone();
+                next.apply_movement(&first);
+                if &next == gcube {
+                    *state = next;
+                    *solution = Alg::new(solution.movements()[1..].to_vec());
+                }
+            }
+        }
+        match self.solving.as_ref() {
+            Some((state, solution)) if state == gcube => Ok(solution.movements().first().copied()),
+            _ => {
+                self.solving = None;
+                if gcube.dims != [3; 3] {
+                    return Err(StateError::WrongLength {
+                        expected: 54,
+                        found: gcube.facelets().len(),
+                    });
+                }
+                let solution = solve(&gcube.to_facelet_model())?;
+                let next = solution.movements().first().copied();
+                self.solving = Some((gcube.clone(), solution));
+                Ok(next)
+            }
+        }
+    }
+}
+
+/// Solves 3x3s with the [shared](Solver::shared) solver on a thread of its
+/// own, so neither building its tables nor searching holds up drawing. The
+/// tables are built as soon as it's made, and solutions are polled for,
+/// e.g. once a frame.
+pub struct SolverThread {
+    sender: Sender<(CubieCube, SolvePreference, MoveCosts)>,
+    receiver: Receiver<(CubieCube, Alg)>,
+}
+
+impl SolverThread {
+    pub fn new() -> Self {
+        let (sender, requests) = mpsc::channel::<(CubieCube, SolvePreference, MoveCosts)>();
+        let (solved, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let solver = Solver::shared();
+            while let Ok((cube, preference, costs)) = requests.recv() {
+                let solution = solver.solve_preferring(&cube, preference, &costs, 50);
+                if solved.send((cube, solution)).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { sender, receiver }
+    }
+
+    /// Starts solving cube as preferred, after the cubes it's solving already.
+    pub fn solve(&self, cube: CubieCube, preference: SolvePreference, costs: &MoveCosts) {
+        // there's nothing to do if the thread's gone
+        self.sender.send((cube, preference, costs.clone())).ok();
+    }
+
+    /// Returns the cubes solved since the last poll with their solutions,
+    /// without blocking.
+    pub fn poll(&self) -> Vec<(CubieCube, Alg)> {
+        self.receiver.try_iter().collect()
+    }
+}
+
+impl Default for SolverThread {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn to_movement(m: usize) -> Movement {
+    let moves = [Move::U, Move::R, Move::F, Move::D, Move::L, Move::B];
+    let turns = [Turn::Single, Turn::Double, Turn::Inverse];
+    Movement::new(moves[m / 3], turns[m % 3])
+}
+
+// tabulates how every move changes a coordinate, from a cube with each of its
+// n values, only for phase 2 moves if the coordinate's only valid in phase 2
+fn move_table(
+    n: usize,
+    get: impl Fn(&CubieCube) -> usize,
+    set: impl Fn(&mut CubieCube, usize),
+    is_phase_2: bool,
+) -> Vec<u16> {
+    let mut table = vec![0; n * N_MOVES];
+    for coord in 0..n {
+        let mut cube = CubieCube::default();
+        set(&mut cube, coord);
+        for face in 0..6 {
+            for quarter_turns in 0..4 {
+                cube.turn_face(face, 1);
+                let m = 3 * face + quarter_turns;
+                if quarter_turns < 3 && (!is_phase_2 || is_phase_2_move(m)) {
+                    table[coord * N_MOVES + m] = get(&cube) as u16;
+                }
+            }
+        }
+    }
+    table
+}
+
+// tabulates the fewest moves to solve a pair of coordinates, searching
+// breadth first from solved
+fn depth_table(moves_a: &[u16], moves_b: &[u16], n_b: usize, moves: &[usize]) -> Vec<u8> {
+    let n = moves_a.len() / N_MOVES * n_b;
+    let mut depths = vec![u8::MAX; n];
+    depths[0] = 0;
+    let mut frontier = vec![0];
+    let mut depth = 0;
+    while !frontier.is_empty() {
+        depth += 1;
+        let mut next = vec![];
+        for &i in frontier.iter() {
+            let (a, b) = (i / n_b, i % n_b);
+            for &m in moves {
+                let j = moves_a[a * N_MOVES + m] as usize * n_b + moves_b[b * N_MOVES + m] as usize;
+                if depths[j] == u8::MAX {
+                    depths[j] = depth;
+                    next.push(j);
+                }
+            }
+        }
+        frontier = next;
+    }
+    depths
+}
+
+// an iterative deepening search for a solution, the moves so far kept in moves
+struct Search<'a> {
+    solver: &'a Solver,
+    cube: &'a CubieCube,
+    max_length: usize,
+    tries: usize, // how many more phase 1 solutions to try phase 2 from
+    moves: Vec<usize>,
+    found: Vec<Vec<usize>>,
+    limit: usize, // how many solutions to find before stopping
+}
+
+impl Search<'_> {
+    // searches for togo more phase 1 moves, then phase 2, returning whether
+    // enough solutions were found
+    fn phase_1(&mut self, twist: usize, flip: usize, slice: usize, togo: usize) -> bool {
+        let s = self.solver;
+        if togo == 0 {
+            // a phase 1 solution ending in a phase 2 move was tried a move shorter
+            let is_new = self.moves.last().is_none_or(|&m| !is_phase_2_move(m));
+            if twist != 0 || flip != 0 || slice != 0 || !is_new || self.tries == 0 {
+                return false;
+            }
+            self.tries -= 1;
+            return self.start_phase_2();
+        }
+        let depth = s.twist_slice_depths[twist * N_SLICE + slice]
+            .max(s.flip_slice_depths[flip * N_SLICE + slice]);
+        if depth as usize > togo {
+            return false;
+        }
+        for m in 0..N_MOVES {
+            if is_redundant(self.moves.last().copied(), m) {
+                continue;
+            }
+            self.moves.push(m);
+            let (t, f, sl) = (
+                s.twist_moves[twist * N_MOVES + m] as usize,
+                s.flip_moves[flip * N_MOVES + m] as usize,
+                s.slice_moves[slice * N_MOVES + m] as usize,
+            );
+            if self.phase_1(t, f, sl, togo - 1) {
+                return true;
+            }
+            self.moves.pop();
+            if self.tries == 0 {
+                return false;
+            }
+        }
+        false
+    }
+
+    fn start_phase_2(&mut self) -> bool {
+        let mut cube = *self.cube;
+        for &m in self.moves.iter() {
+            cube.turn_face(m / 3, m % 3 + 1);
+        }
+        let corners = perm_coord(&cube.cp, 0);
+        let edges = perm_coord(&cube.ep[..8], 0);
+        let slice = perm_coord(&cube.ep[8..], 8);
+        let phase_1_length = self.moves.len();
+        for depth in 0..=self.max_length - phase_1_length {
+            if self.phase_2(corners, edges, slice, depth) {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn phase_2(&mut self, corners: usize, edges: usize, slice: usize, togo: usize) -> bool {
+        let s = self.solver;
+        if togo == 0 {
+            if corners != 0 || edges != 0 || slice != 0 {
+                return false;
+            }
+            self.found.push(self.moves.clone());
+            return self.found.len() >= self.limit;
+        }
+        let depth = s.corner_slice_depths[corners * N_PERM_4 + slice]
+            .max(s.edge_slice_depths[edges * N_PERM_4 + slice]);
+        if depth as usize > togo {
+            return false;
+        }
+        for m in (0..N_MOVES).filter(|&m| is_phase_2_move(m)) {
+            if is_redundant(self.moves.last().copied(), m) {
+                continue;
+            }
+            self.moves.push(m);
+            let (c, e, sl) = (
+                s.corner_moves[corners * N_MOVES + m] as usize,
+                s.edge_moves[edges * N_MOVES + m] as usize,
+                s.slice_perm_moves[slice * N_MOVES + m] as usize,
+            );
+            if self.phase_2(c, e, sl, togo - 1) {
+                return true;
+            }
+            self.moves.pop();
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{random_scramble, GCube, Rng};
+
+    #[test]
+    fn coordinates_round_trip() {
+        let mut cube = CubieCube::default();
+        for slice in 0..N_SLICE {
+            cube.set_slice(slice);
+            assert_eq!(cube.slice(), slice);
+        }
+        for twist in [0, 1, 1000, N_TWIST - 1] {
+            cube.set_twist(twist);
+            assert_eq!(cube.twist(), twist);
+        }
+        for flip in [0, 7, N_FLIP - 1] {
+            cube.set_flip(flip);
+            assert_eq!(cube.flip(), flip);
+        }
+        for perm in [0, 1, 12345, N_PERM_8 - 1] {
+            set_perm_coord(&mut cube.cp, 0, perm);
+            assert_eq!(perm_coord(&cube.cp, 0), perm);
+        }
+        assert_eq!(CubieCube::default().slice(), 0);
+    }
+
+    #[test]
+    fn solves_random_states() {
+        let mut rng = Rng::new(7);
+        for _ in 0..3 {
+            let mut gcube = GCube::new(3);
+            gcube.apply_movements(random_scramble(3, 25, &mut rng).movements());
+            let solution = solve(&gcube.to_facelet_model()).unwrap();
+            assert!(solution.len() <= 25, "{}", solution);
+            gcube.apply_movements(solution.movements());
+            assert!(gcube.is_solved());
+        }
+        assert_eq!(solve(&FaceletModel::new()).unwrap().len(), 0);
+        let mut cube = CubieCube::default();
+        cube.twist_corner(0, 2);
+        assert_eq!(solve(&cube.to_facelets()), Err(StateError::TwistedCorner));
+    }
+
+    #[test]
+    fn preferred_solutions_are_a_little_longer_at_most() {
+        let mut gcube = GCube::new(3);
+        gcube.apply_movements(random_scramble(3, 25, &mut Rng::new(11)).movements());
+        let cube = CubieCube::from_facelets(&gcube.to_facelet_model()).unwrap();
+        let solver = Solver::shared();
+        let costs = MoveCosts::default();
+        let shortest = solver.solve(&cube, 50);
+        let pick = |preference| solver.solve_preferring(&cube, preference, &costs, 50);
+        assert_eq!(pick(SolvePreference::Shortest), shortest);
+        let ergonomic = pick(SolvePreference::Ergonomic);
+        let ruf = pick(SolvePreference::RufGen);
+        let off_ruf = |alg: &Alg| {
+            alg.movements()
+                .iter()
+                .filter(|m| !matches!(m.0, Move::R | Move::U | Move::F))
+                .count()
+        };
+        assert!(costs.cost_all(ergonomic.movements()) <= costs.cost_all(shortest.movements()));
+        assert!(off_ruf(&ruf) <= off_ruf(&shortest));
+        for solution in [ergonomic, ruf] {
+            assert!(solution.len() <= shortest.len() + PREFERENCE_SLACK);
+            let mut solved = gcube.clone();
+            solved.apply_movements(solution.movements());
+            assert!(solved.is_solved());
+        }
+
+        let several = solver.solve_all_within(&cube, shortest.len() + 1, 50, 5);
+        assert_eq!(several.len(), 5);
+        assert!(several.iter().all(|s| s.len() <= shortest.len() + 1));
+    }
+    #[test]
+    fn hints_follow_the_solution_until_it_is_left() {
+        let mut gcube = GCube::new(3);
+        gcube.apply_movements(random_scramble(3, 25, &mut Rng::new(5)).movements());
+        let mut hint = SolutionHint::new();
+        let first = hint.next_move(&gcube).unwrap().unwrap();
+        let solution = hint.solving.as_ref().unwrap().1.clone();
+        gcube.apply_movement(&first);
+        assert_eq!(hint.next_move(&gcube), Ok(Some(solution.movements()[1])));
+        // going off the solution solves from where the cube is
+        let off = Movement::new(Move::D, Turn::Single);
+        gcube.apply_movement(&off);
+        let next = hint.next_move(&gcube).unwrap().unwrap();
+        assert_ne!(hint.solving.as_ref().unwrap().1, solution);
+        let mut solved = gcube.clone();
+        solved.apply_movements(hint.solving.as_ref().unwrap().1.movements());
+        assert!(solved.is_solved());
+        assert_eq!(hint.solving.as_ref().unwrap().1.movements()[0], next);
+        assert_eq!(hint.next_move(&solved), Ok(None));
+        assert!(hint.next_move(&GCube::new(2)).is_err());
+    }
+
+    #[test]
+    fn solver_thread_solves_in_the_background() {
+        let mut gcube = GCube::new(3);
+        gcube.apply_movements(random_scramble(3, 25, &mut Rng::new(6)).movements());
+        let cube = CubieCube::from_facelets(&gcube.to_facelet_model()).unwrap();
+        let solver = SolverThread::new();
+        assert_eq!(solver.poll(), []);
+        solver.solve(cube, SolvePreference::Shortest, &MoveCosts::default());
+        // for up to a minute, as the tables might still be being built
+        let (solved, solution) = (0..6000)
+            .find_map(|_| {
+                thread::sleep(std::time::Duration::from_millis(10));
+                solver.poll().pop()
+            })
+            .unwrap();
+        assert_eq!(solved, cube);
+        assert_eq!(solution, Solver::shared().solve(&cube, 50));
+    }
+}
@@ -0,0 +1,168 @@
+use std::f32::consts::TAU;
+
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter};
+
+use crate::{Face, Move, Movement, ORDERED_FACES};
+
+const SAMPLE_RATE: u32 = 22050;
+
+/// The set of sounds to make, or none.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, EnumIter, Display, Serialize, Deserialize)]
+pub enum SoundTheme {
+    Off,
+    /// short square wave ticks, like a mechanical keyboard
+    Click,
+    /// softer sine wave tones that ring out
+    Chime,
+}
+
+/// What a sound is played for.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SoundEffect {
+    /// a turn, pitched by the face turned, or the face a slice or rotation
+    /// turns like, e.g. R for x
+    Turn(Face),
+    TimerStart,
+    TimerStop,
+    /// the nth inspection warning, see Inspection::WARNINGS
+    InspectionWarning(usize),
+    /// a tick of the metronome
+    Beat,
+}
+
+impl SoundEffect {
+    pub fn of_movement(movement: Movement) -> Self {
+        let face = match movement.0 {
+            Move::U | Move::Uw | Move::Y => Face::U,
+            Move::D | Move::Dw | Move::E => Face::D,
+            Move::R | Move::Rw | Move::X => Face::R,
+            Move::L | Move::Lw | Move::M => Face::L,
+            Move::F | Move::Fw | Move::S | Move::Z => Face::F,
+            Move::B | Move::Bw => Face::B,
+        };
+        SoundEffect::Turn(face)
+    }
+
+    /// Returns every sound effect, e.g. to load them all up front.
+    pub fn all() -> Vec<Self> {
+        let mut effects: Vec<Self> = ORDERED_FACES
+            .iter()
+            .map(|&f| SoundEffect::Turn(f))
+            .collect();
+        effects.extend([SoundEffect::TimerStart, SoundEffect::TimerStop]);
+        effects.extend((1..=2).map(SoundEffect::InspectionWarning));
+        effects.push(SoundEffect::Beat);
+        effects
+    }
+
+    /// Returns the sound effect as a WAV file, synthesized for theme.
+    pub fn to_wav(self, theme: SoundTheme) -> Vec<u8> {
+        // a note for each face, of a pentatonic scale
+        let turn_frequency = |face| match face {
+            Face::U => 880.,
+            Face::R => 784.,
+            Face::F => 659.,
+            Face::D => 587.,
+            Face::L => 523.,
+            // B
+            _ => 440.,
+        };
+        // (frequency, seconds) of each tone, played one after another
+        let tones: Vec<(f32, f32)> = match self {
+            SoundEffect::Turn(face) => vec![(turn_frequency(face), 0.04)],
+            SoundEffect::TimerStart => vec![(660., 0.08), (990., 0.12)],
+            SoundEffect::TimerStop => vec![(990., 0.08), (660., 0.16)],
+            SoundEffect::InspectionWarning(n) => vec![(1200., 0.1); n],
+            SoundEffect::Beat => vec![(1760., 0.02)],
+        };
+        let samples: Vec<f32> = tones
+            .iter()
+            .flat_map(|&(frequency, seconds)| tone(theme, frequency, seconds))
+            .collect();
+        wav(&samples)
+    }
+}
+
+// returns the samples of a tone that fades out, longer for chimes
+fn tone(theme: SoundTheme, frequency: f32, seconds: f32) -> Vec<f32> {
+    let seconds = match theme {
+        SoundTheme::Off => 0.,
+        SoundTheme::Click => seconds,
+        SoundTheme::Chime => seconds * 3.,
+    };
+    let len = (seconds * SAMPLE_RATE as f32) as usize;
+    (0..len)
+        .map(|i| {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let phase = (t * frequency).fract();
+            let wave = match theme {
+                SoundTheme::Click if phase < 0.5 => 0.5,
+                SoundTheme::Click => -0.5,
+                _ => (phase * TAU).sin(),
+            };
+            let fade = 1. - i as f32 / len as f32;
+            wave * fade * fade
+        })
+        .collect()
+}
+
+// encodes samples (-1..1) as a 16 bit mono PCM WAV file
+fn wav(samples: &[f32]) -> Vec<u8> {
+    let data_len = samples.len() as u32 * 2;
+    let mut bytes = Vec::with_capacity(44 + data_len as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes()); // format chunk length
+    bytes.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    bytes.extend_from_slice(&1u16.to_le_bytes()); // channels
+    bytes.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    bytes.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes()); // bytes per second
+    bytes.extend_from_slice(&2u16.to_le_bytes()); // bytes per sample
+    bytes.extend_from_slice(&16u16.to_le_bytes()); // bits per sample
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        let sample = (sample.clamp(-1., 1.) * i16::MAX as f32) as i16;
+        bytes.extend_from_slice(&sample.to_le_bytes());
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn wav_files_are_well_formed() {
+        let wav = SoundEffect::TimerStart.to_wav(SoundTheme::Click);
+        assert_eq!(&wav[..4], b"RIFF");
+        assert_eq!(&wav[8..16], b"WAVEfmt ");
+        let data_len = u32::from_le_bytes([wav[40], wav[41], wav[42], wav[43]]);
+        assert_eq!(wav.len(), 44 + data_len as usize);
+        // 0.2 seconds of samples
+        assert_eq!(
+            data_len,
+            (0.08 * 22050.) as u32 * 2 + (0.12 * 22050.) as u32 * 2
+        );
+        assert_eq!(SoundEffect::TimerStart.to_wav(SoundTheme::Off).len(), 44);
+    }
+
+    #[test]
+    fn turns_are_pitched_by_face() {
+        let turn = |s| SoundEffect::of_movement(Movement::from_str(s).unwrap());
+        assert_eq!(turn("R'"), SoundEffect::Turn(Face::R));
+        assert_eq!(turn("x2"), turn("Rw"));
+        assert_eq!(turn("M"), SoundEffect::Turn(Face::L));
+        let wavs: Vec<Vec<u8>> = ORDERED_FACES
+            .iter()
+            .map(|&f| SoundEffect::Turn(f).to_wav(SoundTheme::Chime))
+            .collect();
+        for (i, wav) in wavs.iter().enumerate() {
+            assert!(wavs[i + 1..].iter().all(|other| other != wav));
+        }
+        assert_eq!(SoundEffect::all().len(), 11);
+    }
+}
@@ -0,0 +1,141 @@
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+    sync::mpsc::{self, Sender},
+    thread,
+};
+
+use crate::{Alg, CubeObserver, GCube, Move, Movement, Turn};
+
+// text to speech programs to try, in order, each given the text on stdin so
+// it's never taken as options or script: macOS's, then Windows' through
+// PowerShell, then Linux's
+const SPEAKERS: [(&str, &[&str]); 4] = [
+    ("say", &[]),
+    (
+        "powershell",
+        &[
+            "-NoProfile",
+            "-Command",
+            "Add-Type -AssemblyName System.Speech; \
+             (New-Object System.Speech.Synthesis.SpeechSynthesizer).Speak([Console]::In.ReadToEnd())",
+        ],
+    ),
+    ("espeak-ng", &["--stdin"]),
+    ("espeak", &["--stdin"]),
+];
+
+/// Returns a movement as it's read out, e.g. "R prime" for R', "R wide two"
+/// for Rw2, "2 R" for 2R, or "x" for x.
+pub fn spoken_movement(movement: Movement) -> String {
+    let Movement(m, turn, depth) = movement;
+    let name = match m {
+        Move::Uw | Move::Lw | Move::Fw | Move::Rw | Move::Bw | Move::Dw => {
+            format!("{} wide", &m.to_string()[..1])
+        }
+        Move::X | Move::Y | Move::Z => m.to_string().to_lowercase(),
+        _ => m.to_string(),
+    };
+    let depth = match depth != m.default_depth() {
+        true => format!("{} ", depth),
+        false => String::new(),
+    };
+    let turn = match turn {
+        Turn::Single => "",
+        Turn::Double => " two",
+        Turn::Inverse => " prime",
+    };
+    format!("{}{}{}", depth, name, turn)
+}
+
+/// Returns an alg as it's read out, a pause between movements.
+pub fn spoken_alg(alg: &Alg) -> String {
+    let movements: Vec<String> = alg
+        .movements()
+        .iter()
+        .map(|&m| spoken_movement(m))
+        .collect();
+    movements.join(", ")
+}
+
+/// Reads text out with the system's text to speech, on a thread of its own
+/// so turning isn't held up. Text said while earlier text is being read out
+/// is read out together after it, so a fast solve doesn't leave a backlog.
+pub struct Speech {
+    sender: Sender<String>,
+}
+
+impl Speech {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel::<String>();
+        thread::spawn(move || {
+            // the first program that runs, once found
+            let mut speaker = None;
+            while let Ok(text) = receiver.recv() {
+                let text: Vec<String> = Some(text).into_iter().chain(receiver.try_iter()).collect();
+                let text = text.join(", ");
+                let candidates = match speaker {
+                    Some(i) => i..i + 1,
+                    None => 0..SPEAKERS.len(),
+                };
+                speaker = candidates.into_iter().find(|&i| {
+                    let (program, args) = SPEAKERS[i];
+                    let child = Command::new(program)
+                        .args(args)
+                        .stdin(Stdio::piped())
+                        .stdout(Stdio::null())
+                        .stderr(Stdio::null())
+                        .spawn();
+                    child.is_ok_and(|mut child| {
+                        // stdin is closed once written, ending the text
+                        if let Some(mut stdin) = child.stdin.take() {
+                            stdin.write_all(text.as_bytes()).ok();
+                        }
+                        // a program that fails, e.g. without an audio device,
+                        // gives way to the next
+                        child.wait().is_ok_and(|status| status.success())
+                    })
+                });
+            }
+        });
+        Self { sender }
+    }
+
+    /// Reads text out, after anything being read out already.
+    pub fn say(&self, text: impl Into<String>) {
+        // there's nothing to do if the thread's gone
+        self.sender.send(text.into()).ok();
+    }
+}
+
+impl Default for Speech {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// seeks through the history are left unread, as they aren't turns
+impl CubeObserver for Speech {
+    fn on_move(&mut self, movement: Movement, _gcube: &GCube) {
+        self.say(spoken_movement(movement));
+    }
+
+    fn on_scramble(&mut self, scramble: &Alg, _gcube: &GCube) {
+        self.say(format!("Scramble: {}", spoken_alg(scramble)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn movements_are_read_out() {
+        let spoken = |s: &str| spoken_alg(&s.parse().unwrap());
+        assert_eq!(
+            spoken("R' U2 Rw 3Rw2 2R' x' M"),
+            "R prime, U two, R wide, 3 R wide two, 2 R prime, x prime, M"
+        );
+        assert_eq!(spoken(""), "");
+    }
+}
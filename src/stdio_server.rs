@@ -0,0 +1,193 @@
+use std::io::{self, BufRead, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Alg, ConsoleCommand, ConsoleEffect, GCube, Movement, Rng, Session};
+
+/// What a program driving the cube as a subprocess can ask of it, sent as a
+/// JSON object a line tagged by its type, e.g. `{"type":"apply","alg":"R U"}`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ToolRequest {
+    Apply {
+        alg: Alg,
+    },
+    /// solves a 3x3 with the solver
+    Solve,
+    /// scrambles the cube from solved, resizing it first if there's a size
+    Scramble {
+        #[serde(default)]
+        size: Option<usize>,
+    },
+    /// solves the cube (as if by magic), resizing it first if there's a size
+    Reset {
+        #[serde(default)]
+        size: Option<usize>,
+    },
+    /// changes nothing, to read the cube's state
+    State,
+}
+
+/// The reply to each request, a JSON object on a line of its own.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ToolResponse {
+    /// the cube after the request
+    State {
+        size: usize,
+        /// the colour of every sticker, ordered by U, R, F, D, L, then B face
+        facelets: String,
+        solved: bool,
+        /// the movements since the cube was last solved by a reset or
+        /// scramble, including the scramble
+        moves: Alg,
+        /// the movements the request turned, e.g. a solution
+        turned: Alg,
+    },
+    Error {
+        message: String,
+    },
+}
+
+/// A cube served over a pair of streams, e.g. stdin and stdout, running
+/// requests as the console runs its commands.
+pub struct StdioServer {
+    pub gcube: GCube,
+    moves: Vec<Movement>,
+    // bookmarks aren't served, but commands are run with a session
+    session: Session,
+    rng: Rng,
+}
+
+impl StdioServer {
+    pub fn new(size: usize, rng: Rng) -> Self {
+        Self {
+            gcube: GCube::new(size),
+            moves: vec![],
+            session: Session::default(),
+            rng,
+        }
+    }
+
+    /// Runs a request, replying with the cube's state after it, or why it
+    /// couldn't be run (in which case the cube's unchanged).
+    pub fn handle(&mut self, request: ToolRequest) -> ToolResponse {
+        let command = match request {
+            ToolRequest::Apply { alg } => Some(ConsoleCommand::Apply(alg)),
+            ToolRequest::Solve => Some(ConsoleCommand::Solve),
+            ToolRequest::Scramble { size } => Some(ConsoleCommand::Scramble(size)),
+            ToolRequest::Reset { size } => Some(ConsoleCommand::Reset(size)),
+            ToolRequest::State => None,
+        };
+        let applied = Alg::new(self.moves.clone());
+        let effects = match command {
+            Some(c) => c.run(&self.gcube, &applied, &mut self.session, &mut self.rng),
+            None => Ok(vec![]),
+        };
+        let effects = match effects {
+            Ok(effects) => effects,
+            Err(e) => {
+                return ToolResponse::Error {
+                    message: e.to_string(),
+                }
+            }
+        };
+        let mut turned = vec![];
+        for effect in effects {
+            match effect {
+                ConsoleEffect::Reset(size) => {
+                    self.gcube = GCube::new(size);
+                    self.moves.clear();
+                }
+                ConsoleEffect::Scramble(alg) | ConsoleEffect::Turn(alg) => {
+                    self.gcube.apply_movements(alg.movements());
+                    self.moves.extend_from_slice(alg.movements());
+                    turned.extend_from_slice(alg.movements());
+                }
+                ConsoleEffect::SaveSession | ConsoleEffect::Print(_) => {}
+            }
+        }
+        ToolResponse::State {
+            size: self.gcube.size,
+            facelets: self
+                .gcube
+                .facelets()
+                .iter()
+                .map(|f| f.to_string())
+                .collect(),
+            solved: self.gcube.is_solved(),
+            moves: Alg::new(self.moves.clone()),
+            turned: Alg::new(turned),
+        }
+    }
+
+    /// Replies to each line of input with a line of output until the input
+    /// ends, flushing each reply. Blank lines are skipped and lines that
+    /// aren't requests get an error.
+    pub fn serve(&mut self, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let response = match serde_json::from_str(&line) {
+                Ok(request) => self.handle(request),
+                Err(e) => ToolResponse::Error {
+                    message: format!("Invalid request: {}", e),
+                },
+            };
+            serde_json::to_writer(&mut output, &response)?;
+            writeln!(output)?;
+            output.flush()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serves_a_line_per_request() {
+        let input = r#"{"type":"apply","alg":"R U"}
+
+{"type":"solve"}
+{"type":"scramble","size":2}
+{"type":"apply","alg":"R Q"}
+{"type":"state"}
+"#;
+        let mut output = vec![];
+        let mut server = StdioServer::new(3, Rng::new(1));
+        server.serve(input.as_bytes(), &mut output).unwrap();
+        let responses: Vec<ToolResponse> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(responses.len(), 5);
+        match &responses[0] {
+            ToolResponse::State { solved, moves, .. } => {
+                assert!(!solved);
+                assert_eq!(moves.to_string(), "R U");
+            }
+            r => panic!("expected the state, got {:?}", r),
+        }
+        match &responses[1] {
+            ToolResponse::State { solved, turned, .. } => {
+                assert!(solved);
+                assert!(!turned.is_empty());
+            }
+            r => panic!("expected the state, got {:?}", r),
+        }
+        assert!(matches!(responses[2], ToolResponse::State { size: 2, .. }));
+        assert!(matches!(responses[3], ToolResponse::Error { .. }));
+        match (&responses[2], &responses[4]) {
+            (ToolResponse::State { facelets: a, .. }, ToolResponse::State { facelets: b, .. }) => {
+                assert_eq!(a, b);
+                assert_eq!(a.len(), 24);
+            }
+            r => panic!("expected states, got {:?}", r),
+        }
+    }
+}
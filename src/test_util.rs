@@ -0,0 +1,85 @@
+use std::ops::RangeInclusive;
+
+use proptest::{
+    arbitrary::{any, Arbitrary},
+    collection::vec,
+    sample::select,
+    strategy::{BoxedStrategy, Just, Strategy},
+};
+use strum::IntoEnumIterator;
+
+use crate::{Alg, CubieCube, GCube, Move, Movement, Rng, Turn};
+
+/// Generates any movement, of any Move and Turn, turned up to max_depth
+/// deep where the Move can be, e.g. 3Rw2 or 2U' for a max_depth of 3.
+pub fn any_movement(max_depth: u8) -> impl Strategy<Value = Movement> {
+    let moves: Vec<Move> = Move::iter().collect();
+    let turns: Vec<Turn> = Turn::iter().collect();
+    (select(moves), select(turns), 1..=max_depth.max(1)).prop_map(|(m, turn, depth)| {
+        let movement = Movement::new(m, turn);
+        movement.with_depth(depth).unwrap_or(movement)
+    })
+}
+
+/// Generates algs of up to max_len movements, see any_movement.
+pub fn any_alg(max_len: usize, max_depth: u8) -> impl Strategy<Value = Alg> {
+    vec(any_movement(max_depth), 0..=max_len).prop_map(Alg::new)
+}
+
+/// Generates NxN cubes of a size in sizes, turned from solved by an alg of
+/// up to max_len movements no deeper than the cube, so every state is one a
+/// real cube can be in.
+pub fn any_gcube(sizes: RangeInclusive<usize>, max_len: usize) -> impl Strategy<Value = GCube> {
+    sizes.prop_flat_map(move |size| {
+        (Just(size), any_alg(max_len, size as u8)).prop_map(|(size, alg)| {
+            let mut gcube = GCube::new(size);
+            gcube.apply_movements(alg.movements());
+            gcube
+        })
+    })
+}
+
+/// Generates random 3x3 states, every solvable state being as likely, see
+/// CubieCube::random.
+pub fn any_cubie_cube() -> impl Strategy<Value = CubieCube> {
+    any::<u64>().prop_map(|seed| CubieCube::random(&mut Rng::new(seed)))
+}
+
+impl Arbitrary for Movement {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    // deep enough for a 7x7's inner layers
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        any_movement(4).boxed()
+    }
+}
+
+impl Arbitrary for Alg {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        any_alg(40, 4).boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::{prop_assert, prop_assert_eq, proptest};
+
+    proptest! {
+        #[test]
+        fn generated_states_are_valid(
+            gcube in any_gcube(1..=5, 30),
+            cube in any_cubie_cube(),
+            alg in any::<Alg>(),
+        ) {
+            prop_assert_eq!(gcube.facelets().len(), 6 * gcube.size * gcube.size);
+            prop_assert!(cube.verify().is_ok());
+            prop_assert!(alg.len() <= 40);
+            prop_assert!(alg.movements().iter().all(|m| m.depth() <= 4));
+        }
+    }
+}
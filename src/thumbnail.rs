@@ -0,0 +1,96 @@
+use crate::{ColorScheme, Face, GCube};
+
+/// A small picture of a cube's state, its net in RGBA pixels, e.g. for a
+/// list of bookmarks or the positions of a solve.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Thumbnail {
+    pub width: usize,
+    pub height: usize,
+    /// 4 bytes a pixel, left to right then top to bottom, transparent
+    /// around the net
+    pub pixels: Vec<u8>,
+}
+
+impl Thumbnail {
+    /// Returns the colour of the pixel at x, y.
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        let i = 4 * (y * self.width + x);
+        [
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        ]
+    }
+}
+
+/// Returns a thumbnail of an NxN cube's net, size_px wide and 3/4 as tall,
+/// with a dark gap around stickers of 4 pixels or more. It's cheap enough to
+/// render every frame, but not for every state in a long list. Cuboids have
+/// no net, so None.
+pub fn render_thumbnail(gcube: &GCube, scheme: ColorScheme, size_px: usize) -> Option<Thumbnail> {
+    if !gcube.is_cube() {
+        return None;
+    }
+    let (width, height) = (size_px, size_px * 3 / 4);
+    let n = gcube.size;
+    let mut pixels = vec![0; 4 * width * height];
+    // where each face is in the net, in faces, ordered by U, R, F, D, L, then B face
+    let offsets = [(1, 0), (2, 1), (1, 1), (1, 2), (0, 1), (3, 1)];
+    // the net is 4n stickers wide, so sticker i's left edge is at i * width / 4n
+    let edge = |i: usize| i * width / (4 * n);
+    let gap = usize::from(edge(1) >= 4);
+    for (i, &face) in gcube.facelets().iter().enumerate() {
+        let (face_x, face_y) = offsets[i / (n * n)];
+        let col = face_x * n + i % n;
+        let row = face_y * n + i % (n * n) / n;
+        let [r, g, b] = match face {
+            Face::X => [128, 128, 128],
+            face => scheme.color(face),
+        };
+        for y in edge(row)..edge(row + 1) {
+            for x in edge(col)..edge(col + 1) {
+                let is_gap = x + gap >= edge(col + 1) || y + gap >= edge(row + 1);
+                let color = if is_gap {
+                    [0, 0, 0, 255]
+                } else {
+                    [r, g, b, 255]
+                };
+                let p = 4 * (y * width + x);
+                pixels[p..p + 4].copy_from_slice(&color);
+            }
+        }
+    }
+    Some(Thumbnail {
+        width,
+        height,
+        pixels,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scramble_to_movements;
+
+    #[test]
+    fn thumbnails_draw_the_net() {
+        let scheme = ColorScheme::Standard;
+        let mut gcube = GCube::new(3);
+        gcube.apply_movements(&scramble_to_movements("R").unwrap());
+        let thumbnail = render_thumbnail(&gcube, scheme, 120).unwrap();
+        assert_eq!((thumbnail.width, thumbnail.height), (120, 90));
+        assert_eq!(thumbnail.pixels.len(), 120 * 90 * 4);
+        let color = |face| {
+            let [r, g, b] = scheme.color(face);
+            [r, g, b, 255]
+        };
+        // stickers are 10 pixels wide, U's top right one is F's after R
+        assert_eq!(thumbnail.pixel(32, 2), color(Face::U));
+        assert_eq!(thumbnail.pixel(52, 2), color(Face::F));
+        assert_eq!(thumbnail.pixel(39, 2), [0, 0, 0, 255]);
+        // nothing left of U
+        assert_eq!(thumbnail.pixel(2, 2), [0; 4]);
+        assert!(render_thumbnail(&GCube::cuboid([2, 3, 3]), scheme, 120).is_none());
+    }
+}
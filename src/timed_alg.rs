@@ -0,0 +1,140 @@
+use std::{fmt, str::FromStr};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{tokens, Movement, ParseMovementError, ParseMovementErrorKind};
+
+/// An element of a timed alg, either a movement with an optional timestamp
+/// (in milliseconds since the start of the alg), or a "." pause.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TimedNode {
+    Movement(Movement, Option<u32>),
+    Pause,
+}
+
+/// An alg annotated with pauses and per-move timestamps, written like
+/// `R@0 U@120 . R'@480 U'@590`, so recorded solves round-trip through text.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TimedAlg(pub Vec<TimedNode>);
+
+impl TimedAlg {
+    /// Returns the movements of the alg, dropping pauses and timestamps.
+    pub fn movements(&self) -> Vec<Movement> {
+        self.0
+            .iter()
+            .filter_map(|node| match node {
+                TimedNode::Movement(movement, _) => Some(*movement),
+                TimedNode::Pause => None,
+            })
+            .collect()
+    }
+}
+
+impl FromStr for TimedNode {
+    type Err = ParseMovementError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "." {
+            return Ok(TimedNode::Pause);
+        }
+        let (movement, time) = match s.find('@') {
+            Some(i) => (&s[..i], Some(&s[i + 1..])),
+            None => (s, None),
+        };
+        let time = match time {
+            Some(t) => Some(t.parse::<u32>().map_err(|_| {
+                ParseMovementError::new(ParseMovementErrorKind::InvalidTimestamp, s)
+            })?),
+            None => None,
+        };
+        let movement = Movement::from_str(movement).map_err(|e| ParseMovementError {
+            token: s.to_string(),
+            ..e
+        })?;
+        Ok(TimedNode::Movement(movement, time))
+    }
+}
+
+impl FromStr for TimedAlg {
+    type Err = ParseMovementError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let nodes: Result<Vec<TimedNode>, _> = tokens(s)
+            .map(|(offset, token)| TimedNode::from_str(token).map_err(|e| e.offset_by(offset)))
+            .collect();
+        Ok(Self(nodes?))
+    }
+}
+
+impl fmt::Display for TimedNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TimedNode::Movement(movement, Some(time)) => write!(f, "{}@{}", movement, time),
+            TimedNode::Movement(movement, None) => write!(f, "{}", movement),
+            TimedNode::Pause => write!(f, "."),
+        }
+    }
+}
+
+impl fmt::Display for TimedAlg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let nodes: Vec<String> = self.0.iter().map(|node| node.to_string()).collect();
+        write!(f, "{}", nodes.join(" "))
+    }
+}
+
+// like algs, timed algs are serialized as a single string, e.g. "R@0 U@120"
+impl Serialize for TimedAlg {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for TimedAlg {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        TimedAlg::from_str(&s).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Move, Turn};
+
+    #[test]
+    fn parses_pauses_and_timestamps() {
+        let alg = TimedAlg::from_str("R@0 U2@120 . r'@480 . . M").unwrap();
+        assert_eq!(
+            alg.0,
+            vec![
+                TimedNode::Movement(Movement::new(Move::R, Turn::Single), Some(0)),
+                TimedNode::Movement(Movement::new(Move::U, Turn::Double), Some(120)),
+                TimedNode::Pause,
+                TimedNode::Movement(Movement::new(Move::Rw, Turn::Inverse), Some(480)),
+                TimedNode::Pause,
+                TimedNode::Pause,
+                TimedNode::Movement(Movement::new(Move::M, Turn::Single), None),
+            ]
+        );
+        assert_eq!(alg.movements().len(), 4);
+    }
+
+    #[test]
+    fn round_trips_through_text() {
+        let text = "F@10 . R'@250 U2 . D@1000";
+        assert_eq!(TimedAlg::from_str(text).unwrap().to_string(), text);
+    }
+
+    #[test]
+    fn invalid_timed_algs() {
+        let invalid_algs = ["R@", "R@-5", "R@1.5", "@100", "..", "R@100@200"];
+        for alg in invalid_algs {
+            assert!(TimedAlg::from_str(alg).is_err());
+        }
+        let err = TimedAlg::from_str("R@0 . U@x").unwrap_err();
+        assert_eq!(err.kind, ParseMovementErrorKind::InvalidTimestamp);
+        assert_eq!(err.token, "U@x");
+        assert_eq!(err.offset, 6);
+    }
+}
@@ -0,0 +1,306 @@
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter};
+
+use crate::{Move, Movement, TimedAlg, TimedNode, Turn};
+
+/// Ways of counting the moves of a solve.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, EnumIter, Display, Serialize, Deserialize)]
+pub enum Metric {
+    /// half turn metric, any turn of an outer layer (or wide) is 1 move,
+    /// slices (including inner layers like 2R) count as 2 and rotations
+    /// are free
+    #[strum(serialize = "HTM")]
+    Htm,
+    /// quarter turn metric, like HTM but double turns count twice
+    #[strum(serialize = "QTM")]
+    Qtm,
+    /// slice turn metric, any turn of any layer is 1 move
+    #[strum(serialize = "STM")]
+    Stm,
+    /// execution turn metric, every movement is 1 move, including rotations
+    #[strum(serialize = "ETM")]
+    Etm,
+}
+
+impl Metric {
+    /// Returns the number of moves movement counts as.
+    pub fn count(self, movement: Movement) -> usize {
+        let Movement(m, turn, depth) = movement;
+        let is_rotation = matches!(m, Move::X | Move::Y | Move::Z);
+        // inner layers of big cubes are slices too, e.g. 2R
+        let is_slice = matches!(m, Move::E | Move::M | Move::S) || (m.is_face() && depth > 1);
+        let quarter_turns = if turn == Turn::Double { 2 } else { 1 };
+        match self {
+            _ if is_rotation && self != Metric::Etm => 0,
+            Metric::Htm => {
+                if is_slice {
+                    2
+                } else {
+                    1
+                }
+            }
+            Metric::Qtm => quarter_turns * if is_slice { 2 } else { 1 },
+            Metric::Stm | Metric::Etm => 1,
+        }
+    }
+
+    pub fn count_all(self, movements: &[Movement]) -> usize {
+        movements.iter().map(|&m| self.count(m)).sum()
+    }
+
+    /// Returns the next metric, wrapping back around to HTM.
+    pub fn next(self) -> Self {
+        match self {
+            Metric::Htm => Metric::Qtm,
+            Metric::Qtm => Metric::Stm,
+            Metric::Stm => Metric::Etm,
+            Metric::Etm => Metric::Htm,
+        }
+    }
+}
+
+/// Times a solve and records its movements. Times are passed in by the
+/// caller, in seconds from any fixed point.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Timer {
+    started_at: Option<f64>,
+    stopped_at: Option<f64>,
+    movements: Vec<Movement>,
+    // when each movement was made, in seconds since the start
+    times: Vec<f64>,
+}
+
+impl Timer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn start(&mut self, now: f64) {
+        *self = Self {
+            started_at: Some(now),
+            ..Self::new()
+        };
+    }
+
+    pub fn stop(&mut self, now: f64) {
+        if self.is_running() {
+            self.stopped_at = Some(now);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.started_at.is_some() && self.stopped_at.is_none()
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stopped_at.is_some()
+    }
+
+    /// Records a movement of the solve made at now, if the timer is running.
+    pub fn record(&mut self, movement: Movement, now: f64) {
+        if self.is_running() {
+            self.movements.push(movement);
+            self.times.push(self.elapsed(now));
+        }
+    }
+
+    pub fn movements(&self) -> &[Movement] {
+        &self.movements
+    }
+
+    /// Returns the movements of the solve, each timestamped with the
+    /// milliseconds since the start, e.g. for a replay.
+    pub fn timed_alg(&self) -> TimedAlg {
+        let nodes = self
+            .movements
+            .iter()
+            .zip(self.times.iter())
+            .map(|(&m, &t)| TimedNode::Movement(m, Some((t * 1000.).round().max(0.) as u32)));
+        TimedAlg(nodes.collect())
+    }
+
+    /// Returns the seconds since the timer started, up until it stopped.
+    pub fn elapsed(&self, now: f64) -> f64 {
+        match (self.started_at, self.stopped_at) {
+            (Some(start), Some(stop)) => stop - start,
+            (Some(start), None) => now - start,
+            _ => 0.,
+        }
+    }
+
+    pub fn move_count(&self, metric: Metric) -> usize {
+        metric.count_all(&self.movements)
+    }
+
+    /// Returns the turns per second so far.
+    pub fn tps(&self, now: f64, metric: Metric) -> f64 {
+        let elapsed = self.elapsed(now);
+        if elapsed <= 0. {
+            return 0.;
+        }
+        self.move_count(metric) as f64 / elapsed
+    }
+}
+
+/// A timer's state that doesn't depend on when it was taken, e.g. to
+/// restore the timer after the app restarts.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct TimerSnapshot {
+    /// the seconds timed so far, or None if the timer hasn't started
+    pub elapsed: Option<f64>,
+    pub is_running: bool,
+    pub moves: TimedAlg,
+}
+
+impl Timer {
+    pub fn snapshot(&self, now: f64) -> TimerSnapshot {
+        TimerSnapshot {
+            elapsed: self.started_at.map(|_| self.elapsed(now)),
+            is_running: self.is_running(),
+            moves: self.timed_alg(),
+        }
+    }
+
+    /// Returns the timer of a snapshot, carrying on from now as if no time
+    /// passed between, e.g. while the app was closed.
+    pub fn restore(snapshot: &TimerSnapshot, now: f64) -> Self {
+        let started_at = match snapshot.elapsed {
+            Some(elapsed) => now - elapsed,
+            None => return Self::new(),
+        };
+        let (movements, times) = snapshot
+            .moves
+            .0
+            .iter()
+            .filter_map(|node| match *node {
+                TimedNode::Movement(movement, time) => {
+                    Some((movement, time.unwrap_or(0) as f64 / 1000.))
+                }
+                TimedNode::Pause => None,
+            })
+            .unzip();
+        Self {
+            started_at: Some(started_at),
+            stopped_at: Some(now).filter(|_| !snapshot.is_running),
+            movements,
+            times,
+        }
+    }
+}
+
+/// WCA style inspection, the 15 seconds to look over a scrambled cube
+/// before solving, with warnings as time runs out.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Inspection {
+    started_at: f64,
+    warnings: usize, // number of warnings given
+}
+
+impl Inspection {
+    /// Seconds into inspection that warnings are given at.
+    pub const WARNINGS: [f64; 2] = [8., 12.];
+
+    pub fn new(now: f64) -> Self {
+        Self {
+            started_at: now,
+            warnings: 0,
+        }
+    }
+
+    pub fn elapsed(&self, now: f64) -> f64 {
+        now - self.started_at
+    }
+
+    /// Returns the number of the warning that's become due since the last
+    /// call (1 at 8 seconds, 2 at 12 seconds), if any.
+    pub fn warning(&mut self, now: f64) -> Option<usize> {
+        let due = Self::WARNINGS
+            .iter()
+            .filter(|&&at| self.elapsed(now) >= at)
+            .count();
+        if due > self.warnings {
+            self.warnings = due;
+            Some(due)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scramble_to_movements;
+
+    #[test]
+    fn metrics() {
+        let movements = scramble_to_movements("R U2 M' x r2 E2 y'").unwrap();
+        assert_eq!(Metric::Htm.count_all(&movements), 7);
+        assert_eq!(Metric::Qtm.count_all(&movements), 11);
+        assert_eq!(Metric::Stm.count_all(&movements), 5);
+        assert_eq!(Metric::Etm.count_all(&movements), 7);
+
+        // inner layers are slices, wide moves aren't
+        let movements = scramble_to_movements("2R 3Rw2").unwrap();
+        assert_eq!(Metric::Htm.count_all(&movements), 3);
+        assert_eq!(Metric::Stm.count_all(&movements), 2);
+    }
+
+    #[test]
+    fn timer_records_while_running() {
+        let mut timer = Timer::new();
+        let r = "R".parse().unwrap();
+        timer.record(r, 5.);
+        assert_eq!(timer.movements().len(), 0);
+        timer.start(10.);
+        timer.record(r, 10.25);
+        timer.record(r, 10.5);
+        assert!(timer.is_running());
+        assert_eq!(timer.elapsed(11.), 1.);
+        assert_eq!(timer.tps(11., Metric::Htm), 2.);
+        timer.stop(12.);
+        timer.record(r, 12.5);
+        assert_eq!(timer.timed_alg().to_string(), "R@250 R@500");
+        assert!(timer.is_stopped());
+        assert_eq!(timer.elapsed(100.), 2.);
+        assert_eq!(timer.move_count(Metric::Qtm), 2);
+        let restored = Timer::restore(&timer.snapshot(100.), 500.);
+        assert_eq!(restored.elapsed(1000.), 2.);
+        assert_eq!(restored.movements(), timer.movements());
+        assert_eq!(restored.snapshot(1000.), timer.snapshot(100.));
+        timer.reset();
+        assert_eq!(timer, Timer::new());
+        assert_eq!(Timer::restore(&timer.snapshot(5.), 10.), timer);
+    }
+
+    #[test]
+    fn running_timers_carry_on_when_restored() {
+        let mut timer = Timer::new();
+        timer.start(10.);
+        timer.record("R".parse().unwrap(), 11.);
+        let snapshot = timer.snapshot(13.);
+        assert_eq!(snapshot.elapsed, Some(3.));
+        // the time the app was closed for isn't counted
+        let restored = Timer::restore(&snapshot, 50.);
+        assert!(restored.is_running());
+        assert_eq!(restored.elapsed(52.), 5.);
+        assert_eq!(restored.timed_alg(), timer.timed_alg());
+    }
+
+    #[test]
+    fn inspection_warns_once_per_warning() {
+        let mut inspection = Inspection::new(100.);
+        assert_eq!(inspection.warning(107.), None);
+        assert_eq!(inspection.warning(108.), Some(1));
+        assert_eq!(inspection.warning(109.), None);
+        // warnings missed by a slow frame are given as the latest one
+        let mut late = Inspection::new(0.);
+        assert_eq!(late.warning(13.), Some(2));
+        assert_eq!(inspection.warning(112.5), Some(2));
+        assert_eq!(inspection.warning(120.), None);
+    }
+}
@@ -0,0 +1,180 @@
+use std::fmt::Write;
+
+use strum::IntoEnumIterator;
+
+use crate::{Alg, Move, Movement, Turn};
+
+/// How many turns in a row of the faces awkward to turn from the home grip
+/// (F, B and D, and their wide turns) suggest a regrip.
+pub const REGRIP_RUN: usize = 3;
+
+/// Turns in a row that suggest a regrip, see REGRIP_RUN.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegripSuspect {
+    /// which of the analysed solves it's in
+    pub solve: usize,
+    /// the index of its first movement in the solve
+    pub start: usize,
+    pub moves: Alg,
+}
+
+/// How often each move is turned each way over solves, and the turns in
+/// them that suggest a regrip, e.g. to find moves to practise or fingertricks
+/// to change.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TurnStats {
+    /// the times each move was turned clockwise, double and anticlockwise,
+    /// for every move turned, in the order of Move
+    pub counts: Vec<(Move, [usize; 3])>,
+    pub regrip_suspects: Vec<RegripSuspect>,
+    pub solves: usize,
+}
+
+impl TurnStats {
+    /// Analyses the movements of solves.
+    pub fn new<'a>(solves: impl IntoIterator<Item = &'a Alg>) -> Self {
+        let mut counts: Vec<(Move, [usize; 3])> = Move::iter().map(|m| (m, [0; 3])).collect();
+        let mut regrip_suspects = vec![];
+        let mut n_solves = 0;
+        for (solve, alg) in solves.into_iter().enumerate() {
+            n_solves += 1;
+            for &Movement(m, turn, _) in alg.movements() {
+                let i = Move::iter().position(|other| other == m).unwrap();
+                counts[i].1[turn_index(turn)] += 1;
+            }
+            // runs of awkward turns, e.g. F D F' D'
+            let movements = alg.movements();
+            let mut start = 0;
+            while start < movements.len() {
+                let length = movements[start..]
+                    .iter()
+                    .take_while(|m| is_awkward(m.0))
+                    .count();
+                if length >= REGRIP_RUN {
+                    regrip_suspects.push(RegripSuspect {
+                        solve,
+                        start,
+                        moves: Alg::new(movements[start..start + length].to_vec()),
+                    });
+                }
+                start += length.max(1);
+            }
+        }
+        counts.retain(|(_, turns)| turns.iter().sum::<usize>() > 0);
+        Self {
+            counts,
+            regrip_suspects,
+            solves: n_solves,
+        }
+    }
+
+    /// Returns the number of movements analysed.
+    pub fn total(&self) -> usize {
+        self.counts.iter().flat_map(|(_, turns)| turns.iter()).sum()
+    }
+
+    /// Returns the stats as CSV, a row for each move with its turns each
+    /// way, its share of every movement (as a percentage), and how many
+    /// regrip suspects start with it.
+    pub fn to_csv(&self) -> String {
+        let mut csv =
+            String::from("Move,Clockwise,Double,Anticlockwise,Total,Share,Regrip suspects\n");
+        let total = self.total().max(1) as f64;
+        for &(m, [clockwise, double, anticlockwise]) in self.counts.iter() {
+            let sum = clockwise + double + anticlockwise;
+            let regrips = self
+                .regrip_suspects
+                .iter()
+                .filter(|s| s.moves.movements()[0].0 == m)
+                .count();
+            writeln!(
+                csv,
+                "{},{},{},{},{},{:.1},{}",
+                m,
+                clockwise,
+                double,
+                anticlockwise,
+                sum,
+                sum as f64 / total * 100.,
+                regrips
+            )
+            .unwrap();
+        }
+        csv
+    }
+}
+
+fn turn_index(turn: Turn) -> usize {
+    match turn {
+        Turn::Single => 0,
+        Turn::Double => 1,
+        Turn::Inverse => 2,
+    }
+}
+
+fn is_awkward(m: Move) -> bool {
+    matches!(
+        m,
+        Move::F | Move::Fw | Move::B | Move::Bw | Move::D | Move::Dw
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn counts_each_move_each_way() {
+        let solves = [
+            Alg::from_str("R U R' U' R U2 R'").unwrap(),
+            Alg::from_str("y R' F R F'").unwrap(),
+        ];
+        let stats = TurnStats::new(&solves);
+        assert_eq!(stats.solves, 2);
+        assert_eq!(stats.total(), 12);
+        assert_eq!(
+            stats.counts,
+            [
+                (Move::U, [1, 1, 1]),
+                (Move::F, [1, 0, 1]),
+                (Move::R, [3, 0, 3]),
+                (Move::Y, [1, 0, 0])
+            ]
+        );
+        assert!(stats.regrip_suspects.is_empty());
+        assert_eq!(
+            stats.to_csv(),
+            "Move,Clockwise,Double,Anticlockwise,Total,Share,Regrip suspects\n\
+             U,1,1,1,3,25.0,0\n\
+             F,1,0,1,2,16.7,0\n\
+             R,3,0,3,6,50.0,0\n\
+             Y,1,0,0,1,8.3,0\n"
+        );
+    }
+
+    #[test]
+    fn awkward_runs_are_regrip_suspects() {
+        let solves = [
+            Alg::from_str("R F D F' D' R' F2 B").unwrap(),
+            Alg::from_str("U f f' Dw2 B").unwrap(),
+        ];
+        let stats = TurnStats::new(&solves);
+        assert_eq!(
+            stats.regrip_suspects,
+            [
+                RegripSuspect {
+                    solve: 0,
+                    start: 1,
+                    moves: Alg::from_str("F D F' D'").unwrap()
+                },
+                RegripSuspect {
+                    solve: 1,
+                    start: 1,
+                    moves: Alg::from_str("Fw Fw' Dw2 B").unwrap()
+                }
+            ]
+        );
+        assert!(stats.to_csv().contains("\nF,1,1,1,3,23.1,1\n"));
+    }
+}
@@ -33,7 +33,7 @@ pub enum Axis {
 }
 
 /// specialised vec3 for i16 only (-128..128)
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Vec3 {
     pub x: i16,
     pub y: i16,
@@ -273,7 +273,7 @@ mod tests {
 
         #[test]
         fn neg_op_idempotent(v in gen_vec3(-i16::MAX, i16::MAX)) {
-            prop_assert_eq!(v, --v);
+            prop_assert_eq!(v, -(-v));
         }
 
         #[test]
@@ -354,6 +354,7 @@ mod tests {
         }
 
         #[test]
+        #[allow(clippy::erasing_op)]
         fn mul_scalar_zero(v in arb_vec3()) {
             prop_assert_eq!(v * 0, Vec3::zero());
         }
@@ -0,0 +1,100 @@
+//! JavaScript API for embedding a cube in web pages, enabled by the `wasm`
+//! feature, e.g. `wasm-pack build --target web -- --features wasm`.
+
+use std::str::FromStr;
+
+use js_sys::Function;
+use wasm_bindgen::prelude::*;
+
+use crate::{Alg, CubeObserver, GCube, Movement, ObservedCube, ObserverId};
+
+// calls JavaScript functions on cube events, errors thrown by them are ignored
+struct JsObserver {
+    on_move: Option<Function>,
+    on_solved: Option<Function>,
+}
+
+impl CubeObserver for JsObserver {
+    fn on_move(&mut self, movement: Movement, _gcube: &GCube) {
+        if let Some(on_move) = self.on_move.as_ref() {
+            let _ = on_move.call1(&JsValue::NULL, &JsValue::from_str(&movement.to_string()));
+        }
+    }
+
+    fn on_solved(&mut self, _gcube: &GCube) {
+        if let Some(on_solved) = self.on_solved.as_ref() {
+            let _ = on_solved.call0(&JsValue::NULL);
+        }
+    }
+}
+
+/// A cube that JavaScript can turn and read back.
+#[wasm_bindgen]
+pub struct Cube {
+    cube: ObservedCube,
+    observer: Option<ObserverId>,
+    on_move: Option<Function>,
+    on_solved: Option<Function>,
+}
+
+#[wasm_bindgen]
+impl Cube {
+    #[wasm_bindgen(constructor)]
+    pub fn new(size: usize) -> Cube {
+        Cube {
+            cube: ObservedCube::new(GCube::new(size)),
+            observer: None,
+            on_move: None,
+            on_solved: None,
+        }
+    }
+
+    /// Applies an alg, e.g. "R U R' U'", calling the on_move callback with
+    /// each movement. Throws the parse error if the alg is invalid, in which
+    /// case nothing is applied.
+    pub fn apply_alg(&mut self, alg: &str) -> Result<(), JsValue> {
+        let alg = Alg::from_str(alg).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        self.cube.apply_movements(alg.movements());
+        Ok(())
+    }
+
+    /// Returns the colour of every sticker as a string of faces, ordered by
+    /// U, R, F, D, L, then B face, e.g. "UUUUUUUUURRR..." when solved.
+    pub fn get_state(&self) -> String {
+        self.cube
+            .gcube()
+            .facelets()
+            .iter()
+            .map(|f| f.to_string())
+            .collect()
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.cube.gcube().is_solved()
+    }
+
+    /// Sets the function called with each movement applied, e.g. "R'".
+    pub fn on_move(&mut self, callback: Function) {
+        self.on_move = Some(callback);
+        self.resubscribe();
+    }
+
+    /// Sets the function called when a movement solves the cube.
+    pub fn on_solved(&mut self, callback: Function) {
+        self.on_solved = Some(callback);
+        self.resubscribe();
+    }
+}
+
+impl Cube {
+    // replaces the observer with one calling the current callbacks
+    fn resubscribe(&mut self) {
+        if let Some(id) = self.observer.take() {
+            self.cube.unsubscribe(id);
+        }
+        self.observer = Some(self.cube.subscribe(Box::new(JsObserver {
+            on_move: self.on_move.clone(),
+            on_solved: self.on_solved.clone(),
+        })));
+    }
+}
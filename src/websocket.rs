@@ -0,0 +1,381 @@
+//! WebSocket server and client, for talking to browser overlays and other
+//! cubedesu instances, on top of tungstenite. Sockets are non-blocking, and
+//! polled once a frame rather than run on threads of their own.
+
+use std::{
+    io,
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    time::{Duration, Instant},
+};
+
+use tungstenite::{
+    error::Error,
+    handshake::{
+        server::{NoCallback, ServerHandshake},
+        HandshakeError, MidHandshake,
+    },
+    protocol::{frame::coding::CloseCode, CloseFrame, WebSocketConfig},
+    Message, WebSocket,
+};
+
+// the longest message (and frame) either end takes, far longer than any
+// event or command, so a peer can't run the other out of memory
+const MAX_MESSAGE_SIZE: usize = 64 * 1024;
+// how much can be waiting to be sent to a peer that isn't keeping up, before
+// it's disconnected
+const MAX_WRITE_BUFFER_SIZE: usize = 1024 * 1024;
+// how long a client has to finish its handshake, so idle connections don't
+// hold on to a slot (tungstenite caps the handshake's size itself)
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn config() -> WebSocketConfig {
+    WebSocketConfig::default()
+        .max_message_size(Some(MAX_MESSAGE_SIZE))
+        .max_frame_size(Some(MAX_MESSAGE_SIZE))
+        .max_write_buffer_size(MAX_WRITE_BUFFER_SIZE)
+}
+
+// whether an error is only that the socket has nothing more for now
+fn is_would_block(e: &Error) -> bool {
+    matches!(e, Error::Io(e) if e.kind() == io::ErrorKind::WouldBlock)
+}
+
+enum State {
+    // a client's handshake being received, and when it connected
+    Handshaking(
+        Box<MidHandshake<ServerHandshake<TcpStream, NoCallback>>>,
+        Instant,
+    ),
+    Open(Box<WebSocket<TcpStream>>),
+    Closed,
+}
+
+// one end of a WebSocket connection
+struct Connection {
+    state: State,
+    was_open: bool, // whether the handshake was ever finished
+}
+
+impl Connection {
+    fn accept(stream: TcpStream) -> Self {
+        let mut connection = Self {
+            state: State::Closed,
+            was_open: false,
+        };
+        connection.handshake(
+            tungstenite::accept_with_config(stream, Some(config())),
+            Instant::now(),
+        );
+        connection
+    }
+
+    fn open(socket: WebSocket<TcpStream>) -> Self {
+        Self {
+            state: State::Open(Box::new(socket)),
+            was_open: true,
+        }
+    }
+
+    fn handshake(
+        &mut self,
+        result: Result<
+            WebSocket<TcpStream>,
+            HandshakeError<ServerHandshake<TcpStream, NoCallback>>,
+        >,
+        started: Instant,
+    ) {
+        self.state = match result {
+            Ok(socket) => {
+                self.was_open = true;
+                State::Open(Box::new(socket))
+            }
+            Err(HandshakeError::Interrupted(mid)) => State::Handshaking(Box::new(mid), started),
+            Err(HandshakeError::Failure(_)) => State::Closed,
+        };
+    }
+
+    fn is_open(&self) -> bool {
+        matches!(self.state, State::Open(_))
+    }
+
+    fn is_closed(&self) -> bool {
+        matches!(self.state, State::Closed)
+    }
+
+    fn send(&mut self, text: &str) {
+        if let State::Open(socket) = &mut self.state {
+            // what can't be sent yet is sent on later polls, but a peer that
+            // doesn't keep up is disconnected, rather than blocking
+            match socket.send(Message::text(text)) {
+                Err(e) if !is_would_block(&e) => self.state = State::Closed,
+                _ => {}
+            }
+        }
+    }
+
+    // reads everything received since the last poll, returning the messages
+    fn poll(&mut self, handshake_timeout: Duration) -> Vec<String> {
+        if let State::Handshaking(..) = self.state {
+            if let State::Handshaking(mid, started) =
+                std::mem::replace(&mut self.state, State::Closed)
+            {
+                if started.elapsed() < handshake_timeout {
+                    self.handshake(mid.handshake(), started);
+                }
+            }
+        }
+        let socket = match &mut self.state {
+            State::Open(socket) => socket,
+            _ => return vec![],
+        };
+        let mut messages = vec![];
+        let is_closed = loop {
+            match socket.read() {
+                Ok(Message::Text(text)) => messages.push(text.as_str().to_string()),
+                // pings are answered by tungstenite, and binary messages aren't used
+                Ok(Message::Close(_)) => {
+                    // sends tungstenite's reply to the close
+                    socket.flush().ok();
+                    break true;
+                }
+                Ok(_) => {}
+                Err(e) if is_would_block(&e) => match socket.flush() {
+                    Err(e) => break !is_would_block(&e),
+                    Ok(()) => break false,
+                },
+                Err(Error::Capacity(_)) => {
+                    let frame = CloseFrame {
+                        code: CloseCode::Size,
+                        reason: "Message too big".into(),
+                    };
+                    socket.close(Some(frame)).ok();
+                    socket.flush().ok();
+                    break true;
+                }
+                Err(_) => break true,
+            }
+        };
+        if is_closed {
+            self.state = State::Closed;
+        }
+        messages
+    }
+}
+
+/// Identifies a client of a WebSocketServer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ClientId(usize);
+
+/// What happened on a WebSocketServer since it was last polled.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ServerEvent {
+    Connected(ClientId),
+    Message(ClientId, String),
+    Disconnected(ClientId),
+}
+
+/// Accepts WebSocket clients, exchanging text messages with them. Clients
+/// sending messages over 64 KiB are disconnected with status 1009 (message
+/// too big), and clients that don't finish their handshake within 5 seconds
+/// are dropped.
+pub struct WebSocketServer {
+    listener: TcpListener,
+    clients: Vec<(ClientId, Connection)>,
+    next_id: usize,
+    handshake_timeout: Duration,
+}
+
+impl WebSocketServer {
+    /// Listens for clients at addr, e.g. "127.0.0.1:9001".
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            listener,
+            clients: vec![],
+            next_id: 0,
+            handshake_timeout: HANDSHAKE_TIMEOUT,
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accepts new clients and reads what they've sent, without blocking.
+    pub fn poll(&mut self) -> Vec<ServerEvent> {
+        let mut events = vec![];
+        while let Ok((stream, _)) = self.listener.accept() {
+            if stream.set_nonblocking(true).is_ok() {
+                let id = ClientId(self.next_id);
+                self.next_id += 1;
+                let connection = Connection::accept(stream);
+                if connection.is_open() {
+                    events.push(ServerEvent::Connected(id));
+                }
+                self.clients.push((id, connection));
+            }
+        }
+        for (id, connection) in self.clients.iter_mut() {
+            let was_open = connection.is_open();
+            let messages = connection.poll(self.handshake_timeout);
+            if connection.is_open() && !was_open {
+                events.push(ServerEvent::Connected(*id));
+            }
+            events.extend(messages.into_iter().map(|m| ServerEvent::Message(*id, m)));
+        }
+        self.remove_closed(&mut events);
+        events
+    }
+
+    fn remove_closed(&mut self, events: &mut Vec<ServerEvent>) {
+        for (id, connection) in self.clients.iter() {
+            if connection.is_closed() && connection.was_open {
+                events.push(ServerEvent::Disconnected(*id));
+            }
+        }
+        self.clients
+            .retain(|(_, connection)| !connection.is_closed());
+    }
+
+    pub fn send(&mut self, client: ClientId, text: &str) {
+        if let Some((_, connection)) = self.clients.iter_mut().find(|(id, _)| *id == client) {
+            connection.send(text);
+        }
+    }
+
+    /// Sends text to every connected client.
+    pub fn broadcast(&mut self, text: &str) {
+        for (_, connection) in self.clients.iter_mut() {
+            connection.send(text);
+        }
+    }
+
+    /// Returns the number of clients that have finished their handshake.
+    pub fn client_count(&self) -> usize {
+        self.clients.iter().filter(|(_, c)| c.is_open()).count()
+    }
+}
+
+/// Connects to a WebSocketServer (or any WebSocket server), exchanging text
+/// messages with it.
+pub struct WebSocketClient {
+    connection: Connection,
+}
+
+impl WebSocketClient {
+    /// Connects to the server at addr, e.g. "127.0.0.1:9001", blocking until
+    /// the server accepts the handshake.
+    pub fn connect(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let stream = TcpStream::connect(addr)?;
+        let url = format!("ws://{}/", stream.peer_addr()?);
+        stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+        let (socket, _) = tungstenite::client::client_with_config(url, stream, Some(config()))
+            .map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("WebSocket handshake was refused: {}", e),
+                )
+            })?;
+        socket.get_ref().set_nonblocking(true)?;
+        Ok(Self {
+            connection: Connection::open(socket),
+        })
+    }
+
+    /// Returns the messages received since the last poll, without blocking.
+    pub fn poll(&mut self) -> Vec<String> {
+        self.connection.poll(HANDSHAKE_TIMEOUT)
+    }
+
+    pub fn send(&mut self, text: &str) {
+        self.connection.send(text);
+    }
+
+    /// Whether the server closed the connection, or it was lost.
+    pub fn is_closed(&self) -> bool {
+        self.connection.is_closed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    // polls until f returns something, or panics after a few seconds
+    fn poll_until<T>(mut f: impl FnMut() -> Option<T>) -> T {
+        for _ in 0..500 {
+            if let Some(t) = f() {
+                return t;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("timed out");
+    }
+
+    #[test]
+    fn server_and_client_exchange_messages() {
+        let mut server = WebSocketServer::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        let client = thread::spawn(move || WebSocketClient::connect(addr).unwrap());
+        let id = poll_until(|| match server.poll().pop() {
+            Some(ServerEvent::Connected(id)) => Some(id),
+            _ => None,
+        });
+        let mut client = client.join().unwrap();
+        assert_eq!(server.client_count(), 1);
+
+        client.send("R U R' U'");
+        let event = poll_until(|| server.poll().pop());
+        assert_eq!(event, ServerEvent::Message(id, "R U R' U'".to_string()));
+        server.broadcast("solved");
+        assert_eq!(poll_until(|| client.poll().pop()), "solved");
+
+        drop(client);
+        let event = poll_until(|| server.poll().pop());
+        assert_eq!(event, ServerEvent::Disconnected(id));
+        assert_eq!(server.client_count(), 0);
+    }
+
+    #[test]
+    fn messages_too_big_close_the_connection() {
+        let mut server = WebSocketServer::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        let client = thread::spawn(move || {
+            let stream = TcpStream::connect(addr).unwrap();
+            let (mut socket, _) = tungstenite::client(format!("ws://{}/", addr), stream).unwrap();
+            socket
+                .send(Message::text("R".repeat(MAX_MESSAGE_SIZE + 1)))
+                .unwrap();
+            // the server's close, then the end of the connection
+            loop {
+                match socket.read() {
+                    Ok(Message::Close(frame)) => return frame.map(|f| f.code),
+                    Ok(_) => {}
+                    Err(e) => panic!("{}", e),
+                }
+            }
+        });
+        let id = poll_until(|| match server.poll().pop() {
+            Some(ServerEvent::Disconnected(id)) => Some(id),
+            _ => None,
+        });
+        assert_eq!(id, ClientId(0));
+        assert_eq!(client.join().unwrap(), Some(CloseCode::Size));
+    }
+
+    #[test]
+    fn idle_handshakes_time_out() {
+        let mut server = WebSocketServer::bind("127.0.0.1:0").unwrap();
+        server.handshake_timeout = Duration::from_millis(50);
+        let _idle = TcpStream::connect(server.local_addr().unwrap()).unwrap();
+        poll_until(|| {
+            server.poll();
+            Some(()).filter(|_| server.clients.len() == 1)
+        });
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(server.poll(), []);
+        assert!(server.clients.is_empty());
+    }
+}